@@ -8,7 +8,8 @@ use crate::helpers::Class;
 /// Trait implemented by specialization of cause
 pub trait Cause {}
 
-/// A `Cause` on a `CauseAction`
+/// A `Cause` on a `CauseAction`. Causes of a class unknown to this crate still deserialize to
+/// a `CommonCause`, keeping their `class` and `short_description`
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct CommonCause {
@@ -65,7 +66,7 @@ pub struct UpstreamCause {
     /// URL to the upstream `Build`
     pub upstream_url: String,
 }
-register_class!("hudson.model.Cause$RemoteCause" => UpstreamCause);
+register_class!("hudson.model.Cause$UpstreamCause" => UpstreamCause);
 impl Cause for UpstreamCause {}
 
 /// Caused by a timer
@@ -87,3 +88,68 @@ pub struct SCMTriggerCause {
 }
 register_class!("hudson.triggers.SCMTrigger$SCMTriggerCause" => SCMTriggerCause);
 impl Cause for SCMTriggerCause {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cause(json: &str) -> CommonCause {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn can_read_user_cause() {
+        let cause = cause(
+            r#"{"_class":"hudson.model.Cause$UserIdCause","shortDescription":"Started by user Alice",
+            "userId":"alice","userName":"Alice"}"#,
+        );
+        let user = cause.as_variant::<UserIdCause>().unwrap();
+        assert_eq!(user.user_id, "alice");
+        assert_eq!(user.user_name, "Alice");
+    }
+
+    #[test]
+    fn can_read_upstream_cause() {
+        let cause = cause(
+            r#"{"_class":"hudson.model.Cause$UpstreamCause",
+            "shortDescription":"Started by upstream project \"parent\" build number 4",
+            "upstreamBuild":4,"upstreamProject":"parent","upstreamUrl":"job/parent/"}"#,
+        );
+        assert!(cause.as_variant::<RemoteCause>().is_err());
+        let upstream = cause.as_variant::<UpstreamCause>().unwrap();
+        assert_eq!(upstream.upstream_project, "parent");
+        assert_eq!(upstream.upstream_build, 4);
+    }
+
+    #[test]
+    fn can_read_remote_cause() {
+        let cause = cause(
+            r#"{"_class":"hudson.model.Cause$RemoteCause",
+            "shortDescription":"Started by remote host 10.0.0.1 with note: nightly",
+            "addr":"10.0.0.1","note":"nightly"}"#,
+        );
+        let remote = cause.as_variant::<RemoteCause>().unwrap();
+        assert_eq!(remote.addr, "10.0.0.1");
+        assert_eq!(remote.note, Some("nightly".to_string()));
+    }
+
+    #[test]
+    fn can_read_trigger_causes() {
+        let timer = cause(
+            r#"{"_class":"hudson.triggers.TimerTrigger$TimerTriggerCause",
+            "shortDescription":"Started by timer"}"#,
+        );
+        assert!(timer.as_variant::<TimerTriggerCause>().is_ok());
+
+        let scm = cause(
+            r#"{"_class":"hudson.triggers.SCMTrigger$SCMTriggerCause",
+            "shortDescription":"Started by an SCM change"}"#,
+        );
+        assert_eq!(
+            scm.as_variant::<SCMTriggerCause>()
+                .unwrap()
+                .short_description,
+            "Started by an SCM change"
+        );
+    }
+}
@@ -7,8 +7,8 @@ use crate::client;
 use crate::client_internals::path::Path;
 use crate::Jenkins;
 
-/// Artifact produced by a build
-#[derive(Deserialize, Debug)]
+/// Artifact produced by a build. The default artifact has empty fields
+#[derive(Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Artifact {
     /// Artifact ID
@@ -64,8 +64,8 @@ pub struct MavenArtifactRecord {
     pub url: String,
     /// List of the artifacts
     pub attached_artifacts: Vec<Artifact>,
-    /// Main artifact
-    pub main_artifact: Artifact,
+    /// Main artifact, missing for modules like `pom` packaged ones
+    pub main_artifact: Option<Artifact>,
     /// Parent build
     pub parent: crate::build::ShortBuild,
     /// POM artifact
@@ -3,7 +3,7 @@
 use serde::{self, Deserialize, Serialize};
 use serde_json;
 
-use crate::helpers::Class;
+use crate::helpers::{Class, MaybeDuration};
 
 pub mod causes;
 pub mod git;
@@ -82,10 +82,10 @@ impl Action for RepoTagAction {}
 #[derive(Deserialize, Debug, Copy, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TimeInQueueAction {
-    /// Time spent in queue (milliseconds)
-    pub queuing_duration_millis: u64,
-    /// Total time spent in queue and building (milliseconds)
-    pub total_duration_millis: u64,
+    /// Time spent in queue
+    pub queuing_duration_millis: MaybeDuration,
+    /// Total time spent in queue and building
+    pub total_duration_millis: MaybeDuration,
 }
 register_class!("jenkins.metrics.impl.TimeInQueueAction" => TimeInQueueAction);
 impl Action for TimeInQueueAction {}
@@ -115,8 +115,8 @@ pub struct MavenArtifactRecord {
     pub url: String,
     /// List of the artifacts
     pub attached_artifacts: Vec<maven::Artifact>,
-    /// Main artifact
-    pub main_artifact: maven::Artifact,
+    /// Main artifact, missing for modules like `pom` packaged ones
+    pub main_artifact: Option<maven::Artifact>,
     /// Parent build
     pub parent: crate::build::ShortBuild,
     /// POM artifact
@@ -167,6 +167,22 @@ pub struct SurefireAggregatedReport {
 register_class!("hudson.maven.reporters.SurefireAggregatedReport" => SurefireAggregatedReport);
 impl Action for SurefireAggregatedReport {}
 
+/// An action with a JUnit test report
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TestResultAction {
+    /// Number of tests failed
+    pub fail_count: u32,
+    /// Number of tests skipped
+    pub skip_count: u32,
+    /// Number of tests
+    pub total_count: u32,
+    /// URL to the report
+    pub url_name: String,
+}
+register_class!("hudson.tasks.junit.TestResultAction" => TestResultAction);
+impl Action for TestResultAction {}
+
 /// An action marking an approval on a pipeline
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -176,3 +192,43 @@ pub struct PipelineApproverAction {
 }
 register_class!("org.jenkinsci.plugins.workflow.support.steps.input.ApproverAction" => PipelineApproverAction);
 impl Action for PipelineApproverAction {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_test_result_action() {
+        let action: CommonAction = serde_json::from_str(
+            r#"{"_class":"hudson.tasks.junit.TestResultAction","failCount":1,"skipCount":2,
+            "totalCount":10,"urlName":"testReport"}"#,
+        )
+        .unwrap();
+        let tests = action.as_variant::<TestResultAction>().unwrap();
+        assert_eq!(tests.fail_count, 1);
+        assert_eq!(tests.skip_count, 2);
+        assert_eq!(tests.total_count, 10);
+        assert_eq!(tests.url_name, "testReport");
+    }
+
+    #[test]
+    fn can_read_maven_record_without_main_artifact() {
+        let action: CommonAction = serde_json::from_str(
+            r#"{"_class":"hudson.maven.reporters.MavenArtifactRecord",
+            "url":"http://none/job/maven%20job/1/org.example$parent/",
+            "attachedArtifacts":[],
+            "parent":{"_class":"hudson.maven.MavenBuild","number":1,"url":"http://none/job/maven%20job/1/"},
+            "pomArtifact":{"artifactId":"parent","canonicalName":"parent-1.0.pom","classifier":null,
+            "fileName":"pom.xml","groupId":"org.example","md5sum":"d41d8cd98f00b204e9800998ecf8427e",
+            "type":"pom","version":"1.0"}}"#,
+        )
+        .unwrap();
+        let record = action.as_variant::<MavenArtifactRecord>().unwrap();
+        assert_eq!(record.pom_artifact.canonical_name, "parent-1.0.pom");
+        assert!(record.main_artifact.is_none());
+
+        let artifact = maven::Artifact::default();
+        assert_eq!(artifact.canonical_name, "");
+        assert_eq!(artifact.classifier, None);
+    }
+}
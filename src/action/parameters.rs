@@ -23,6 +23,12 @@ pub struct CommonParameter {
 }
 specialize!(CommonParameter => Parameter);
 impl Parameter for CommonParameter {}
+impl CommonParameter {
+    /// Value of the parameter, if it has one
+    pub(crate) fn value(&self) -> Option<&serde_json::Value> {
+        self.other_fields.get("value")
+    }
+}
 
 /// A boolean parameter
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,273 @@
+//! Asynchronous Jenkins client, available with the `async` feature
+//!
+//! Its methods return futures, to be run on a `tokio` runtime, and use the same types as the
+//! blocking client.
+//!
+//! It shares the session of the blocking client it is built from, sending its credentials and
+//! cookies and reporting a `405 Method Not Allowed` as an
+//! [`Error::MethodNotAllowed`](../client/enum.Error.html#variant.MethodNotAllowed). Requests
+//! are never retried, whatever was set with
+//! [`JenkinsBuilder::with_retries`](../struct.JenkinsBuilder.html#method.with_retries).
+
+use std::sync::Arc;
+
+use failure::Error;
+use futures::Future;
+
+use crate::build::{BuildNumber, CommonBuild};
+use crate::client_internals::{Name, Path};
+use crate::home::Home;
+use crate::job::{builder::JobBuilder, CommonJob, JobName};
+use crate::queue::ShortQueueItem;
+
+/// Future returned by the asynchronous client
+pub type JenkinsFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// Asynchronous client to query Jenkins
+#[derive(Debug)]
+pub struct Jenkins {
+    blocking: Arc<crate::Jenkins>,
+}
+
+impl Jenkins {
+    pub(crate) fn new(blocking: crate::Jenkins) -> Self {
+        Jenkins {
+            blocking: Arc::new(blocking),
+        }
+    }
+
+    /// Get the blocking client, with the same configuration and session
+    pub fn blocking(&self) -> &crate::Jenkins {
+        &self.blocking
+    }
+
+    /// Blocking client shared with the futures, to keep its session up to date
+    pub(crate) fn shared(&self) -> &Arc<crate::Jenkins> {
+        &self.blocking
+    }
+
+    /// Get Jenkins `Home`
+    pub fn get_home(&self) -> JenkinsFuture<Home> {
+        Box::new(
+            self.blocking
+                .async_get(&Path::Home)
+                .and_then(|mut response| response.json().map_err(Error::from)),
+        )
+    }
+
+    /// Get a `Job` from it's `job_name`
+    pub fn get_job<'a, J>(&self, job_name: J) -> JenkinsFuture<CommonJob>
+    where
+        J: Into<JobName<'a>>,
+    {
+        Box::new(
+            self.blocking
+                .async_get(&Path::Job {
+                    name: Name::Name(job_name.into().0),
+                    configuration: None,
+                })
+                .and_then(|mut response| response.json().map_err(Error::from)),
+        )
+    }
+
+    /// Get a build from a `job_name` and `build_number`
+    pub fn get_build<'a, J, B>(&self, job_name: J, build_number: B) -> JenkinsFuture<CommonBuild>
+    where
+        J: Into<JobName<'a>>,
+        B: Into<BuildNumber>,
+    {
+        Box::new(
+            self.blocking
+                .async_get(&Path::Build {
+                    job_name: Name::Name(job_name.into().0),
+                    number: build_number.into(),
+                    configuration: None,
+                })
+                .and_then(|mut response| response.json().map_err(Error::from)),
+        )
+    }
+
+    /// Create a `JobBuilder` to setup a build of a `Job` from it's `job_name`, to send with
+    /// `JobBuilder::send_async`
+    pub fn job_builder<'a, 'b, 'c, 'd>(
+        &'b self,
+        job_name: &'a str,
+    ) -> Result<JobBuilder<'a, 'b, 'c, 'd>, Error> {
+        self.blocking.job_builder(job_name)
+    }
+
+    /// Build a `Job` from it's `job_name`
+    pub fn build_job(&self, job_name: &str) -> JenkinsFuture<ShortQueueItem> {
+        match self.job_builder(job_name) {
+            Ok(job_builder) => job_builder.send_async(self),
+            Err(error) => Box::new(futures::future::err(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+    use tokio::runtime::Runtime;
+
+    static HOME: &str = r#"{"mode":"NORMAL","nodeDescription":"the master Jenkins node",
+        "nodeName":"","numExecutors":2,"description":null,"jobs":[],"quietingDown":false,
+        "slaveAgentPort":50000,"useCrumbs":true,"useSecurity":true,"views":[]}"#;
+
+    #[test]
+    fn can_get_home() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/async-home", mockito::server_url()))
+                .build_async()
+                .unwrap();
+
+        let _mock = mockito::mock("GET", "/async-home/api/json?depth=1")
+            .with_body(HOME)
+            .create();
+
+        let home = Runtime::new()
+            .unwrap()
+            .block_on(jenkins_client.get_home())
+            .unwrap();
+        assert_eq!(home.num_executors, 2);
+    }
+
+    #[test]
+    fn should_fail_on_error_status() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/async-missing", mockito::server_url()))
+                .build_async()
+                .unwrap();
+
+        let _mock = mockito::mock("GET", "/async-missing/job/none/api/json?depth=1")
+            .with_status(404)
+            .create();
+
+        let job = Runtime::new()
+            .unwrap()
+            .block_on(jenkins_client.get_job("none"));
+        assert!(job.is_err());
+    }
+
+    #[test]
+    fn filters_json_responses() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/async-filter", mockito::server_url()))
+                .with_response_filter(crate::client::ResponseFilters::strip_bom_and_leading_junk())
+                .build_async()
+                .unwrap();
+
+        let _mock = mockito::mock("GET", "/async-filter/api/json?depth=1")
+            .with_header("Content-Type", "application/json;charset=utf-8")
+            .with_body(format!(
+                "\u{feff}<!-- Served by Corporate Proxy -->\n{}",
+                HOME
+            ))
+            .create();
+
+        let home = Runtime::new()
+            .unwrap()
+            .block_on(jenkins_client.get_home())
+            .unwrap();
+        assert_eq!(home.num_executors, 2);
+    }
+
+    #[test]
+    fn can_build_job_with_crumb() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/async-build", mockito::server_url()))
+                .build_async()
+                .unwrap();
+
+        let _crumb = mockito::mock("GET", "/async-build/crumbIssuer/api/json")
+            .with_body(r#"{"crumb":"abc","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let build = mockito::mock("POST", "/async-build/job/a%20job/build")
+            .match_header("jenkins-crumb", "abc")
+            .with_status(201)
+            .with_header("Location", "http://jenkins/queue/item/1/")
+            .create();
+
+        let queue_item = Runtime::new()
+            .unwrap()
+            .block_on(jenkins_client.build_job("a job"))
+            .unwrap();
+        build.assert();
+        assert_eq!(queue_item.url, "http://jenkins/queue/item/1/");
+    }
+
+    #[test]
+    fn reuses_crumb_and_cookies_of_session() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/async-session", mockito::server_url()))
+                .build_async()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/async-session/crumbIssuer/api/json")
+            .with_header("Set-Cookie", "JSESSIONID.abc=strict; Path=/; HttpOnly")
+            .with_body(r#"{"crumb":"abc","crumbRequestField":"Jenkins-Crumb"}"#)
+            .expect(1)
+            .create();
+        let build = mockito::mock("POST", "/async-session/job/a%20job/build")
+            .match_header("jenkins-crumb", "abc")
+            .match_header("cookie", "JSESSIONID.abc=strict")
+            .with_status(201)
+            .with_header("Location", "http://jenkins/queue/item/1/")
+            .expect(2)
+            .create();
+
+        let mut runtime = Runtime::new().unwrap();
+        for _ in 0..2 {
+            let _ = runtime.block_on(jenkins_client.build_job("a job")).unwrap();
+        }
+        crumb.assert();
+        build.assert();
+        assert_eq!(
+            jenkins_client.blocking().export_session().cookies,
+            vec!["JSESSIONID.abc=strict".to_string()]
+        );
+    }
+
+    #[test]
+    fn renews_rejected_crumb() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/async-rejected", mockito::server_url()))
+                .with_session(crate::client::SessionState {
+                    crumb: Some(crate::client::Crumb {
+                        crumb: "expired".to_string(),
+                        crumb_request_field: "Jenkins-Crumb".to_string(),
+                    }),
+                    cookies: vec!["JSESSIONID.abc=old".to_string()],
+                    version: None,
+                })
+                .build_async()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/async-rejected/crumbIssuer/api/json")
+            .with_header("Set-Cookie", "JSESSIONID.abc=renewed; Path=/")
+            .with_body(r#"{"crumb":"renewed","crumbRequestField":"Jenkins-Crumb"}"#)
+            .expect(1)
+            .create();
+        let rejected = mockito::mock("POST", "/async-rejected/job/a%20job/build")
+            .match_header("jenkins-crumb", "expired")
+            .with_status(403)
+            .expect(1)
+            .create();
+        let accepted = mockito::mock("POST", "/async-rejected/job/a%20job/build")
+            .match_header("jenkins-crumb", "renewed")
+            .match_header("cookie", "JSESSIONID.abc=renewed")
+            .with_status(201)
+            .with_header("Location", "http://jenkins/queue/item/2/")
+            .expect(1)
+            .create();
+
+        let queue_item = Runtime::new()
+            .unwrap()
+            .block_on(jenkins_client.build_job("a job"))
+            .unwrap();
+        assert_eq!(queue_item.url, "http://jenkins/queue/item/2/");
+        crumb.assert();
+        rejected.assert();
+        accepted.assert();
+    }
+}
@@ -0,0 +1,334 @@
+//! Runs, nodes and steps of pipelines from the Blue Ocean REST API
+//!
+//! The Blue Ocean plugin must be installed in Jenkins. Its API gives a graph of the stages and
+//! parallel branches of a pipeline run, and the steps of each with their logs.
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::client_internals::{Name, Path};
+use crate::helpers::MaybeDuration;
+use crate::job::JobName;
+use crate::Jenkins;
+
+/// Result of a run, node or step
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BlueOceanResult {
+    /// Successful
+    Success,
+    /// Unstable
+    Unstable,
+    /// Failed
+    Failure,
+    /// Not built
+    NotBuilt,
+    /// Aborted
+    Aborted,
+    /// Not known yet, or not known by this crate
+    #[serde(other)]
+    Unknown,
+}
+
+/// State of a run, node or step
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BlueOceanState {
+    /// Waiting in the queue
+    Queued,
+    /// Running
+    Running,
+    /// Paused, waiting for an input
+    Paused,
+    /// Skipped
+    Skipped,
+    /// Not built
+    NotBuilt,
+    /// Finished
+    Finished,
+    /// State not known by this crate
+    #[serde(other)]
+    Unknown,
+}
+
+/// A run of a pipeline
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineRun {
+    /// Id of the run, its build number
+    pub id: String,
+    /// Name of the pipeline
+    pub pipeline: String,
+    /// Result of the run
+    pub result: BlueOceanResult,
+    /// State of the run
+    pub state: BlueOceanState,
+    /// Start of the run
+    pub start_time: Option<String>,
+    /// End of the run
+    pub end_time: Option<String>,
+    /// Duration of the run, unknown while it didn't run
+    pub duration_in_millis: MaybeDuration,
+}
+
+/// A link from a node to the nodes following it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PipelineNodeEdge {
+    /// Id of the following node
+    pub id: String,
+}
+
+/// A node of a pipeline run, a stage or a parallel branch
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineNode {
+    /// Id of the node
+    pub id: String,
+    /// Name of the stage or branch
+    pub display_name: String,
+    /// Type of the node, `STAGE` or `PARALLEL`
+    #[serde(rename = "type")]
+    pub node_type: String,
+    /// Result of the node, `None` if it didn't run
+    pub result: Option<BlueOceanResult>,
+    /// State of the node, `None` if it didn't run
+    pub state: Option<BlueOceanState>,
+    /// Start of the node
+    pub start_time: Option<String>,
+    /// Duration of the node, unknown while it didn't run
+    pub duration_in_millis: MaybeDuration,
+    /// Id of the node this one is nested in, for parallel branches
+    pub first_parent: Option<String>,
+    /// Nodes following this one
+    #[serde(default)]
+    pub edges: Vec<PipelineNodeEdge>,
+}
+
+/// A step of a node
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStep {
+    /// Id of the step
+    pub id: String,
+    /// Name of the step
+    pub display_name: String,
+    /// Description of the step, like the script of a `sh` step
+    pub display_description: Option<String>,
+    /// Result of the step
+    pub result: BlueOceanResult,
+    /// State of the step
+    pub state: BlueOceanState,
+    /// Start of the step
+    pub start_time: Option<String>,
+    /// Duration of the step, unknown while it didn't run
+    pub duration_in_millis: MaybeDuration,
+}
+
+/// Client for the Blue Ocean REST API, see `Jenkins::blue_ocean`
+#[derive(Debug, Clone, Copy)]
+pub struct BlueOcean<'a> {
+    jenkins_client: &'a Jenkins,
+}
+
+impl Jenkins {
+    /// Use the Blue Ocean REST API of this Jenkins
+    pub fn blue_ocean(&self) -> BlueOcean<'_> {
+        BlueOcean {
+            jenkins_client: self,
+        }
+    }
+}
+
+impl<'a> BlueOcean<'a> {
+    /// Get the runs of a pipeline, the most recent first
+    pub fn get_pipeline_runs<'b, J>(&self, pipeline: J) -> Result<Vec<PipelineRun>, Error>
+    where
+        J: Into<JobName<'b>>,
+    {
+        Ok(self
+            .jenkins_client
+            .get_file(&Path::BlueOceanRuns {
+                pipeline: Name::Name(pipeline.into().0),
+            })?
+            .json()?)
+    }
+
+    /// Get the stages and parallel branches of a run of a pipeline
+    pub fn get_run_nodes<'b, J>(&self, pipeline: J, run: u32) -> Result<Vec<PipelineNode>, Error>
+    where
+        J: Into<JobName<'b>>,
+    {
+        Ok(self
+            .jenkins_client
+            .get_file(&Path::BlueOceanRunNodes {
+                pipeline: Name::Name(pipeline.into().0),
+                run,
+            })?
+            .json()?)
+    }
+
+    /// Get the steps of a node of a run of a pipeline
+    pub fn get_node_steps<'b, J>(
+        &self,
+        pipeline: J,
+        run: u32,
+        node_id: &str,
+    ) -> Result<Vec<PipelineStep>, Error>
+    where
+        J: Into<JobName<'b>>,
+    {
+        Ok(self
+            .jenkins_client
+            .get_file(&Path::BlueOceanNodeSteps {
+                pipeline: Name::Name(pipeline.into().0),
+                run,
+                node_id: Name::Name(node_id),
+            })?
+            .json()?)
+    }
+
+    /// Get the log of a step
+    pub fn get_step_log<'b, J>(
+        &self,
+        pipeline: J,
+        run: u32,
+        node_id: &str,
+        step_id: &str,
+    ) -> Result<String, Error>
+    where
+        J: Into<JobName<'b>>,
+    {
+        Ok(self
+            .jenkins_client
+            .get_file(&Path::BlueOceanStepLog {
+                pipeline: Name::Name(pipeline.into().0),
+                run,
+                node_id: Name::Name(node_id),
+                step_id: Name::Name(step_id),
+            })?
+            .text()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static RUNS: &str = r#"[{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineRunImpl",
+        "_links":{},"id":"2","pipeline":"deploy","organization":"jenkins",
+        "result":"UNKNOWN","state":"RUNNING","startTime":"2019-03-01T10:00:00.000+0000",
+        "endTime":null,"durationInMillis":0,"runSummary":"?"
+    },{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineRunImpl",
+        "_links":{},"id":"1","pipeline":"deploy","organization":"jenkins",
+        "result":"FAILURE","state":"FINISHED","startTime":"2019-02-28T10:00:00.000+0000",
+        "endTime":"2019-02-28T10:05:00.000+0000","durationInMillis":300000
+    }]"#;
+
+    static NODES: &str = r#"[{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineNodeImpl","_links":{},
+        "actions":[],"displayDescription":null,"displayName":"Build","durationInMillis":1000,
+        "id":"6","input":null,"result":"SUCCESS","startTime":"2019-02-28T10:00:01.000+0000",
+        "state":"FINISHED","type":"STAGE","causeOfBlockage":null,"edges":[{"_class":"x",
+        "id":"10","type":"PARALLEL"},{"_class":"x","id":"11","type":"PARALLEL"}],
+        "firstParent":null,"restartable":false
+    },{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineNodeImpl","_links":{},
+        "displayName":"linux","durationInMillis":2000,"id":"10","result":"SUCCESS",
+        "startTime":"2019-02-28T10:00:02.000+0000","state":"FINISHED","type":"PARALLEL",
+        "edges":[{"id":"20","type":"STAGE"}],"firstParent":"6"
+    },{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineNodeImpl","_links":{},
+        "displayName":"windows","durationInMillis":3000,"id":"11","result":"FAILURE",
+        "startTime":"2019-02-28T10:00:02.000+0000","state":"FINISHED","type":"PARALLEL",
+        "edges":[{"id":"20","type":"STAGE"}],"firstParent":"6"
+    },{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineNodeImpl","_links":{},
+        "displayName":"Deploy","durationInMillis":null,"id":"20","result":null,
+        "startTime":null,"state":null,"type":"STAGE","edges":[],"firstParent":null
+    }]"#;
+
+    static STEPS: &str = r#"[{
+        "_class":"io.jenkins.blueocean.rest.impl.pipeline.PipelineStepImpl","_links":{},
+        "actions":[],"displayDescription":"make test","displayName":"Shell Script",
+        "durationInMillis":2900,"id":"14","input":null,"result":"FAILURE",
+        "startTime":"2019-02-28T10:00:02.100+0000","state":"FINISHED","type":"STEP"
+    }]"#;
+
+    #[test]
+    fn can_parse_runs() {
+        let runs: Vec<PipelineRun> = serde_json::from_str(RUNS).unwrap();
+        assert_eq!(runs[0].result, BlueOceanResult::Unknown);
+        assert_eq!(runs[0].state, BlueOceanState::Running);
+        assert_eq!(runs[0].end_time, None);
+        assert_eq!(runs[1].result, BlueOceanResult::Failure);
+        assert_eq!(
+            runs[1].duration_in_millis.as_duration(),
+            Some(std::time::Duration::from_secs(300))
+        );
+    }
+
+    #[test]
+    fn can_parse_parallel_nodes() {
+        let nodes: Vec<PipelineNode> = serde_json::from_str(NODES).unwrap();
+        let branches = nodes
+            .iter()
+            .filter(|node| node.first_parent.as_deref() == Some("6"))
+            .map(|node| (node.display_name.as_str(), node.result))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            branches,
+            vec![
+                ("linux", Some(BlueOceanResult::Success)),
+                ("windows", Some(BlueOceanResult::Failure))
+            ]
+        );
+        assert_eq!(nodes[0].edges.len(), 2);
+        assert_eq!(nodes[3].node_type, "STAGE");
+        assert_eq!(nodes[3].state, None);
+        assert!(!nodes[3].duration_in_millis.is_known());
+    }
+
+    #[test]
+    fn can_get_runs_nodes_steps_and_logs() {
+        let jenkins_client = crate::JenkinsBuilder::new(&format!("{}/blue", mockito::server_url()))
+            .build()
+            .unwrap();
+        let base = "/blue/blue/rest/organizations/jenkins/pipelines/folder/pipelines/deploy";
+        let _runs = mockito::mock("GET", format!("{}/runs/", base).as_str())
+            .with_body(RUNS)
+            .create();
+        let _nodes = mockito::mock("GET", format!("{}/runs/1/nodes/", base).as_str())
+            .with_body(NODES)
+            .create();
+        let _steps = mockito::mock("GET", format!("{}/runs/1/nodes/11/steps/", base).as_str())
+            .with_body(STEPS)
+            .create();
+        let _log = mockito::mock(
+            "GET",
+            format!("{}/runs/1/nodes/11/steps/14/log/", base).as_str(),
+        )
+        .with_body("+ make test\nFAILED\n")
+        .create();
+
+        let blue_ocean = jenkins_client.blue_ocean();
+        assert_eq!(
+            blue_ocean.get_pipeline_runs("folder/deploy").unwrap().len(),
+            2
+        );
+        assert_eq!(
+            blue_ocean.get_run_nodes("folder/deploy", 1).unwrap().len(),
+            4
+        );
+        let steps = blue_ocean.get_node_steps("folder/deploy", 1, "11").unwrap();
+        assert_eq!(steps[0].display_description, Some("make test".to_string()));
+        assert_eq!(steps[0].result, BlueOceanResult::Failure);
+        assert_eq!(
+            blue_ocean
+                .get_step_log("folder/deploy", 1, "11", &steps[0].id)
+                .unwrap(),
+            "+ make test\nFAILED\n"
+        );
+    }
+}
@@ -1,15 +1,29 @@
+use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use failure::Error;
-use serde::{self, Deserialize, Serialize};
+use serde::{self, de::DeserializeOwned, Deserialize, Serialize};
 use serde_json;
+use serde_urlencoded;
 
-use crate::helpers::Class;
+use crate::helpers::{html_to_text, is_not_found, Class, MaybeDuration, MaybeTimestamp};
 
-use crate::action::CommonAction;
+use super::report::{build_report, BuildReport, ReportOptions};
+use super::{RetentionStatus, TestReport, TimelineEvent, WorkflowRun};
+use crate::action::causes::CommonCause;
+use crate::action::parameters::RunParameterValue;
+use crate::action::{
+    maven, CauseAction, CommonAction, GitBuildData, MavenAggregatedArtifactRecord,
+    MavenArtifactRecord, ParametersAction,
+};
+use crate::changeset::{CommonChangeSet, CommonChangeSetList};
 use crate::client;
 use crate::client_internals::path::Path;
+use crate::job::builder::JobBuilder;
 use crate::job::{CommonJob, Job};
+use crate::queue::ShortQueueItem;
 use crate::Jenkins;
 
 /// Short Build that is used in lists and links from other structs
@@ -73,9 +87,21 @@ pub struct Artifact {
     /// Path to the file
     pub relative_path: String,
 }
+impl<'a> From<&'a maven::Artifact> for Artifact {
+    fn from(artifact: &'a maven::Artifact) -> Artifact {
+        Artifact {
+            display_path: None,
+            file_name: artifact.canonical_name.clone(),
+            relative_path: format!(
+                "{}/{}/{}/{}",
+                artifact.group_id, artifact.artifact_id, artifact.version, artifact.canonical_name
+            ),
+        }
+    }
+}
 
 /// Helper type to act on a build
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum BuildNumber {
     /// Alias to last build
     LastBuild,
@@ -152,6 +178,93 @@ into_buildnumber!(i16);
 into_buildnumber!(i32);
 into_buildnumber!(i64);
 
+/// Get the last `lines` lines of `text`, a final line terminator not starting a new line
+fn last_lines(text: &str, lines: usize) -> &str {
+    if lines == 0 {
+        return "";
+    }
+    let content = text.strip_suffix('\n').unwrap_or(text);
+    match content.rmatch_indices('\n').nth(lines - 1) {
+        Some((index, _)) => &text[(index + 1)..],
+        None => text,
+    }
+}
+
+/// Only whether the logs of a build are kept forever
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepLogState {
+    keep_log: bool,
+}
+
+/// Set whether the build at `url` is kept forever, toggling it only if its current state,
+/// read from Jenkins, differs
+fn set_keep_log(jenkins_client: &Jenkins, url: &str, keep_log: bool) -> Result<(), Error> {
+    let path = jenkins_client.url_to_path(url);
+    if let Path::Build {
+        ref job_name,
+        ref number,
+        ref configuration,
+    } = path
+    {
+        let state: KeepLogState = jenkins_client
+            .get_with_params(&path, [("tree", "keepLog")])?
+            .json()?;
+        if state.keep_log != keep_log {
+            let _ = jenkins_client.post(&Path::ToggleLogKeep {
+                job_name: job_name.clone(),
+                number: number.clone(),
+                configuration: configuration.clone(),
+            })?;
+        }
+        Ok(())
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: url.to_string(),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
+/// Only whether a build is running, to poll it cheaply
+#[derive(Debug, Deserialize)]
+struct BuildingState {
+    building: bool,
+}
+
+/// Delete the build at `path`, after checking with Jenkins that it is not running
+pub(crate) fn delete_build(jenkins_client: &Jenkins, path: Path<'_>) -> Result<(), Error> {
+    if let Path::Build {
+        ref job_name,
+        ref number,
+        ref configuration,
+    } = path
+    {
+        let state: BuildingState = jenkins_client
+            .get_with_params(&path, [("tree", "building")])?
+            .json()?;
+        if state.building {
+            return Err(client::Error::BuildStillRunning {
+                build_url: format!("{}/", jenkins_client.url(&path.to_string())),
+            }
+            .into());
+        }
+        let _ = jenkins_client.post(&Path::DeleteBuild {
+            job_name: job_name.clone(),
+            number: number.clone(),
+            configuration: configuration.clone(),
+        })?;
+        Ok(())
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: jenkins_client.url(&path.to_string()),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
 /// Trait implemented by specializations of `Build` and providing common methods
 pub trait Build {
     /// Type of the job that triggered this build
@@ -160,6 +273,72 @@ pub trait Build {
     /// Get the url of a build
     fn url(&self) -> &str;
 
+    /// Get the artifacts archived by a build
+    fn artifacts(&self) -> &[Artifact];
+
+    /// Get the actions of a build
+    fn actions(&self) -> &[CommonAction];
+
+    /// Get the description of a build, that can contain HTML
+    fn description(&self) -> Option<&str>;
+
+    /// Get the display name of a build, usually "#" followed by the build number
+    fn display_name(&self) -> &str;
+
+    /// Get the result of a build, `None` while it's running
+    fn result(&self) -> Option<BuildStatus>;
+
+    /// Get the timestamp of the start of a build, in milliseconds since the epoch, as sent by
+    /// Jenkins: `0` if unknown
+    fn timestamp(&self) -> u64;
+
+    /// Get the duration of a build, `0` while it's running
+    fn duration(&self) -> MaybeDuration;
+
+    /// Get the timestamp of the start of a build as a `DateTime`
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::InvalidTimestamp`](../client/enum.Error.html#variant.InvalidTimestamp)
+    /// if the timestamp is out of the range of `DateTime`
+    #[cfg(feature = "chrono")]
+    fn timestamp_datetime(&self) -> Result<chrono::DateTime<chrono::Utc>, Error> {
+        use chrono::TimeZone;
+
+        let timestamp = self.timestamp();
+        let invalid = || client::Error::InvalidTimestamp { timestamp };
+        let seconds = std::convert::TryFrom::try_from(timestamp / 1000).map_err(|_| invalid())?;
+        let nanoseconds = (timestamp % 1000) as u32 * 1_000_000;
+        chrono::Utc
+            .timestamp_opt(seconds, nanoseconds)
+            .single()
+            .ok_or_else(|| invalid().into())
+    }
+
+    /// Get the timestamp of the start of a build, unknown while it's queued
+    fn started_at(&self) -> MaybeTimestamp {
+        MaybeTimestamp::from_millis(self.timestamp())
+    }
+
+    /// Get the duration of a build as a `Duration`, zero while it's running or if it is unknown
+    fn duration_as_duration(&self) -> Duration {
+        self.duration()
+            .as_duration()
+            .unwrap_or_else(|| Duration::from_secs(0))
+    }
+
+    /// Get the URL of a build for users, on the public URL of Jenkins, see
+    /// [`Jenkins::public_url_for`](../struct.Jenkins.html#method.public_url_for)
+    fn public_url(&self, jenkins_client: &Jenkins) -> Result<String, Error> {
+        jenkins_client.public_url_for(self.url())
+    }
+
+    /// Get the description of a build as plain text, without HTML tags and with entities
+    /// decoded
+    fn description_plain(&self) -> Option<String> {
+        self.description().map(html_to_text)
+    }
+
     /// Get the `Job` from a `Build`
     fn get_job(&self, jenkins_client: &Jenkins) -> Result<Self::ParentJob, Error>
     where
@@ -187,6 +366,155 @@ pub trait Build {
         }
     }
 
+    /// Stop a running `Build`
+    fn stop(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build {
+            job_name,
+            number,
+            configuration,
+        } = path
+        {
+            let _ = jenkins_client.post(&Path::StopBuild {
+                job_name,
+                number,
+                configuration,
+            })?;
+            Ok(())
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Keep this `Build` forever, so that it is not deleted when discarding old builds
+    fn keep_forever(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        set_keep_log(jenkins_client, self.url(), true)
+    }
+
+    /// Stop keeping this `Build` forever, letting it be deleted when discarding old builds
+    fn dont_keep_forever(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        set_keep_log(jenkins_client, self.url(), false)
+    }
+
+    /// Set the description of this `Build`, that can contain HTML
+    fn set_description(&self, jenkins_client: &Jenkins, description: &str) -> Result<(), Error> {
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build {
+            job_name,
+            number,
+            configuration,
+        } = path
+        {
+            let body = serde_urlencoded::to_string([("description", description)])?;
+            let _ = jenkins_client.post_with_body(
+                &Path::SubmitBuildDescription {
+                    job_name,
+                    number,
+                    configuration,
+                },
+                body,
+                &[],
+            )?;
+            Ok(())
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Set the display name of this `Build`, an empty `name` restoring the default one. Jenkins
+    /// sets the description at the same time, so the current description is read first to keep
+    /// it
+    fn set_display_name(&self, jenkins_client: &Jenkins, name: &str) -> Result<(), Error> {
+        #[derive(Deserialize)]
+        struct DescriptionState {
+            description: Option<String>,
+        }
+
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build {
+            ref job_name,
+            ref number,
+            ref configuration,
+        } = path
+        {
+            let state: DescriptionState = jenkins_client
+                .get_with_params(&path, [("tree", "description")])?
+                .json()?;
+            let description = state.description.unwrap_or_default();
+            let json =
+                serde_json::json!({ "displayName": name, "description": description }).to_string();
+            let body = serde_urlencoded::to_string([
+                ("displayName", name),
+                ("description", &description),
+                ("json", &json),
+            ])?;
+            let _ = jenkins_client.post_with_body(
+                &Path::BuildConfigSubmit {
+                    job_name: job_name.clone(),
+                    number: number.clone(),
+                    configuration: configuration.clone(),
+                },
+                body,
+                &[],
+            )?;
+            Ok(())
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Will this `Build` survive the build discarder of its job: is it kept forever, skipped by
+    /// the discarder, within the limits of builds or artifacts to keep, or about to be
+    /// discarded. Only the discarder provided by Jenkins, `LogRotator`, can be evaluated
+    fn retention_status(&self, jenkins_client: &Jenkins) -> Result<RetentionStatus, Error> {
+        super::retention::retention_status(jenkins_client, self.url(), !self.artifacts().is_empty())
+    }
+
+    /// Get the chronological timeline of this `Build`: when it was queued, started, when the
+    /// stages of a pipeline started and finished, and when it finished. Events timed before an
+    /// event that should have happened first are kept and flagged as out of order
+    fn timeline(&self, jenkins_client: &Jenkins) -> Result<Vec<TimelineEvent>, Error> {
+        let finished = self
+            .result()
+            .map(|result| (self.started_at() + self.duration(), result));
+        super::timeline::timeline(
+            jenkins_client,
+            self.url(),
+            self.actions(),
+            self.started_at(),
+            finished,
+        )
+    }
+
+    /// Delete this `Build`
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::BuildStillRunning`](../client/enum.Error.html#variant.BuildStillRunning)
+    /// if the build is running
+    fn delete(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        match jenkins_client.url_to_path(self.url()) {
+            path @ Path::Build { .. } => delete_build(jenkins_client, path),
+            _ => Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into()),
+        }
+    }
+
     /// Get the console output from a `Build`
     fn get_console(&self, jenkins_client: &Jenkins) -> Result<String, Error> {
         let path = jenkins_client.url_to_path(&self.url());
@@ -211,6 +539,308 @@ pub trait Build {
             .into())
         }
     }
+
+    /// Get the last `lines` lines of the console output from a `Build`
+    fn console_tail(&self, jenkins_client: &Jenkins, lines: usize) -> Result<String, Error> {
+        Ok(last_lines(&self.get_console(jenkins_client)?, lines).to_string())
+    }
+
+    /// Get the test report of a `Build`
+    fn get_test_report(&self, jenkins_client: &Jenkins) -> Result<TestReport, Error> {
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build {
+            job_name,
+            number,
+            configuration,
+        } = path
+        {
+            let report: TestReport = jenkins_client
+                .get(&Path::TestReport {
+                    job_name,
+                    number,
+                    configuration,
+                })?
+                .json()?;
+            Ok(report.with_build_url(self.url()))
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Get the environment variables of a `Build`, as injected by the EnvInject plugin
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::NoInjectedEnvVars`](../client/enum.Error.html#variant.NoInjectedEnvVars)
+    /// if the build has none, as when the plugin is not installed
+    fn get_env_vars(&self, jenkins_client: &Jenkins) -> Result<HashMap<String, String>, Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct InjectedEnvVars {
+            env_map: HashMap<String, String>,
+        }
+
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build {
+            job_name,
+            number,
+            configuration,
+        } = path
+        {
+            match jenkins_client.get(&Path::BuildEnvVars {
+                job_name,
+                number,
+                configuration,
+            }) {
+                Ok(mut response) => Ok(response.json::<InjectedEnvVars>()?.env_map),
+                Err(ref error) if is_not_found(error) => Err(client::Error::NoInjectedEnvVars {
+                    build_url: self.url().to_string(),
+                }
+                .into()),
+                Err(error) => Err(error),
+            }
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Trigger the job of this `Build` again, with the same parameters
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::UnsupportedParameter`](../client/enum.Error.html#variant.UnsupportedParameter)
+    /// if a parameter can't be sent again, like a file, a password or credentials
+    fn rebuild(&self, jenkins_client: &Jenkins) -> Result<ShortQueueItem, Error> {
+        let parameters = rebuild_parameters(self.actions())?;
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build { job_name, .. } = path {
+            let builder = JobBuilder::new_from_name(job_name, jenkins_client);
+            if parameters.is_empty() {
+                builder.send()
+            } else {
+                builder.with_additional_parameters(parameters)?.send()
+            }
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Get the causes of a `Build`, from its `CauseAction`. Use `as_variant` to read them as
+    /// `UserIdCause`, `UpstreamCause`, ...
+    fn get_causes(&self) -> Result<Vec<CommonCause>, Error> {
+        let mut causes = vec![];
+        for action in self.actions() {
+            if action.class.as_deref() == Some(CauseAction::with_class()) {
+                causes.extend(action.as_variant::<CauseAction>()?.causes);
+            }
+        }
+        Ok(causes)
+    }
+
+    /// Get the SHA1 of the git revision that was built, from the git build data of the `Build`
+    fn git_revision(&self) -> Option<String> {
+        self.actions()
+            .iter()
+            .filter_map(|action| action.as_variant::<GitBuildData>().ok())
+            .map(|data| data.last_built_revision.sha1)
+            .next()
+    }
+
+    /// Read the parameters of a `Build` into a struct, as the one used to build it with
+    /// `JobBuilder::with_parameters`
+    ///
+    /// Parameters are read as strings, so that a boolean or a number can be read from a string
+    /// parameter. Parameters without a value, like file or password parameters, are skipped
+    fn parameters_as<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        let mut parameters = vec![];
+        for action in self.actions() {
+            if let Ok(action) = action.as_variant::<ParametersAction>() {
+                for parameter in action.parameters {
+                    let value = match parameter.value() {
+                        Some(serde_json::Value::String(value)) => value.clone(),
+                        Some(serde_json::Value::Bool(value)) => value.to_string(),
+                        Some(serde_json::Value::Number(value)) => value.to_string(),
+                        _ => continue,
+                    };
+                    parameters.push((parameter.name, value));
+                }
+            }
+        }
+        Ok(serde_urlencoded::from_str(&serde_urlencoded::to_string(
+            parameters,
+        )?)?)
+    }
+
+    /// Wait for the build to complete, checking it every `poll_interval`, and return it
+    /// refreshed once done
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::WaitTimeout`](../client/enum.Error.html#variant.WaitTimeout) if the build is
+    /// still running after `timeout`
+    fn wait_for_completion(
+        &self,
+        jenkins_client: &Jenkins,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Self, Error>
+    where
+        for<'de> Self: Deserialize<'de>,
+        Self: Sized,
+    {
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build { .. } = path {
+            let start = Instant::now();
+            loop {
+                let state: BuildingState = jenkins_client
+                    .get_with_params(&path, [("tree", "building")])?
+                    .json()?;
+                if !state.building {
+                    return Ok(jenkins_client.get(&path)?.json()?);
+                }
+
+                let waited = start.elapsed();
+                if waited >= timeout {
+                    return Err(client::Error::WaitTimeout {
+                        waiting_for: client::error::ExpectedType::Build,
+                        waited,
+                    }
+                    .into());
+                }
+                thread::sleep(std::cmp::min(poll_interval, timeout - waited));
+            }
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+
+    /// Gather everything about a `Build` in a report that can be archived, fetching the sections
+    /// selected in `options` concurrently
+    fn full_report(
+        &self,
+        jenkins_client: &Jenkins,
+        options: &ReportOptions,
+    ) -> Result<BuildReport, Error>
+    where
+        Self: Sync,
+    {
+        build_report(self, jenkins_client, options)
+    }
+
+    /// Get the artifacts of a `Build`, both archived files and artifacts recorded by maven
+    fn artifact_records(&self, jenkins_client: &Jenkins) -> Result<Vec<Artifact>, Error> {
+        let mut artifacts = self.artifacts().to_vec();
+
+        let has_maven_record = self
+            .actions()
+            .iter()
+            .filter_map(|action| action.class.as_ref())
+            .any(|class| {
+                class == MavenArtifactRecord::with_class()
+                    || class == MavenAggregatedArtifactRecord::with_class()
+            });
+        if !has_maven_record {
+            return Ok(artifacts);
+        }
+
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Build {
+            job_name,
+            number,
+            configuration,
+        } = path
+        {
+            let record: CommonAction = jenkins_client
+                .get(&Path::MavenArtifactRecord {
+                    job_name,
+                    number,
+                    configuration,
+                })?
+                .json()?;
+            if let Ok(record) = record.as_variant::<MavenArtifactRecord>() {
+                artifacts.push(Artifact::from(&record.pom_artifact));
+                artifacts.extend(record.main_artifact.as_ref().map(Artifact::from));
+                artifacts.extend(record.attached_artifacts.iter().map(Artifact::from));
+            } else {
+                let aggregated = record.as_variant::<MavenAggregatedArtifactRecord>()?;
+                for module_record in &aggregated.module_records {
+                    artifacts.push(Artifact::from(&module_record.pom_artifact));
+                    artifacts.extend(module_record.main_artifact.as_ref().map(Artifact::from));
+                    artifacts.extend(module_record.attached_artifacts.iter().map(Artifact::from));
+                }
+            }
+            Ok(artifacts)
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+}
+
+/// Common trait for builds recording the changes they built
+pub trait BuildWithChangeSets: Build {
+    /// Lists of changes of the `Build`, one for each SCM checked out
+    fn change_set_lists(&self) -> Vec<&CommonChangeSetList>;
+
+    /// Changes of the `Build`, from all its SCMs. Use `as_variant` to read them as
+    /// `GitChangeSet`, `SubversionLogEntry`, ...
+    fn change_sets(&self) -> Vec<&CommonChangeSet> {
+        self.change_set_lists()
+            .into_iter()
+            .flat_map(|list| list.items.iter())
+            .collect()
+    }
+}
+
+/// Parameters of a build, as they can be sent to trigger it again
+fn rebuild_parameters(actions: &[CommonAction]) -> Result<Vec<(String, String)>, Error> {
+    let mut parameters = vec![];
+    for action in actions {
+        if let Ok(action) = action.as_variant::<ParametersAction>() {
+            for parameter in action.parameters {
+                let value = if let Ok(run) = parameter.as_variant::<RunParameterValue>() {
+                    Some(format!("{}#{}", run.job_name, run.number))
+                } else {
+                    match parameter.value() {
+                        Some(serde_json::Value::String(value)) => Some(value.clone()),
+                        Some(serde_json::Value::Bool(value)) => Some(value.to_string()),
+                        Some(serde_json::Value::Number(value)) => Some(value.to_string()),
+                        _ => None,
+                    }
+                };
+                match value {
+                    Some(value) => parameters.push((parameter.name, value)),
+                    None => {
+                        return Err(client::Error::UnsupportedParameter {
+                            name: parameter.name,
+                            class: parameter.class.unwrap_or_else(|| "unknown".to_string()),
+                        }
+                        .into())
+                    }
+                }
+            }
+        }
+    }
+    Ok(parameters)
 }
 
 macro_rules! build_with_common_fields_and_impl {
@@ -268,12 +898,12 @@ macro_rules! build_with_common_fields_and_impl {
             pub url: String,
             /// Build number for this job
             pub number: u32,
-            /// Duration
-            pub duration: i64,
-            /// Estimated duration
-            pub estimated_duration: i64,
-            /// Timestamp of the build start
-            pub timestamp: u64,
+            /// Duration, `0` while the build is running
+            pub duration: $crate::helpers::MaybeDuration,
+            /// Estimated duration, unknown if the job has no successful build
+            pub estimated_duration: $crate::helpers::MaybeDuration,
+            /// Timestamp of the build start, unknown while the build is queued
+            pub timestamp: $crate::helpers::MaybeTimestamp,
             /// Are the logs kept?
             pub keep_log: bool,
             /// Build result
@@ -291,8 +921,10 @@ macro_rules! build_with_common_fields_and_impl {
             /// ID while in the build queue
             pub queue_id: i32,
             /// Build actions
+            #[serde(default)]
             pub actions: Vec<CommonAction>,
             /// Artifacts saved by archived by this build
+            #[serde(default)]
             pub artifacts: Vec<Artifact>,
             $(
                 $(#[$field_attr])*
@@ -308,6 +940,34 @@ macro_rules! build_with_common_fields_and_impl {
             fn url(&self) -> &str {
                 &self.url
             }
+
+            fn artifacts(&self) -> &[Artifact] {
+                &self.artifacts
+            }
+
+            fn actions(&self) -> &[CommonAction] {
+                &self.actions
+            }
+
+            fn description(&self) -> Option<&str> {
+                self.description.as_deref()
+            }
+
+            fn display_name(&self) -> &str {
+                &self.display_name
+            }
+
+            fn result(&self) -> Option<BuildStatus> {
+                self.result
+            }
+
+            fn timestamp(&self) -> u64 {
+                self.timestamp.millis()
+            }
+
+            fn duration(&self) -> $crate::helpers::MaybeDuration {
+                self.duration
+            }
         }
     };
 }
@@ -329,4 +989,690 @@ build_with_common_fields_and_impl!(
 );
 specialize!(CommonBuild => Build);
 
-impl CommonBuild {}
+impl CommonBuild {
+    /// Replay this build if it is a pipeline run, see `WorkflowRun::replay`
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::InvalidObjectType`](../client/enum.Error.html#variant.InvalidObjectType)
+    /// if the build is not a `WorkflowRun`
+    pub fn replay(&self, jenkins_client: &Jenkins, script: Option<&str>) -> Result<(), Error> {
+        match self.class.as_deref() {
+            Some(class) if class == WorkflowRun::with_class() => {
+                super::pipeline::replay(jenkins_client, &self.url, script)
+            }
+            class => Err(client::Error::InvalidObjectType {
+                object_type: client::error::ExpectedType::Build,
+                variant_name: class.unwrap_or("unknown").to_string(),
+                action: client::error::Action::Replay,
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::{last_lines, Build, CommonBuild};
+
+    /// JSON of a finished free style build, with `fields` added or replacing the defaults
+    fn build_json(url: &str, number: u32, fields: serde_json::Value) -> serde_json::Value {
+        let mut build = serde_json::json!({
+            "_class": "hudson.model.FreeStyleBuild",
+            "url": url,
+            "number": number,
+            "duration": 10,
+            "estimatedDuration": 10,
+            "timestamp": 1500000000000u64,
+            "keepLog": false,
+            "result": "SUCCESS",
+            "displayName": number.to_string(),
+            "description": null,
+            "building": false,
+            "id": number.to_string(),
+            "queueId": number
+        });
+        if let (Some(build), serde_json::Value::Object(fields)) = (build.as_object_mut(), fields) {
+            build.extend(fields);
+        }
+        build
+    }
+
+    fn build_with(url: &str, number: u32, fields: serde_json::Value) -> CommonBuild {
+        serde_json::from_value(build_json(url, number, fields)).unwrap()
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Parameters {
+        #[serde(rename = "bool-param")]
+        bool_param: bool,
+        #[serde(rename = "free string param")]
+        free_string_param: String,
+        count: u32,
+        missing: Option<String>,
+    }
+
+    #[test]
+    fn can_read_parameters_as_struct() {
+        let build: CommonBuild = serde_json::from_str(
+            r#"{
+                "_class": "hudson.model.FreeStyleBuild",
+                "url": "http://none:8080/job/parameterized%20job/1/",
+                "number": 1,
+                "duration": 10,
+                "estimatedDuration": 10,
+                "timestamp": 1500000000000,
+                "keepLog": false,
+                "result": "SUCCESS",
+                "displayName": "1",
+                "building": false,
+                "id": "1",
+                "queueId": 1,
+                "actions": [
+                    {"_class": "hudson.model.CauseAction", "causes": []},
+                    {
+                        "_class": "hudson.model.ParametersAction",
+                        "parameters": [
+                            {
+                                "_class": "hudson.model.BooleanParameterValue",
+                                "name": "bool-param",
+                                "value": true
+                            },
+                            {
+                                "_class": "hudson.model.StringParameterValue",
+                                "name": "free string param",
+                                "value": "my string & param"
+                            },
+                            {
+                                "_class": "hudson.model.StringParameterValue",
+                                "name": "count",
+                                "value": "3"
+                            },
+                            {
+                                "_class": "hudson.model.PasswordParameterValue",
+                                "name": "secret"
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            build.parameters_as::<Parameters>().unwrap(),
+            Parameters {
+                bool_param: true,
+                free_string_param: "my string & param".to_string(),
+                count: 3,
+                missing: None,
+            }
+        );
+    }
+
+    #[test]
+    fn can_get_last_lines() {
+        let console = "Started by user admin\nBuilding\nFailed\nFinished: FAILURE\n";
+        assert_eq!(last_lines(console, 2), "Failed\nFinished: FAILURE\n");
+        assert_eq!(last_lines(console, 4), console);
+        assert_eq!(last_lines(console, 100), console);
+        assert_eq!(last_lines(console, 0), "");
+        assert_eq!(last_lines("one\ntwo", 1), "two");
+        assert_eq!(last_lines("one\r\ntwo\r\n", 1), "two\r\n");
+        assert_eq!(last_lines("", 3), "");
+    }
+
+    #[test]
+    fn can_get_plain_description() {
+        let mut build = build_with(
+            "http://none:8080/job/job/1/",
+            1,
+            serde_json::json!({
+                "description": "Deployed <b>v1.2</b> to <a href=\"https://prod\">prod</a><br/>Tests: 3 &lt; 5 &amp; ok&#33;<script>alert(1)</script>"
+            }),
+        );
+
+        assert_eq!(
+            build.description_plain(),
+            Some("Deployed v1.2 to prod\nTests: 3 < 5 & ok!".to_string())
+        );
+        assert!(build.description().unwrap().contains("<b>"));
+
+        build.description = Some("<p>first</p><p>second &unknown; & more</p>".to_string());
+        assert_eq!(
+            build.description_plain(),
+            Some("first\nsecond &unknown; & more".to_string())
+        );
+
+        build.description = None;
+        assert_eq!(build.description_plain(), None);
+    }
+
+    #[test]
+    fn can_get_result_of_running_build() {
+        let build = build_with(
+            "http://none:8080/job/job/2/",
+            2,
+            serde_json::json!({ "duration": 0, "result": null, "building": true }),
+        );
+        assert_eq!(build.result(), None);
+
+        let build = build_with(
+            "http://none:8080/job/job/1/",
+            1,
+            serde_json::json!({ "result": "NOT_BUILT" }),
+        );
+        assert_eq!(build.result(), Some(super::BuildStatus::NotBuilt));
+    }
+
+    #[test]
+    fn can_get_env_vars() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/env-vars", mockito::server_url()))
+                .build()
+                .unwrap();
+        let build = |number: u32| {
+            build_with(
+                &format!("{}/env-vars/job/job/{}/", mockito::server_url(), number),
+                number,
+                serde_json::json!({}),
+            )
+        };
+        let _injected = mockito::mock(
+            "GET",
+            "/env-vars/job/job/1/injectedEnvVars/api/json?depth=1",
+        )
+        .with_body(
+            r#"{"_class":"org.jenkinsci.plugins.envinject.EnvInjectVarList",
+                "envMap":{"BUILD_NUMBER":"1","GIT_BRANCH":"origin/master"}}"#,
+        )
+        .create();
+        let _missing = mockito::mock(
+            "GET",
+            "/env-vars/job/job/2/injectedEnvVars/api/json?depth=1",
+        )
+        .with_status(404)
+        .create();
+
+        let env_vars = build(1).get_env_vars(&jenkins_client).unwrap();
+        assert_eq!(env_vars.len(), 2);
+        assert_eq!(env_vars["GIT_BRANCH"], "origin/master");
+
+        match build(2)
+            .get_env_vars(&jenkins_client)
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::NoInjectedEnvVars { build_url }) => {
+                assert!(build_url.ends_with("/env-vars/job/job/2/"))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    fn build_with_parameters(base: &str, parameters: &str) -> CommonBuild {
+        let parameters: serde_json::Value =
+            serde_json::from_str(&format!("[{}]", parameters)).unwrap();
+        build_with(
+            &format!("{}{}/job/job/3/", mockito::server_url(), base),
+            3,
+            serde_json::json!({
+                "actions": [{"_class": "hudson.model.ParametersAction", "parameters": parameters}]
+            }),
+        )
+    }
+
+    #[test]
+    fn can_rebuild_with_same_parameters() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/rebuild", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let build = build_with_parameters(
+            "/rebuild",
+            r#"{"_class": "hudson.model.StringParameterValue", "name": "branch", "value": "main"},
+            {"_class": "hudson.model.BooleanParameterValue", "name": "deploy", "value": true},
+            {"_class": "hudson.model.RunParameterValue", "name": "upstream",
+             "jobName": "upstream", "number": "12"}"#,
+        );
+        let trigger = mockito::mock("POST", "/rebuild/job/job/buildWithParameters")
+            .match_body("branch=main&deploy=true&upstream=upstream%2312")
+            .with_status(201)
+            .with_header("Location", "http://none/queue/item/8/")
+            .create();
+
+        let queue_item = build.rebuild(&jenkins_client).unwrap();
+        assert_eq!(queue_item.url, "http://none/queue/item/8/");
+        trigger.assert();
+    }
+
+    #[test]
+    fn can_rebuild_without_parameters() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/rebuild-plain", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let build = build_with_parameters("/rebuild-plain", "");
+        let trigger = mockito::mock("POST", "/rebuild-plain/job/job/build")
+            .with_status(201)
+            .with_header("Location", "http://none/queue/item/9/")
+            .create();
+
+        assert_eq!(
+            build.rebuild(&jenkins_client).unwrap().url,
+            "http://none/queue/item/9/"
+        );
+        trigger.assert();
+    }
+
+    #[test]
+    fn cant_rebuild_with_file_parameter() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .build()
+            .unwrap();
+        let build = build_with_parameters(
+            "/rebuild-file",
+            r#"{"_class": "hudson.model.StringParameterValue", "name": "branch", "value": "main"},
+            {"_class": "hudson.model.FileParameterValue", "name": "archive"}"#,
+        );
+
+        match build
+            .rebuild(&jenkins_client)
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::UnsupportedParameter { name, class }) => {
+                assert_eq!(name, "archive");
+                assert_eq!(class, "hudson.model.FileParameterValue");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_get_causes() {
+        use crate::action::causes::{UpstreamCause, UserIdCause};
+
+        let build = build_with(
+            "http://none:8080/job/job%20name/7/",
+            7,
+            serde_json::json!({
+                "actions": [
+                    {
+                        "_class": "hudson.model.CauseAction",
+                        "causes": [
+                            {
+                                "_class": "hudson.model.Cause$UpstreamCause",
+                                "shortDescription": "Started by upstream project \"upstream job\" build number 4",
+                                "upstreamBuild": 4,
+                                "upstreamProject": "upstream job",
+                                "upstreamUrl": "job/upstream%20job/"
+                            }
+                        ]
+                    },
+                    {},
+                    {
+                        "_class": "hudson.model.CauseAction",
+                        "causes": [
+                            {
+                                "_class": "hudson.model.Cause$UserIdCause",
+                                "shortDescription": "Started by user user",
+                                "userId": "user",
+                                "userName": "user"
+                            }
+                        ]
+                    }
+                ]
+            }),
+        );
+
+        let causes = build.get_causes().unwrap();
+        assert_eq!(causes.len(), 2);
+        let upstream = causes[0].as_variant::<UpstreamCause>().unwrap();
+        assert_eq!(upstream.upstream_project, "upstream job");
+        assert_eq!(upstream.upstream_build, 4);
+        assert!(causes[1].as_variant::<UserIdCause>().is_ok());
+    }
+
+    #[test]
+    fn can_get_git_revision() {
+        let build = build_with(
+            "http://none:8080/job/job/1/",
+            1,
+            serde_json::json!({
+                "actions": [
+                    {"_class": "hudson.model.CauseAction", "causes": []},
+                    {
+                        "_class": "hudson.plugins.git.util.BuildData",
+                        "buildsByBranchName": {},
+                        "lastBuiltRevision": {
+                            "SHA1": "9e8f6f0c1b2a",
+                            "branch": [{"SHA1": "9e8f6f0c1b2a", "name": "refs/remotes/origin/master"}]
+                        },
+                        "remoteUrls": ["https://github.com/example/repo.git"],
+                        "scmName": ""
+                    }
+                ]
+            }),
+        );
+        assert_eq!(build.git_revision(), Some("9e8f6f0c1b2a".to_string()));
+
+        let build = build_with("http://none:8080/job/job/2/", 2, serde_json::json!({}));
+        assert_eq!(build.git_revision(), None);
+    }
+
+    #[test]
+    fn can_read_unknown_estimated_duration() {
+        let build = build_with(
+            "http://none:8080/job/job/1/",
+            1,
+            serde_json::json!({
+                "duration": 0,
+                "estimatedDuration": -1,
+                "result": null,
+                "building": true
+            }),
+        );
+        assert_eq!(
+            build.duration.as_duration(),
+            Some(std::time::Duration::from_millis(0))
+        );
+        assert_eq!(build.estimated_duration.millis(), -1);
+        assert_eq!(build.estimated_duration.as_duration(), None);
+    }
+
+    fn finished_build() -> CommonBuild {
+        build_with(
+            "http://none:8080/job/job/1/",
+            1,
+            serde_json::json!({
+                "duration": 61250,
+                "estimatedDuration": 60000,
+                "timestamp": 1500000000123u64
+            }),
+        )
+    }
+
+    #[test]
+    fn can_get_duration_as_duration() {
+        let build = finished_build();
+        assert_eq!(
+            build.duration_as_duration(),
+            std::time::Duration::from_millis(61250)
+        );
+
+        let mut unknown = build.clone();
+        unknown.duration = crate::helpers::MaybeDuration::Unknown;
+        assert_eq!(
+            unknown.duration_as_duration(),
+            std::time::Duration::from_secs(0)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn can_get_timestamp_as_datetime() {
+        use chrono::{TimeZone, Utc};
+
+        let build = finished_build();
+        assert_eq!(
+            build.timestamp_datetime().unwrap(),
+            Utc.ymd(2017, 7, 14).and_hms_milli(2, 40, 0, 123)
+        );
+
+        let mut out_of_range = build.clone();
+        out_of_range.timestamp = crate::helpers::MaybeTimestamp::Known(u64::max_value());
+        match out_of_range
+            .timestamp_datetime()
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::InvalidTimestamp { timestamp }) => {
+                assert_eq!(timestamp, u64::max_value())
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_wait_for_completion() {
+        use crate::helpers::test_server::{response, serve_in_order};
+
+        let build = |url: &str, building: bool, result: serde_json::Value| {
+            build_json(
+                &format!("{}/job/short/3/", url),
+                3,
+                serde_json::json!({ "duration": 0, "result": result, "building": building }),
+            )
+        };
+        let url = serve_in_order(vec![
+            response("200 OK", "", r#"{"building": true}"#),
+            response("200 OK", "", r#"{"building": false}"#),
+            response(
+                "200 OK",
+                "",
+                &build("http://none", false, "UNSTABLE".into()).to_string(),
+            ),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let running: CommonBuild =
+            serde_json::from_value(build(&url, true, serde_json::Value::Null)).unwrap();
+
+        let done = running
+            .wait_for_completion(
+                &jenkins_client,
+                std::time::Duration::from_millis(1),
+                std::time::Duration::from_secs(5),
+            )
+            .unwrap();
+        assert!(!done.building);
+        assert_eq!(done.result(), Some(super::BuildStatus::Unstable));
+    }
+
+    #[test]
+    fn can_keep_build_forever() {
+        use crate::helpers::test_server::{response, serve_in_order};
+
+        let build = |url: &str, keep_log: bool| {
+            build_json(
+                &format!("{}/job/kept/4/", url),
+                4,
+                serde_json::json!({ "keepLog": keep_log }),
+            )
+        };
+        let url = serve_in_order(vec![
+            response("200 OK", "", r#"{"keepLog": false}"#),
+            response("200 OK", "", ""),
+            response("200 OK", "", &build("http://none", true).to_string()),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .build()
+            .unwrap();
+        let build: CommonBuild = serde_json::from_value(build(&url, false)).unwrap();
+
+        build.keep_forever(&jenkins_client).unwrap();
+        let refreshed: CommonBuild = jenkins_client
+            .get(&jenkins_client.url_to_path(&build.url))
+            .unwrap()
+            .json()
+            .unwrap();
+        assert!(refreshed.keep_log);
+    }
+
+    #[test]
+    fn only_toggles_keeping_build_when_needed() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/keep-log", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let build = build_with(
+            &format!("{}/keep-log/job/job/5/", mockito::server_url()),
+            5,
+            serde_json::json!({}),
+        );
+        let _state = mockito::mock("GET", "/keep-log/job/job/5/api/json?tree=keepLog")
+            .with_body(r#"{"_class":"hudson.model.FreeStyleBuild","keepLog":true}"#)
+            .create();
+        let toggle = mockito::mock("POST", "/keep-log/job/job/5/toggleLogKeep")
+            .expect(1)
+            .create();
+
+        build.keep_forever(&jenkins_client).unwrap();
+        build.dont_keep_forever(&jenkins_client).unwrap();
+        toggle.assert();
+    }
+
+    #[test]
+    fn artifact_records_skip_missing_main_artifacts() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/maven-records", mockito::server_url()))
+                .build()
+                .unwrap();
+        let build = build_with(
+            &format!("{}/maven-records/job/job/7/", mockito::server_url()),
+            7,
+            serde_json::json!({
+                "actions": [{"_class": "hudson.maven.reporters.MavenAggregatedArtifactRecord"}]
+            }),
+        );
+        let artifact = |artifact_id: &str, packaging: &str| {
+            serde_json::json!({
+                "artifactId": artifact_id,
+                "canonicalName": format!("{}-1.0.{}", artifact_id, packaging),
+                "classifier": null,
+                "fileName": format!("{}-1.0.{}", artifact_id, packaging),
+                "groupId": "org.example",
+                "md5sum": "d41d8cd98f00b204e9800998ecf8427e",
+                "type": packaging,
+                "version": "1.0"
+            })
+        };
+        let module_record = |artifact_id: &str, main_artifact: Option<serde_json::Value>| {
+            let mut record = serde_json::json!({
+                "url": format!("http://none/job/job/7/org.example${}/", artifact_id),
+                "attachedArtifacts": [],
+                "parent": {
+                    "_class": "hudson.maven.MavenBuild",
+                    "number": 7,
+                    "url": "http://none/job/job/7/"
+                },
+                "pomArtifact": artifact(artifact_id, "pom")
+            });
+            if let Some(main_artifact) = main_artifact {
+                record["mainArtifact"] = main_artifact;
+            }
+            record
+        };
+        let _record = mockito::mock(
+            "GET",
+            "/maven-records/job/job/7/mavenArtifacts/api/json?depth=1",
+        )
+        .with_body(
+            serde_json::json!({
+                "_class": "hudson.maven.reporters.MavenAggregatedArtifactRecord",
+                "moduleRecords": [
+                    module_record("parent", None),
+                    module_record("core", Some(artifact("core", "jar")))
+                ]
+            })
+            .to_string(),
+        )
+        .create();
+
+        let artifacts = build.artifact_records(&jenkins_client).unwrap();
+        assert_eq!(
+            artifacts
+                .iter()
+                .map(|artifact| artifact.relative_path.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "org.example/parent/1.0/parent-1.0.pom",
+                "org.example/core/1.0/core-1.0.pom",
+                "org.example/core/1.0/core-1.0.jar",
+            ]
+        );
+    }
+
+    fn described_build(url: &str, display_name: &str, description: Option<&str>) -> String {
+        build_json(
+            url,
+            6,
+            serde_json::json!({ "displayName": display_name, "description": description }),
+        )
+        .to_string()
+    }
+
+    #[test]
+    fn can_set_build_description() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/describe", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let url = format!("{}/describe/job/job/6/", mockito::server_url());
+        let description = "Déployé en <b>production</b> ✅";
+        let build: CommonBuild = serde_json::from_str(&described_build(&url, "#6", None)).unwrap();
+
+        let submit = mockito::mock("POST", "/describe/job/job/6/submitDescription")
+            .match_body(
+                serde_urlencoded::to_string([("description", description)])
+                    .unwrap()
+                    .as_str(),
+            )
+            .create();
+        let _build = mockito::mock("GET", "/describe/job/job/6/api/json?depth=1")
+            .with_body(described_build(&url, "#6", Some(description)))
+            .create();
+
+        build.set_description(&jenkins_client, description).unwrap();
+        submit.assert();
+        let refreshed: CommonBuild = jenkins_client
+            .get(&jenkins_client.url_to_path(&build.url))
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(refreshed.description(), Some(description));
+    }
+
+    #[test]
+    fn setting_display_name_keeps_description() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/rename-build", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let url = format!("{}/rename-build/job/job/6/", mockito::server_url());
+        let build: CommonBuild =
+            serde_json::from_str(&described_build(&url, "#6", Some("old"))).unwrap();
+        assert_eq!(build.display_name(), "#6");
+
+        let _description =
+            mockito::mock("GET", "/rename-build/job/job/6/api/json?tree=description")
+                .with_body(r#"{"_class":"hudson.model.FreeStyleBuild","description":"kept"}"#)
+                .create();
+        let json = serde_json::json!({ "displayName": "release 1.0 🚀", "description": "kept" })
+            .to_string();
+        let submit = mockito::mock("POST", "/rename-build/job/job/6/configSubmit")
+            .match_body(
+                serde_urlencoded::to_string([
+                    ("displayName", "release 1.0 🚀"),
+                    ("description", "kept"),
+                    ("json", &json),
+                ])
+                .unwrap()
+                .as_str(),
+            )
+            .create();
+
+        build
+            .set_display_name(&jenkins_client, "release 1.0 🚀")
+            .unwrap();
+        submit.assert();
+    }
+}
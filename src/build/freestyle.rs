@@ -2,7 +2,7 @@ use serde::Deserialize;
 
 use crate::helpers::Class;
 
-use super::{Artifact, Build, BuildStatus};
+use super::{Artifact, Build, BuildStatus, BuildWithChangeSets};
 use crate::action::CommonAction;
 use crate::changeset;
 use crate::job::FreeStyleProject;
@@ -24,3 +24,9 @@ build_with_common_fields_and_impl!(
 register_class!("hudson.model.FreeStyleBuild" => FreeStyleBuild);
 
 impl FreeStyleBuild {}
+
+impl BuildWithChangeSets for FreeStyleBuild {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        vec![&self.change_set]
+    }
+}
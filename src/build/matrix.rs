@@ -2,7 +2,7 @@ use serde::Deserialize;
 
 use crate::helpers::Class;
 
-use super::{Artifact, Build, BuildStatus, ShortBuild};
+use super::{Artifact, Build, BuildStatus, BuildWithChangeSets, ShortBuild};
 use crate::action::CommonAction;
 use crate::changeset;
 use crate::job::{MatrixConfiguration, MatrixProject};
@@ -27,6 +27,12 @@ register_class!("hudson.matrix.MatrixBuild" => MatrixBuild);
 
 impl MatrixBuild {}
 
+impl BuildWithChangeSets for MatrixBuild {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        vec![&self.change_set]
+    }
+}
+
 build_with_common_fields_and_impl!(
     /// A `Build` from a MatrixConfiguration
     #[derive(Deserialize, Debug)]
@@ -43,3 +49,9 @@ build_with_common_fields_and_impl!(
 register_class!("hudson.matrix.MatrixRun" => MatrixRun);
 
 impl MatrixRun {}
+
+impl BuildWithChangeSets for MatrixRun {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        vec![&self.change_set]
+    }
+}
@@ -4,7 +4,7 @@ use serde::Deserialize;
 
 use crate::helpers::Class;
 
-use super::{Artifact, Build, BuildStatus};
+use super::{Artifact, Build, BuildStatus, BuildWithChangeSets};
 use crate::action::CommonAction;
 use crate::changeset;
 use crate::job::{MavenModule, MavenModuleSet};
@@ -31,6 +31,12 @@ register_class!("hudson.maven.MavenModuleSetBuild" => MavenModuleSetBuild);
 
 impl MavenModuleSetBuild {}
 
+impl BuildWithChangeSets for MavenModuleSetBuild {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        vec![&self.change_set]
+    }
+}
+
 build_with_common_fields_and_impl!(
     /// A `Build` of a MavenModule
     #[derive(Deserialize, Debug)]
@@ -49,3 +55,9 @@ build_with_common_fields_and_impl!(
 register_class!("hudson.maven.MavenBuild" => MavenBuild);
 
 impl MavenBuild {}
+
+impl BuildWithChangeSets for MavenBuild {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        vec![&self.change_set]
+    }
+}
@@ -1,26 +1,64 @@
 //! Jenkins Builds
 
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use failure::Error;
+use serde::Deserialize;
 
 use crate::client_internals::path::{Name, Path};
+use crate::helpers::{MaybeDuration, MaybeTimestamp};
 use crate::job::JobName;
 use crate::Jenkins;
 
 #[macro_use]
 mod common;
-pub use self::common::{Artifact, Build, BuildNumber, BuildStatus, CommonBuild, ShortBuild};
+pub use self::common::{
+    Artifact, Build, BuildNumber, BuildStatus, BuildWithChangeSets, CommonBuild, ShortBuild,
+};
 mod flow;
 pub use self::flow::BuildFlowRun;
 mod freestyle;
 pub use self::freestyle::FreeStyleBuild;
 mod pipeline;
-pub use self::pipeline::WorkflowRun;
+pub use self::pipeline::{ArtifactProvenance, Provenance, WorkflowRun};
 mod matrix;
 pub use self::matrix::{MatrixBuild, MatrixRun};
 mod maven;
 pub use self::maven::{MavenBuild, MavenModuleSetBuild};
 mod multijob;
 pub use self::multijob::MultiJobBuild;
+mod report;
+pub use self::report::{BuildReport, ReportConsole, ReportOptions, ReportParameter, TestSummary};
+mod retention;
+pub use self::retention::{RetentionReason, RetentionStatus};
+mod timeline;
+pub use self::timeline::{TimelineEvent, TimelineEventKind};
+mod test_report;
+pub use self::test_report::{SuiteResult, TestCase, TestReport, TestStatus};
+
+/// A build with only its timing, to find the build running at a given time
+#[derive(Debug, Deserialize)]
+struct BuildTiming {
+    number: u32,
+    timestamp: MaybeTimestamp,
+    duration: MaybeDuration,
+    building: bool,
+}
+
+impl BuildTiming {
+    /// Was the build running at `time`, in milliseconds since epoch. A build with an unknown
+    /// start, or finished with an unknown duration, covers no time
+    fn covers(&self, time: u64) -> bool {
+        let time = MaybeTimestamp::Known(time);
+        self.timestamp <= time && (self.building || time < self.timestamp + self.duration)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AllBuilds {
+    all_builds: Vec<BuildTiming>,
+}
 
 impl Jenkins {
     /// Get a build from a `job_name` and `build_number`
@@ -37,4 +75,179 @@ impl Jenkins {
             })?
             .json()?)
     }
+
+    /// Delete the build `build_number` of `job_name`
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::BuildStillRunning`](../client/enum.Error.html#variant.BuildStillRunning)
+    /// if the build is running
+    pub fn delete_build<'a, J, B>(&self, job_name: J, build_number: B) -> Result<(), Error>
+    where
+        J: Into<JobName<'a>>,
+        B: Into<BuildNumber>,
+    {
+        common::delete_build(
+            self,
+            Path::Build {
+                job_name: Name::Name(job_name.into().0),
+                number: build_number.into(),
+                configuration: None,
+            },
+        )
+    }
+
+    /// Get a build from a `job_name` and `build_number`, overriding the depth set when building
+    /// the client
+    pub fn get_build_with_depth<'a, J, B>(
+        &self,
+        job_name: J,
+        build_number: B,
+        depth: u8,
+    ) -> Result<CommonBuild, Error>
+    where
+        J: Into<JobName<'a>>,
+        B: Into<BuildNumber>,
+    {
+        Ok(self
+            .get_with_depth(
+                &Path::Build {
+                    job_name: Name::Name(job_name.into().0),
+                    number: build_number.into(),
+                    configuration: None,
+                },
+                depth,
+            )?
+            .json()?)
+    }
+
+    /// Find the build of `job_name` that was running at `time`, or `None` if no build covers
+    /// that instant. If several builds were running at that time, the last one started is
+    /// returned
+    pub fn find_build_at<'a, J>(
+        &self,
+        job_name: J,
+        time: SystemTime,
+    ) -> Result<Option<CommonBuild>, Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        let job_name = job_name.into().0;
+        let time = match time.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_millis() as u64,
+            Err(_) => return Ok(None),
+        };
+        let builds: AllBuilds = self
+            .get_with_params(
+                &Path::Job {
+                    name: Name::Name(job_name),
+                    configuration: None,
+                },
+                [("tree", "allBuilds[number,timestamp,duration,building]")],
+            )?
+            .json()?;
+        match builds
+            .all_builds
+            .iter()
+            .filter(|build| build.covers(time))
+            .max_by_key(|build| build.timestamp.millis())
+        {
+            Some(build) => Ok(Some(self.get_build(job_name, build.number)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn can_delete_build_unless_running() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/delete-build", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let _done = mockito::mock("GET", "/delete-build/job/job/1/api/json?tree=building")
+            .with_body(r#"{"_class":"hudson.model.FreeStyleBuild","building":false}"#)
+            .create();
+        let _running = mockito::mock("GET", "/delete-build/job/job/2/api/json?tree=building")
+            .with_body(r#"{"_class":"hudson.model.FreeStyleBuild","building":true}"#)
+            .create();
+        let delete_done = mockito::mock("POST", "/delete-build/job/job/1/doDelete").create();
+        let delete_running = mockito::mock("POST", "/delete-build/job/job/2/doDelete")
+            .expect(0)
+            .create();
+
+        jenkins_client.delete_build("job", 1).unwrap();
+        match jenkins_client
+            .delete_build("job", 2)
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::BuildStillRunning { build_url }) => assert_eq!(
+                build_url,
+                format!("{}/delete-build/job/job/2/", mockito::server_url())
+            ),
+            other => panic!("unexpected result {:?}", other),
+        }
+        delete_done.assert();
+        delete_running.assert();
+    }
+
+    #[test]
+    fn can_find_build_at_time() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/build-at", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _builds = mockito::mock(
+            "GET",
+            "/build-at/job/job/api/json?tree=allBuilds%5Bnumber%2Ctimestamp%2Cduration%2Cbuilding%5D",
+        )
+        .with_body(
+            r#"{"allBuilds": [
+                {"number": 4, "timestamp": 0, "duration": 0, "building": true},
+                {"number": 3, "timestamp": 5000, "duration": 0, "building": true},
+                {"number": 2, "timestamp": 2000, "duration": 1000, "building": false},
+                {"number": 1, "timestamp": 1000, "duration": 500, "building": false}
+            ]}"#,
+        )
+        .create();
+        let build = mockito::mock("GET", "/build-at/job/job/2/api/json?depth=1")
+            .with_body(
+                r#"{
+                    "_class": "hudson.model.FreeStyleBuild",
+                    "url": "http://none:8080/job/job/2/",
+                    "number": 2,
+                    "duration": 1000,
+                    "estimatedDuration": 1000,
+                    "timestamp": 2000,
+                    "keepLog": false,
+                    "result": "SUCCESS",
+                    "displayName": "2",
+                    "description": null,
+                    "building": false,
+                    "id": "2",
+                    "queueId": 2
+                }"#,
+            )
+            .create();
+
+        let at = |millis| UNIX_EPOCH + Duration::from_millis(millis);
+        let found = jenkins_client.find_build_at("job", at(2500)).unwrap();
+        build.assert();
+        assert_eq!(found.unwrap().number, 2);
+        assert!(jenkins_client
+            .find_build_at("job", at(1700))
+            .unwrap()
+            .is_none());
+        assert!(jenkins_client
+            .find_build_at("job", at(500))
+            .unwrap()
+            .is_none());
+    }
 }
@@ -2,7 +2,7 @@ use serde::Deserialize;
 
 use crate::helpers::Class;
 
-use super::{Artifact, Build, BuildStatus};
+use super::{Artifact, Build, BuildStatus, BuildWithChangeSets};
 use crate::action::CommonAction;
 /* use build::ShortBuild; */
 use crate::changeset;
@@ -28,6 +28,12 @@ register_class!("com.tikal.jenkins.plugins.multijob.MultiJobBuild" => MultiJobBu
 
 impl MultiJobBuild {}
 
+impl BuildWithChangeSets for MultiJobBuild {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        vec![&self.change_set]
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 /// A sub-build from a multi job project
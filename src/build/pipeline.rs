@@ -1,11 +1,15 @@
+use failure::Error;
 use serde::Deserialize;
 
-use crate::helpers::Class;
+use crate::helpers::{wildcard_matches, Class, MaybeTimestamp};
 
-use super::{Artifact, Build, BuildStatus, ShortBuild};
+use super::{Artifact, Build, BuildStatus, BuildWithChangeSets, ShortBuild};
 use crate::action::CommonAction;
 use crate::changeset;
+use crate::client;
+use crate::client_internals::{Name, Path};
 use crate::job::WorkflowJob;
+use crate::Jenkins;
 
 build_with_common_fields_and_impl!(
     /// A `Build` from a WorkflowJob
@@ -20,4 +24,475 @@ build_with_common_fields_and_impl!(
 );
 register_class!("org.jenkinsci.plugins.workflow.job.WorkflowRun" => WorkflowRun);
 
-impl WorkflowRun {}
+impl BuildWithChangeSets for WorkflowRun {
+    fn change_set_lists(&self) -> Vec<&changeset::CommonChangeSetList> {
+        self.change_sets.iter().collect()
+    }
+}
+
+/// Where an `Artifact` of a `WorkflowRun` comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Provenance {
+    /// Archived by an `archiveArtifacts` step whose pattern matches the artifact path
+    ArchiveStep {
+        /// Id of the flow node of the step
+        node_id: String,
+        /// Artifacts pattern of the step
+        pattern: String,
+        /// Timestamp of the start of the step
+        timestamp: MaybeTimestamp,
+    },
+    /// No step could be found for this artifact
+    Unknown,
+}
+
+/// An `Artifact` with the step that archived it
+#[derive(Debug, Clone)]
+pub struct ArtifactProvenance {
+    /// The artifact
+    pub artifact: Artifact,
+    /// Step that archived the artifact
+    pub provenance: Provenance,
+}
+
+#[derive(Deserialize, Debug)]
+struct FlowGraph {
+    #[serde(default)]
+    actions: Vec<Option<FlowGraphNodes>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FlowGraphNodes {
+    #[serde(default)]
+    nodes: Vec<FlowNode>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct FlowNode {
+    id: String,
+    display_function_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct FlowNodeDescription {
+    id: String,
+    start_time_millis: MaybeTimestamp,
+    parameter_description: Option<String>,
+}
+
+/// Steps archiving artifacts
+const ARCHIVE_STEPS: [&str; 2] = ["archiveArtifacts", "archive"];
+
+impl WorkflowRun {
+    /// Find which step archived each artifact of this build
+    ///
+    /// This is best effort: Jenkins doesn't record it, so it is derived by matching the pattern
+    /// of each `archiveArtifacts` step of the pipeline against the path of the artifacts. When
+    /// several steps match, the last one to start is kept as it would have overwritten the
+    /// artifact. Artifacts archived in another way, or whose step ignored them through its
+    /// `excludes`, may be reported wrongly or as `Provenance::Unknown`
+    pub fn artifact_provenance(
+        &self,
+        jenkins_client: &Jenkins,
+    ) -> Result<Vec<ArtifactProvenance>, Error> {
+        let path = jenkins_client.url_to_path(&self.url);
+        let (job_name, number) = if let Path::Build {
+            job_name, number, ..
+        } = path
+        {
+            (job_name, number)
+        } else {
+            return Err(client::Error::InvalidUrl {
+                url: self.url.clone(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into());
+        };
+
+        let flow_graph: FlowGraph = jenkins_client
+            .get_with_params(
+                &Path::Build {
+                    job_name: job_name.clone(),
+                    number: number.clone(),
+                    configuration: None,
+                },
+                [("tree", "actions[nodes[id,displayFunctionName]]")],
+            )?
+            .json()?;
+
+        let mut archive_steps = flow_graph
+            .actions
+            .iter()
+            .flatten()
+            .flat_map(|action| action.nodes.iter())
+            .filter(|node| {
+                node.display_function_name
+                    .as_ref()
+                    .map(|name| ARCHIVE_STEPS.contains(&name.as_str()))
+                    .unwrap_or(false)
+            })
+            .map(|node| {
+                Ok(jenkins_client
+                    .get_file(&Path::FlowNodeDescription {
+                        job_name: job_name.clone(),
+                        number: number.clone(),
+                        node_id: Name::Name(&node.id),
+                    })?
+                    .json()?)
+            })
+            .collect::<Result<Vec<FlowNodeDescription>, Error>>()?;
+        archive_steps.sort_by_key(|step| step.start_time_millis.known_millis());
+
+        Ok(self
+            .artifacts
+            .iter()
+            .map(|artifact| ArtifactProvenance {
+                artifact: artifact.clone(),
+                provenance: archive_steps
+                    .iter()
+                    .rev()
+                    .filter_map(|step| {
+                        step.parameter_description
+                            .as_ref()
+                            .map(|pattern| (step, pattern))
+                    })
+                    .find(|(_, pattern)| patterns_match(pattern, &artifact.relative_path))
+                    .map(|(step, pattern)| Provenance::ArchiveStep {
+                        node_id: step.id.clone(),
+                        pattern: pattern.clone(),
+                        timestamp: step.start_time_millis,
+                    })
+                    .unwrap_or(Provenance::Unknown),
+            })
+            .collect())
+    }
+}
+
+/// Replay the pipeline run at `build_url`, with a new `script` or the same one
+pub(crate) fn replay(
+    jenkins_client: &Jenkins,
+    build_url: &str,
+    script: Option<&str>,
+) -> Result<(), Error> {
+    let path = jenkins_client.url_to_path(build_url);
+    if let Path::Build {
+        job_name,
+        number,
+        configuration,
+    } = path
+    {
+        match script {
+            Some(script) => {
+                let body = serde_urlencoded::to_string(&[
+                    ("mainScript", script.to_string()),
+                    (
+                        "json",
+                        serde_json::json!({ "mainScript": script }).to_string(),
+                    ),
+                ])?;
+                let _ = jenkins_client.post_with_body(
+                    &Path::Replay {
+                        job_name,
+                        number,
+                        configuration,
+                    },
+                    body,
+                    &[],
+                )?;
+            }
+            None => {
+                let _ = jenkins_client.post(&Path::ReplayWithSameScript {
+                    job_name,
+                    number,
+                    configuration,
+                })?;
+            }
+        }
+        Ok(())
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: build_url.to_string(),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
+impl WorkflowRun {
+    /// Replay this pipeline run, with the same parameters. The pipeline is run with `script` if
+    /// set, or with the same script otherwise
+    pub fn replay(&self, jenkins_client: &Jenkins, script: Option<&str>) -> Result<(), Error> {
+        replay(jenkins_client, &self.url, script)
+    }
+}
+
+/// Check if `path` matches any of the comma separated Ant-style `patterns`
+fn patterns_match(patterns: &str, path: &str) -> bool {
+    patterns
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| pattern_matches(pattern, path))
+}
+
+/// Check if `path` matches an Ant-style `pattern`, where `**` matches any number of
+/// directories, `*` any characters in a path segment, and `?` a single character
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.replace('\\', "/");
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+    let pattern_segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path_segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    segments_match(&pattern_segments, &path_segments)
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skipped| segments_match(rest, &path[skipped..]))
+        }
+        Some((segment, rest)) => match path.split_first() {
+            Some((path_segment, path_rest)) => {
+                wildcard_matches(
+                    &segment.chars().collect::<Vec<_>>(),
+                    &path_segment.chars().collect::<Vec<_>>(),
+                ) && segments_match(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use super::{pattern_matches, patterns_match, BuildWithChangeSets, Provenance, WorkflowRun};
+    use crate::changeset::{GitChangeSet, SubversionLogEntry};
+    use crate::helpers::MaybeTimestamp;
+
+    #[test]
+    fn can_match_simple_patterns() {
+        assert!(pattern_matches("target/app.jar", "target/app.jar"));
+        assert!(!pattern_matches("target/app.jar", "target/app.war"));
+        assert!(pattern_matches("target/*.jar", "target/app.jar"));
+        assert!(!pattern_matches("target/*.jar", "target/lib/app.jar"));
+        assert!(!pattern_matches("*.jar", "target/app.jar"));
+        assert!(pattern_matches("target/app-?.jar", "target/app-1.jar"));
+        assert!(!pattern_matches("target/app-?.jar", "target/app-10.jar"));
+        assert!(pattern_matches("target/*", "target/app.jar"));
+        assert!(pattern_matches("*", "app.jar"));
+    }
+
+    #[test]
+    fn can_match_recursive_patterns() {
+        assert!(pattern_matches("**/*.jar", "app.jar"));
+        assert!(pattern_matches("**/*.jar", "target/app.jar"));
+        assert!(pattern_matches("**/*.jar", "module/target/app.jar"));
+        assert!(!pattern_matches("**/*.jar", "module/target/app.war"));
+        assert!(pattern_matches("module/**/app.jar", "module/app.jar"));
+        assert!(pattern_matches(
+            "module/**/app.jar",
+            "module/target/libs/app.jar"
+        ));
+        assert!(!pattern_matches(
+            "module/**/app.jar",
+            "other/target/app.jar"
+        ));
+        assert!(pattern_matches("**", "any/thing"));
+        assert!(pattern_matches("reports/", "reports/junit/result.xml"));
+        assert!(pattern_matches("reports\\*.xml", "reports/result.xml"));
+    }
+
+    #[test]
+    fn can_match_lists_of_patterns() {
+        assert!(patterns_match("*.war, target/*.jar", "target/app.jar"));
+        assert!(patterns_match("*.war,target/*.jar", "app.war"));
+        assert!(!patterns_match("*.war, target/*.jar", "app.zip"));
+        assert!(!patterns_match(" , ", "app.zip"));
+    }
+
+    #[test]
+    fn can_read_changes_from_all_scms() {
+        let run: WorkflowRun = serde_json::from_str(
+            r#"{
+                "_class": "org.jenkinsci.plugins.workflow.job.WorkflowRun",
+                "url": "http://none/job/pipeline/3/",
+                "number": 3,
+                "duration": 10,
+                "estimatedDuration": 10,
+                "timestamp": 1500000000000,
+                "keepLog": false,
+                "result": "SUCCESS",
+                "displayName": "3",
+                "building": false,
+                "id": "3",
+                "queueId": 1,
+                "artifacts": [],
+                "changeSets": [
+                    {"_class": "hudson.plugins.git.GitChangeSetList", "kind": "git", "items": [
+                        {"_class": "hudson.plugins.git.GitChangeSet", "affectedPaths": ["a"],
+                        "commitId": "0f3c2a1", "timestamp": 1551000000000,
+                        "author": {"absoluteUrl": "http://none/user/alice", "fullName": "Alice"},
+                        "authorEmail": "alice@example.com", "comment": "First\n",
+                        "date": "2019-02-24 10:00:00 +0100", "id": "0f3c2a1", "msg": "First",
+                        "paths": [{"editType": "edit", "file": "a"}]},
+                        {"_class": "hudson.plugins.git.GitChangeSet", "affectedPaths": ["b"],
+                        "commitId": "7b1d9e4", "timestamp": 1551000001000,
+                        "author": {"absoluteUrl": "http://none/user/alice", "fullName": "Alice"},
+                        "authorEmail": "alice@example.com", "comment": "Second\n",
+                        "date": "2019-02-24 10:00:01 +0100", "id": "7b1d9e4", "msg": "Second",
+                        "paths": [{"editType": "add", "file": "b"}]}
+                    ]},
+                    {"_class": "hudson.scm.SubversionChangeLogSet", "kind": "svn", "items": [
+                        {"_class": "hudson.scm.SubversionChangeLogSet$LogEntry",
+                        "affectedPaths": ["trunk/c"], "commitId": "12", "timestamp": 1551000002000,
+                        "author": {"absoluteUrl": "http://none/user/bob", "fullName": "bob"},
+                        "date": "2019-02-24T09:00:02.000000Z", "msg": "Third",
+                        "paths": [{"editType": "delete", "file": "trunk/c"}],
+                        "revision": 12, "user": "bob"}
+                    ], "revisions": [{"module": "http://svn/repo/trunk", "revision": 12}]},
+                    {"_class": "hudson.scm.EmptyChangeLogSet", "items": []}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(run.change_set_lists().len(), 3);
+        let changes = run.change_sets();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(
+            changes[1].as_variant::<GitChangeSet>().unwrap().commit_id,
+            "7b1d9e4"
+        );
+        assert_eq!(
+            changes[2]
+                .as_variant::<SubversionLogEntry>()
+                .unwrap()
+                .revision,
+            12
+        );
+    }
+
+    #[test]
+    fn can_replay_pipeline_run() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/replay", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let build: crate::build::CommonBuild = serde_json::from_str(&format!(
+            r#"{{
+                "_class": "org.jenkinsci.plugins.workflow.job.WorkflowRun",
+                "url": "{}/replay/job/pipeline/4/",
+                "number": 4,
+                "duration": 10,
+                "estimatedDuration": 10,
+                "timestamp": 1500000000000,
+                "keepLog": false,
+                "result": "FAILURE",
+                "displayName": "4",
+                "building": false,
+                "id": "4",
+                "queueId": 1
+            }}"#,
+            mockito::server_url()
+        ))
+        .unwrap();
+        let same_script = mockito::mock("POST", "/replay/job/pipeline/4/replay/rebuild")
+            .with_status(302)
+            .create();
+        let new_script = mockito::mock("POST", "/replay/job/pipeline/4/replay/run")
+            .match_body(
+                "mainScript=echo+%27hi%27&json=%7B%22mainScript%22%3A%22echo+%27hi%27%22%7D",
+            )
+            .with_status(302)
+            .create();
+
+        build.replay(&jenkins_client, None).unwrap();
+        build.replay(&jenkins_client, Some("echo 'hi'")).unwrap();
+        same_script.assert();
+        new_script.assert();
+    }
+
+    #[test]
+    fn can_get_artifact_provenance() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .build()
+            .unwrap();
+
+        let run: WorkflowRun = serde_json::from_str(&format!(
+            r#"{{
+                "_class": "org.jenkinsci.plugins.workflow.job.WorkflowRun",
+                "url": "{}/job/provenance/1/",
+                "number": 1,
+                "duration": 10,
+                "estimatedDuration": 10,
+                "timestamp": 1500000000000,
+                "keepLog": false,
+                "result": "SUCCESS",
+                "displayName": "1",
+                "building": false,
+                "id": "1",
+                "queueId": 1,
+                "artifacts": [
+                    {{"fileName": "app.jar", "relativePath": "target/app.jar"}},
+                    {{"fileName": "result.xml", "relativePath": "reports/result.xml"}},
+                    {{"fileName": "notes.txt", "relativePath": "notes.txt"}}
+                ],
+                "changeSets": []
+            }}"#,
+            mockito::server_url()
+        ))
+        .unwrap();
+
+        let _graph = mockito::mock(
+            "GET",
+            "/job/provenance/1/api/json?tree=actions%5Bnodes%5Bid%2CdisplayFunctionName%5D%5D",
+        )
+        .with_body(
+            r#"{"actions": [{}, {"nodes": [
+                {"id": "3", "displayFunctionName": "sh"},
+                {"id": "5", "displayFunctionName": "archiveArtifacts"},
+                {"id": "8", "displayFunctionName": "archiveArtifacts"}
+            ]}]}"#,
+        )
+        .create();
+        let _first = mockito::mock("GET", "/job/provenance/1/execution/node/5/wfapi/describe")
+            .with_body(r#"{"id": "5", "startTimeMillis": 1500000001000, "parameterDescription": "target/*.jar, reports/"}"#)
+            .create();
+        let _second = mockito::mock(
+            "GET",
+            "/job/provenance/1/execution/node/8/wfapi/describe",
+        )
+        .with_body(
+            r#"{"id": "8", "startTimeMillis": 1500000002000, "parameterDescription": "**/*.xml"}"#,
+        )
+        .create();
+
+        let provenance = run.artifact_provenance(&jenkins_client).unwrap();
+
+        assert_eq!(provenance.len(), 3);
+        assert_eq!(
+            provenance[0].provenance,
+            Provenance::ArchiveStep {
+                node_id: "5".to_string(),
+                pattern: "target/*.jar, reports/".to_string(),
+                timestamp: MaybeTimestamp::Known(1_500_000_001_000),
+            }
+        );
+        assert_eq!(
+            provenance[1].provenance,
+            Provenance::ArchiveStep {
+                node_id: "8".to_string(),
+                pattern: "**/*.xml".to_string(),
+                timestamp: MaybeTimestamp::Known(1_500_000_002_000),
+            }
+        );
+        assert_eq!(provenance[2].provenance, Provenance::Unknown);
+    }
+}
@@ -0,0 +1,419 @@
+//! Report gathering everything about a build, to archive it
+
+use std::thread;
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use super::{Artifact, Build, BuildStatus};
+use crate::action::{CauseAction, ParametersAction};
+use crate::changeset::CommonChangeSet;
+use crate::helpers::{is_not_found, MaybeDuration, MaybeTimestamp};
+use crate::Jenkins;
+
+/// Console output to include in a `BuildReport`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportConsole {
+    /// Don't include the console output
+    Skip,
+    /// Include the whole console output
+    Full,
+    /// Include the last lines of the console output
+    Tail(usize),
+}
+
+/// Sections to include in a `BuildReport`. By default, every section is included, with the
+/// last 100 lines of the console output
+#[derive(Debug, Clone, Copy)]
+pub struct ReportOptions {
+    console: ReportConsole,
+    parameters: bool,
+    causes: bool,
+    tests: bool,
+    changes: bool,
+    artifacts: bool,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            console: ReportConsole::Tail(100),
+            parameters: true,
+            causes: true,
+            tests: true,
+            changes: true,
+            artifacts: true,
+        }
+    }
+}
+
+impl ReportOptions {
+    /// Set how much of the console output to include
+    pub fn with_console(mut self, console: ReportConsole) -> Self {
+        self.console = console;
+        self
+    }
+
+    /// Don't include the parameters
+    pub fn without_parameters(mut self) -> Self {
+        self.parameters = false;
+        self
+    }
+
+    /// Don't include the causes
+    pub fn without_causes(mut self) -> Self {
+        self.causes = false;
+        self
+    }
+
+    /// Don't include the test summary
+    pub fn without_tests(mut self) -> Self {
+        self.tests = false;
+        self
+    }
+
+    /// Don't include the changes
+    pub fn without_changes(mut self) -> Self {
+        self.changes = false;
+        self
+    }
+
+    /// Don't include the artifacts
+    pub fn without_artifacts(mut self) -> Self {
+        self.artifacts = false;
+        self
+    }
+}
+
+/// A parameter of a build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportParameter {
+    /// Name of the parameter
+    pub name: String,
+    /// Value of the parameter, missing for parameters without a value like files or passwords
+    pub value: Option<serde_json::Value>,
+}
+
+/// Summary of the test report of a build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestSummary {
+    /// Number of tests that passed
+    pub pass_count: u32,
+    /// Number of tests that failed
+    pub fail_count: u32,
+    /// Number of tests that were skipped
+    pub skip_count: u32,
+    /// Names of the tests that failed, as `class.name`
+    pub failed: Vec<String>,
+}
+
+/// Everything about a build, each section being `None` when it wasn't requested
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildReport {
+    /// URL for the build
+    pub url: String,
+    /// Build number
+    pub number: u32,
+    /// Build result
+    pub result: Option<BuildStatus>,
+    /// Is this build currently running
+    pub building: bool,
+    /// Timestamp of the build start
+    pub timestamp: MaybeTimestamp,
+    /// Duration
+    pub duration: MaybeDuration,
+    /// Console output, or its last lines
+    pub console: Option<String>,
+    /// Parameters of the build
+    pub parameters: Option<Vec<ReportParameter>>,
+    /// Short descriptions of the causes of the build
+    pub causes: Option<Vec<String>>,
+    /// Summary of the tests, `None` too if the build has no test report
+    pub tests: Option<TestSummary>,
+    /// Changes built
+    pub changes: Option<Vec<CommonChangeSet>>,
+    /// Artifacts of the build
+    pub artifacts: Option<Vec<Artifact>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ChangeSetItems {
+    items: Vec<CommonChangeSet>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildSummary {
+    number: u32,
+    result: Option<BuildStatus>,
+    building: bool,
+    timestamp: MaybeTimestamp,
+    duration: MaybeDuration,
+    change_set: Option<ChangeSetItems>,
+    #[serde(default)]
+    change_sets: Vec<ChangeSetItems>,
+}
+
+static BUILD_SUMMARY_TREE: &str = "number,result,building,timestamp,duration";
+static BUILD_SUMMARY_WITH_CHANGES_TREE: &str = "number,result,building,timestamp,duration,\
+                                                changeSet[items[*]],changeSets[items[*]]";
+
+fn test_summary<B: Build + ?Sized>(
+    build: &B,
+    jenkins_client: &Jenkins,
+) -> Result<Option<TestSummary>, Error> {
+    let report = match build.get_test_report(jenkins_client) {
+        Ok(report) => report,
+        Err(ref error) if is_not_found(error) => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    Ok(Some(TestSummary {
+        pass_count: report.pass_count,
+        fail_count: report.fail_count,
+        skip_count: report.skip_count,
+        failed: report
+            .suites
+            .iter()
+            .flat_map(|suite| suite.cases.iter())
+            .filter(|case| case.is_failed())
+            .map(|case| format!("{}.{}", case.class_name, case.name))
+            .collect(),
+    }))
+}
+
+pub(crate) fn build_report<B: Build + Sync + ?Sized>(
+    build: &B,
+    jenkins_client: &Jenkins,
+    options: &ReportOptions,
+) -> Result<BuildReport, Error> {
+    let (summary, console, tests, artifacts) = thread::scope(|scope| {
+        let summary = scope.spawn(|| -> Result<BuildSummary, Error> {
+            let tree = if options.changes {
+                BUILD_SUMMARY_WITH_CHANGES_TREE
+            } else {
+                BUILD_SUMMARY_TREE
+            };
+            Ok(jenkins_client
+                .get_with_params(&jenkins_client.url_to_path(build.url()), [("tree", tree)])?
+                .json()?)
+        });
+        let console = scope.spawn(|| match options.console {
+            ReportConsole::Skip => Ok(None),
+            ReportConsole::Full => build.get_console(jenkins_client).map(Some),
+            ReportConsole::Tail(lines) => build.console_tail(jenkins_client, lines).map(Some),
+        });
+        let tests = scope.spawn(|| {
+            if options.tests {
+                test_summary(build, jenkins_client)
+            } else {
+                Ok(None)
+            }
+        });
+        let artifacts = scope.spawn(|| {
+            if options.artifacts {
+                build.artifact_records(jenkins_client).map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+        (
+            summary.join().expect("fetching the build summary panicked"),
+            console.join().expect("fetching the console panicked"),
+            tests.join().expect("fetching the test report panicked"),
+            artifacts.join().expect("fetching the artifacts panicked"),
+        )
+    });
+    let summary = summary?;
+
+    let parameters = if options.parameters {
+        Some(
+            build
+                .actions()
+                .iter()
+                .filter_map(|action| action.as_variant::<ParametersAction>().ok())
+                .flat_map(|action| action.parameters)
+                .map(|parameter| ReportParameter {
+                    value: parameter.value().cloned(),
+                    name: parameter.name,
+                })
+                .collect(),
+        )
+    } else {
+        None
+    };
+    let causes = if options.causes {
+        Some(
+            build
+                .actions()
+                .iter()
+                .filter_map(|action| action.as_variant::<CauseAction>().ok())
+                .flat_map(|action| action.causes)
+                .map(|cause| cause.short_description)
+                .collect(),
+        )
+    } else {
+        None
+    };
+    let changes = if options.changes {
+        Some(
+            summary
+                .change_set
+                .into_iter()
+                .chain(summary.change_sets)
+                .flat_map(|change_set| change_set.items)
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    Ok(BuildReport {
+        url: build.url().to_string(),
+        number: summary.number,
+        result: summary.result,
+        building: summary.building,
+        timestamp: summary.timestamp,
+        duration: summary.duration,
+        console: console?,
+        parameters,
+        causes,
+        tests: tests?,
+        changes,
+        artifacts: artifacts?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::CommonBuild;
+
+    fn build(url: &str) -> CommonBuild {
+        serde_json::from_str(&format!(
+            r##"{{
+                "_class": "hudson.model.FreeStyleBuild",
+                "url": "{}/job/deploy/7/",
+                "number": 7,
+                "duration": 1200,
+                "estimatedDuration": 1000,
+                "timestamp": 1550000000000,
+                "keepLog": false,
+                "result": "FAILURE",
+                "displayName": "#7",
+                "description": null,
+                "building": false,
+                "id": "7",
+                "queueId": 12,
+                "actions": [{{
+                    "_class": "hudson.model.ParametersAction",
+                    "parameters": [
+                        {{"_class": "hudson.model.StringParameterValue", "name": "env", "value": "prod"}},
+                        {{"_class": "hudson.model.PasswordParameterValue", "name": "secret"}}
+                    ]
+                }}, {{
+                    "_class": "hudson.model.CauseAction",
+                    "causes": [{{
+                        "_class": "hudson.model.Cause$UserIdCause",
+                        "shortDescription": "Started by user admin",
+                        "userId": "admin",
+                        "userName": "admin"
+                    }}]
+                }}],
+                "artifacts": [{{
+                    "displayPath": "out.log",
+                    "fileName": "out.log",
+                    "relativePath": "target/out.log"
+                }}]
+            }}"##,
+            url
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_build_full_report() {
+        let url = format!("{}/report", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let _summary = mockito::mock(
+            "GET",
+            "/report/job/deploy/7/api/json?tree=number%2Cresult%2Cbuilding%2Ctimestamp%2Cduration%2CchangeSet%5Bitems%5B*%5D%5D%2CchangeSets%5Bitems%5B*%5D%5D",
+        )
+        .with_body(
+            r#"{
+                "_class": "hudson.model.FreeStyleBuild",
+                "number": 7,
+                "result": "FAILURE",
+                "building": false,
+                "timestamp": 1550000000000,
+                "duration": 1200,
+                "changeSet": {"items": [{"_class": "hudson.plugins.git.GitChangeSet", "msg": "fix"}]}
+            }"#,
+        )
+        .create();
+        let _console = mockito::mock("GET", "/report/job/deploy/7/consoleText/api/json?depth=1")
+            .with_body("one\ntwo\nthree\n")
+            .create();
+        let _tests = mockito::mock("GET", "/report/job/deploy/7/testReport/api/json?depth=1")
+            .with_status(404)
+            .create();
+
+        let report = build(&url)
+            .full_report(
+                &jenkins_client,
+                &ReportOptions::default().with_console(ReportConsole::Tail(2)),
+            )
+            .unwrap();
+        assert_eq!(report.result, Some(BuildStatus::Failure));
+        assert_eq!(report.console, Some("two\nthree\n".to_string()));
+        let parameters = report.parameters.as_ref().unwrap();
+        assert_eq!(parameters[0].value, Some(serde_json::json!("prod")));
+        assert_eq!(parameters[1].value, None);
+        assert_eq!(
+            report.causes,
+            Some(vec!["Started by user admin".to_string()])
+        );
+        assert!(report.tests.is_none());
+        assert_eq!(report.changes.as_ref().unwrap().len(), 1);
+        assert_eq!(report.artifacts.as_ref().unwrap()[0].file_name, "out.log");
+        assert!(serde_json::to_string(&report).is_ok());
+    }
+
+    #[test]
+    fn can_skip_sections_of_report() {
+        let url = format!("{}/report-light", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let summary = mockito::mock(
+            "GET",
+            "/report-light/job/deploy/7/api/json?tree=number%2Cresult%2Cbuilding%2Ctimestamp%2Cduration",
+        )
+        .with_body(
+            r#"{"number": 7, "result": null, "building": true, "timestamp": 1, "duration": 0}"#,
+        )
+        .create();
+
+        let report = build(&url)
+            .full_report(
+                &jenkins_client,
+                &ReportOptions::default()
+                    .with_console(ReportConsole::Skip)
+                    .without_parameters()
+                    .without_causes()
+                    .without_tests()
+                    .without_changes()
+                    .without_artifacts(),
+            )
+            .unwrap();
+        summary.assert();
+        assert!(report.building);
+        assert!(report.result.is_none());
+        assert!(report.console.is_none());
+        assert!(report.parameters.is_none());
+        assert!(report.changes.is_none());
+        assert!(report.artifacts.is_none());
+    }
+}
@@ -0,0 +1,669 @@
+//! Whether a build will survive the build discarder of its job
+
+use std::time::{Duration, SystemTime};
+
+use failure::Error;
+use serde::Deserialize;
+
+use super::BuildNumber;
+use crate::client;
+use crate::client_internals::path::Path;
+use crate::helpers::{Class, MaybeTimestamp};
+use crate::property::{BuildDiscarderProperty, BuildDiscarderStrategy, CommonProperty};
+use crate::Jenkins;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Whether a build will survive the build discarder of its job
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetentionStatus {
+    /// The build is kept forever
+    KeptForever,
+    /// The build is not discarded for now, `until` telling what protects it
+    SafeForNow {
+        /// What protects the build, or what will stop protecting it first
+        until: RetentionReason,
+    },
+    /// The build, or its artifacts, will be discarded the next time the discarder runs
+    AtRisk {
+        /// Why the build will be discarded
+        reason: RetentionReason,
+    },
+}
+
+/// Why a build is protected from the discarder, or why it will be discarded
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetentionReason {
+    /// The job has no build discarder, or one without limits
+    NoDiscarder,
+    /// The build is running, the discarder skips it
+    Building,
+    /// The build is the last stable or the last successful build of its job, the discarder
+    /// skips it
+    Permalink(BuildNumber),
+    /// Position of the build among the builds of its job, from `0` for the most recent, compared
+    /// to the number of builds to keep
+    BuildCount {
+        /// Position of the build
+        position: u32,
+        /// Number of builds to keep
+        num_to_keep: u32,
+    },
+    /// Age of the build compared to the number of days to keep builds
+    BuildAge {
+        /// Time since the build started
+        age: Duration,
+        /// Number of days to keep builds
+        days_to_keep: u32,
+    },
+    /// Position of the build among the builds of its job, from `0` for the most recent, compared
+    /// to the number of builds to keep artifacts of
+    ArtifactCount {
+        /// Position of the build
+        position: u32,
+        /// Number of builds to keep artifacts of
+        artifact_num_to_keep: u32,
+    },
+    /// Age of the build compared to the number of days to keep artifacts
+    ArtifactAge {
+        /// Time since the build started
+        age: Duration,
+        /// Number of days to keep artifacts
+        artifact_days_to_keep: u32,
+    },
+    /// The job discards builds with a strategy that can't be evaluated
+    UnknownStrategy {
+        /// _class of the strategy, if provided by Jenkins
+        class: Option<String>,
+    },
+}
+
+impl RetentionReason {
+    /// Is the limit of the discarder exceeded
+    fn is_exceeded(&self) -> bool {
+        match *self {
+            RetentionReason::BuildCount {
+                position,
+                num_to_keep: keep,
+            }
+            | RetentionReason::ArtifactCount {
+                position,
+                artifact_num_to_keep: keep,
+            } => position >= keep,
+            RetentionReason::BuildAge {
+                age,
+                days_to_keep: days,
+            }
+            | RetentionReason::ArtifactAge {
+                age,
+                artifact_days_to_keep: days,
+            } => age > DAY * days,
+            _ => false,
+        }
+    }
+
+    fn days_to_keep(&self) -> Option<u32> {
+        match *self {
+            RetentionReason::BuildAge { days_to_keep, .. } => Some(days_to_keep),
+            RetentionReason::ArtifactAge {
+                artifact_days_to_keep,
+                ..
+            } => Some(artifact_days_to_keep),
+            _ => None,
+        }
+    }
+
+    fn num_to_keep(&self) -> Option<u32> {
+        match *self {
+            RetentionReason::BuildCount { num_to_keep, .. } => Some(num_to_keep),
+            RetentionReason::ArtifactCount {
+                artifact_num_to_keep,
+                ..
+            } => Some(artifact_num_to_keep),
+            _ => None,
+        }
+    }
+}
+
+/// What is needed about a build and its job to know if the build will be discarded
+#[derive(Debug)]
+struct RetentionFacts<'a> {
+    number: u32,
+    timestamp: MaybeTimestamp,
+    keep_log: bool,
+    building: bool,
+    has_artifacts: bool,
+    /// Builds of the job, most recent first
+    builds: &'a [u32],
+    last_stable_build: Option<u32>,
+    last_successful_build: Option<u32>,
+    /// `None` when the job has no build discarder
+    discarder: Option<&'a BuildDiscarderProperty>,
+}
+
+/// A limit of a discarder, a negative number meaning there is no limit
+fn limit(value: i32) -> Option<u32> {
+    if value < 0 {
+        None
+    } else {
+        Some(value as u32)
+    }
+}
+
+/// Evaluate the rules of the `LogRotator` at `now`: builds kept forever, running or linked by
+/// the last stable or last successful permalinks are skipped, others are discarded once past
+/// the number of builds or the number of days to keep. Artifacts limits only apply to builds
+/// with artifacts, and days limits to builds with a known start
+fn evaluate(facts: &RetentionFacts<'_>, now: SystemTime) -> RetentionStatus {
+    if facts.keep_log {
+        return RetentionStatus::KeptForever;
+    }
+    if facts.building {
+        return RetentionStatus::SafeForNow {
+            until: RetentionReason::Building,
+        };
+    }
+    if facts.last_stable_build == Some(facts.number) {
+        return RetentionStatus::SafeForNow {
+            until: RetentionReason::Permalink(BuildNumber::LastStableBuild),
+        };
+    }
+    if facts.last_successful_build == Some(facts.number) {
+        return RetentionStatus::SafeForNow {
+            until: RetentionReason::Permalink(BuildNumber::LastSuccessfulBuild),
+        };
+    }
+
+    let strategy: &BuildDiscarderStrategy = match facts.discarder {
+        None => {
+            return RetentionStatus::SafeForNow {
+                until: RetentionReason::NoDiscarder,
+            }
+        }
+        Some(BuildDiscarderProperty {
+            strategy: Some(strategy),
+        }) if strategy.is_log_rotator() => strategy,
+        Some(discarder) => {
+            return RetentionStatus::AtRisk {
+                reason: RetentionReason::UnknownStrategy {
+                    class: discarder
+                        .strategy
+                        .as_ref()
+                        .and_then(|strategy| strategy.class.clone()),
+                },
+            }
+        }
+    };
+
+    let position = facts
+        .builds
+        .iter()
+        .position(|number| *number == facts.number)
+        .unwrap_or(facts.builds.len()) as u32;
+    let age = facts.timestamp.elapsed_at(now).as_duration();
+
+    let mut limits = vec![];
+    if let Some(num_to_keep) = limit(strategy.num_to_keep) {
+        limits.push(RetentionReason::BuildCount {
+            position,
+            num_to_keep,
+        });
+    }
+    if let (Some(days_to_keep), Some(age)) = (limit(strategy.days_to_keep), age) {
+        limits.push(RetentionReason::BuildAge { age, days_to_keep });
+    }
+    if facts.has_artifacts {
+        if let Some(artifact_num_to_keep) = limit(strategy.artifact_num_to_keep) {
+            limits.push(RetentionReason::ArtifactCount {
+                position,
+                artifact_num_to_keep,
+            });
+        }
+        if let (Some(artifact_days_to_keep), Some(age)) =
+            (limit(strategy.artifact_days_to_keep), age)
+        {
+            limits.push(RetentionReason::ArtifactAge {
+                age,
+                artifact_days_to_keep,
+            });
+        }
+    }
+
+    if let Some(reason) = limits.iter().find(|reason| reason.is_exceeded()) {
+        return RetentionStatus::AtRisk {
+            reason: reason.clone(),
+        };
+    }
+
+    // the limit reached first is the one protecting the build for the shortest time, days being
+    // known to run out while the number of builds depends on the next builds
+    let until = limits
+        .iter()
+        .filter_map(|reason| reason.days_to_keep().map(|days| (days, reason)))
+        .min_by_key(|(days, _)| *days)
+        .or_else(|| {
+            limits
+                .iter()
+                .filter_map(|reason| reason.num_to_keep().map(|keep| (keep, reason)))
+                .min_by_key(|(keep, _)| *keep)
+        })
+        .map(|(_, reason)| reason.clone())
+        .unwrap_or(RetentionReason::NoDiscarder);
+    RetentionStatus::SafeForNow { until }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildRetentionState {
+    number: u32,
+    timestamp: MaybeTimestamp,
+    keep_log: bool,
+    building: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct NumberOnly {
+    number: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobRetentionState {
+    #[serde(default)]
+    all_builds: Vec<NumberOnly>,
+    last_stable_build: Option<NumberOnly>,
+    last_successful_build: Option<NumberOnly>,
+    #[serde(default)]
+    property: Vec<CommonProperty>,
+}
+
+/// Get the retention status of the build at `url`, reading the build and its job from Jenkins
+pub(crate) fn retention_status(
+    jenkins_client: &Jenkins,
+    url: &str,
+    has_artifacts: bool,
+) -> Result<RetentionStatus, Error> {
+    let path = jenkins_client.url_to_path(url);
+    if let Path::Build {
+        ref job_name,
+        ref configuration,
+        ..
+    } = path
+    {
+        let build: BuildRetentionState = jenkins_client
+            .get_with_params(&path, [("tree", "number,timestamp,keepLog,building")])?
+            .json()?;
+        let job: JobRetentionState = jenkins_client
+            .get_with_params(
+                &Path::Job {
+                    name: job_name.clone(),
+                    configuration: configuration.clone(),
+                },
+                [(
+                    "tree",
+                    "allBuilds[number],lastStableBuild[number],lastSuccessfulBuild[number],\
+                     property[strategy[*]]",
+                )],
+            )?
+            .json()?;
+
+        let discarder = match job.property.iter().find(|property| {
+            property.class.as_deref() == Some(BuildDiscarderProperty::with_class())
+        }) {
+            Some(property) => Some(property.as_variant::<BuildDiscarderProperty>()?),
+            None => None,
+        };
+        let builds: Vec<u32> = job.all_builds.iter().map(|build| build.number).collect();
+
+        Ok(evaluate(
+            &RetentionFacts {
+                number: build.number,
+                timestamp: build.timestamp,
+                keep_log: build.keep_log,
+                building: build.building,
+                has_artifacts,
+                builds: &builds,
+                last_stable_build: job.last_stable_build.map(|build| build.number),
+                last_successful_build: job.last_successful_build.map(|build| build.number),
+                discarder: discarder.as_ref(),
+            },
+            SystemTime::now(),
+        ))
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: url.to_string(),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::{evaluate, RetentionFacts, RetentionReason, RetentionStatus, DAY};
+    use crate::build::BuildNumber;
+    use crate::helpers::MaybeTimestamp;
+    use crate::property::{BuildDiscarderProperty, BuildDiscarderStrategy};
+
+    static BUILDS: [u32; 5] = [10, 9, 8, 7, 6];
+
+    fn now() -> SystemTime {
+        UNIX_EPOCH + DAY * 100
+    }
+
+    fn started_days_ago(days: u32) -> MaybeTimestamp {
+        MaybeTimestamp::Known(
+            (now() - DAY * days)
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        )
+    }
+
+    fn facts(number: u32, discarder: Option<&BuildDiscarderProperty>) -> RetentionFacts<'_> {
+        RetentionFacts {
+            number,
+            timestamp: started_days_ago(3),
+            keep_log: false,
+            building: false,
+            has_artifacts: true,
+            builds: &BUILDS,
+            last_stable_build: Some(10),
+            last_successful_build: Some(10),
+            discarder,
+        }
+    }
+
+    fn log_rotator(
+        days_to_keep: i32,
+        num_to_keep: i32,
+        artifact_days_to_keep: i32,
+        artifact_num_to_keep: i32,
+    ) -> BuildDiscarderProperty {
+        BuildDiscarderProperty {
+            strategy: Some(BuildDiscarderStrategy {
+                class: Some("hudson.tasks.LogRotator".to_string()),
+                days_to_keep,
+                num_to_keep,
+                artifact_days_to_keep,
+                artifact_num_to_keep,
+            }),
+        }
+    }
+
+    #[test]
+    fn kept_builds_are_kept_forever() {
+        let discarder = log_rotator(1, 1, 1, 1);
+        let mut facts = facts(6, Some(&discarder));
+        facts.keep_log = true;
+        assert_eq!(evaluate(&facts, now()), RetentionStatus::KeptForever);
+    }
+
+    #[test]
+    fn running_builds_are_skipped() {
+        let discarder = log_rotator(1, 1, -1, -1);
+        let mut facts = facts(6, Some(&discarder));
+        facts.building = true;
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::Building
+            }
+        );
+    }
+
+    #[test]
+    fn permalinked_builds_are_skipped() {
+        let discarder = log_rotator(1, 1, -1, -1);
+        let mut facts = facts(7, Some(&discarder));
+        facts.last_stable_build = Some(9);
+        facts.last_successful_build = Some(7);
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::Permalink(BuildNumber::LastSuccessfulBuild)
+            }
+        );
+        facts.last_stable_build = Some(7);
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::Permalink(BuildNumber::LastStableBuild)
+            }
+        );
+    }
+
+    #[test]
+    fn builds_without_discarder_are_safe() {
+        assert_eq!(
+            evaluate(&facts(6, None), now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::NoDiscarder
+            }
+        );
+    }
+
+    #[test]
+    fn unlimited_discarder_keeps_everything() {
+        let discarder = log_rotator(-1, -1, -1, -1);
+        assert_eq!(
+            evaluate(&facts(6, Some(&discarder)), now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::NoDiscarder
+            }
+        );
+    }
+
+    #[test]
+    fn builds_past_the_count_are_at_risk() {
+        let discarder = log_rotator(-1, 3, -1, -1);
+        assert_eq!(
+            evaluate(&facts(8, Some(&discarder)), now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::BuildCount {
+                    position: 2,
+                    num_to_keep: 3
+                }
+            }
+        );
+        assert_eq!(
+            evaluate(&facts(7, Some(&discarder)), now()),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::BuildCount {
+                    position: 3,
+                    num_to_keep: 3
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn builds_past_the_days_are_at_risk() {
+        let discarder = log_rotator(5, -1, -1, -1);
+        let mut facts = facts(6, Some(&discarder));
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::BuildAge {
+                    age: DAY * 3,
+                    days_to_keep: 5
+                }
+            }
+        );
+        facts.timestamp = started_days_ago(6);
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::BuildAge {
+                    age: DAY * 6,
+                    days_to_keep: 5
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn days_limits_skip_builds_with_unknown_start() {
+        let discarder = log_rotator(5, 10, 1, -1);
+        let mut facts = facts(6, Some(&discarder));
+        facts.timestamp = MaybeTimestamp::Unknown;
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::BuildCount {
+                    position: 4,
+                    num_to_keep: 10
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn safe_builds_are_protected_until_the_days_limit() {
+        let discarder = log_rotator(30, 5, 7, -1);
+        assert_eq!(
+            evaluate(&facts(6, Some(&discarder)), now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::ArtifactAge {
+                    age: DAY * 3,
+                    artifact_days_to_keep: 7
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn artifacts_limits_apply_to_builds_with_artifacts() {
+        let discarder = log_rotator(-1, 10, -1, 2);
+        let mut facts = facts(8, Some(&discarder));
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::ArtifactCount {
+                    position: 2,
+                    artifact_num_to_keep: 2
+                }
+            }
+        );
+        facts.has_artifacts = false;
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::SafeForNow {
+                until: RetentionReason::BuildCount {
+                    position: 2,
+                    num_to_keep: 10
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn build_limits_are_checked_before_artifacts_limits() {
+        let discarder = log_rotator(1, 10, 1, 1);
+        let mut facts = facts(8, Some(&discarder));
+        facts.timestamp = started_days_ago(2);
+        assert_eq!(
+            evaluate(&facts, now()),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::BuildAge {
+                    age: DAY * 2,
+                    days_to_keep: 1
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn zero_keeps_nothing() {
+        let discarder = log_rotator(-1, 0, -1, -1);
+        assert_eq!(
+            evaluate(&facts(9, Some(&discarder)), now()),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::BuildCount {
+                    position: 1,
+                    num_to_keep: 0
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_strategies_are_at_risk() {
+        let mut discarder = log_rotator(-1, -1, -1, -1);
+        if let Some(ref mut strategy) = discarder.strategy {
+            strategy.class = Some("org.example.CustomDiscarder".to_string());
+        }
+        assert_eq!(
+            evaluate(&facts(6, Some(&discarder)), now()),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::UnknownStrategy {
+                    class: Some("org.example.CustomDiscarder".to_string())
+                }
+            }
+        );
+        assert_eq!(
+            evaluate(
+                &facts(6, Some(&BuildDiscarderProperty { strategy: None })),
+                now()
+            ),
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::UnknownStrategy { class: None }
+            }
+        );
+    }
+
+    #[test]
+    fn can_get_retention_status_from_jenkins() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/retention", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _build = mockito::mock(
+            "GET",
+            "/retention/job/job/1/api/json?tree=number%2Ctimestamp%2CkeepLog%2Cbuilding",
+        )
+        .with_body(r#"{"number": 1, "timestamp": 0, "keepLog": false, "building": false}"#)
+        .create();
+        let _job = mockito::mock(
+            "GET",
+            mockito::Matcher::Regex(r"^/retention/job/job/api/json\?tree=allBuilds".to_string()),
+        )
+        .with_body(
+            r#"{
+                "allBuilds": [{"number": 3}, {"number": 2}, {"number": 1}],
+                "lastStableBuild": {"number": 3},
+                "lastSuccessfulBuild": {"number": 3},
+                "property": [{
+                    "_class": "jenkins.model.BuildDiscarderProperty",
+                    "strategy": {
+                        "_class": "hudson.tasks.LogRotator",
+                        "artifactDaysToKeep": -1,
+                        "artifactNumToKeep": -1,
+                        "daysToKeep": -1,
+                        "numToKeep": 2
+                    }
+                }]
+            }"#,
+        )
+        .create();
+
+        let status = super::retention_status(
+            &jenkins_client,
+            &format!("{}/retention/job/job/1/", mockito::server_url()),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            status,
+            RetentionStatus::AtRisk {
+                reason: RetentionReason::BuildCount {
+                    position: 2,
+                    num_to_keep: 2
+                }
+            }
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! Test report of a build, published by the JUnit plugin
+
+use std::io::Write;
+
+use failure::Error;
+use serde::{Deserialize, Deserializer};
+
+use crate::client;
+use crate::client_internals::{Name, Path};
+use crate::Jenkins;
+
+/// Status of a `TestCase`
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TestStatus {
+    /// Test passed, and passed in the previous build
+    Passed,
+    /// Test was skipped
+    Skipped,
+    /// Test failed, and failed in the previous build
+    Failed,
+    /// Test passed, but failed in the previous build
+    Fixed,
+    /// Test failed, but passed in the previous build
+    Regression,
+}
+
+/// Test report of a build
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TestReport {
+    /// Duration of the tests, in seconds
+    pub duration: f32,
+    /// Is the report empty
+    pub empty: bool,
+    /// Number of tests that failed
+    pub fail_count: u32,
+    /// Number of tests that passed
+    pub pass_count: u32,
+    /// Number of tests that were skipped
+    pub skip_count: u32,
+    /// Test suites of the report
+    pub suites: Vec<SuiteResult>,
+}
+
+/// Result of a test suite
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuiteResult {
+    /// Name of the suite
+    pub name: String,
+    /// Duration of the suite, in seconds
+    pub duration: f32,
+    /// Test cases of the suite
+    pub cases: Vec<TestCase>,
+    /// Standard output of the suite
+    pub stdout: Option<String>,
+    /// Standard error of the suite
+    pub stderr: Option<String>,
+    /// Timestamp of the suite
+    pub timestamp: Option<String>,
+}
+
+/// Result of a test case
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TestCase {
+    /// Name of the class of the test
+    pub class_name: String,
+    /// Name of the test
+    pub name: String,
+    /// Status of the test
+    pub status: TestStatus,
+    /// Duration of the test, in seconds
+    pub duration: f32,
+    /// Was the test skipped
+    pub skipped: bool,
+    /// Message explaining why the test was skipped
+    pub skipped_message: Option<String>,
+    /// Error message of a failed test
+    pub error_details: Option<String>,
+    /// Stack trace of a failed test
+    pub error_stack_trace: Option<String>,
+    /// Number of builds this test has been failing for
+    pub age: u32,
+    /// Standard output of the test
+    pub stdout: Option<String>,
+    /// Standard error of the test
+    pub stderr: Option<String>,
+    /// Names of the files attached to the test by the JUnit attachments plugin
+    #[serde(
+        rename = "testActions",
+        default,
+        deserialize_with = "attachments_from_test_actions"
+    )]
+    pub attachments: Vec<String>,
+    #[serde(skip)]
+    build_url: String,
+}
+
+#[derive(Deserialize)]
+struct TestAction {
+    #[serde(default)]
+    attachments: Vec<String>,
+}
+
+fn attachments_from_test_actions<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let actions: Vec<TestAction> = Deserialize::deserialize(deserializer)?;
+    Ok(actions
+        .into_iter()
+        .flat_map(|action| action.attachments)
+        .collect())
+}
+
+/// Name of a test as used by Jenkins in URLs, where characters that can't be part of a Java
+/// identifier are replaced with `_`
+fn safe_test_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '$' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Name of a package or class as used by Jenkins in URLs
+fn safe_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '?' | '#' | '%' | '<' | '>' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+impl TestReport {
+    pub(crate) fn with_build_url(mut self, build_url: &str) -> Self {
+        for case in self
+            .suites
+            .iter_mut()
+            .flat_map(|suite| suite.cases.iter_mut())
+        {
+            case.build_url = build_url.to_string();
+        }
+        self
+    }
+}
+
+impl TestCase {
+    /// Did the test fail, either again or for the first time
+    pub fn is_failed(&self) -> bool {
+        matches!(self.status, TestStatus::Failed | TestStatus::Regression)
+    }
+
+    /// Package and class of the test, as used by Jenkins in URLs. Tests without a package are
+    /// in the `(root)` package
+    fn package_and_class(&self) -> (String, String) {
+        match self.class_name.rfind('.') {
+            Some(index) => (
+                safe_name(&self.class_name[..index]),
+                safe_name(&self.class_name[(index + 1)..]),
+            ),
+            None => ("(root)".to_string(), safe_name(&self.class_name)),
+        }
+    }
+
+    /// Download an attachment of this test, writing it to `writer`, and return the number of
+    /// bytes written
+    pub fn download_attachment<W: Write>(
+        &self,
+        jenkins_client: &Jenkins,
+        name: &str,
+        writer: &mut W,
+    ) -> Result<u64, Error> {
+        let path = jenkins_client.url_to_path(&self.build_url);
+        if let Path::Build {
+            job_name,
+            number,
+            configuration,
+        } = path
+        {
+            let (package, class) = self.package_and_class();
+            let test = safe_test_name(&self.name);
+            Ok(jenkins_client
+                .get_file(&Path::TestCaseAttachment {
+                    job_name,
+                    number,
+                    configuration,
+                    package: Name::Name(&package),
+                    class: Name::Name(&class),
+                    test: Name::Name(&test),
+                    attachment: Name::Name(name),
+                })?
+                .copy_to(writer)?)
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.build_url.clone(),
+                expected: client::error::ExpectedType::Build,
+            }
+            .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito;
+
+    static TEST_REPORT: &str = r#"{
+        "_class": "hudson.tasks.junit.TestResult",
+        "testActions": [],
+        "duration": 3.2,
+        "empty": false,
+        "failCount": 1,
+        "passCount": 1,
+        "skipCount": 0,
+        "suites": [{
+            "cases": [{
+                "testActions": [{
+                    "_class": "hudson.plugins.junitattachments.AttachmentTestAction",
+                    "attachments": ["screenshot.png", "page source.html"]
+                }],
+                "age": 1,
+                "className": "com.example.ui.LoginTest",
+                "duration": 2.5,
+                "errorDetails": "element not found",
+                "errorStackTrace": "org.openqa.selenium.NoSuchElementException",
+                "failedSince": 12,
+                "name": "login[1: admin/secret]",
+                "skipped": false,
+                "skippedMessage": null,
+                "status": "REGRESSION",
+                "stderr": null,
+                "stdout": null
+            }, {
+                "testActions": [],
+                "age": 0,
+                "className": "RootTest",
+                "duration": 0.7,
+                "errorDetails": null,
+                "errorStackTrace": null,
+                "failedSince": 0,
+                "name": "works",
+                "skipped": false,
+                "skippedMessage": null,
+                "status": "PASSED",
+                "stderr": null,
+                "stdout": null
+            }],
+            "duration": 3.2,
+            "id": null,
+            "name": "com.example.ui.LoginTest",
+            "stderr": null,
+            "stdout": null,
+            "timestamp": "2019-03-01T10:00:00"
+        }]
+    }"#;
+
+    fn report(build_url: &str) -> TestReport {
+        serde_json::from_str::<TestReport>(TEST_REPORT)
+            .unwrap()
+            .with_build_url(build_url)
+    }
+
+    #[test]
+    fn can_read_attachments() {
+        let report = report("http://none:8080/job/ui/12/");
+        let cases = &report.suites[0].cases;
+        assert_eq!(cases[0].status, TestStatus::Regression);
+        assert_eq!(
+            cases[0].attachments,
+            vec!["screenshot.png".to_string(), "page source.html".to_string()]
+        );
+        assert!(cases[1].attachments.is_empty());
+    }
+
+    #[test]
+    fn can_download_attachment_of_parameterized_test() {
+        let url = format!("{}/junit-attachments", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let report = report(&format!("{}/job/ui/12/", url));
+
+        let mock = mockito::mock(
+            "GET",
+            "/junit-attachments/job/ui/12/testReport/com.example.ui/LoginTest/login_1__admin_secret_/attachments/page%20source.html",
+        )
+        .with_body("<html></html>")
+        .create();
+
+        let mut content = vec![];
+        let written = report.suites[0].cases[0]
+            .download_attachment(&jenkins_client, "page source.html", &mut content)
+            .unwrap();
+        mock.assert();
+        assert_eq!(written, 13);
+        assert_eq!(content, b"<html></html>");
+    }
+
+    #[test]
+    fn can_download_attachment_of_test_in_root_package() {
+        let url = format!("{}/junit-root", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let report = report(&format!("{}/job/ui/12/", url));
+
+        let mock = mockito::mock(
+            "GET",
+            "/junit-root/job/ui/12/testReport/%28root%29/RootTest/works/attachments/out.txt",
+        )
+        .with_body("out")
+        .create();
+
+        let mut content = vec![];
+        let _ = report.suites[0].cases[1]
+            .download_attachment(&jenkins_client, "out.txt", &mut content)
+            .unwrap();
+        mock.assert();
+        assert_eq!(content, b"out");
+    }
+
+    #[test]
+    fn can_get_test_report_of_build_with_test_results() {
+        use crate::action::TestResultAction;
+        use crate::build::{Build, CommonBuild};
+
+        let url = format!("{}/junit-report", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let build: CommonBuild = serde_json::from_value(serde_json::json!({
+            "_class": "hudson.model.FreeStyleBuild",
+            "url": format!("{}/job/junit%20job/12/", url),
+            "number": 12,
+            "duration": 3200,
+            "estimatedDuration": 3200,
+            "timestamp": 1500000000000u64,
+            "keepLog": false,
+            "result": "UNSTABLE",
+            "displayName": "12",
+            "description": null,
+            "building": false,
+            "id": "12",
+            "queueId": 12,
+            "actions": [
+                {"_class": "hudson.model.CauseAction", "causes": []},
+                {
+                    "_class": "hudson.tasks.junit.TestResultAction",
+                    "failCount": 1,
+                    "skipCount": 0,
+                    "totalCount": 2,
+                    "urlName": "testReport"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let action = build
+            .actions
+            .iter()
+            .filter_map(|action| action.as_variant::<TestResultAction>().ok())
+            .next()
+            .unwrap();
+        assert_eq!(action.fail_count, 1);
+        assert_eq!(action.total_count, 2);
+
+        let _report = mockito::mock(
+            "GET",
+            "/junit-report/job/junit%20job/12/testReport/api/json?depth=1",
+        )
+        .with_body(TEST_REPORT)
+        .create();
+
+        let report = build.get_test_report(&jenkins_client).unwrap();
+        assert!(report
+            .suites
+            .iter()
+            .flat_map(|suite| &suite.cases)
+            .any(|case| case.is_failed() && case.error_details.is_some()));
+    }
+
+    #[test]
+    fn can_build_safe_names() {
+        assert_eq!(safe_test_name("test[0: a/b]"), "test_0__a_b_");
+        assert_eq!(safe_test_name("my test(x)"), "my_test_x_");
+        assert_eq!(safe_name("Outer$Inner"), "Outer$Inner");
+        assert_eq!(safe_name("a/b:c"), "a_b_c");
+    }
+}
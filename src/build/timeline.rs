@@ -0,0 +1,475 @@
+//! Chronological timeline of the events of a build
+
+use failure::Error;
+use serde::Deserialize;
+
+use super::{BuildStatus, WorkflowRun};
+use crate::action::{CommonAction, TimeInQueueAction};
+use crate::client;
+use crate::client_internals::path::Path;
+use crate::helpers::{is_not_found, Class, MaybeTimestamp};
+use crate::pipeline::{get_workflow_description, WorkflowStage, WorkflowStatus};
+use crate::Jenkins;
+
+/// Kind of an event of a build timeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineEventKind {
+    /// The build entered the queue
+    Queued,
+    /// The build started on an executor
+    Started,
+    /// A stage of a pipeline started
+    StageStarted {
+        /// Name of the stage
+        name: String,
+    },
+    /// A stage of a pipeline finished
+    StageFinished {
+        /// Name of the stage
+        name: String,
+        /// Status of the stage
+        status: WorkflowStatus,
+    },
+    /// The build finished
+    Finished {
+        /// Result of the build
+        result: BuildStatus,
+    },
+}
+
+impl TimelineEventKind {
+    /// Position of the event in the life of a build, an event should not happen before an event
+    /// of a lower rank
+    fn rank(&self) -> u8 {
+        match *self {
+            TimelineEventKind::Queued => 0,
+            TimelineEventKind::Started => 1,
+            TimelineEventKind::StageStarted { .. } => 2,
+            TimelineEventKind::StageFinished { .. } => 3,
+            TimelineEventKind::Finished { .. } => 4,
+        }
+    }
+}
+
+/// An event of a build timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    /// Time of the event, in milliseconds since epoch. Events whose time Jenkins doesn't know
+    /// are not part of the timeline
+    pub timestamp: u64,
+    /// Kind of the event
+    pub kind: TimelineEventKind,
+    /// Description of the event, for humans
+    pub label: String,
+    /// Is the event timed before an event that should have happened before it, like a stage
+    /// starting before the build. Jenkins data is kept as is, the event is only flagged
+    pub out_of_order: bool,
+}
+
+/// What is known about a build to build its timeline
+#[derive(Debug)]
+struct TimelineFacts<'a> {
+    /// Time the build entered the queue
+    queued_at: MaybeTimestamp,
+    started_at: MaybeTimestamp,
+    /// Time the build finished and its result, `None` while it is running
+    finished: Option<(MaybeTimestamp, BuildStatus)>,
+    /// Stages of the build, empty if it's not a pipeline run
+    stages: &'a [WorkflowStage],
+}
+
+/// Add an event to `events` if its time is known
+fn push_event(
+    events: &mut Vec<TimelineEvent>,
+    timestamp: MaybeTimestamp,
+    kind: TimelineEventKind,
+    label: String,
+) {
+    if let Some(timestamp) = timestamp.known_millis() {
+        events.push(TimelineEvent {
+            timestamp,
+            kind,
+            label,
+            out_of_order: false,
+        });
+    }
+}
+
+/// Assemble the events of a build, flag those timed before an event of a lower rank and sort
+/// them by time, events timed alike staying in the order they happened. Events with an unknown
+/// time are skipped, as are stages that were not executed, and stages still running only have
+/// their start
+fn assemble(facts: &TimelineFacts<'_>) -> Vec<TimelineEvent> {
+    let mut events = vec![];
+    push_event(
+        &mut events,
+        facts.queued_at,
+        TimelineEventKind::Queued,
+        "Queued".to_string(),
+    );
+    push_event(
+        &mut events,
+        facts.started_at,
+        TimelineEventKind::Started,
+        "Started".to_string(),
+    );
+    for stage in facts
+        .stages
+        .iter()
+        .filter(|stage| stage.status != WorkflowStatus::NotExecuted)
+    {
+        push_event(
+            &mut events,
+            stage.start_time_millis,
+            TimelineEventKind::StageStarted {
+                name: stage.name.clone(),
+            },
+            format!("Stage '{}' started", stage.name),
+        );
+        match stage.status {
+            WorkflowStatus::InProgress | WorkflowStatus::PausedPendingInput => (),
+            status => push_event(
+                &mut events,
+                stage.start_time_millis + stage.duration_millis,
+                TimelineEventKind::StageFinished {
+                    name: stage.name.clone(),
+                    status,
+                },
+                format!("Stage '{}' finished: {:?}", stage.name, status),
+            ),
+        }
+    }
+    if let Some((finished_at, result)) = facts.finished {
+        push_event(
+            &mut events,
+            finished_at,
+            TimelineEventKind::Finished { result },
+            format!("Finished: {:?}", result),
+        );
+    }
+
+    let ranks_and_times: Vec<(u8, u64)> = events
+        .iter()
+        .map(|event| (event.kind.rank(), event.timestamp))
+        .collect();
+    for event in &mut events {
+        let rank = event.kind.rank();
+        event.out_of_order = ranks_and_times
+            .iter()
+            .any(|(other_rank, other_time)| *other_rank < rank && *other_time > event.timestamp);
+    }
+
+    events.sort_by_key(|event| event.timestamp);
+    events
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildQueueState {
+    #[serde(rename = "_class")]
+    class: Option<String>,
+    queue_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueueItemSince {
+    in_queue_since: MaybeTimestamp,
+}
+
+/// Get the timeline of the build at `url`, reading the time it was queued from its
+/// `TimeInQueueAction` or from its queue item while Jenkins remembers it, and its stages if it
+/// is a pipeline run
+pub(crate) fn timeline(
+    jenkins_client: &Jenkins,
+    url: &str,
+    actions: &[CommonAction],
+    started_at: MaybeTimestamp,
+    finished: Option<(MaybeTimestamp, BuildStatus)>,
+) -> Result<Vec<TimelineEvent>, Error> {
+    let path = jenkins_client.url_to_path(url);
+    if let Path::Build { .. } = path {
+        let state: BuildQueueState = jenkins_client
+            .get_with_params(&path, [("tree", "_class,queueId")])?
+            .json()?;
+
+        let queuing_duration = actions
+            .iter()
+            .find(|action| action.class.as_deref() == Some(TimeInQueueAction::with_class()))
+            .map(|action| action.as_variant::<TimeInQueueAction>())
+            .transpose()?
+            .map(|action| action.queuing_duration_millis)
+            .filter(|duration| duration.is_known());
+        let queued_at = match queuing_duration {
+            Some(duration) => started_at - duration,
+            None => match jenkins_client.get_with_params(
+                &Path::QueueItem { id: state.queue_id },
+                [("tree", "inQueueSince")],
+            ) {
+                Ok(mut response) => response.json::<QueueItemSince>()?.in_queue_since,
+                Err(ref error) if is_not_found(error) => MaybeTimestamp::Unknown,
+                Err(error) => return Err(error),
+            },
+        };
+
+        let stages = if state.class.as_deref() == Some(WorkflowRun::with_class()) {
+            get_workflow_description(jenkins_client, url)?.stages
+        } else {
+            vec![]
+        };
+
+        Ok(assemble(&TimelineFacts {
+            queued_at,
+            started_at,
+            finished,
+            stages: &stages,
+        }))
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: url.to_string(),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use super::{assemble, TimelineEventKind, TimelineFacts};
+    use crate::build::{Build, BuildStatus, CommonBuild};
+    use crate::helpers::MaybeTimestamp;
+    use crate::pipeline::{WorkflowStage, WorkflowStatus};
+
+    fn stage(name: &str, status: WorkflowStatus, start: u64, duration: u64) -> WorkflowStage {
+        serde_json::from_value(serde_json::json!({
+            "id": name,
+            "name": name,
+            "status": status,
+            "startTimeMillis": start,
+            "durationMillis": duration,
+        }))
+        .unwrap()
+    }
+
+    fn kinds(facts: &TimelineFacts<'_>) -> Vec<(u64, TimelineEventKind, bool)> {
+        assemble(facts)
+            .into_iter()
+            .map(|event| (event.timestamp, event.kind, event.out_of_order))
+            .collect()
+    }
+
+    #[test]
+    fn freestyle_build_has_short_timeline() {
+        let events = assemble(&TimelineFacts {
+            queued_at: MaybeTimestamp::Known(900),
+            started_at: MaybeTimestamp::Known(1000),
+            finished: Some((MaybeTimestamp::Known(1500), BuildStatus::Success)),
+            stages: &[],
+        });
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.timestamp, event.label.as_str(), event.out_of_order))
+                .collect::<Vec<_>>(),
+            vec![
+                (900, "Queued", false),
+                (1000, "Started", false),
+                (1500, "Finished: Success", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_queue_time_and_running_build() {
+        assert_eq!(
+            kinds(&TimelineFacts {
+                queued_at: MaybeTimestamp::Unknown,
+                started_at: MaybeTimestamp::Known(1000),
+                finished: None,
+                stages: &[],
+            }),
+            vec![(1000, TimelineEventKind::Started, false)]
+        );
+    }
+
+    #[test]
+    fn pipeline_stages_are_interleaved() {
+        let stages = [
+            stage("Build", WorkflowStatus::Success, 1100, 200),
+            stage("Test", WorkflowStatus::Failed, 1300, 100),
+            stage("Deploy", WorkflowStatus::NotExecuted, 0, 0),
+        ];
+        assert_eq!(
+            kinds(&TimelineFacts {
+                queued_at: MaybeTimestamp::Known(900),
+                started_at: MaybeTimestamp::Known(1000),
+                finished: Some((MaybeTimestamp::Known(1500), BuildStatus::Failure)),
+                stages: &stages,
+            }),
+            vec![
+                (900, TimelineEventKind::Queued, false),
+                (1000, TimelineEventKind::Started, false),
+                (
+                    1100,
+                    TimelineEventKind::StageStarted {
+                        name: "Build".to_string()
+                    },
+                    false
+                ),
+                (
+                    1300,
+                    TimelineEventKind::StageFinished {
+                        name: "Build".to_string(),
+                        status: WorkflowStatus::Success
+                    },
+                    false
+                ),
+                (
+                    1300,
+                    TimelineEventKind::StageStarted {
+                        name: "Test".to_string()
+                    },
+                    false
+                ),
+                (
+                    1400,
+                    TimelineEventKind::StageFinished {
+                        name: "Test".to_string(),
+                        status: WorkflowStatus::Failed
+                    },
+                    false
+                ),
+                (
+                    1500,
+                    TimelineEventKind::Finished {
+                        result: BuildStatus::Failure
+                    },
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_with_unknown_times_are_skipped() {
+        let stages = [
+            stage("Build", WorkflowStatus::Success, 0, 200),
+            stage("Test", WorkflowStatus::Success, 1300, 100),
+        ];
+        assert_eq!(
+            kinds(&TimelineFacts {
+                queued_at: MaybeTimestamp::Known(900),
+                started_at: MaybeTimestamp::Unknown,
+                finished: Some((MaybeTimestamp::Unknown, BuildStatus::Success)),
+                stages: &stages,
+            }),
+            vec![
+                (900, TimelineEventKind::Queued, false),
+                (
+                    1300,
+                    TimelineEventKind::StageStarted {
+                        name: "Test".to_string()
+                    },
+                    false
+                ),
+                (
+                    1400,
+                    TimelineEventKind::StageFinished {
+                        name: "Test".to_string(),
+                        status: WorkflowStatus::Success
+                    },
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn running_stage_has_no_end() {
+        let stages = [stage("Build", WorkflowStatus::InProgress, 1100, 50)];
+        assert_eq!(
+            kinds(&TimelineFacts {
+                queued_at: MaybeTimestamp::Unknown,
+                started_at: MaybeTimestamp::Known(1000),
+                finished: None,
+                stages: &stages,
+            }),
+            vec![
+                (1000, TimelineEventKind::Started, false),
+                (
+                    1100,
+                    TimelineEventKind::StageStarted {
+                        name: "Build".to_string()
+                    },
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn out_of_order_events_are_flagged_and_kept() {
+        let stages = [stage("Build", WorkflowStatus::Success, 950, 600)];
+        let events = kinds(&TimelineFacts {
+            queued_at: MaybeTimestamp::Known(1200),
+            started_at: MaybeTimestamp::Known(1000),
+            finished: Some((MaybeTimestamp::Known(1500), BuildStatus::Success)),
+            stages: &stages,
+        });
+        assert_eq!(
+            events
+                .iter()
+                .map(|(time, kind, out_of_order)| (*time, kind.rank(), *out_of_order))
+                .collect::<Vec<_>>(),
+            vec![
+                (950, 2, true),
+                (1000, 1, true),
+                (1200, 0, false),
+                (1500, 4, true),
+                (1550, 3, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_get_timeline_from_queue_item() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/timeline", mockito::server_url()))
+                .build()
+                .unwrap();
+        let build: CommonBuild = serde_json::from_str(&format!(
+            r##"{{"_class":"hudson.model.FreeStyleBuild","url":"{}/timeline/job/job/2/",
+            "number":2,"duration":500,"estimatedDuration":500,"timestamp":1000,"keepLog":false,
+            "result":"SUCCESS","displayName":"#2","building":false,"id":"2","queueId":7}}"##,
+            mockito::server_url()
+        ))
+        .unwrap();
+
+        let _build = mockito::mock("GET", "/timeline/job/job/2/api/json?tree=_class%2CqueueId")
+            .with_body(r#"{"_class":"hudson.model.FreeStyleBuild","queueId":7}"#)
+            .create();
+        let _item = mockito::mock("GET", "/timeline/queue/item/7/api/json?tree=inQueueSince")
+            .with_body(r#"{"_class":"hudson.model.Queue$LeftItem","inQueueSince":800}"#)
+            .create();
+
+        let events = build.timeline(&jenkins_client).unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.timestamp, event.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (800, TimelineEventKind::Queued),
+                (1000, TimelineEventKind::Started),
+                (
+                    1500,
+                    TimelineEventKind::Finished {
+                        result: BuildStatus::Success
+                    }
+                ),
+            ]
+        );
+    }
+}
@@ -83,6 +83,22 @@ changesetlist_with_common_fields_and_impl!(
 );
 register_class!("hudson.plugins.repo.RepoChangeLogSet" => RepoChangeLogSet);
 
+changesetlist_with_common_fields_and_impl!(
+    /// Changes found from subversion
+    #[derive(Serialize, Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SubversionChangeLogSet {}
+);
+register_class!("hudson.scm.SubversionChangeLogSet" => SubversionChangeLogSet);
+
+changesetlist_with_common_fields_and_impl!(
+    /// Changes found from mercurial
+    #[derive(Serialize, Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MercurialChangeSetList {}
+);
+register_class!("hudson.plugins.mercurial.MercurialChangeSetList" => MercurialChangeSetList);
+
 changesetlist_with_common_fields_and_impl!(
     /// Changes filtered by maven module
     #[derive(Serialize, Deserialize, Debug)]
@@ -94,6 +110,18 @@ register_class!("hudson.maven.FilteredChangeLogSet" => FilteredChangeLogSet);
 /// Trait implemented by specialization of changeset
 pub trait ChangeSet {}
 
+/// Fields common to the changes of all SCMs
+pub trait ChangeSetEntry {
+    /// ID of the change, as a string: the commit id for git, the revision for subversion, ...
+    fn id_string(&self) -> Option<String>;
+    /// Name of the author of the change
+    fn author_name(&self) -> Option<&str>;
+    /// Message of the change
+    fn message(&self) -> &str;
+    /// Files changed by the change
+    fn affected_paths(&self) -> Vec<&str>;
+}
+
 /// A Change Set
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -107,6 +135,35 @@ pub struct CommonChangeSet {
 specialize!(CommonChangeSet => ChangeSet);
 impl ChangeSet for CommonChangeSet {}
 
+impl ChangeSetEntry for CommonChangeSet {
+    fn id_string(&self) -> Option<String> {
+        match self.other_fields.get("commitId") {
+            Some(serde_json::Value::String(id)) => Some(id.clone()),
+            Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+    fn author_name(&self) -> Option<&str> {
+        self.other_fields
+            .get("author")
+            .and_then(|author| author.get("fullName"))
+            .and_then(serde_json::Value::as_str)
+    }
+    fn message(&self) -> &str {
+        self.other_fields
+            .get("msg")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or("")
+    }
+    fn affected_paths(&self) -> Vec<&str> {
+        self.other_fields
+            .get("affectedPaths")
+            .and_then(serde_json::Value::as_array)
+            .map(|paths| paths.iter().filter_map(serde_json::Value::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
 /// Changes found from git
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -135,6 +192,21 @@ pub struct GitChangeSet {
 register_class!("hudson.plugins.git.GitChangeSet" => GitChangeSet);
 impl ChangeSet for GitChangeSet {}
 
+impl ChangeSetEntry for GitChangeSet {
+    fn id_string(&self) -> Option<String> {
+        Some(self.commit_id.clone())
+    }
+    fn author_name(&self) -> Option<&str> {
+        Some(&self.author.full_name)
+    }
+    fn message(&self) -> &str {
+        &self.msg
+    }
+    fn affected_paths(&self) -> Vec<&str> {
+        self.affected_paths.iter().map(String::as_str).collect()
+    }
+}
+
 /// Changes found from a repo
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -153,6 +225,99 @@ pub struct ChangeLogEntry {
 register_class!("hudson.plugins.repo.ChangeLogEntry" => ChangeLogEntry);
 impl ChangeSet for ChangeLogEntry {}
 
+impl ChangeSetEntry for ChangeLogEntry {
+    fn id_string(&self) -> Option<String> {
+        self.commit_id.clone()
+    }
+    fn author_name(&self) -> Option<&str> {
+        Some(&self.author.full_name)
+    }
+    fn message(&self) -> &str {
+        &self.msg
+    }
+    fn affected_paths(&self) -> Vec<&str> {
+        self.affected_paths
+            .iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Changes found from subversion
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SubversionLogEntry {
+    /// Revision of the commit, as a string
+    pub commit_id: String,
+    /// Revision of the commit
+    pub revision: u32,
+    /// Commit message
+    pub msg: String,
+    /// Timestamp of the commit
+    pub timestamp: u64,
+    /// Files changed in the commit
+    pub affected_paths: Vec<String>,
+    /// Author of the commit
+    pub author: ShortUser,
+    /// Files changed in the commit, and how
+    pub paths: Vec<PathChange>,
+}
+register_class!("hudson.scm.SubversionChangeLogSet$LogEntry" => SubversionLogEntry);
+impl ChangeSet for SubversionLogEntry {}
+
+impl ChangeSetEntry for SubversionLogEntry {
+    fn id_string(&self) -> Option<String> {
+        Some(self.revision.to_string())
+    }
+    fn author_name(&self) -> Option<&str> {
+        Some(&self.author.full_name)
+    }
+    fn message(&self) -> &str {
+        &self.msg
+    }
+    fn affected_paths(&self) -> Vec<&str> {
+        self.affected_paths.iter().map(String::as_str).collect()
+    }
+}
+
+/// Changes found from mercurial
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MercurialChangeSet {
+    /// Changeset id of the commit
+    pub node: String,
+    /// Local revision number of the commit
+    pub rev: i64,
+    /// Branch of the commit, `None` for the default branch
+    pub branch: Option<String>,
+    /// Commit message
+    pub msg: String,
+    /// Timestamp of the commit
+    pub timestamp: u64,
+    /// Files changed in the commit
+    pub affected_paths: Vec<String>,
+    /// Author of the commit
+    pub author: ShortUser,
+}
+register_class!("hudson.plugins.mercurial.MercurialChangeSet" => MercurialChangeSet);
+impl ChangeSet for MercurialChangeSet {}
+
+impl ChangeSetEntry for MercurialChangeSet {
+    fn id_string(&self) -> Option<String> {
+        Some(self.node.clone())
+    }
+    fn author_name(&self) -> Option<&str> {
+        Some(&self.author.full_name)
+    }
+    fn message(&self) -> &str {
+        &self.msg
+    }
+    fn affected_paths(&self) -> Vec<&str> {
+        self.affected_paths.iter().map(String::as_str).collect()
+    }
+}
+
 /// Edit type on a file
 #[derive(Debug, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
@@ -174,3 +339,103 @@ pub struct PathChange {
     /// How it was changed
     pub edit_type: EditType,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_git_changes() {
+        let list: CommonChangeSetList = serde_json::from_str(
+            r#"{"_class":"hudson.plugins.git.GitChangeSetList","kind":"git","items":[{
+            "_class":"hudson.plugins.git.GitChangeSet","affectedPaths":["src/lib.rs"],
+            "commitId":"0f3c2a1","timestamp":1551000000000,
+            "author":{"absoluteUrl":"http://none/user/alice","fullName":"Alice"},
+            "authorEmail":"alice@example.com","comment":"Fix parsing\n","date":"2019-02-24 10:00:00 +0100",
+            "id":"0f3c2a1","msg":"Fix parsing","paths":[{"editType":"edit","file":"src/lib.rs"}]}]}"#,
+        )
+        .unwrap();
+        assert!(list.as_variant::<GitChangeSetList>().is_ok());
+        assert_eq!(list.kind, Some("git".to_string()));
+
+        let change = list.items[0].as_variant::<GitChangeSet>().unwrap();
+        assert_eq!(change.commit_id, "0f3c2a1");
+        assert_eq!(change.author.full_name, "Alice");
+        assert_eq!(change.affected_paths, vec!["src/lib.rs"]);
+    }
+
+    #[test]
+    fn can_read_subversion_changes() {
+        let list: CommonChangeSetList = serde_json::from_str(
+            r#"{"_class":"hudson.scm.SubversionChangeLogSet","kind":"svn","items":[{
+            "_class":"hudson.scm.SubversionChangeLogSet$LogEntry","affectedPaths":["trunk/README"],
+            "author":{"absoluteUrl":"http://none/user/bob","fullName":"bob"},"commitId":"1234",
+            "timestamp":1551000000000,"date":"2019-02-24T09:00:00.000000Z","msg":"Update readme",
+            "paths":[{"editType":"add","file":"trunk/README"}],"revision":1234,"user":"bob"}],
+            "revisions":[{"module":"http://svn/repo/trunk","revision":1234}]}"#,
+        )
+        .unwrap();
+        assert!(list.as_variant::<SubversionChangeLogSet>().is_ok());
+        assert_eq!(list.kind, Some("svn".to_string()));
+
+        let change = list.items[0].as_variant::<SubversionLogEntry>().unwrap();
+        assert_eq!(change.revision, 1234);
+        assert_eq!(change.msg, "Update readme");
+        assert_eq!(change.author.full_name, "bob");
+    }
+
+    #[test]
+    fn can_read_mercurial_changes() {
+        let list: CommonChangeSetList = serde_json::from_str(
+            r#"{"_class":"hudson.plugins.mercurial.MercurialChangeSetList","kind":"hg","items":[{
+            "_class":"hudson.plugins.mercurial.MercurialChangeSet","affectedPaths":["setup.py"],
+            "author":{"absoluteUrl":"http://none/user/carol","fullName":"carol"},
+            "commitId":"b2c4f1e09a7d","timestamp":1551000000000,"msg":"Bump version",
+            "paths":[{"editType":"edit","file":"setup.py"}],"addedPaths":[],"deletedPaths":[],
+            "modifiedPaths":["setup.py"],"branch":null,"node":"b2c4f1e09a7d","rev":42,
+            "tags":["tip"]}]}"#,
+        )
+        .unwrap();
+        assert!(list.as_variant::<MercurialChangeSetList>().is_ok());
+        assert_eq!(list.kind, Some("hg".to_string()));
+
+        let change = list.items[0].as_variant::<MercurialChangeSet>().unwrap();
+        assert_eq!(change.rev, 42);
+        assert_eq!(change.node, "b2c4f1e09a7d");
+    }
+
+    #[test]
+    fn can_read_changes_of_any_scm() {
+        let changes: Vec<CommonChangeSet> = serde_json::from_str(
+            r#"[{
+            "_class":"hudson.plugins.git.GitChangeSet","affectedPaths":["src/lib.rs"],
+            "commitId":"0f3c2a1","timestamp":1551000000000,
+            "author":{"absoluteUrl":"http://none/user/alice","fullName":"Alice"},
+            "authorEmail":"alice@example.com","comment":"Fix parsing\n","date":"2019-02-24 10:00:00 +0100",
+            "id":"0f3c2a1","msg":"Fix parsing","paths":[{"editType":"edit","file":"src/lib.rs"}]},{
+            "_class":"hudson.scm.SubversionChangeLogSet$LogEntry","affectedPaths":["trunk/README"],
+            "author":{"absoluteUrl":"http://none/user/bob","fullName":"bob"},"commitId":"1234",
+            "timestamp":1551000000000,"date":"2019-02-24T09:00:00.000000Z","msg":"Update readme",
+            "paths":[{"editType":"add","file":"trunk/README"}],"revision":1234,"user":"bob"},{
+            "_class":"some.unknown.ChangeSet","commitId":7,"msg":"Something else"}]"#,
+        )
+        .unwrap();
+
+        let git = changes[0].as_variant::<GitChangeSet>().unwrap();
+        let svn = changes[1].as_variant::<SubversionLogEntry>().unwrap();
+        let typed: Vec<&dyn ChangeSetEntry> = vec![&git, &svn];
+        for (typed, common) in typed.iter().zip(&changes) {
+            assert_eq!(typed.id_string(), common.id_string());
+            assert_eq!(typed.author_name(), common.author_name());
+            assert_eq!(typed.message(), common.message());
+            assert_eq!(typed.affected_paths(), common.affected_paths());
+        }
+        assert_eq!(svn.id_string(), Some("1234".to_string()));
+        assert_eq!(svn.author_name(), Some("bob"));
+
+        assert_eq!(changes[2].id_string(), Some("7".to_string()));
+        assert_eq!(changes[2].author_name(), None);
+        assert_eq!(changes[2].message(), "Something else");
+        assert!(changes[2].affected_paths().is_empty());
+    }
+}
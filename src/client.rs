@@ -9,6 +9,8 @@ use crate::client_internals::InternalAdvancedQueryParams;
 // pub use client_internals::path::Name;
 pub use crate::client_internals::AdvancedQuery;
 pub use crate::client_internals::{error, Error};
+pub use crate::client_internals::{is_transient_error, Crumb, SessionState};
+pub use crate::client_internals::{RequestParts, ResponseFilters, ResponseKind, ResponseMeta};
 pub use crate::client_internals::{TreeBuilder, TreeQueryParam};
 
 use crate::build;
@@ -62,6 +64,8 @@ pub enum Path<'a> {
         /// The computer name
         name: &'a str,
     },
+    /// Path to the plugin manager
+    PluginManager,
     /// Unknown path
     Raw {
         /// The path itself
@@ -107,6 +111,7 @@ impl<'a> Into<PrivatePath<'a>> for Path<'a> {
             Path::Computer { name } => PrivatePath::Computer {
                 name: Name::Name(name),
             },
+            Path::PluginManager => PrivatePath::PluginManager,
             Path::Raw { path } => PrivatePath::Raw { path },
         }
     }
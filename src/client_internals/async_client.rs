@@ -0,0 +1,195 @@
+//! Requests to Jenkins with the asynchronous client
+
+use std::sync::Arc;
+
+use futures::future::{self, Future};
+use log::{debug, warn};
+use reqwest::{
+    header::HeaderName,
+    header::HeaderValue,
+    header::ACCEPT,
+    header::CONTENT_TYPE,
+    header::COOKIE,
+    r#async::{RequestBuilder, Response},
+    Method, StatusCode,
+};
+use serde::Serialize;
+
+use super::csrf::Crumb;
+use super::{check_status, path::Path, Jenkins, NO_PARAMS};
+use crate::r#async::JenkinsFuture;
+
+fn error_for_status(method: &Method, response: Response) -> Result<Response, failure::Error> {
+    check_status(
+        method,
+        response.status(),
+        response.url(),
+        response.headers(),
+    )?;
+    Ok(response.error_for_status()?)
+}
+
+impl Jenkins {
+    /// Send a request with the asynchronous client, with the credentials and the cookies of the
+    /// session and after running the request middlewares, keeping the cookies set by Jenkins.
+    /// The `Response` is returned whatever its status. Requests are not retried
+    fn async_send(
+        self: &Arc<Self>,
+        mut request_builder: RequestBuilder,
+    ) -> JenkinsFuture<Response> {
+        if let Some(ref user) = self.user {
+            request_builder =
+                request_builder.basic_auth(user.username.clone(), user.password.clone());
+        }
+        if let Some(cookies) = self.session_cookies() {
+            request_builder = request_builder.header(COOKIE, cookies);
+        }
+        let mut query = match request_builder.build() {
+            Ok(query) => query,
+            Err(error) => return Box::new(future::err(error.into())),
+        };
+        if let Err(error) = self.request_middlewares.apply_async(&mut query) {
+            return Box::new(future::err(error.into()));
+        }
+        debug!("sending {} {}", query.method(), query.url());
+        let jenkins_client = self.clone();
+        Box::new(
+            self.async_client
+                .execute(query)
+                .map_err(super::request_error)
+                .map(move |response| {
+                    jenkins_client.update_session(response.headers());
+                    response
+                }),
+        )
+    }
+
+    pub(crate) fn async_get(self: &Arc<Self>, path: &Path) -> JenkinsFuture<Response> {
+        self.async_get_with_params(path, &[("depth", self.depth.to_string())])
+    }
+
+    /// Send a `GET` request, running the response filters on its body as
+    /// `Jenkins::get_with_params` does
+    pub(crate) fn async_get_with_params<T: Serialize>(
+        self: &Arc<Self>,
+        path: &Path,
+        qps: T,
+    ) -> JenkinsFuture<Response> {
+        let kind = path.response_kind();
+        let jenkins_client = self.clone();
+        Box::new(
+            self.async_send(
+                self.async_client
+                    .get(&self.url_api_json(&path.to_string()))
+                    .header(ACCEPT, self.accept.clone())
+                    .query(&qps),
+            )
+            .and_then(|response| error_for_status(&Method::GET, response))
+            .and_then(move |response| jenkins_client.response_filters.apply_async(kind, response)),
+        )
+    }
+
+    /// Get the crumb of the session and its generation, or a new crumb if there is none, if it
+    /// expired, or if it is of generation `rejected`, as `session_crumb` does without blocking
+    fn async_session_crumb(
+        self: &Arc<Self>,
+        rejected: Option<u64>,
+    ) -> JenkinsFuture<(Crumb, Option<u64>)> {
+        if let Some((crumb, generation)) = self.unexpired_session_crumb(rejected) {
+            return Box::new(future::ok((crumb, Some(generation))));
+        }
+        let jenkins_client = self.clone();
+        Box::new(
+            self.async_get_with_params(&Path::CrumbIssuer, NO_PARAMS)
+                .and_then(|mut response| response.json::<Crumb>().map_err(failure::Error::from))
+                .map(move |crumb| {
+                    jenkins_client.renew_session_crumb(&crumb);
+                    (crumb, None)
+                }),
+        )
+    }
+
+    /// Send a request with the crumb of the session, replacing it first if it's of generation
+    /// `rejected`, and tell the generation of the crumb sent if it was the cached one
+    fn async_send_with_crumb(
+        self: &Arc<Self>,
+        request_builder: RequestBuilder,
+        rejected: Option<u64>,
+    ) -> JenkinsFuture<(Response, Option<u64>)> {
+        let (jenkins_client, session_client) = (self.clone(), self.clone());
+        Box::new(
+            self.async_session_crumb(rejected)
+                .and_then(
+                    move |(crumb, cached)| -> JenkinsFuture<((HeaderName, HeaderValue), _)> {
+                        match crumb.header() {
+                            Ok(header) => Box::new(future::ok((header, cached))),
+                            Err(error) if cached.is_some() => {
+                                warn!("invalid crumb in session: {}", error);
+                                Box::new(
+                                    session_client
+                                        .async_session_crumb(cached)
+                                        .and_then(|(crumb, _)| Ok((crumb.header()?, None))),
+                                )
+                            }
+                            Err(error) => Box::new(future::err(error)),
+                        }
+                    },
+                )
+                .and_then(move |((name, value), cached)| {
+                    jenkins_client
+                        .async_send(request_builder.header(name, value))
+                        .map(move |response| (response, cached))
+                }),
+        )
+    }
+
+    /// Send a POST request with a crumb, as `Jenkins::send_post` does: if the crumb of the
+    /// session is rejected, the request is sent again with a new crumb
+    pub(crate) fn async_post_with_body<T: Serialize>(
+        self: &Arc<Self>,
+        path: &Path,
+        body: String,
+        qps: T,
+    ) -> JenkinsFuture<Response> {
+        let query = match serde_urlencoded::to_string(&qps) {
+            Ok(query) => query,
+            Err(error) => return Box::new(future::err(error.into())),
+        };
+        let mut url = self.url(&path.to_string());
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query);
+        }
+        let async_client = self.async_client.clone();
+        let request = move || {
+            async_client
+                .post(&url)
+                .header(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/x-www-form-urlencoded"),
+                )
+                .body(body.clone())
+        };
+
+        let response: JenkinsFuture<Response> = if self.csrf_enabled {
+            let jenkins_client = self.clone();
+            Box::new(self.async_send_with_crumb(request(), None).and_then(
+                move |(response, cached)| -> JenkinsFuture<Response> {
+                    match response.status() {
+                        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN if cached.is_some() => {
+                            warn!("session was rejected, getting a new crumb");
+                            Box::new(
+                                jenkins_client
+                                    .async_send_with_crumb(request(), cached)
+                                    .map(|(response, _)| response),
+                            )
+                        }
+                        _ => Box::new(future::ok(response)),
+                    }
+                },
+            ))
+        } else {
+            self.async_send(request())
+        };
+        Box::new(response.and_then(|response| error_for_status(&Method::POST, response)))
+    }
+}
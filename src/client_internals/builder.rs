@@ -1,9 +1,16 @@
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
 
 use failure::Error;
-use reqwest::{self, Client, Url};
+use reqwest::{self, header::HeaderValue, Certificate, Client, Url};
 
-use super::{Jenkins, User};
+use super::csrf::CrumbRefresh;
+use super::middleware::{RequestMiddlewareChain, RequestParts};
+use super::response_filter::{ResponseFilterChain, ResponseMeta};
+use super::retry::RetryPolicy;
+use super::{Error as JenkinsError, Jenkins, SessionState, User};
 
 /// Builder for Jenkins client
 ///
@@ -22,47 +29,123 @@ use super::{Jenkins, User};
 #[derive(Debug)]
 pub struct JenkinsBuilder {
     url: String,
+    public_url: Option<String>,
+    /// Error of the first invalid URL, checked as given before its trailing `/` is removed
+    invalid_url: Option<JenkinsError>,
     user: Option<User>,
     csrf_enabled: bool,
     depth: u8,
+    timeout: Option<Duration>,
+    root_certificates: Vec<Vec<u8>>,
+    #[cfg(feature = "insecure-tls")]
+    accept_invalid_certificates: bool,
+    session: SessionState,
+    crumb_ttl: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    retry_posts: bool,
+    response_filters: ResponseFilterChain,
+    request_middlewares: RequestMiddlewareChain,
+    accept: String,
+}
+
+/// Apply the options of a `JenkinsBuilder` to a reqwest `ClientBuilder`, blocking or
+/// asynchronous, and build the client
+macro_rules! build_client {
+    ($jenkins_builder:expr, $client_builder:expr) => {{
+        let mut client_builder = $client_builder;
+        if let Some(timeout) = $jenkins_builder.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+        for pem in &$jenkins_builder.root_certificates {
+            client_builder = client_builder.add_root_certificate(Certificate::from_pem(pem)?);
+        }
+        #[cfg(feature = "insecure-tls")]
+        {
+            if $jenkins_builder.accept_invalid_certificates {
+                client_builder = client_builder.danger_accept_invalid_certs(true);
+            }
+        }
+        client_builder.build()?
+    }};
 }
 
 impl JenkinsBuilder {
     /// Create a new builder with Jenkins url
     pub fn new(url: &str) -> Self {
         JenkinsBuilder {
-            url: {
-                let last: String = url.chars().rev().take(1).collect();
-                match last.as_str() {
-                    "/" => url[0..(url.len() - 1)].to_string(),
-                    _ => url.to_string(),
-                }
-            },
+            url: without_trailing_slash(url),
+            public_url: None,
+            invalid_url: validate_url(url).err(),
             user: None,
             csrf_enabled: true,
             depth: 1,
+            timeout: None,
+            root_certificates: vec![],
+            #[cfg(feature = "insecure-tls")]
+            accept_invalid_certificates: false,
+            session: SessionState::default(),
+            crumb_ttl: None,
+            retry: None,
+            retry_posts: false,
+            response_filters: ResponseFilterChain::default(),
+            request_middlewares: RequestMiddlewareChain::default(),
+            accept: "application/json".to_string(),
         }
     }
 
     /// Build the Jenkins client
     pub fn build(self) -> Result<Jenkins, Error> {
-        let url = Url::from_str(&self.url)?;
-        if url.cannot_be_a_base() {
-            Err(reqwest::UrlError::RelativeUrlWithoutBase)?;
-        };
-        if !url.has_host() {
-            Err(reqwest::UrlError::EmptyHost)?;
+        if let Some(error) = self.invalid_url {
+            return Err(error.into());
         }
 
+        let client = build_client!(self, Client::builder());
+        #[cfg(feature = "async")]
+        let async_client = build_client!(self, reqwest::r#async::Client::builder());
+
+        let retry_posts = self.retry_posts;
+        let retry = self.retry.map(|retry| RetryPolicy {
+            retry_posts,
+            ..retry
+        });
+
         Ok(Jenkins {
             url: self.url,
-            client: Client::builder().build()?,
+            public_url: self.public_url,
+            client,
             user: self.user,
             csrf_enabled: self.csrf_enabled,
             depth: self.depth,
+            crumb_refresh: CrumbRefresh::new(self.crumb_ttl, self.session.crumb.is_some()),
+            session: Mutex::new(self.session),
+            retry,
+            response_filters: self.response_filters,
+            request_middlewares: self.request_middlewares,
+            accept: HeaderValue::from_str(&self.accept)?,
+            class_descriptors: Mutex::new(HashMap::new()),
+            command_methods: Mutex::new(HashMap::new()),
+            #[cfg(feature = "async")]
+            async_client,
         })
     }
 
+    /// Set the URL users reach Jenkins at, when it differs from the URL of the client, to
+    /// build links for them with
+    /// [`Jenkins::public_url_for`](struct.Jenkins.html#method.public_url_for)
+    pub fn with_public_url(mut self, url: &str) -> Self {
+        if self.invalid_url.is_none() {
+            self.invalid_url = validate_url(url).err();
+        }
+        self.public_url = Some(without_trailing_slash(url));
+        self
+    }
+
+    /// Build the asynchronous Jenkins client
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<crate::r#async::Jenkins, Error> {
+        Ok(crate::r#async::Jenkins::new(self.build()?))
+    }
+
     /// Specify the user to use for authorizing queries
     pub fn with_user(mut self, login: &str, password: Option<&str>) -> Self {
         self.user = Some(User {
@@ -78,18 +161,172 @@ impl JenkinsBuilder {
         self
     }
 
+    /// Retry `GET` requests up to `count` times when they fail with a transient error, see
+    /// [`is_transient_error`](client/fn.is_transient_error.html). The delay between two
+    /// attempts starts at `base_delay` and doubles after each attempt
+    pub fn with_retries(mut self, count: u32, base_delay: Duration) -> Self {
+        self.retry = Some(RetryPolicy {
+            retries: count,
+            base_delay,
+            retry_posts: false,
+        });
+        self
+    }
+
+    /// Also retry `POST` requests when retries are set with `with_retries`. They are not
+    /// retried by default, as Jenkins may have acted on a request before failing, like
+    /// triggering a build
+    pub fn with_post_retries(mut self) -> Self {
+        self.retry_posts = true;
+        self
+    }
+
+    /// Rewrite the body of responses before they are parsed, to work around a proxy altering
+    /// them. Filters run in the order they were added, on every successful response except
+    /// binary downloads like artifacts or the console output. See
+    /// [`ResponseFilters`](client/struct.ResponseFilters.html) for ready-made filters
+    pub fn with_response_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&mut Vec<u8>, &ResponseMeta) + Send + Sync + 'static,
+    {
+        self.response_filters.push(filter);
+        self
+    }
+
+    /// Change every request before it is sent, to add headers computed for each request like a
+    /// trace id or a signature of the URL. Middlewares run in the order they were added, on
+    /// every request of the blocking and asynchronous clients including the crumb fetch. If a
+    /// middleware fails, the request is not sent and an
+    /// [`Error::MiddlewareFailed`](client/enum.Error.html#variant.MiddlewareFailed) is returned
+    pub fn with_request_middleware<F>(mut self, middleware: F) -> Self
+    where
+        F: Fn(&mut RequestParts<'_>) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.request_middlewares.push(middleware);
+        self
+    }
+
+    /// Change the `Accept` header sent when getting JSON from the Jenkins API, by default
+    /// `application/json`, for gateways in front of Jenkins expecting a vendor-specific value
+    ///
+    /// An invalid header value makes `build` fail
+    pub fn with_accept(mut self, header_value: &str) -> Self {
+        self.accept = header_value.to_string();
+        self
+    }
+
+    /// Resume a session exported with
+    /// [`Jenkins::export_session`](struct.Jenkins.html#method.export_session). If Jenkins
+    /// rejects it, a new session is started
+    pub fn with_session(mut self, session: SessionState) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Refresh the crumb before sending a `POST` once it's older than `ttl`, for instances
+    /// expiring crumbs like with the Strict Crumb Issuer plugin. Without it, the crumb is only
+    /// refreshed after Jenkins rejected it
+    ///
+    /// The refresh is brought forward by up to a tenth of `ttl` at random, and only one thread
+    /// gets a new crumb when several threads share the client, the others waiting for it. The
+    /// asynchronous client shares the crumb and its time to live, but requests in flight
+    /// together that find it expired each get a new one instead of waiting
+    pub fn with_crumb_ttl(mut self, ttl: Duration) -> Self {
+        self.crumb_ttl = Some(ttl);
+        self
+    }
+
     /// Change the default depth parameters of requests made to Jenkins. It
     /// controls the amount of data in responses
     pub fn with_depth(mut self, depth: u8) -> Self {
         self.depth = depth;
         self
     }
+
+    /// Set a timeout for requests made to Jenkins. A request that times out returns an
+    /// [`Error::Timeout`](client/enum.Error.html#variant.Timeout)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Trust an additional root certificate, in PEM format, like the certificate of a Jenkins
+    /// using a self-signed certificate. This is the preferred way to connect to such a Jenkins
+    ///
+    /// An invalid certificate makes `build` fail
+    pub fn with_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Accept any TLS certificate, even invalid or for another host, making connections
+    /// vulnerable to man-in-the-middle attacks. Prefer `with_root_certificate` when possible
+    ///
+    /// Only available with the `insecure-tls` feature
+    #[cfg(feature = "insecure-tls")]
+    pub fn danger_disable_tls_verification(mut self) -> Self {
+        self.accept_invalid_certificates = true;
+        self
+    }
+}
+
+/// Remove the trailing `/` of a URL
+fn without_trailing_slash(url: &str) -> String {
+    url.strip_suffix('/').unwrap_or(url).to_string()
+}
+
+/// Check that a base URL is an absolute http or https URL with a host
+fn validate_url(url: &str) -> Result<(), JenkinsError> {
+    let invalid = |reason: String| JenkinsError::InvalidBaseUrl {
+        url: url.to_string(),
+        reason,
+    };
+    let url = match Url::from_str(url) {
+        Ok(url) => url,
+        Err(reqwest::UrlError::RelativeUrlWithoutBase) => {
+            return Err(invalid("missing scheme, like 'http://'".to_string()))
+        }
+        Err(reqwest::UrlError::EmptyHost) => return Err(invalid("missing host".to_string())),
+        Err(error) => return Err(invalid(format!("invalid host or port: {}", error))),
+    };
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(invalid(format!(
+            "unsupported scheme '{}', expected 'http' or 'https'",
+            url.scheme()
+        )));
+    }
+    if url.host_str().unwrap_or("").is_empty() {
+        return Err(invalid("missing host".to_string()));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     static JENKINS_URL: &'static str = "http://none:8080";
 
+    static SELF_SIGNED_CERTIFICATE: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIDHzCCAgegAwIBAgIUTKv+hGcI2S97dbI73W9FKOa4eNswDQYJKoZIhvcNAQEL
+BQAwHjEcMBoGA1UEAwwTamVua2lucy5leGFtcGxlLmNvbTAgFw0yNjEwMTYxODEy
+MjRaGA8yMTI2MDkyMjE4MTIyNFowHjEcMBoGA1UEAwwTamVua2lucy5leGFtcGxl
+LmNvbTCCASIwDQYJKoZIhvcNAQEBBQADggEPADCCAQoCggEBAMoSs43BTqqbVv4v
+skfW6Yvj58gvLKOKerCxT4/iA0dSFAj91sLF6Yv6TkHhVdSRpBY6wAilWbguN0rN
+3pmTpMtr4ero5BjAKcSOh1XqsU1XtfY4Olg9OxBtmBlstLNnalrHPnFddFp94TNe
+bGj/5j9bHC7GB0CQtVDcXJykiQFfh+fXV/0AYnegYt0vzUgc9kjO7XXNxKnU9eoG
+yy7/UKFfn7IVYwLawVpsSfRPz6fmWl56OujmseuCfshOJbkT4K5WfSXdmYnlFgMQ
+zec4L+XbkDzToRwvPfpsRvBl1Yb1dn6SUq6OHvzN2ZX6ZIl5hY6VlUapvlr2ky3K
+d+SbisUCAwEAAaNTMFEwHQYDVR0OBBYEFEapht1YXGxceuhpJuaP7rMyJrXbMB8G
+A1UdIwQYMBaAFEapht1YXGxceuhpJuaP7rMyJrXbMA8GA1UdEwEB/wQFMAMBAf8w
+DQYJKoZIhvcNAQELBQADggEBADgOYvEnJvFT8FRQDMTYi0bbC87/lqvSs86ipflX
+n3nqjqFrr9rdvGvWvNh1b5bnSO/8UpNk0mT92W2SHVSeVePY/EjQNikuuk1G7h42
+i+Bay1kWseC/wzXd6U9wSyvM3caxecXBoSp8lTWuafzP/4C6rYYqZkp8PKj3U5Zy
+m+Y1zl71K5MM+vadf9xoBonKl0YCM1e0CavaZGR/7h2QofSbd9uTM9T3rRQyzcrm
+ObgBo38x0zbHVz9FdxudZ0CeVTPlgW47g6Y3Gh9Ohclqw91bOlqJa+1q4vN67OXQ
+9pZ/b2ZT6J26SFMPcqcXpAsneIjjYMPh8W36pcftbcNITZM=
+-----END CERTIFICATE-----
+";
+
     #[test]
     fn create_builder() {
         let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL);
@@ -108,6 +345,33 @@ mod tests {
         assert_eq!(jenkins_client.csrf_enabled, true);
     }
 
+    #[test]
+    fn should_fail_to_build_with_invalid_url() {
+        let error_for =
+            |url: &str| format!("{}", crate::JenkinsBuilder::new(url).build().unwrap_err());
+
+        assert_eq!(
+            error_for("/jenkins"),
+            "invalid Jenkins url '/jenkins': missing scheme, like 'http://'"
+        );
+        assert_eq!(
+            error_for("localhost:8080"),
+            "invalid Jenkins url 'localhost:8080': unsupported scheme 'localhost', expected 'http' or 'https'"
+        );
+        assert_eq!(
+            error_for("http://"),
+            "invalid Jenkins url 'http://': missing host"
+        );
+        assert!(error_for("http://jenkins:port")
+            .starts_with("invalid Jenkins url 'http://jenkins:port': invalid host or port"));
+        assert!(error_for("http://exa mple.com").contains("invalid host or port"));
+        assert!(
+            crate::JenkinsBuilder::new("https://jenkins.example.com/ci/")
+                .build()
+                .is_ok()
+        );
+    }
+
     #[test]
     fn disable_csrf() {
         let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).disable_csrf();
@@ -117,4 +381,19 @@ mod tests {
         assert_eq!(jenkins_client.csrf_enabled, false);
     }
 
+    #[test]
+    fn can_add_root_certificate() {
+        assert!(crate::JenkinsBuilder::new(JENKINS_URL)
+            .with_root_certificate(SELF_SIGNED_CERTIFICATE.as_bytes())
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn should_fail_to_build_with_invalid_root_certificate() {
+        assert!(crate::JenkinsBuilder::new(JENKINS_URL)
+            .with_root_certificate(b"-----BEGIN CERTIFICATE-----\nnot a certificate\n")
+            .build()
+            .is_err());
+    }
 }
@@ -1,35 +1,392 @@
-use failure;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
+use failure;
+use log::{debug, warn};
 use reqwest::{header::HeaderName, header::HeaderValue, RequestBuilder};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::{path::Path, Jenkins};
+use super::{path::Path, Jenkins, NO_PARAMS};
 
-#[derive(Debug, Deserialize, Clone)]
+/// Crumb protecting Jenkins from CSRF
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct Crumb {
-    crumb: String,
-    crumb_request_field: String,
+pub struct Crumb {
+    /// Value of the crumb
+    pub crumb: String,
+    /// Name of the field in which to pass the crumb
+    pub crumb_request_field: String,
+}
+
+impl Crumb {
+    /// Header to add to a request to pass the crumb
+    pub(crate) fn header(&self) -> Result<(HeaderName, HeaderValue), failure::Error> {
+        Ok((
+            HeaderName::from_lowercase(self.crumb_request_field.to_lowercase().as_bytes())?,
+            HeaderValue::from_str(&self.crumb)?,
+        ))
+    }
+}
+
+/// Share of the time to live of crumbs by which their refresh is brought forward at random,
+/// so clients started together don't all refresh their crumbs at the same time
+const CRUMB_TTL_JITTER: f64 = 0.1;
+
+/// When the crumb of the session must be refreshed, and how many were fetched, so a crumb that
+/// expired or was rejected is only refreshed once when several threads find it out together
+#[derive(Debug)]
+pub(crate) struct CrumbRefresh {
+    /// Time to live of crumbs, `None` to refresh them only once rejected
+    ttl: Option<Duration>,
+    clock: Mutex<CrumbClock>,
+}
+
+#[derive(Debug, Default)]
+struct CrumbClock {
+    /// Number of crumbs fetched by the client, identifying the crumb of the session
+    generation: u64,
+    /// When the crumb of the session expires
+    refresh_at: Option<Instant>,
+}
+
+/// Random duration up to `CRUMB_TTL_JITTER` of `ttl`
+fn jitter(ttl: Duration) -> Duration {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(ttl.as_nanos() as u64);
+    let share = (hasher.finish() % 1000) as f64 / 1000.0;
+    ttl.mul_f64(share * CRUMB_TTL_JITTER)
+}
+
+impl CrumbRefresh {
+    /// Track the crumbs of a client, `resumed` telling if it starts with the crumb of a resumed
+    /// session
+    pub(crate) fn new(ttl: Option<Duration>, resumed: bool) -> Self {
+        let mut clock = CrumbClock::default();
+        if resumed {
+            clock.expire_after(ttl);
+        }
+        CrumbRefresh {
+            ttl,
+            clock: Mutex::new(clock),
+        }
+    }
+}
+
+impl CrumbClock {
+    fn expire_after(&mut self, ttl: Option<Duration>) {
+        self.refresh_at = ttl.map(|ttl| Instant::now() + ttl - jitter(ttl));
+    }
+
+    fn is_expired(&self) -> bool {
+        self.refresh_at
+            .is_some_and(|refresh_at| Instant::now() >= refresh_at)
+    }
+
+    /// Crumb of the session and its generation, `None` if there is none or if it expired
+    fn current(&self, jenkins_client: &Jenkins) -> Option<(Crumb, u64)> {
+        if self.is_expired() {
+            debug!("crumb expired, getting a new one");
+            return None;
+        }
+        jenkins_client
+            .cached_crumb()
+            .map(|crumb| (crumb, self.generation))
+    }
+
+    /// Keep a new crumb as the crumb of the session
+    fn renew(&mut self, jenkins_client: &Jenkins, crumb: &Crumb, ttl: Option<Duration>) {
+        jenkins_client.cache_crumb(crumb);
+        self.generation += 1;
+        self.expire_after(ttl);
+    }
 }
 
 impl Jenkins {
+    /// Add the crumb to a request, using the one of the session if there is one and it hasn't
+    /// expired, and tell its generation if it was, to refresh it if it's rejected
     pub(crate) fn add_csrf_to_request(
         &self,
         request_builder: RequestBuilder,
-    ) -> Result<RequestBuilder, failure::Error> {
-        if self.csrf_enabled {
-            let crumb = self.get_csrf()?;
-            Ok(request_builder.header(
-                HeaderName::from_lowercase(crumb.crumb_request_field.to_lowercase().as_bytes())?,
-                HeaderValue::from_str(&crumb.crumb)?,
-            ))
-        } else {
-            Ok(request_builder)
+    ) -> Result<(RequestBuilder, Option<u64>), failure::Error> {
+        self.add_csrf_to_request_replacing(request_builder, None)
+    }
+
+    /// Add the crumb to a request, as `add_csrf_to_request`, after replacing the crumb of
+    /// generation `rejected` if it's still the crumb of the session
+    pub(crate) fn add_csrf_to_request_replacing(
+        &self,
+        request_builder: RequestBuilder,
+        rejected: Option<u64>,
+    ) -> Result<(RequestBuilder, Option<u64>), failure::Error> {
+        if !self.csrf_enabled {
+            return Ok((request_builder, None));
+        }
+        let (crumb, cached) = self.session_crumb(rejected)?;
+        match crumb.header() {
+            Ok((name, value)) => Ok((request_builder.header(name, value), cached)),
+            Err(error) if cached.is_some() => {
+                warn!("invalid crumb in session: {}", error);
+                let (crumb, _) = self.session_crumb(cached)?;
+                let (name, value) = crumb.header()?;
+                Ok((request_builder.header(name, value), None))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn crumb_clock(&self) -> MutexGuard<'_, CrumbClock> {
+        self.crumb_refresh
+            .clock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Get the crumb of the session and its generation, or a new crumb if there is none, if it
+    /// expired, or if it is of generation `rejected`. The lock on the clock is held while
+    /// getting a new crumb: other threads wait for it instead of getting their own
+    fn session_crumb(&self, rejected: Option<u64>) -> Result<(Crumb, Option<u64>), failure::Error> {
+        let mut clock = self.crumb_clock();
+        if rejected == Some(clock.generation) {
+            self.reset_session();
+        }
+        if let Some((crumb, generation)) = clock.current(self) {
+            return Ok((crumb, Some(generation)));
+        }
+
+        let crumb = self.get_csrf()?;
+        clock.renew(self, &crumb, self.crumb_refresh.ttl);
+        Ok((crumb, None))
+    }
+
+    /// Get the crumb of the session and its generation as `session_crumb` does, but without
+    /// getting a new crumb: `None` is returned instead, for the asynchronous client to get one
+    /// and keep it with `renew_session_crumb`
+    #[cfg(feature = "async")]
+    pub(crate) fn unexpired_session_crumb(&self, rejected: Option<u64>) -> Option<(Crumb, u64)> {
+        let clock = self.crumb_clock();
+        if rejected == Some(clock.generation) {
+            self.reset_session();
         }
+        clock.current(self)
     }
 
+    /// Keep a crumb got by the asynchronous client as the crumb of the session
+    #[cfg(feature = "async")]
+    pub(crate) fn renew_session_crumb(&self, crumb: &Crumb) {
+        self.crumb_clock()
+            .renew(self, crumb, self.crumb_refresh.ttl);
+    }
+
+    /// Get a new crumb. The `depth` parameter is not sent, as some gateways in front of Jenkins
+    /// reject it on this endpoint
     pub(crate) fn get_csrf(&self) -> Result<Crumb, failure::Error> {
-        let crumb: Crumb = self.get(&Path::CrumbIssuer)?.json()?;
+        let crumb: Crumb = self
+            .get_with_params(&Path::CrumbIssuer, NO_PARAMS)?
+            .json()?;
         Ok(crumb)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::super::Path;
+
+    /// Crumbs issued and requests seen by the stub of `crumb_issuer_stub`
+    #[derive(Debug, Default)]
+    struct StubState {
+        issued: usize,
+        /// Crumb accepted, with when it was issued, `None` once it expired
+        valid: Option<(String, Instant)>,
+        accepted: usize,
+        rejected: usize,
+        expirations: usize,
+    }
+
+    /// Jenkins stub with a crumb issuer expiring crumbs after `expire_every` accepted posts, or
+    /// once older than `max_age`
+    fn crumb_issuer_stub(
+        expire_every: usize,
+        max_age: Option<Duration>,
+    ) -> (String, Arc<Mutex<StubState>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(StubState::default()));
+        let server_state = state.clone();
+        let _ = thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let state = server_state.clone();
+                let _ = thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    let _ = reader.read_line(&mut request_line).unwrap();
+                    let mut crumb = None;
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).unwrap() > 2 {
+                        if line.to_lowercase().starts_with("jenkins-crumb:") {
+                            crumb = Some(line[14..].trim().to_string());
+                        }
+                        line.clear();
+                    }
+
+                    let (status, body) = if request_line.starts_with("GET /crumbIssuer") {
+                        // a slow issuer, for concurrent refreshes to overlap
+                        thread::sleep(Duration::from_millis(20));
+                        let mut state = state.lock().unwrap();
+                        state.issued += 1;
+                        let value = format!("crumb-{}", state.issued);
+                        state.valid = Some((value.clone(), Instant::now()));
+                        (
+                            "200 OK",
+                            format!(
+                                r#"{{"crumb":"{}","crumbRequestField":"Jenkins-Crumb"}}"#,
+                                value
+                            ),
+                        )
+                    } else {
+                        let mut state = state.lock().unwrap();
+                        let too_old = match (max_age, &state.valid) {
+                            (Some(max_age), Some((_, issued_at))) => issued_at.elapsed() > max_age,
+                            _ => false,
+                        };
+                        if too_old {
+                            state.valid = None;
+                            state.expirations += 1;
+                        }
+                        let valid = match state.valid {
+                            Some((ref valid, _)) => Some(valid) == crumb.as_ref(),
+                            None => false,
+                        };
+                        if valid {
+                            state.accepted += 1;
+                            if state.accepted % expire_every == 0 {
+                                state.valid = None;
+                                state.expirations += 1;
+                            }
+                            ("200 OK", String::new())
+                        } else {
+                            state.rejected += 1;
+                            ("403 Forbidden", String::new())
+                        }
+                    };
+                    let _ = stream.write_all(
+                        format!(
+                            "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+                            status,
+                            body.len(),
+                            body
+                        )
+                        .as_bytes(),
+                    );
+                });
+            }
+        });
+        (url, state)
+    }
+
+    /// Post `posts` times from each of `threads` threads, returning the number of failed posts
+    fn post_concurrently(jenkins_client: crate::Jenkins, threads: usize, posts: usize) -> usize {
+        let jenkins_client = Arc::new(jenkins_client);
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                let jenkins_client = jenkins_client.clone();
+                thread::spawn(move || {
+                    (0..posts)
+                        .filter(|_| {
+                            jenkins_client
+                                .post(&Path::Raw {
+                                    path: "/job/job/build",
+                                })
+                                .is_err()
+                        })
+                        .count()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn rejected_crumb_is_refreshed_once_for_all_threads() {
+        let (url, state) = crumb_issuer_stub(50, None);
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let failed = post_concurrently(jenkins_client, 8, 40);
+
+        let state = state.lock().unwrap();
+        assert_eq!(failed, 0);
+        assert_eq!(state.accepted, 8 * 40);
+        assert!(state.expirations > 0);
+        assert!(
+            state.issued <= state.expirations + 1,
+            "{} crumbs issued for {} expirations",
+            state.issued,
+            state.expirations
+        );
+    }
+
+    #[test]
+    fn crumb_is_refreshed_before_it_expires() {
+        let (url, state) = crumb_issuer_stub(usize::MAX, Some(Duration::from_millis(300)));
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .with_crumb_ttl(Duration::from_millis(150))
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        let failed = post_concurrently(jenkins_client, 8, 60);
+        let elapsed = start.elapsed();
+
+        let state = state.lock().unwrap();
+        assert_eq!(failed, 0);
+        assert_eq!(state.rejected, 0);
+        assert_eq!(state.accepted, 8 * 60);
+        // a crumb lives at least nine tenths of its time to live
+        let max_refreshes = (elapsed.as_millis() / 135) as usize + 1;
+        assert!(
+            state.issued <= max_refreshes,
+            "{} crumbs issued in {:?}",
+            state.issued,
+            elapsed
+        );
+    }
+
+    #[test]
+    fn resumed_crumb_expires_after_ttl() {
+        let (url, state) = crumb_issuer_stub(usize::MAX, None);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .with_session(crate::client::SessionState {
+                crumb: Some(super::Crumb {
+                    crumb: "resumed".to_string(),
+                    crumb_request_field: "Jenkins-Crumb".to_string(),
+                }),
+                cookies: vec![],
+                version: None,
+            })
+            .with_crumb_ttl(Duration::from_millis(50))
+            .build()
+            .unwrap();
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(jenkins_client
+            .post(&Path::Raw {
+                path: "/job/job/build"
+            })
+            .is_ok());
+
+        let state = state.lock().unwrap();
+        assert_eq!(state.issued, 1);
+        assert_eq!(state.rejected, 0);
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::time::Duration;
 
 use failure::Fail;
 
@@ -14,6 +15,31 @@ pub enum Error {
         expected: ExpectedType,
     },
 
+    /// Error thrown when a URL of an object of Jenkins is neither an absolute URL nor a path
+    #[fail(display = "invalid url '{}', expected an absolute url or a path", url)]
+    InvalidObjectUrl {
+        /// URL given
+        url: String,
+    },
+
+    /// Error thrown when building a client with an invalid base URL for Jenkins
+    #[fail(display = "invalid Jenkins url '{}': {}", url, reason)]
+    InvalidBaseUrl {
+        /// URL given
+        url: String,
+        /// What is wrong with the URL
+        reason: String,
+    },
+
+    /// Error thrown when a label expression can't be parsed
+    #[fail(display = "invalid label expression '{}': {}", expression, reason)]
+    InvalidLabelExpression {
+        /// Expression given
+        expression: String,
+        /// What is wrong with the expression
+        reason: String,
+    },
+
     /// Error thrown when CSRF protection use an unexpected field name
     #[fail(
         display = "invalid crumbfield '{}', expected 'Jenkins-Crumb'",
@@ -41,6 +67,185 @@ pub enum Error {
     #[fail(display = "can't build a job remotely with parameters")]
     UnsupportedBuildConfiguration,
 
+    /// Error when setting a parameter that was already set to build a job
+    #[fail(display = "parameter '{}' is already set", name)]
+    DuplicateParameter {
+        /// Name of the parameter
+        name: String,
+    },
+
+    /// Error when reading parameters from an invalid file
+    #[fail(display = "invalid parameters file at line {}: {}", line, message)]
+    InvalidParametersFile {
+        /// Line of the error, 0 if unknown
+        line: usize,
+        /// Description of the error
+        message: String,
+    },
+
+    /// Error when Jenkins was still not ready when waiting for it timed out
+    #[fail(display = "Jenkins is still not ready after {:?}", waited)]
+    NotReady {
+        /// Time spent waiting for Jenkins
+        waited: Duration,
+    },
+
+    /// Error when waiting for a queue item or a build timed out
+    #[fail(display = "{} still not done after {:?}", waiting_for, waited)]
+    WaitTimeout {
+        /// What was being waited for
+        waiting_for: ExpectedType,
+        /// Time spent waiting
+        waited: Duration,
+    },
+
+    /// Error when a queue item was cancelled while waiting for its build
+    #[fail(display = "queue item {} was cancelled", url)]
+    QueueItemCancelled {
+        /// URL of the queue item
+        url: String,
+    },
+
+    /// Error when an operation was cancelled with a `CancellationToken`
+    #[fail(display = "operation was cancelled")]
+    Cancelled,
+
+    /// Error when a job has no successful build
+    #[fail(display = "job '{}' never succeeded", job_name)]
+    NoSuccessfulBuild {
+        /// Name of the job
+        job_name: String,
+    },
+
+    /// Error when a build has no artifact with the name requested
+    #[fail(display = "no artifact '{}' in build {}", name, build_url)]
+    ArtifactNotFound {
+        /// Name of the artifact requested
+        name: String,
+        /// URL of the build
+        build_url: String,
+    },
+
+    /// Error when a parameter of a build can't be sent again to rebuild it
+    #[fail(display = "can't resubmit parameter '{}' of type {}", name, class)]
+    UnsupportedParameter {
+        /// Name of the parameter
+        name: String,
+        /// Class of the parameter
+        class: String,
+    },
+
+    /// Error when a build has no injected environment variables, usually because the EnvInject
+    /// plugin is not installed
+    #[fail(
+        display = "no injected environment variables for build {}, is the EnvInject plugin installed?",
+        build_url
+    )]
+    NoInjectedEnvVars {
+        /// URL of the build
+        build_url: String,
+    },
+
+    /// Error when trying to create a job that already exists
+    #[fail(display = "job '{}' already exists", job_name)]
+    JobAlreadyExists {
+        /// Name of the job
+        job_name: String,
+    },
+
+    /// Error when Jenkins refused to create a view
+    #[fail(display = "view '{}' was not created: {}", view_name, message)]
+    ViewNotCreated {
+        /// Name of the view
+        view_name: String,
+        /// Message from Jenkins
+        message: String,
+    },
+
+    /// Error when a request middleware failed, the request was not sent
+    #[fail(display = "request to {} aborted by a middleware: {}", url, reason)]
+    MiddlewareFailed {
+        /// URL of the request
+        url: String,
+        /// Error of the middleware
+        reason: String,
+    },
+
+    /// Error when a timestamp from Jenkins can't be represented as a date
+    #[fail(display = "invalid timestamp {}", timestamp)]
+    InvalidTimestamp {
+        /// Timestamp found, in milliseconds since the epoch
+        timestamp: u64,
+    },
+
+    /// Error when a response has no `X-Jenkins` header, the URL is probably not a Jenkins server
+    #[fail(display = "no X-Jenkins header in response from {}", url)]
+    NotJenkins {
+        /// URL of the request
+        url: String,
+    },
+
+    /// Error when the version of Jenkins can't be parsed
+    #[fail(display = "invalid Jenkins version '{}'", version)]
+    InvalidJenkinsVersion {
+        /// Version found
+        version: String,
+    },
+
+    /// Error when Jenkins doesn't allow the method of a request on an endpoint
+    #[fail(
+        display = "method {} not allowed on {}, allowed: {:?}",
+        method, url, allow
+    )]
+    MethodNotAllowed {
+        /// URL of the endpoint
+        url: String,
+        /// Method of the request
+        method: String,
+        /// Methods allowed, from the `Allow` header
+        allow: Vec<String>,
+    },
+
+    /// Error when Jenkins has neither metadata nor an instance for a class
+    #[fail(display = "no description found for class '{}'", class)]
+    UnknownClass {
+        /// _class looked for
+        class: String,
+    },
+
+    /// Error when trying to delete a build that is still running
+    #[fail(display = "can't delete build {} while it is running", build_url)]
+    BuildStillRunning {
+        /// URL of the build
+        build_url: String,
+    },
+
+    /// Error when a Groovy script run by Jenkins printed an unexpected output, usually the
+    /// stack trace of an exception
+    #[fail(display = "script failed: {}", output)]
+    ScriptFailed {
+        /// Output of the script
+        output: String,
+    },
+
+    /// Error when Jenkins refused to generate or revoke an API token
+    #[fail(display = "API token was not changed: {}", message)]
+    ApiTokenNotChanged {
+        /// Message from Jenkins
+        message: String,
+    },
+
+    /// Error when Jenkins is ready but rejects the credentials used
+    #[fail(display = "Jenkins is ready but rejected the credentials: {}", status)]
+    CredentialsRejected {
+        /// HTTP status returned by Jenkins
+        status: u16,
+    },
+
+    /// Error when a request to Jenkins timed out
+    #[fail(display = "request to Jenkins timed out")]
+    Timeout,
+
     /// Error when trying to do an action on an object not supporting it
     #[fail(
         display = "can't do '{}' on a {} of type {}",
@@ -93,6 +298,10 @@ pub enum Action {
     GetField(&'static str),
     /// Get linked item
     GetLinkedItem(ExpectedType),
+    /// Get the description of a pipeline run
+    GetWorkflowDescription,
+    /// Replay a pipeline run
+    Replay,
 }
 
 impl fmt::Display for Action {
@@ -100,6 +309,8 @@ impl fmt::Display for Action {
         match *self {
             Action::GetField(field) => write!(f, "get field '{}'", field),
             Action::GetLinkedItem(item) => write!(f, "get linked item '{}'", item),
+            Action::GetWorkflowDescription => write!(f, "get workflow description"),
+            Action::Replay => write!(f, "replay"),
         }
     }
 }
@@ -0,0 +1,235 @@
+//! Changing requests before they are sent, to sign or trace them
+
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use reqwest::r#async;
+use reqwest::{header::HeaderMap, Method, Request, Url};
+
+use super::Error;
+
+/// Parts of a request given to request middlewares, with its headers that can be changed
+#[derive(Debug)]
+pub struct RequestParts<'a> {
+    method: Method,
+    url: Url,
+    headers: &'a mut HeaderMap,
+}
+
+impl<'a> RequestParts<'a> {
+    /// Method of the request
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// URL of the request, with its query
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Headers of the request
+    pub fn headers(&self) -> &HeaderMap {
+        &*self.headers
+    }
+
+    /// Headers of the request, to add or change some
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut *self.headers
+    }
+}
+
+type Middleware = Arc<dyn Fn(&mut RequestParts<'_>) -> Result<(), failure::Error> + Send + Sync>;
+
+/// Middlewares set with `JenkinsBuilder::with_request_middleware`, run in order
+#[derive(Clone, Default)]
+pub(crate) struct RequestMiddlewareChain(Vec<Middleware>);
+
+impl fmt::Debug for RequestMiddlewareChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RequestMiddlewareChain({} middlewares)", self.0.len())
+    }
+}
+
+impl RequestMiddlewareChain {
+    pub(crate) fn push<F>(&mut self, middleware: F)
+    where
+        F: Fn(&mut RequestParts<'_>) -> Result<(), failure::Error> + Send + Sync + 'static,
+    {
+        self.0.push(Arc::new(middleware));
+    }
+
+    /// Run the middlewares on `request`, stopping at the first one failing
+    pub(crate) fn apply(&self, request: &mut Request) -> Result<(), Error> {
+        let (method, url) = (request.method().clone(), request.url().clone());
+        self.apply_to_parts(method, url, request.headers_mut())
+    }
+
+    /// Run the middlewares on `request` of the asynchronous client, as `apply` does
+    #[cfg(feature = "async")]
+    pub(crate) fn apply_async(&self, request: &mut r#async::Request) -> Result<(), Error> {
+        let (method, url) = (request.method().clone(), request.url().clone());
+        self.apply_to_parts(method, url, request.headers_mut())
+    }
+
+    fn apply_to_parts(
+        &self,
+        method: Method,
+        url: Url,
+        headers: &mut HeaderMap,
+    ) -> Result<(), Error> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let mut parts = RequestParts {
+            method,
+            url,
+            headers,
+        };
+        for middleware in &self.0 {
+            middleware(&mut parts).map_err(|error| Error::MiddlewareFailed {
+                url: parts.url.to_string(),
+                reason: error.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use failure::format_err;
+    use mockito;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn middlewares_run_in_order_on_every_request() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/middleware", mockito::server_url()))
+                .with_request_middleware(move |request| {
+                    let trace_id = format!("trace-{}", counter.fetch_add(1, Ordering::SeqCst) + 1);
+                    let _ = request
+                        .headers_mut()
+                        .insert("x-trace-id", HeaderValue::from_str(&trace_id)?);
+                    Ok(())
+                })
+                .with_request_middleware(|request| {
+                    let signature = format!(
+                        "{} {} {}",
+                        request.headers()["x-trace-id"].to_str()?,
+                        request.method(),
+                        request.url().path()
+                    );
+                    let _ = request
+                        .headers_mut()
+                        .insert("x-signature", HeaderValue::from_str(&signature)?);
+                    Ok(())
+                })
+                .build()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/middleware/crumbIssuer/api/json")
+            .match_header("x-trace-id", "trace-1")
+            .match_header(
+                "x-signature",
+                "trace-1 GET /middleware/crumbIssuer/api/json",
+            )
+            .with_body(r#"{"crumb":"abc","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let post = mockito::mock("POST", "/middleware/mypath")
+            .match_header("x-trace-id", "trace-2")
+            .match_header("x-signature", "trace-2 POST /middleware/mypath")
+            .create();
+        let get = mockito::mock("GET", "/middleware/mypath/api/json?depth=1")
+            .match_header("x-trace-id", "trace-3")
+            .match_header("x-signature", "trace-3 GET /middleware/mypath/api/json")
+            .with_body("{}")
+            .create();
+
+        assert!(jenkins_client
+            .post(&super::super::Path::Raw { path: "/mypath" })
+            .is_ok());
+        assert!(jenkins_client
+            .get(&super::super::Path::Raw { path: "/mypath" })
+            .is_ok());
+
+        crumb.assert();
+        post.assert();
+        get.assert();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn middlewares_run_on_async_requests() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/middleware-async", mockito::server_url()))
+                .with_request_middleware(move |request| {
+                    let trace_id = format!("trace-{}", counter.fetch_add(1, Ordering::SeqCst) + 1);
+                    let signature = format!("{} {}", request.method(), request.url().path());
+                    let headers = request.headers_mut();
+                    let _ = headers.insert("x-trace-id", HeaderValue::from_str(&trace_id)?);
+                    let _ = headers.insert("x-signature", HeaderValue::from_str(&signature)?);
+                    Ok(())
+                })
+                .build_async()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/middleware-async/crumbIssuer/api/json")
+            .match_header("x-trace-id", "trace-1")
+            .match_header("x-signature", "GET /middleware-async/crumbIssuer/api/json")
+            .with_body(r#"{"crumb":"abc","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let build = mockito::mock("POST", "/middleware-async/job/a%20job/build")
+            .match_header("x-trace-id", "trace-2")
+            .match_header("x-signature", "POST /middleware-async/job/a%20job/build")
+            .match_header("jenkins-crumb", "abc")
+            .with_status(201)
+            .with_header("Location", "http://jenkins/queue/item/1/")
+            .create();
+
+        let queue_item = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(jenkins_client.build_job("a job"))
+            .unwrap();
+        assert_eq!(queue_item.url, "http://jenkins/queue/item/1/");
+        crumb.assert();
+        build.assert();
+    }
+
+    #[test]
+    fn failing_middleware_aborts_request() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/middleware-fail", mockito::server_url()))
+                .with_request_middleware(|_| Err(format_err!("no signing key")))
+                .build()
+                .unwrap();
+
+        let mock = mockito::mock("GET", "/middleware-fail/mypath/api/json?depth=1")
+            .expect(0)
+            .create();
+
+        match jenkins_client
+            .get(&super::super::Path::Raw { path: "/mypath" })
+            .unwrap_err()
+            .downcast::<super::Error>()
+        {
+            Ok(super::Error::MiddlewareFailed { url, reason }) => {
+                assert_eq!(
+                    url,
+                    format!(
+                        "{}/middleware-fail/mypath/api/json?depth=1",
+                        mockito::server_url()
+                    )
+                );
+                assert_eq!(reason, "no signing key");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        mock.assert();
+    }
+}
@@ -1,23 +1,43 @@
 //! Jenkins Client
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::string::ToString;
+use std::sync::Mutex;
 
 use failure;
 use log::{debug, warn};
 use regex::Regex;
 use reqwest::{
-    header::HeaderValue, header::CONTENT_TYPE, Body, Client, RequestBuilder, Response, StatusCode,
+    header::HeaderMap, header::HeaderValue, header::ACCEPT, header::ALLOW, header::CONTENT_TYPE,
+    Body, Client, Method, RequestBuilder, Response, StatusCode, Url,
 };
 use serde::Serialize;
 
+use crate::schema::ClassDescriptor;
+
 mod errors;
 pub use self::errors::Error;
 mod builder;
 pub mod path;
 pub use self::builder::JenkinsBuilder;
 pub use self::path::{Name, Path};
+#[cfg(feature = "async")]
+mod async_client;
 mod csrf;
+pub use self::csrf::Crumb;
+use self::csrf::CrumbRefresh;
+mod middleware;
+mod public_url;
+use self::middleware::RequestMiddlewareChain;
+pub use self::middleware::RequestParts;
+mod response_filter;
+use self::response_filter::ResponseFilterChain;
+pub use self::response_filter::{ResponseFilters, ResponseKind, ResponseMeta};
+mod retry;
+pub use self::retry::is_transient_error;
+mod session;
+pub use self::session::SessionState;
 mod tree;
 pub use self::tree::{TreeBuilder, TreeQueryParam};
 
@@ -37,10 +57,21 @@ struct User {
 #[derive(Debug)]
 pub struct Jenkins {
     url: String,
+    public_url: Option<String>,
     client: Client,
     user: Option<User>,
     csrf_enabled: bool,
     pub(crate) depth: u8,
+    session: Mutex<SessionState>,
+    crumb_refresh: CrumbRefresh,
+    retry: Option<retry::RetryPolicy>,
+    response_filters: ResponseFilterChain,
+    request_middlewares: RequestMiddlewareChain,
+    accept: HeaderValue,
+    pub(crate) class_descriptors: Mutex<HashMap<String, ClassDescriptor>>,
+    command_methods: Mutex<HashMap<String, Method>>,
+    #[cfg(feature = "async")]
+    async_client: reqwest::r#async::Client,
 }
 
 /// Advanced query parameters supported by Jenkins to control the amount of data retrieved
@@ -75,6 +106,18 @@ impl From<AdvancedQuery> for InternalAdvancedQueryParams {
     }
 }
 
+/// No query parameters, for endpoints where gateways in front of Jenkins reject `depth`
+pub(crate) const NO_PARAMS: [(&str, &str); 0] = [];
+
+/// Convert an error of a request, a timeout being reported as `Error::Timeout`
+fn request_error(error: reqwest::Error) -> failure::Error {
+    if error.is_timeout() {
+        Error::Timeout.into()
+    } else {
+        error.into()
+    }
+}
+
 impl Jenkins {
     pub(crate) fn url_api_json(&self, endpoint: &str) -> String {
         format!("{}{}/api/json", self.url, endpoint)
@@ -89,21 +132,38 @@ impl Jenkins {
             request_builder =
                 request_builder.basic_auth(user.username.clone(), user.password.clone());
         }
-        let query = request_builder.build()?;
+        request_builder = self.add_cookies_to_request(request_builder);
+        let mut query = request_builder.build()?;
+        self.request_middlewares.apply(&mut query)?;
         debug!("sending {} {}", query.method(), query.url());
-        Ok(self.client.execute(query)?)
+        let response = retry::execute(self.retry.as_ref(), query, |query| {
+            self.client.execute(query)
+        })
+        .map_err(request_error)?;
+        self.update_session(response.headers());
+        Ok(response)
     }
 
-    fn error_for_status(response: Response) -> Result<Response, failure::Error> {
-        let status = response.status();
-        if status.is_client_error() || status.is_server_error() {
-            warn!("got an error: {}", status);
-        }
+    fn error_for_status(method: &Method, response: Response) -> Result<Response, failure::Error> {
+        check_status(
+            method,
+            response.status(),
+            response.url(),
+            response.headers(),
+        )?;
         Ok(response.error_for_status()?)
     }
 
     pub(crate) fn get(&self, path: &Path) -> Result<Response, failure::Error> {
-        self.get_with_params(path, &[("depth", &self.depth.to_string())])
+        self.get_with_depth(path, self.depth)
+    }
+
+    pub(crate) fn get_with_depth(
+        &self,
+        path: &Path,
+        depth: u8,
+    ) -> Result<Response, failure::Error> {
+        self.get_with_params(path, &[("depth", &depth.to_string())])
     }
 
     pub(crate) fn get_with_params<T: Serialize>(
@@ -114,35 +174,188 @@ impl Jenkins {
         let query = self
             .client
             .get(&self.url_api_json(&path.to_string()))
+            .header(ACCEPT, self.accept.clone())
             .query(&qps);
-        Ok(Self::error_for_status(self.send(query)?)?)
+        let response = Self::error_for_status(&Method::GET, self.send(query)?)?;
+        self.response_filters.apply(path.response_kind(), response)
+    }
+
+    /// Send a `GET` request to a file, without the `/api/json` suffix
+    pub(crate) fn get_file(&self, path: &Path) -> Result<Response, failure::Error> {
+        let mut query = self.client.get(&self.url(&path.to_string()));
+        if path.response_kind() == ResponseKind::Json {
+            query = query.header(ACCEPT, self.accept.clone());
+        }
+        let response = Self::error_for_status(&Method::GET, self.send(query)?)?;
+        self.response_filters.apply(path.response_kind(), response)
+    }
+
+    /// Send a `GET` request, returning the `Response` whatever its status
+    pub(crate) fn get_unchecked_with_params<T: Serialize>(
+        &self,
+        path: &Path,
+        qps: T,
+    ) -> Result<Response, failure::Error> {
+        let query = self
+            .client
+            .get(&self.url_api_json(&path.to_string()))
+            .header(ACCEPT, self.accept.clone())
+            .query(&qps);
+        self.response_filters
+            .apply(path.response_kind(), self.send(query)?)
+    }
+
+    /// Send a `GET` request without credentials, returning the `Response` whatever its status
+    pub(crate) fn get_anonymous_unchecked_with_params<T: Serialize>(
+        &self,
+        path: &Path,
+        qps: T,
+    ) -> Result<Response, failure::Error> {
+        let mut query = self
+            .client
+            .get(&self.url_api_json(&path.to_string()))
+            .header(ACCEPT, self.accept.clone())
+            .query(&qps)
+            .build()?;
+        self.request_middlewares.apply(&mut query)?;
+        debug!("sending {} {}", query.method(), query.url());
+        self.response_filters.apply(
+            path.response_kind(),
+            self.client.execute(query).map_err(request_error)?,
+        )
+    }
+
+    /// Are credentials set to authenticate to Jenkins
+    pub(crate) fn has_credentials(&self) -> bool {
+        self.user.is_some()
+    }
+
+    /// Is this client configured to send a crumb with its requests
+    pub(crate) fn is_csrf_enabled(&self) -> bool {
+        self.csrf_enabled
+    }
+
+    /// Hide the credentials of this client from a text
+    pub(crate) fn redact(&self, text: &str) -> String {
+        let re = Regex::new(r"://[^/@\s]+@").unwrap();
+        let mut redacted = re.replace_all(text, "://****@").to_string();
+        if let Some(password) = self.user.as_ref().and_then(|user| user.password.as_ref()) {
+            if !password.is_empty() {
+                redacted = redacted.replace(password.as_str(), "****");
+            }
+        }
+        redacted
+    }
+
+    /// Send a POST request with a crumb. If the crumb of the session is rejected, the session is
+    /// reset and the request is sent again with a fresh crumb. When several requests find the
+    /// same crumb rejected, only one of them gets a new crumb
+    fn send_post<F>(&self, path: &Path, prepare: F) -> Result<Response, failure::Error>
+    where
+        F: Fn(RequestBuilder) -> RequestBuilder,
+    {
+        let url = self.url(&path.to_string());
+        let (request_builder, cached_crumb) = self.add_csrf_to_request(self.client.post(&url))?;
+        let response = self.send(prepare(request_builder))?;
+
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN if cached_crumb.is_some() => {
+                warn!("session was rejected, getting a new crumb");
+                let (request_builder, _) =
+                    self.add_csrf_to_request_replacing(self.client.post(&url), cached_crumb)?;
+                self.send(prepare(request_builder))
+            }
+            _ => Ok(response),
+        }
     }
 
     pub(crate) fn post(&self, path: &Path) -> Result<Response, failure::Error> {
-        let mut request_builder = self.client.post(&self.url(&path.to_string()));
+        Ok(Self::error_for_status(
+            &Method::POST,
+            self.send_post(path, |request_builder| request_builder)?,
+        )?)
+    }
 
-        request_builder = self.add_csrf_to_request(request_builder)?;
+    /// Send an idempotent command with a POST request. If Jenkins answers that the endpoint
+    /// only allows `GET`, the command is sent once again with a `GET` request, and the method
+    /// that succeeded is used for the next commands sent to the endpoint
+    pub(crate) fn post_idempotent(&self, path: &Path) -> Result<Response, failure::Error> {
+        let endpoint = path.to_string();
+        if self.command_method(&endpoint) == Some(Method::GET) {
+            return self.get_command(path);
+        }
+        let response = self.post_unchecked(path)?;
+        if response.status() == StatusCode::METHOD_NOT_ALLOWED
+            && allowed_methods(response.headers())
+                .iter()
+                .any(|method| method == Method::GET.as_str())
+        {
+            warn!("POST not allowed on {}, sending a GET instead", endpoint);
+            let response = self.get_command(path)?;
+            self.record_command_method(endpoint, Method::GET);
+            return Ok(response);
+        }
+        Self::error_for_status(&Method::POST, response)
+    }
+
+    /// Send a command with a `GET` request, without the `/api/json` suffix
+    fn get_command(&self, path: &Path) -> Result<Response, failure::Error> {
+        let query = self.client.get(&self.url(&path.to_string()));
+        Self::error_for_status(&Method::GET, self.send(query)?)
+    }
 
-        Ok(Self::error_for_status(self.send(request_builder)?)?)
+    fn command_method(&self, endpoint: &str) -> Option<Method> {
+        self.command_methods
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(endpoint)
+            .cloned()
     }
 
-    pub(crate) fn post_with_body<T: Into<Body> + Debug>(
+    fn record_command_method(&self, endpoint: String, method: Method) {
+        let _ = self
+            .command_methods
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(endpoint, method);
+    }
+
+    /// Send a POST request, returning the `Response` whatever its status
+    pub(crate) fn post_unchecked(&self, path: &Path) -> Result<Response, failure::Error> {
+        self.send_post(path, |request_builder| request_builder)
+    }
+
+    /// Send a POST request with an XML body, returning the `Response` whatever its status
+    pub(crate) fn post_xml_unchecked(
         &self,
         path: &Path,
-        body: T,
+        body: &str,
         qps: &[(&str, &str)],
     ) -> Result<Response, failure::Error> {
-        let mut request_builder = self.client.post(&self.url(&path.to_string()));
-
-        request_builder = self.add_csrf_to_request(request_builder)?;
+        self.send_post(path, |request_builder| {
+            request_builder
+                .header(CONTENT_TYPE, HeaderValue::from_static("application/xml"))
+                .query(qps)
+                .body(body.to_string())
+        })
+    }
 
-        request_builder = request_builder.header(
-            CONTENT_TYPE,
-            HeaderValue::from_static("application/x-www-form-urlencoded"),
-        );
+    pub(crate) fn post_with_body<T: Into<Body> + Clone + Debug>(
+        &self,
+        path: &Path,
+        body: T,
+        qps: &[(&str, &str)],
+    ) -> Result<Response, failure::Error> {
         debug!("{:?}", body);
-        request_builder = request_builder.query(qps).body(body);
-        let mut response = self.send(request_builder)?;
+        let mut response = self.send_post(path, |request_builder| {
+            request_builder
+                .header(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/x-www-form-urlencoded"),
+                )
+                .query(qps)
+                .body(body.clone())
+        })?;
 
         if response.status() == StatusCode::INTERNAL_SERVER_ERROR {
             let body = response.text()?;
@@ -188,8 +401,41 @@ impl Jenkins {
             }
         }
 
-        Ok(Self::error_for_status(response)?)
+        Ok(Self::error_for_status(&Method::POST, response)?)
+    }
+}
+
+/// Warn about an error status, and report a `405 Method Not Allowed` as an
+/// `Error::MethodNotAllowed` with the methods Jenkins allows
+fn check_status(
+    method: &Method,
+    status: StatusCode,
+    url: &Url,
+    headers: &HeaderMap,
+) -> Result<(), Error> {
+    if status.is_client_error() || status.is_server_error() {
+        warn!("got an error: {}", status);
+    }
+    if status == StatusCode::METHOD_NOT_ALLOWED {
+        return Err(Error::MethodNotAllowed {
+            url: url.to_string(),
+            method: method.to_string(),
+            allow: allowed_methods(headers),
+        });
     }
+    Ok(())
+}
+
+/// Methods listed in the `Allow` header of a response
+fn allowed_methods(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all(ALLOW)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|method| method.trim().to_string())
+        .filter(|method| !method.is_empty())
+        .collect()
 }
 
 #[cfg(test)]
@@ -296,6 +542,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_get_with_depth() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .with_depth(0)
+            .build()
+            .unwrap();
+
+        let default_depth = mockito::mock("GET", "/depth-path/api/json?depth=0")
+            .with_body("{}")
+            .create();
+        let overridden_depth = mockito::mock("GET", "/depth-path/api/json?depth=2")
+            .with_body("{}")
+            .create();
+
+        assert!(jenkins_client
+            .get(&super::Path::Raw {
+                path: "/depth-path"
+            })
+            .is_ok());
+        assert!(jenkins_client
+            .get_with_depth(
+                &super::Path::Raw {
+                    path: "/depth-path"
+                },
+                2
+            )
+            .is_ok());
+
+        default_depth.assert();
+        overridden_depth.assert();
+    }
+
     #[test]
     fn can_post_with_query_params() {
         let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
@@ -318,4 +596,107 @@ mod tests {
         mock.assert()
     }
 
+    #[test]
+    fn should_time_out_on_unresponsive_jenkins() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("http://{}", listener.local_addr().unwrap()))
+                .with_timeout(std::time::Duration::from_millis(200))
+                .build()
+                .unwrap();
+
+        let error = jenkins_client.get(&super::Path::Home).unwrap_err();
+        match error.downcast::<super::Error>() {
+            Ok(super::Error::Timeout) => (),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        drop(listener);
+    }
+
+    #[test]
+    fn should_time_out_on_unresponsive_jenkins_without_credentials() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("http://{}", listener.local_addr().unwrap()))
+                .with_timeout(std::time::Duration::from_millis(200))
+                .build()
+                .unwrap();
+
+        let error = jenkins_client
+            .get_anonymous_unchecked_with_params(&super::Path::Home, super::NO_PARAMS)
+            .unwrap_err();
+        match error.downcast::<super::Error>() {
+            Ok(super::Error::Timeout) => (),
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        drop(listener);
+    }
+
+    #[test]
+    fn sends_accept_header_to_api() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/gateway-accept", mockito::server_url()))
+                .build()
+                .unwrap();
+        let vendor_client =
+            crate::JenkinsBuilder::new(&format!("{}/gateway-vendor", mockito::server_url()))
+                .with_accept("application/vnd.gateway.v2+json")
+                .build()
+                .unwrap();
+
+        let _not_acceptable = mockito::mock("GET", "/gateway-accept/mypath/api/json?depth=1")
+            .with_status(406)
+            .create();
+        let _accepted = mockito::mock("GET", "/gateway-accept/mypath/api/json?depth=1")
+            .match_header("accept", "application/json")
+            .with_body("{}")
+            .create();
+        let _vendor_not_acceptable =
+            mockito::mock("GET", "/gateway-vendor/mypath/api/json?depth=1")
+                .with_status(406)
+                .create();
+        let _vendor_accepted = mockito::mock("GET", "/gateway-vendor/mypath/api/json?depth=1")
+            .match_header("accept", "application/vnd.gateway.v2+json")
+            .with_body("{}")
+            .create();
+
+        assert!(jenkins_client
+            .get(&super::Path::Raw { path: "/mypath" })
+            .is_ok());
+        assert!(vendor_client
+            .get(&super::Path::Raw { path: "/mypath" })
+            .is_ok());
+    }
+
+    #[test]
+    fn invalid_accept_header_fails_build() {
+        assert!(crate::JenkinsBuilder::new(&mockito::server_url())
+            .with_accept("application/json\n")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn gets_crumb_without_depth() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/gateway-crumb", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _rejected_depth = mockito::mock("GET", "/gateway-crumb/crumbIssuer/api/json?depth=1")
+            .with_status(400)
+            .create();
+        let _crumb = mockito::mock("GET", "/gateway-crumb/crumbIssuer/api/json")
+            .match_header("accept", "application/json")
+            .with_body(r#"{"crumb":"abc","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let post = mockito::mock("POST", "/gateway-crumb/mypath")
+            .match_header("jenkins-crumb", "abc")
+            .create();
+
+        assert!(jenkins_client
+            .post(&super::Path::Raw { path: "/mypath" })
+            .is_ok());
+        post.assert();
+    }
 }
@@ -4,7 +4,7 @@ use super::Jenkins;
 use crate::build;
 
 /// Name of an object
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Name<'a> {
     /// Name of an object
     Name(&'a str),
@@ -21,6 +21,21 @@ impl<'a> ToString for Name<'a> {
     }
 }
 
+impl<'a> Name<'a> {
+    /// Path to an item that can be nested in folders or views. A `Name::Name` is split on `/`,
+    /// each part being a parent folder or view, while a `Name::UrlEncodedName` already contains the
+    /// intermediate `/{prefix}/` separators
+    fn to_nested_path(&self, prefix: &str) -> String {
+        match *self {
+            Name::Name(name) => name
+                .split('/')
+                .map(|part| format!("/{}/{}", prefix, urlencoding::encode(part)))
+                .collect(),
+            Name::UrlEncodedName(name) => format!("/{}/{}", prefix, name),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Path<'a> {
     Home,
@@ -35,10 +50,20 @@ pub enum Path<'a> {
         job_name: Name<'a>,
         view_name: Name<'a>,
     },
+    CreateView,
+    DeleteView {
+        name: Name<'a>,
+    },
     Job {
         name: Name<'a>,
         configuration: Option<Name<'a>>,
     },
+    JobConfig {
+        name: Name<'a>,
+    },
+    CreateItem {
+        parent: Option<Name<'a>>,
+    },
     BuildJob {
         name: Name<'a>,
     },
@@ -48,12 +73,18 @@ pub enum Path<'a> {
     PollSCMJob {
         name: Name<'a>,
     },
+    SCMPollLog {
+        name: Name<'a>,
+    },
     JobEnable {
         name: Name<'a>,
     },
     JobDisable {
         name: Name<'a>,
     },
+    SubmitJobDescription {
+        name: Name<'a>,
+    },
     Build {
         job_name: Name<'a>,
         number: build::BuildNumber,
@@ -64,73 +95,223 @@ pub enum Path<'a> {
         number: build::BuildNumber,
         configuration: Option<Name<'a>>,
     },
+    StopBuild {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    ToggleLogKeep {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    DeleteBuild {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    SubmitBuildDescription {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    BuildConfigSubmit {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    ProgressiveText {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+        start: u64,
+    },
+    Artifact {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+        relative_path: &'a str,
+    },
+    PipelineDescription {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    Replay {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    ReplayWithSameScript {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    BuildEnvVars {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
     Queue,
     QueueItem {
         id: i32,
     },
+    CancelQueueItem {
+        id: i32,
+    },
     MavenArtifactRecord {
         job_name: Name<'a>,
         number: build::BuildNumber,
         configuration: Option<Name<'a>>,
     },
+    TestReport {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+    },
+    TestCaseAttachment {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        configuration: Option<Name<'a>>,
+        package: Name<'a>,
+        class: Name<'a>,
+        test: Name<'a>,
+        attachment: Name<'a>,
+    },
+    FlowNodeDescription {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        node_id: Name<'a>,
+    },
+    FlowNodeLog {
+        job_name: Name<'a>,
+        number: build::BuildNumber,
+        node_id: Name<'a>,
+    },
+    BlueOceanRuns {
+        pipeline: Name<'a>,
+    },
+    BlueOceanRunNodes {
+        pipeline: Name<'a>,
+        run: u32,
+    },
+    BlueOceanNodeSteps {
+        pipeline: Name<'a>,
+        run: u32,
+        node_id: Name<'a>,
+    },
+    BlueOceanStepLog {
+        pipeline: Name<'a>,
+        run: u32,
+        node_id: Name<'a>,
+        step_id: Name<'a>,
+    },
     Computers,
     Computer {
         name: Name<'a>,
     },
+    ComputerToggleOffline {
+        name: Name<'a>,
+    },
+    OverallLoad,
+    PluginManager,
+    InstallNecessaryPlugins,
+    PluginMakeEnabled {
+        short_name: &'a str,
+    },
+    PluginMakeDisabled {
+        short_name: &'a str,
+    },
+    UpdateCenter,
     Raw {
         path: &'a str,
     },
     CrumbIssuer,
+    WhoAmI,
+    Me,
+    User {
+        id: &'a str,
+    },
+    GenerateApiToken,
+    RevokeApiToken,
+    QuietDown,
+    CancelQuietDown,
+    Restart,
+    SafeRestart,
+    ScriptText,
+    DescriptorByName {
+        class: &'a str,
+    },
 }
 
 impl<'a> ToString for Path<'a> {
     fn to_string(&self) -> String {
         match *self {
             Path::Home => "".to_string(),
-            Path::View { ref name } => format!("/view/{}", name.to_string()),
+            Path::View { ref name } => name.to_nested_path("view"),
             Path::AddJobToView {
                 ref job_name,
                 ref view_name,
             } => format!(
-                "/view/{}/addJobToView?name={}",
-                view_name.to_string(),
+                "{}/addJobToView?name={}",
+                view_name.to_nested_path("view"),
                 job_name.to_string()
             ),
             Path::RemoveJobFromView {
                 ref job_name,
                 ref view_name,
             } => format!(
-                "/view/{}/removeJobFromView?name={}",
-                view_name.to_string(),
+                "{}/removeJobFromView?name={}",
+                view_name.to_nested_path("view"),
                 job_name.to_string()
             ),
+            Path::CreateView => "/createView".to_string(),
+            Path::DeleteView { ref name } => format!("{}/doDelete", name.to_nested_path("view")),
+            Path::JobConfig { ref name } => format!("{}/config.xml", name.to_nested_path("job")),
+            Path::CreateItem { ref parent } => format!(
+                "{}/createItem",
+                parent
+                    .as_ref()
+                    .map(|parent| parent.to_nested_path("job"))
+                    .unwrap_or_default()
+            ),
             Path::Job {
                 ref name,
                 configuration: Some(ref configuration),
-            } => format!("/job/{}/{}", name.to_string(), configuration.to_string()),
+            } => format!(
+                "{}/{}",
+                name.to_nested_path("job"),
+                configuration.to_string()
+            ),
             Path::Job {
                 ref name,
                 configuration: None,
-            } => format!("/job/{}", name.to_string()),
-            Path::BuildJob { ref name } => format!("/job/{}/build", name.to_string()),
+            } => name.to_nested_path("job"),
+            Path::BuildJob { ref name } => format!("{}/build", name.to_nested_path("job")),
             Path::BuildJobWithParameters { ref name } => {
-                format!("/job/{}/buildWithParameters", name.to_string())
+                format!("{}/buildWithParameters", name.to_nested_path("job"))
+            }
+            Path::PollSCMJob { ref name } => format!("{}/polling", name.to_nested_path("job")),
+            Path::SCMPollLog { ref name } => {
+                format!("{}/scmPollLog/pollingLog", name.to_nested_path("job"))
+            }
+            Path::JobEnable { ref name } => format!("{}/enable", name.to_nested_path("job")),
+            Path::JobDisable { ref name } => format!("{}/disable", name.to_nested_path("job")),
+            Path::SubmitJobDescription { ref name } => {
+                format!("{}/submitDescription", name.to_nested_path("job"))
             }
-            Path::PollSCMJob { ref name } => format!("/job/{}/polling", name.to_string()),
-            Path::JobEnable { ref name } => format!("/job/{}/enable", name.to_string()),
-            Path::JobDisable { ref name } => format!("/job/{}/disable", name.to_string()),
             Path::Build {
                 ref job_name,
                 ref number,
                 configuration: None,
-            } => format!("/job/{}/{}", job_name.to_string(), number.to_string()),
+            } => format!("{}/{}", job_name.to_nested_path("job"), number.to_string()),
             Path::Build {
                 ref job_name,
                 ref number,
                 configuration: Some(ref configuration),
             } => format!(
-                "/job/{}/{}/{}",
-                job_name.to_string(),
+                "{}/{}/{}",
+                job_name.to_nested_path("job"),
                 configuration.to_string(),
                 number.to_string()
             ),
@@ -139,8 +320,8 @@ impl<'a> ToString for Path<'a> {
                 ref number,
                 configuration: None,
             } => format!(
-                "/job/{}/{}/consoleText",
-                job_name.to_string(),
+                "{}/{}/consoleText",
+                job_name.to_nested_path("job"),
                 number.to_string()
             ),
             Path::ConsoleText {
@@ -148,20 +329,108 @@ impl<'a> ToString for Path<'a> {
                 ref number,
                 configuration: Some(ref configuration),
             } => format!(
-                "/job/{}/{}/{}/consoleText",
-                job_name.to_string(),
+                "{}/{}/{}/consoleText",
+                job_name.to_nested_path("job"),
                 configuration.to_string(),
                 number.to_string()
             ),
+            Path::StopBuild {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!("{}/stop", build_path(job_name, number, configuration)),
+            Path::ToggleLogKeep {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!(
+                "{}/toggleLogKeep",
+                build_path(job_name, number, configuration)
+            ),
+            Path::DeleteBuild {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!("{}/doDelete", build_path(job_name, number, configuration)),
+            Path::SubmitBuildDescription {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!(
+                "{}/submitDescription",
+                build_path(job_name, number, configuration)
+            ),
+            Path::BuildConfigSubmit {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!(
+                "{}/configSubmit",
+                build_path(job_name, number, configuration)
+            ),
+            Path::ProgressiveText {
+                ref job_name,
+                ref number,
+                ref configuration,
+                start,
+            } => format!(
+                "{}/logText/progressiveText?start={}",
+                build_path(job_name, number, configuration),
+                start
+            ),
+            Path::Artifact {
+                ref job_name,
+                ref number,
+                ref configuration,
+                relative_path,
+            } => format!(
+                "{}/artifact/{}",
+                build_path(job_name, number, configuration),
+                relative_path
+                    .split('/')
+                    .map(urlencoding::encode)
+                    .collect::<Vec<_>>()
+                    .join("/")
+            ),
+            Path::PipelineDescription {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!(
+                "{}/wfapi/describe",
+                build_path(job_name, number, configuration)
+            ),
+            Path::BuildEnvVars {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!(
+                "{}/injectedEnvVars",
+                build_path(job_name, number, configuration)
+            ),
+            Path::Replay {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!("{}/replay/run", build_path(job_name, number, configuration)),
+            Path::ReplayWithSameScript {
+                ref job_name,
+                ref number,
+                ref configuration,
+            } => format!(
+                "{}/replay/rebuild",
+                build_path(job_name, number, configuration)
+            ),
             Path::Queue => "/queue".to_string(),
             Path::QueueItem { ref id } => format!("/queue/item/{}", id),
+            Path::CancelQueueItem { ref id } => format!("/queue/cancelItem?id={}", id),
             Path::MavenArtifactRecord {
                 ref job_name,
                 ref number,
                 configuration: None,
             } => format!(
-                "/job/{}/{}/mavenArtifacts",
-                job_name.to_string(),
+                "{}/{}/mavenArtifacts",
+                job_name.to_nested_path("job"),
                 number.to_string()
             ),
             Path::MavenArtifactRecord {
@@ -169,19 +438,252 @@ impl<'a> ToString for Path<'a> {
                 ref number,
                 configuration: Some(ref configuration),
             } => format!(
-                "/job/{}/{}/{}/mavenArtifacts",
-                job_name.to_string(),
+                "{}/{}/{}/mavenArtifacts",
+                job_name.to_nested_path("job"),
                 configuration.to_string(),
                 number.to_string()
             ),
-            Path::Computers => "/computer/api/json".to_string(),
-            Path::Computer { ref name } => format!("/computer/{}/api/json", name.to_string()),
+            Path::TestReport {
+                ref job_name,
+                ref number,
+                configuration: None,
+            } => format!(
+                "{}/{}/testReport",
+                job_name.to_nested_path("job"),
+                number.to_string()
+            ),
+            Path::TestReport {
+                ref job_name,
+                ref number,
+                configuration: Some(ref configuration),
+            } => format!(
+                "{}/{}/{}/testReport",
+                job_name.to_nested_path("job"),
+                configuration.to_string(),
+                number.to_string()
+            ),
+            Path::TestCaseAttachment {
+                ref job_name,
+                ref number,
+                configuration: None,
+                ref package,
+                ref class,
+                ref test,
+                ref attachment,
+            } => format!(
+                "{}/{}/testReport/{}/{}/{}/attachments/{}",
+                job_name.to_nested_path("job"),
+                number.to_string(),
+                package.to_string(),
+                class.to_string(),
+                test.to_string(),
+                attachment.to_string()
+            ),
+            Path::TestCaseAttachment {
+                ref job_name,
+                ref number,
+                configuration: Some(ref configuration),
+                ref package,
+                ref class,
+                ref test,
+                ref attachment,
+            } => format!(
+                "{}/{}/{}/testReport/{}/{}/{}/attachments/{}",
+                job_name.to_nested_path("job"),
+                configuration.to_string(),
+                number.to_string(),
+                package.to_string(),
+                class.to_string(),
+                test.to_string(),
+                attachment.to_string()
+            ),
+            Path::FlowNodeDescription {
+                ref job_name,
+                ref number,
+                ref node_id,
+            } => format!(
+                "{}/{}/execution/node/{}/wfapi/describe",
+                job_name.to_nested_path("job"),
+                number.to_string(),
+                node_id.to_string()
+            ),
+            Path::FlowNodeLog {
+                ref job_name,
+                ref number,
+                ref node_id,
+            } => format!(
+                "{}/{}/execution/node/{}/wfapi/log",
+                job_name.to_nested_path("job"),
+                number.to_string(),
+                node_id.to_string()
+            ),
+            Path::BlueOceanRuns { ref pipeline } => {
+                format!("{}/runs/", blue_ocean_pipeline_path(pipeline))
+            }
+            Path::BlueOceanRunNodes { ref pipeline, run } => {
+                format!("{}/runs/{}/nodes/", blue_ocean_pipeline_path(pipeline), run)
+            }
+            Path::BlueOceanNodeSteps {
+                ref pipeline,
+                run,
+                ref node_id,
+            } => format!(
+                "{}/runs/{}/nodes/{}/steps/",
+                blue_ocean_pipeline_path(pipeline),
+                run,
+                node_id.to_string()
+            ),
+            Path::BlueOceanStepLog {
+                ref pipeline,
+                run,
+                ref node_id,
+                ref step_id,
+            } => format!(
+                "{}/runs/{}/nodes/{}/steps/{}/log/",
+                blue_ocean_pipeline_path(pipeline),
+                run,
+                node_id.to_string(),
+                step_id.to_string()
+            ),
+            Path::Computers => "/computer".to_string(),
+            Path::Computer { ref name } => format!("/computer/{}", name.to_string()),
+            Path::ComputerToggleOffline { ref name } => {
+                format!("/computer/{}/toggleOffline", name.to_string())
+            }
+            Path::OverallLoad => "/overallLoad".to_string(),
+            Path::PluginManager => "/pluginManager".to_string(),
+            Path::InstallNecessaryPlugins => "/pluginManager/installNecessaryPlugins".to_string(),
+            Path::PluginMakeEnabled { short_name } => {
+                format!("/pluginManager/plugin/{}/makeEnabled", short_name)
+            }
+            Path::PluginMakeDisabled { short_name } => {
+                format!("/pluginManager/plugin/{}/makeDisabled", short_name)
+            }
+            Path::UpdateCenter => "/updateCenter".to_string(),
             Path::Raw { path } => path.to_string(),
             Path::CrumbIssuer => "/crumbIssuer".to_string(),
+            Path::WhoAmI => "/whoAmI".to_string(),
+            Path::Me => "/me".to_string(),
+            Path::User { id } => format!("/user/{}", urlencoding::encode(id)),
+            Path::GenerateApiToken => {
+                "/me/descriptorByName/jenkins.security.ApiTokenProperty/generateNewToken"
+                    .to_string()
+            }
+            Path::RevokeApiToken => {
+                "/me/descriptorByName/jenkins.security.ApiTokenProperty/revoke".to_string()
+            }
+            Path::QuietDown => "/quietDown".to_string(),
+            Path::CancelQuietDown => "/cancelQuietDown".to_string(),
+            Path::Restart => "/restart".to_string(),
+            Path::SafeRestart => "/safeRestart".to_string(),
+            Path::ScriptText => "/scriptText".to_string(),
+            Path::DescriptorByName { class } => format!("/descriptorByName/{}", class),
         }
     }
 }
 
+/// Path to a build, of a job or of one of its configurations
+fn build_path(
+    job_name: &Name<'_>,
+    number: &build::BuildNumber,
+    configuration: &Option<Name<'_>>,
+) -> String {
+    match *configuration {
+        Some(ref configuration) => format!(
+            "{}/{}/{}",
+            job_name.to_nested_path("job"),
+            configuration.to_string(),
+            number.to_string()
+        ),
+        None => format!("{}/{}", job_name.to_nested_path("job"), number.to_string()),
+    }
+}
+
+/// Path to a pipeline in the Blue Ocean REST API, where pipelines in folders are nested
+fn blue_ocean_pipeline_path(pipeline: &Name<'_>) -> String {
+    format!(
+        "/blue/rest/organizations/jenkins{}",
+        pipeline.to_nested_path("pipelines")
+    )
+}
+
+/// Split a path `/a/b/c/` in its segments, keeping the offset of each segment in the path
+fn segments(path: &str) -> Vec<(usize, &str)> {
+    let mut offset = 1;
+    path[1..(path.len() - 1)]
+        .split('/')
+        .map(|segment| {
+            let segment_with_offset = (offset, segment);
+            offset += segment.len() + 1;
+            segment_with_offset
+        })
+        .collect()
+}
+
+/// Parse the segments of a path to a `View`, that can be nested in other views
+/// (`/view/parent/view/name/`)
+fn view_segments_to_path<'a>(path: &'a str, segments: &[(usize, &'a str)]) -> Option<Path<'a>> {
+    if segments
+        .chunks(2)
+        .any(|pair| pair.len() != 2 || pair[0].1 != "view")
+    {
+        return None;
+    }
+    let (last_start, last) = segments[segments.len() - 1];
+    Some(Path::View {
+        name: Name::UrlEncodedName(&path[segments[1].0..(last_start + last.len())]),
+    })
+}
+
+/// Parse the segments of a path to a `Job`, that can be in folders (`/job/folder/job/name/`),
+/// followed by an optional configuration, build number and maven artifacts
+fn job_segments_to_path<'a>(path: &'a str, segments: &[(usize, &'a str)]) -> Option<Path<'a>> {
+    let mut end = 0;
+    while end + 1 < segments.len() && segments[end].1 == "job" {
+        end += 2;
+    }
+    if end == 0 {
+        return None;
+    }
+    let (last_start, last) = segments[end - 1];
+    let name = Name::UrlEncodedName(&path[segments[1].0..(last_start + last.len())]);
+
+    let rest: Vec<&'a str> = segments[end..].iter().map(|segment| segment.1).collect();
+    match rest.as_slice() {
+        [] => Some(Path::Job {
+            name,
+            configuration: None,
+        }),
+        [last] => Some(match last.parse() {
+            Ok(number) => Path::Build {
+                job_name: name,
+                number: build::BuildNumber::Number(number),
+                configuration: None,
+            },
+            Err(_) => Path::Job {
+                name,
+                configuration: Some(Name::UrlEncodedName(last)),
+            },
+        }),
+        [number, "mavenArtifacts"] => Some(Path::MavenArtifactRecord {
+            job_name: name,
+            number: build::BuildNumber::Number(number.parse().ok()?),
+            configuration: None,
+        }),
+        [configuration, number] => Some(Path::Build {
+            job_name: name,
+            number: build::BuildNumber::Number(number.parse().ok()?),
+            configuration: Some(Name::UrlEncodedName(configuration)),
+        }),
+        [configuration, number, "mavenArtifacts"] => Some(Path::MavenArtifactRecord {
+            job_name: name,
+            number: build::BuildNumber::Number(number.parse().ok()?),
+            configuration: Some(Name::UrlEncodedName(configuration)),
+        }),
+        _ => None,
+    }
+}
+
 impl Jenkins {
     pub(crate) fn url_to_path<'a>(&self, url: &'a str) -> Path<'a> {
         let path = if url.starts_with(&self.url) {
@@ -189,69 +691,20 @@ impl Jenkins {
         } else {
             url
         };
-        let slashes: Vec<usize> = path
-            .char_indices()
-            .filter(|c| c.1 == '/')
-            .map(|c| c.0)
-            .collect();
-
-        match (&path[0..slashes[1]], slashes.len()) {
-            ("/view", 3) => Path::View {
-                name: Name::UrlEncodedName(&path[6..(path.len() - 1)]),
-            },
-            ("/job", 3) => Path::Job {
-                name: Name::UrlEncodedName(&path[5..(path.len() - 1)]),
-                configuration: None,
-            },
-            ("/job", 4) => {
-                let last_part = &path[(slashes[2] + 1)..(path.len() - 1)];
-                let number = last_part.parse();
-                if let Ok(number) = number {
-                    Path::Build {
-                        job_name: Name::UrlEncodedName(&path[5..slashes[2]]),
-                        number: build::BuildNumber::Number(number),
-                        configuration: None,
-                    }
-                } else {
-                    Path::Job {
-                        name: Name::UrlEncodedName(&path[5..slashes[2]]),
-                        configuration: Some(Name::UrlEncodedName(last_part)),
-                    }
-                }
-            }
-            ("/job", 5) => {
-                if &path[slashes[3]..slashes[4]] == "mavenArtifacts" {
-                    Path::MavenArtifactRecord {
-                        job_name: Name::UrlEncodedName(&path[5..slashes[2]]),
-                        number: build::BuildNumber::Number(
-                            path[(slashes[3] + 1)..(path.len() - 1)].parse().unwrap(),
-                        ),
-                        configuration: None,
-                    }
-                } else {
-                    Path::Build {
-                        job_name: Name::UrlEncodedName(&path[5..slashes[2]]),
-                        number: build::BuildNumber::Number(
-                            path[(slashes[3] + 1)..(path.len() - 1)].parse().unwrap(),
-                        ),
-                        configuration: Some(Name::UrlEncodedName(
-                            &path[(slashes[2] + 1)..slashes[3]],
-                        )),
-                    }
-                }
-            }
-            ("/job", 6) => Path::MavenArtifactRecord {
-                job_name: Name::UrlEncodedName(&path[5..slashes[2]]),
-                number: build::BuildNumber::Number(
-                    path[(slashes[3] + 1)..slashes[4]].parse().unwrap(),
-                ),
-                configuration: Some(Name::UrlEncodedName(&path[(slashes[2] + 1)..slashes[3]])),
-            },
-            ("/queue", 4) => Path::QueueItem {
-                id: path[(slashes[2] + 1)..(path.len() - 1)].parse().unwrap(),
-            },
-            (_, _) => Path::Raw { path },
+        if path.len() < 2 || !path.starts_with('/') || !path.ends_with('/') {
+            return Path::Raw { path };
         }
+        let segments = segments(path);
+
+        let parsed = match segments[0].1 {
+            "view" => view_segments_to_path(path, &segments),
+            "job" => job_segments_to_path(path, &segments),
+            "queue" if segments.len() == 3 => {
+                segments[2].1.parse().ok().map(|id| Path::QueueItem { id })
+            }
+            _ => None,
+        };
+        parsed.unwrap_or(Path::Raw { path })
     }
 }
 
@@ -274,6 +727,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_nested_view_path() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let path = jenkins_client.url_to_path("http://none:8080/view/parent/view/my%20view/");
+        assert_eq!(
+            path,
+            Path::View {
+                name: Name::UrlEncodedName("parent/view/my%20view")
+            }
+        );
+        assert_eq!(path.to_string(), "/view/parent/view/my%20view");
+        assert_eq!(
+            jenkins_client.url_to_path("/view/parent/job/myjob/"),
+            Path::Raw {
+                path: "/view/parent/job/myjob/"
+            }
+        );
+    }
+
     #[test]
     fn can_parse_job_path() {
         let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
@@ -359,4 +832,80 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn can_parse_job_in_folder_path() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let path = jenkins_client.url_to_path("/job/TeamA/job/my-service/");
+        assert_eq!(
+            path,
+            Path::Job {
+                name: Name::UrlEncodedName("TeamA/job/my-service"),
+                configuration: None
+            }
+        );
+        assert_eq!(path.to_string(), "/job/TeamA/job/my-service");
+    }
+
+    #[test]
+    fn can_parse_build_in_nested_folders_path() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let path_url = format!("{}/job/a/job/b%20c/job/d/12/", JENKINS_URL);
+        let path = jenkins_client.url_to_path(&path_url);
+        assert_eq!(
+            path,
+            Path::Build {
+                job_name: Name::UrlEncodedName("a/job/b%20c/job/d"),
+                number: build::BuildNumber::Number(12),
+                configuration: None
+            }
+        );
+        assert_eq!(path.to_string(), "/job/a/job/b%20c/job/d/12");
+    }
+
+    #[test]
+    fn can_parse_maven_artifacts_path() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let path = jenkins_client.url_to_path("/job/myjob/3/mavenArtifacts/");
+        assert_eq!(
+            path,
+            Path::MavenArtifactRecord {
+                job_name: Name::UrlEncodedName("myjob"),
+                number: build::BuildNumber::Number(3),
+                configuration: None
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_invalid_job_paths_as_raw() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        for raw in &[
+            "/job/",
+            "/job/myjob",
+            "/job/myjob/config/notanumber/",
+            "/",
+            "",
+        ] {
+            assert_eq!(jenkins_client.url_to_path(raw), Path::Raw { path: raw });
+        }
+    }
+
+    #[test]
+    fn job_name_with_folders_is_nested() {
+        let path = Path::Job {
+            name: Name::Name("my folder/my job"),
+            configuration: None,
+        };
+        assert_eq!(path.to_string(), "/job/my%20folder/job/my%20job");
+
+        let path = Path::BuildJob {
+            name: Name::Name("normal job"),
+        };
+        assert_eq!(path.to_string(), "/job/normal%20job/build");
+    }
 }
@@ -0,0 +1,205 @@
+//! Rewriting URLs from the API onto the URL users reach Jenkins at
+
+use std::str::FromStr;
+
+use reqwest::Url;
+
+use super::{Error, Jenkins};
+
+/// Is `url` equal to `base`, or under it
+fn is_under(url: &str, base: &str) -> bool {
+    url.starts_with(base)
+        && match url[base.len()..].chars().next() {
+            None | Some('/') | Some('?') | Some('#') => true,
+            Some(_) => false,
+        }
+}
+
+/// Path of a base URL, without its trailing `/`, empty if Jenkins is served at the root
+fn base_path(base: &str) -> String {
+    Url::from_str(base)
+        .map(|url| url.path().trim_end_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+impl Jenkins {
+    /// Base URL users reach Jenkins at, the URL of the client if no public URL was set
+    fn public_base(&self) -> &str {
+        self.public_url.as_ref().unwrap_or(&self.url)
+    }
+
+    /// Rewrite a URL provided by the API, like the `url` of a job or a build, onto the public
+    /// URL set with
+    /// [`JenkinsBuilder::with_public_url`](struct.JenkinsBuilder.html#method.with_public_url),
+    /// keeping the path of the object
+    ///
+    /// URLs already under the public URL are kept as is. The prefix of the client URL or of the
+    /// public URL, for a Jenkins served under a path like `/jenkins`, is not repeated
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::InvalidObjectUrl`](../client/enum.Error.html#variant.InvalidObjectUrl)
+    /// if `url` is neither an absolute URL nor a path
+    pub fn public_url_for(&self, url: &str) -> Result<String, failure::Error> {
+        let public_base = self.public_base();
+        if is_under(url, public_base) {
+            return Ok(url.to_string());
+        }
+        if is_under(url, &self.url) {
+            return Ok(format!("{}{}", public_base, &url[self.url.len()..]));
+        }
+
+        let path = if url.starts_with('/') {
+            url.to_string()
+        } else {
+            let parsed = Url::from_str(url).map_err(|_| Error::InvalidObjectUrl {
+                url: url.to_string(),
+            })?;
+            let mut path = parsed.path().to_string();
+            if let Some(query) = parsed.query() {
+                path.push('?');
+                path.push_str(query);
+            }
+            if let Some(fragment) = parsed.fragment() {
+                path.push('#');
+                path.push_str(fragment);
+            }
+            path
+        };
+        let relative = [base_path(&self.url), base_path(public_base)]
+            .iter()
+            .filter(|prefix| !prefix.is_empty())
+            .find(|prefix| is_under(&path, prefix))
+            .map(|prefix| path[prefix.len()..].to_string())
+            .unwrap_or(path);
+        Ok(format!("{}{}", public_base, relative))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn can_rewrite_urls_onto_public_url() {
+        let cases = [
+            // client URL, public URL, URL from the API, expected
+            (
+                "http://jenkins:8080",
+                Some("https://ci.example.com"),
+                "http://jenkins:8080/job/my%20job/",
+                "https://ci.example.com/job/my%20job/",
+            ),
+            (
+                "http://jenkins:8080",
+                Some("https://ci.example.com"),
+                "https://ci.example.com/job/my%20job/",
+                "https://ci.example.com/job/my%20job/",
+            ),
+            (
+                "http://jenkins:8080",
+                Some("https://ci.example.com/"),
+                "/job/folder/job/inner/12/",
+                "https://ci.example.com/job/folder/job/inner/12/",
+            ),
+            (
+                "http://jenkins:8080",
+                Some("https://ci.example.com"),
+                "http://10.0.0.5:8080/job/my%20job/3/console?start=0#footer",
+                "https://ci.example.com/job/my%20job/3/console?start=0#footer",
+            ),
+            (
+                "http://gateway/jenkins",
+                Some("https://ci.example.com"),
+                "http://gateway/jenkins/job/my%20job/",
+                "https://ci.example.com/job/my%20job/",
+            ),
+            (
+                "http://gateway/jenkins",
+                Some("https://ci.example.com"),
+                "http://jenkins:8080/jenkins/job/my%20job/",
+                "https://ci.example.com/job/my%20job/",
+            ),
+            (
+                "http://gateway/jenkins",
+                Some("https://ci.example.com"),
+                "http://jenkins:8080/jenkinsfile/",
+                "https://ci.example.com/jenkinsfile/",
+            ),
+            (
+                "http://jenkins:8080",
+                Some("https://example.com/ci"),
+                "http://jenkins:8080/job/my%20job/",
+                "https://example.com/ci/job/my%20job/",
+            ),
+            (
+                "http://gateway",
+                Some("https://example.com/ci"),
+                "http://jenkins:8080/ci/job/my%20job/",
+                "https://example.com/ci/job/my%20job/",
+            ),
+            (
+                "http://jenkins:8080",
+                Some("https://example.com/ci"),
+                "https://example.com/ci",
+                "https://example.com/ci",
+            ),
+            (
+                "http://jenkins:8080",
+                None,
+                "http://10.0.0.5:8080/job/my%20job/",
+                "http://jenkins:8080/job/my%20job/",
+            ),
+            (
+                "http://jenkins:8080",
+                None,
+                "http://jenkins:8080/job/my%20job/",
+                "http://jenkins:8080/job/my%20job/",
+            ),
+        ];
+
+        for (client_url, public_url, url, expected) in cases.iter() {
+            let mut builder = crate::JenkinsBuilder::new(client_url);
+            if let Some(public_url) = public_url {
+                builder = builder.with_public_url(public_url);
+            }
+            let jenkins_client = builder.build().unwrap();
+
+            let rewritten = jenkins_client.public_url_for(url).unwrap();
+            assert_eq!(
+                &rewritten, expected,
+                "rewriting {} with client {} and public {:?}",
+                url, client_url, public_url
+            );
+            assert_eq!(
+                &jenkins_client.public_url_for(&rewritten).unwrap(),
+                expected,
+                "rewriting {} again",
+                rewritten
+            );
+        }
+    }
+
+    #[test]
+    fn should_fail_on_invalid_url() {
+        let jenkins_client = crate::JenkinsBuilder::new("http://jenkins:8080")
+            .with_public_url("https://ci.example.com")
+            .build()
+            .unwrap();
+
+        match jenkins_client
+            .public_url_for("job/my job")
+            .unwrap_err()
+            .downcast::<super::Error>()
+        {
+            Ok(super::Error::InvalidObjectUrl { url }) => assert_eq!(url, "job/my job"),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_fail_on_invalid_public_url() {
+        assert!(crate::JenkinsBuilder::new("http://jenkins:8080")
+            .with_public_url("ci.example.com")
+            .build()
+            .is_err());
+    }
+}
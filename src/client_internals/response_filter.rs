@@ -0,0 +1,318 @@
+//! Rewriting response bodies before they are parsed, to work around proxies altering them
+
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "async")]
+use futures::{future, Future, Stream};
+#[cfg(feature = "async")]
+use reqwest::r#async;
+use reqwest::{
+    header::{HeaderMap, CONTENT_TYPE},
+    Response, StatusCode, Url, Version,
+};
+
+use super::Path;
+
+/// Kind of content a response is expected to hold
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseKind {
+    /// JSON from the Jenkins API, to be deserialized
+    Json,
+    /// Text, like an HTML page or a log
+    Text,
+    /// File downloaded as is, like an artifact or the console output. Response filters never
+    /// run on these
+    Binary,
+}
+
+impl<'a> Path<'a> {
+    /// Kind of content served at this path
+    pub(crate) fn response_kind(&self) -> ResponseKind {
+        match *self {
+            Path::ConsoleText { .. }
+            | Path::ProgressiveText { .. }
+            | Path::Artifact { .. }
+            | Path::TestCaseAttachment { .. } => ResponseKind::Binary,
+            Path::SCMPollLog { .. } | Path::BlueOceanStepLog { .. } | Path::JobConfig { .. } => {
+                ResponseKind::Text
+            }
+            _ => ResponseKind::Json,
+        }
+    }
+}
+
+/// Information about a response, given to response filters
+#[derive(Debug, Clone)]
+pub struct ResponseMeta {
+    /// URL of the request
+    pub url: String,
+    /// HTTP status of the response
+    pub status: u16,
+    /// Content type of the response
+    pub content_type: Option<String>,
+    /// Kind of content expected
+    pub kind: ResponseKind,
+}
+
+impl ResponseMeta {
+    fn new(kind: ResponseKind, url: &Url, status: StatusCode, headers: &HeaderMap) -> Self {
+        ResponseMeta {
+            url: url.to_string(),
+            status: status.as_u16(),
+            content_type: headers
+                .get(CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .map(str::to_string),
+            kind,
+        }
+    }
+
+    /// Does the content type of the response claim it is JSON
+    pub fn claims_json(&self) -> bool {
+        self.content_type
+            .as_ref()
+            .map(|content_type| content_type.contains("json"))
+            .unwrap_or(false)
+    }
+}
+
+type Filter = Arc<dyn Fn(&mut Vec<u8>, &ResponseMeta) + Send + Sync>;
+
+/// Filters set with `JenkinsBuilder::with_response_filter`, run in order
+#[derive(Clone, Default)]
+pub(crate) struct ResponseFilterChain(Vec<Filter>);
+
+impl fmt::Debug for ResponseFilterChain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ResponseFilterChain({} filters)", self.0.len())
+    }
+}
+
+impl ResponseFilterChain {
+    pub(crate) fn push<F>(&mut self, filter: F)
+    where
+        F: Fn(&mut Vec<u8>, &ResponseMeta) + Send + Sync + 'static,
+    {
+        self.0.push(Arc::new(filter));
+    }
+
+    /// Should the filters run on a response of `kind` with `status`: they never run on binary
+    /// downloads nor on errors
+    fn applies_to(&self, kind: ResponseKind, status: StatusCode) -> bool {
+        !self.0.is_empty() && kind != ResponseKind::Binary && status.is_success()
+    }
+
+    /// Run the filters on a body
+    fn filter(&self, body: &mut Vec<u8>, meta: &ResponseMeta) {
+        for filter in &self.0 {
+            filter(body, meta);
+        }
+    }
+
+    /// Run the filters on the body of a successful `response`, unless it is a binary download.
+    /// The body is read, and a new `Response` is built with the filtered body
+    pub(crate) fn apply(
+        &self,
+        kind: ResponseKind,
+        mut response: Response,
+    ) -> Result<Response, failure::Error> {
+        if !self.applies_to(kind, response.status()) {
+            return Ok(response);
+        }
+
+        let meta = ResponseMeta::new(kind, response.url(), response.status(), response.headers());
+        let mut body = vec![];
+        let _ = response.copy_to(&mut body)?;
+        self.filter(&mut body, &meta);
+        Ok(Response::from(with_body(
+            response.status(),
+            response.version(),
+            response.headers(),
+            body,
+        )?))
+    }
+
+    /// Run the filters on the body of a successful `response` of the asynchronous client, as
+    /// `apply` does
+    #[cfg(feature = "async")]
+    pub(crate) fn apply_async(
+        &self,
+        kind: ResponseKind,
+        response: r#async::Response,
+    ) -> crate::r#async::JenkinsFuture<r#async::Response> {
+        if !self.applies_to(kind, response.status()) {
+            return Box::new(future::ok(response));
+        }
+
+        let meta = ResponseMeta::new(kind, response.url(), response.status(), response.headers());
+        let (status, version, headers) = (
+            response.status(),
+            response.version(),
+            response.headers().clone(),
+        );
+        let filters = self.clone();
+        Box::new(
+            response
+                .into_body()
+                .concat2()
+                .map_err(failure::Error::from)
+                .and_then(move |chunk| {
+                    let mut body = chunk.to_vec();
+                    filters.filter(&mut body, &meta);
+                    Ok(r#async::Response::from(with_body(
+                        status, version, &headers, body,
+                    )?))
+                }),
+        )
+    }
+}
+
+/// Response with the status and headers of a response, and a new body
+fn with_body(
+    status: StatusCode,
+    version: Version,
+    headers: &HeaderMap,
+    body: Vec<u8>,
+) -> Result<http::Response<Vec<u8>>, http::Error> {
+    let mut response = http::Response::builder();
+    let _ = response.status(status).version(version);
+    for (name, value) in headers {
+        let _ = response.header(name, value.clone());
+    }
+    response.body(body)
+}
+
+/// Filters that can be set with `JenkinsBuilder::with_response_filter`
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseFilters;
+
+impl ResponseFilters {
+    /// Remove a byte order mark, HTML comments and anything else before the first `{` or `[`
+    /// of responses whose content type claims they are JSON
+    pub fn strip_bom_and_leading_junk() -> impl Fn(&mut Vec<u8>, &ResponseMeta) + Send + Sync {
+        |body: &mut Vec<u8>, meta: &ResponseMeta| {
+            if meta.claims_json() {
+                let start = json_start(body);
+                let _ = body.drain(..start);
+            }
+        }
+    }
+}
+
+/// Offset of the start of the JSON in `body`, skipping a byte order mark, whitespace and HTML
+/// comments, then anything before the first `{` or `[`. `0` if there is no JSON to find
+fn json_start(body: &[u8]) -> usize {
+    const BOM: &[u8] = b"\xEF\xBB\xBF";
+    let mut start = 0;
+    loop {
+        let rest = &body[start..];
+        if rest.starts_with(BOM) {
+            start += BOM.len();
+        } else if rest.first().map(u8::is_ascii_whitespace).unwrap_or(false) {
+            start += 1;
+        } else if rest.starts_with(b"<!--") {
+            match rest.windows(3).position(|end| end == b"-->") {
+                Some(end) => start += end + 3,
+                None => break,
+            }
+        } else {
+            break;
+        }
+    }
+    body[start..]
+        .iter()
+        .position(|c| *c == b'{' || *c == b'[')
+        .map(|offset| start + offset)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(content_type: &str) -> ResponseMeta {
+        ResponseMeta {
+            url: "http://jenkins/api/json".to_string(),
+            status: 200,
+            content_type: Some(content_type.to_string()),
+            kind: ResponseKind::Json,
+        }
+    }
+
+    fn strip(body: &[u8], content_type: &str) -> Vec<u8> {
+        let mut body = body.to_vec();
+        ResponseFilters::strip_bom_and_leading_junk()(&mut body, &meta(content_type));
+        body
+    }
+
+    #[test]
+    fn can_strip_bom_and_banners() {
+        let json = "application/json;charset=utf-8";
+        assert_eq!(strip(b"\xEF\xBB\xBF{\"a\":1}", json), b"{\"a\":1}".to_vec());
+        assert_eq!(
+            strip(b"<!-- proxy [v2] {banner} -->\n[1]", json),
+            b"[1]".to_vec()
+        );
+        assert_eq!(strip(b"garbage {\"a\":1}", json), b"{\"a\":1}".to_vec());
+        assert_eq!(strip(b"{\"a\":1}", json), b"{\"a\":1}".to_vec());
+        assert_eq!(strip(b"<!-- banner -->", json), b"<!-- banner -->".to_vec());
+    }
+
+    #[test]
+    fn only_strips_json() {
+        assert_eq!(
+            strip(b"<!-- banner --><html></html>", "text/html"),
+            b"<!-- banner --><html></html>".to_vec()
+        );
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Home {
+        mode: String,
+    }
+
+    #[test]
+    fn filters_json_responses_but_not_binary_downloads() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/response-filter", mockito::server_url()))
+                .with_response_filter(ResponseFilters::strip_bom_and_leading_junk())
+                .build()
+                .unwrap();
+        let _bom = mockito::mock("GET", "/response-filter/view/bom/api/json?depth=1")
+            .with_header("Content-Type", "application/json;charset=utf-8")
+            .with_body(b"\xEF\xBB\xBF{\"mode\":\"NORMAL\"}")
+            .create();
+        let _banner = mockito::mock("GET", "/response-filter/view/banner/api/json?depth=1")
+            .with_header("Content-Type", "application/json")
+            .with_body("<!-- Served by Corporate Proxy [build 42] -->\n{\"mode\":\"NORMAL\"}")
+            .create();
+        let _artifact = mockito::mock("GET", "/response-filter/job/a/1/artifact/data.json")
+            .with_header("Content-Type", "application/json")
+            .with_body("junk {}")
+            .create();
+
+        for view in &["bom", "banner"] {
+            let home: Home = jenkins_client
+                .get(&Path::View {
+                    name: super::super::Name::Name(view),
+                })
+                .unwrap()
+                .json()
+                .unwrap();
+            assert_eq!(home.mode, "NORMAL");
+        }
+
+        let artifact = jenkins_client
+            .get_file(&Path::Artifact {
+                job_name: super::super::Name::Name("a"),
+                number: crate::build::BuildNumber::Number(1),
+                configuration: None,
+                relative_path: "data.json",
+            })
+            .unwrap()
+            .text()
+            .unwrap();
+        assert_eq!(artifact, "junk {}");
+    }
+}
@@ -0,0 +1,183 @@
+//! Retrying requests that failed because of a transient error
+
+use std::thread;
+use std::time::Duration;
+
+use log::warn;
+use reqwest::{Method, Request, Response};
+
+use super::Error;
+
+/// Policy to retry requests
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryPolicy {
+    pub(crate) retries: u32,
+    pub(crate) base_delay: Duration,
+    pub(crate) retry_posts: bool,
+}
+
+impl RetryPolicy {
+    /// Number of times a request can be retried. Only idempotent requests are retried, and
+    /// `POST` requests when asked
+    pub(crate) fn retries_for(&self, method: &Method) -> u32 {
+        if method == Method::GET || method == Method::HEAD || self.retry_posts {
+            self.retries
+        } else {
+            0
+        }
+    }
+
+    /// Delay before a retry, doubling after each attempt
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(attempt)
+    }
+}
+
+fn is_transient_reqwest_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_http() || error.is_server_error()
+}
+
+/// Is an error transient, so that retrying the request may succeed: a connection error, a
+/// timeout or a 5xx response from Jenkins
+pub fn is_transient_error(error: &failure::Error) -> bool {
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        is_transient_reqwest_error(error)
+    } else {
+        matches!(error.downcast_ref::<Error>(), Some(Error::Timeout))
+    }
+}
+
+fn is_transient(result: &Result<Response, reqwest::Error>) -> bool {
+    match result {
+        Ok(response) => response.status().is_server_error(),
+        Err(error) => is_transient_reqwest_error(error),
+    }
+}
+
+/// Execute a request, retrying it following `policy` while it fails with a transient error.
+/// The last response or error is returned when retries are exhausted
+pub(crate) fn execute<F>(
+    policy: Option<&RetryPolicy>,
+    request: Request,
+    execute: F,
+) -> Result<Response, reqwest::Error>
+where
+    F: Fn(Request) -> Result<Response, reqwest::Error>,
+{
+    let retries = policy.map_or(0, |policy| policy.retries_for(request.method()));
+    let mut request = request;
+    let mut attempt = 0;
+    loop {
+        let next = if attempt < retries {
+            request.try_clone()
+        } else {
+            None
+        };
+        let result = execute(request);
+        match (next, policy) {
+            (Some(next), Some(policy)) if is_transient(&result) => {
+                let delay = policy.delay(attempt);
+                match result {
+                    Ok(ref response) => {
+                        warn!("got a {}, retrying in {:?}", response.status(), delay)
+                    }
+                    Err(ref error) => warn!("request failed: {}, retrying in {:?}", error, delay),
+                }
+                thread::sleep(delay);
+                request = next;
+                attempt += 1;
+            }
+            _ => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::RetryPolicy;
+    use crate::helpers::test_server::{response, serve_in_order};
+
+    #[test]
+    fn should_double_delay() {
+        let policy = RetryPolicy {
+            retries: 3,
+            base_delay: Duration::from_millis(100),
+            retry_posts: false,
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        assert_eq!(policy.retries_for(&reqwest::Method::GET), 3);
+        assert_eq!(policy.retries_for(&reqwest::Method::POST), 0);
+    }
+
+    #[test]
+    fn should_retry_get_on_server_error() {
+        let url = serve_in_order(vec![
+            response("502 Bad Gateway", "", ""),
+            response("503 Service Unavailable", "", ""),
+            response("200 OK", "", r#"{"_class": "hudson.model.Hudson"}"#),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .with_retries(2, Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let home: serde_json::Value = jenkins_client
+            .get(&crate::client_internals::Path::Home)
+            .unwrap()
+            .json()
+            .unwrap();
+        assert_eq!(home["_class"], "hudson.model.Hudson");
+    }
+
+    #[test]
+    fn should_return_last_error_when_retries_are_exhausted() {
+        let url = serve_in_order(vec![
+            response("502 Bad Gateway", "", ""),
+            response("504 Gateway Timeout", "", ""),
+            response("200 OK", "", "{}"),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .with_retries(1, Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let error = jenkins_client
+            .get(&crate::client_internals::Path::Home)
+            .unwrap_err();
+        assert!(super::is_transient_error(&error));
+        assert!(format!("{}", error).contains("504"));
+    }
+
+    #[test]
+    fn should_not_retry_post_by_default() {
+        let url = serve_in_order(vec![
+            response("502 Bad Gateway", "", ""),
+            response("200 OK", "", ""),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .with_retries(2, Duration::from_millis(1))
+            .build()
+            .unwrap();
+        assert!(jenkins_client
+            .post(&crate::client_internals::Path::Raw { path: "/path" })
+            .is_err());
+
+        let url = serve_in_order(vec![
+            response("502 Bad Gateway", "", ""),
+            response("200 OK", "", ""),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .with_retries(2, Duration::from_millis(1))
+            .with_post_retries()
+            .build()
+            .unwrap();
+        assert!(jenkins_client
+            .post(&crate::client_internals::Path::Raw { path: "/path" })
+            .is_ok());
+    }
+}
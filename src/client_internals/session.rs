@@ -0,0 +1,232 @@
+//! Session with Jenkins, that can be exported to be resumed by another client
+
+use std::sync::MutexGuard;
+
+use log::debug;
+use reqwest::{
+    header::{HeaderMap, HeaderValue, COOKIE, SET_COOKIE},
+    RequestBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{csrf::Crumb, Jenkins};
+
+/// State of a session with Jenkins, to resume it with
+/// [`JenkinsBuilder::with_session`](../struct.JenkinsBuilder.html#method.with_session) and save
+/// the handshake getting a crumb. It never contains the credentials of the user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionState {
+    /// Crumb protecting from CSRF
+    pub crumb: Option<Crumb>,
+    /// Cookies of the session, as `name=value`
+    #[serde(default)]
+    pub cookies: Vec<String>,
+    /// Version of Jenkins
+    pub version: Option<String>,
+}
+
+impl Jenkins {
+    fn session(&self) -> MutexGuard<'_, SessionState> {
+        self.session
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Export the state of the session, to resume it later from another client
+    pub fn export_session(&self) -> SessionState {
+        self.session().clone()
+    }
+
+    pub(crate) fn cached_crumb(&self) -> Option<Crumb> {
+        self.session().crumb.clone()
+    }
+
+    pub(crate) fn cache_crumb(&self, crumb: &Crumb) {
+        self.session().crumb = Some(crumb.clone());
+    }
+
+    /// Forget the crumb and cookies of a session that was rejected
+    pub(crate) fn reset_session(&self) {
+        debug!("resetting session");
+        let mut session = self.session();
+        session.crumb = None;
+        session.cookies.clear();
+    }
+
+    /// `Cookie` header with the cookies of the session, `None` if there are none
+    pub(crate) fn session_cookies(&self) -> Option<HeaderValue> {
+        let cookies = self.session().cookies.join("; ");
+        match HeaderValue::from_str(&cookies) {
+            Ok(value) if !cookies.is_empty() => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn add_cookies_to_request(&self, request_builder: RequestBuilder) -> RequestBuilder {
+        match self.session_cookies() {
+            Some(cookies) => request_builder.header(COOKIE, cookies),
+            None => request_builder,
+        }
+    }
+
+    /// Keep the cookies set and the version of Jenkins from the headers of a response
+    pub(crate) fn update_session(&self, headers: &HeaderMap) {
+        let mut session = self.session();
+        for set_cookie in headers.get_all(SET_COOKIE) {
+            let cookie = match set_cookie.to_str() {
+                Ok(cookie) => cookie.split(';').next().unwrap_or("").trim(),
+                Err(_) => continue,
+            };
+            let name = match cookie.find('=') {
+                Some(index) => &cookie[..=index],
+                None => continue,
+            };
+            session
+                .cookies
+                .retain(|existing| !existing.starts_with(name));
+            session.cookies.push(cookie.to_string());
+        }
+        if let Some(version) = headers
+            .get("X-Jenkins")
+            .and_then(|version| version.to_str().ok())
+        {
+            session.version = Some(version.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionState;
+
+    fn session(crumb: &str) -> SessionState {
+        serde_json::from_str(&format!(
+            r#"{{
+                "crumb": {{"crumb": "{}", "crumbRequestField": "Jenkins-Crumb"}},
+                "cookies": ["JSESSIONID.abc=old"],
+                "version": "2.150"
+            }}"#,
+            crumb
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_export_session() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/session-export", mockito::server_url()))
+                .with_user("user", Some("secret password"))
+                .build()
+                .unwrap();
+
+        let _crumb = mockito::mock("GET", "/session-export/crumbIssuer/api/json")
+            .with_header("Set-Cookie", "JSESSIONID.abc=new; Path=/; HttpOnly")
+            .with_header("X-Jenkins", "2.164")
+            .with_body(r#"{"crumb":"fresh","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let post = mockito::mock("POST", "/session-export/path")
+            .match_header("jenkins-crumb", "fresh")
+            .match_header("cookie", "JSESSIONID.abc=new")
+            .create();
+
+        let _ = jenkins_client
+            .post(&super::super::Path::Raw { path: "/path" })
+            .unwrap();
+        post.assert();
+
+        let exported = jenkins_client.export_session();
+        assert_eq!(exported.cookies, vec!["JSESSIONID.abc=new".to_string()]);
+        assert_eq!(exported.version, Some("2.164".to_string()));
+        let serialized = serde_json::to_string(&exported).unwrap();
+        assert!(serialized.contains("fresh"));
+        assert!(!serialized.contains("secret password"));
+    }
+
+    #[test]
+    fn can_resume_valid_session() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/session-valid", mockito::server_url()))
+                .with_session(session("resumed"))
+                .build()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/session-valid/crumbIssuer/api/json")
+            .expect(0)
+            .create();
+        let post = mockito::mock("POST", "/session-valid/path")
+            .match_header("jenkins-crumb", "resumed")
+            .match_header("cookie", "JSESSIONID.abc=old")
+            .expect(2)
+            .create();
+
+        for _ in 0..2 {
+            let _ = jenkins_client
+                .post(&super::super::Path::Raw { path: "/path" })
+                .unwrap();
+        }
+        crumb.assert();
+        post.assert();
+    }
+
+    #[test]
+    fn should_renew_expired_session() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/session-expired", mockito::server_url()))
+                .with_session(session("expired"))
+                .build()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/session-expired/crumbIssuer/api/json")
+            .with_header("Set-Cookie", "JSESSIONID.abc=renewed; Path=/")
+            .with_body(r#"{"crumb":"renewed","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let rejected = mockito::mock("POST", "/session-expired/path")
+            .match_header("jenkins-crumb", "expired")
+            .with_status(403)
+            .create();
+        let accepted = mockito::mock("POST", "/session-expired/path")
+            .match_header("jenkins-crumb", "renewed")
+            .match_header("cookie", "JSESSIONID.abc=renewed")
+            .with_body("ok")
+            .create();
+
+        let response = jenkins_client
+            .post(&super::super::Path::Raw { path: "/path" })
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        crumb.assert();
+        rejected.assert();
+        accepted.assert();
+        assert_eq!(
+            jenkins_client.export_session().cookies,
+            vec!["JSESSIONID.abc=renewed".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_renew_garbage_session() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/session-garbage", mockito::server_url()))
+                .with_session(SessionState {
+                    crumb: session("\\n").crumb,
+                    cookies: vec!["garbage\u{7f}".to_string()],
+                    version: None,
+                })
+                .build()
+                .unwrap();
+
+        let crumb = mockito::mock("GET", "/session-garbage/crumbIssuer/api/json")
+            .with_body(r#"{"crumb":"renewed","crumbRequestField":"Jenkins-Crumb"}"#)
+            .create();
+        let post = mockito::mock("POST", "/session-garbage/path")
+            .match_header("jenkins-crumb", "renewed")
+            .create();
+
+        let _ = jenkins_client
+            .post(&super::super::Path::Raw { path: "/path" })
+            .unwrap();
+        crumb.assert();
+        post.assert();
+    }
+}
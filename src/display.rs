@@ -0,0 +1,233 @@
+//! Helpers to present statuses in terminals and badges
+//!
+//! The strings returned are part of the API and only change with a new major version
+
+use std::fmt;
+
+use crate::build::BuildStatus;
+use crate::job::{BallColor, HealthReport};
+
+/// ANSI escape sequence resetting the color set with `BallColor::ansi_color`
+pub const ANSI_RESET: &str = "\x1b[0m";
+
+impl BallColor {
+    /// Emoji for the color: the status of the last build, or 🔄 while a build is on-going
+    pub fn emoji(self) -> &'static str {
+        match self {
+            BallColor::Blue => "✅",
+            BallColor::Yellow => "⚠️",
+            BallColor::Red => "❌",
+            BallColor::Grey => "⚪",
+            BallColor::Disabled => "🚫",
+            BallColor::Aborted => "⛔",
+            BallColor::NotBuilt => "⚪",
+            BallColor::Unknown => "❔",
+            BallColor::BlueAnime
+            | BallColor::YellowAnime
+            | BallColor::RedAnime
+            | BallColor::GreyAnime
+            | BallColor::DisabledAnime
+            | BallColor::AbortedAnime
+            | BallColor::NotBuiltAnime => "🔄",
+        }
+    }
+
+    /// ANSI escape sequence setting the foreground to the color of the ball, to be followed by
+    /// `ANSI_RESET`. On-going builds have the color of the last build
+    pub fn ansi_color(self) -> &'static str {
+        match self {
+            BallColor::Blue | BallColor::BlueAnime => "\x1b[34m",
+            BallColor::Yellow | BallColor::YellowAnime => "\x1b[33m",
+            BallColor::Red | BallColor::RedAnime => "\x1b[31m",
+            BallColor::Grey
+            | BallColor::GreyAnime
+            | BallColor::Disabled
+            | BallColor::DisabledAnime
+            | BallColor::Aborted
+            | BallColor::AbortedAnime
+            | BallColor::NotBuilt
+            | BallColor::NotBuiltAnime
+            | BallColor::Unknown => "\x1b[90m",
+        }
+    }
+}
+
+/// Name of the color in the Jenkins UI, followed by "(in progress)" while a build is on-going
+impl fmt::Display for BallColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            BallColor::Blue | BallColor::BlueAnime => "Success",
+            BallColor::Yellow | BallColor::YellowAnime => "Unstable",
+            BallColor::Red | BallColor::RedAnime => "Failed",
+            BallColor::Grey | BallColor::GreyAnime => "Pending",
+            BallColor::Disabled | BallColor::DisabledAnime => "Disabled",
+            BallColor::Aborted | BallColor::AbortedAnime => "Aborted",
+            BallColor::NotBuilt | BallColor::NotBuiltAnime => "Not built",
+            BallColor::Unknown => "Unknown",
+        };
+        if self.is_animated() {
+            write!(f, "{} (in progress)", name)
+        } else {
+            write!(f, "{}", name)
+        }
+    }
+}
+
+impl BuildStatus {
+    /// Color of a badge for the status, as the hex colors of shields.io
+    pub fn badge_color(self) -> &'static str {
+        match self {
+            BuildStatus::Success => "#4c1",
+            BuildStatus::Unstable => "#dfb317",
+            BuildStatus::Failure => "#e05d44",
+            BuildStatus::NotBuilt => "#9f9f9f",
+            BuildStatus::Aborted => "#9f9f9f",
+        }
+    }
+}
+
+/// Name of the status in the Jenkins UI
+impl fmt::Display for BuildStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            BuildStatus::Success => "Success",
+            BuildStatus::Unstable => "Unstable",
+            BuildStatus::Failure => "Failed",
+            BuildStatus::NotBuilt => "Not built",
+            BuildStatus::Aborted => "Aborted",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl HealthReport {
+    /// Emoji for the weather of the report, with the buckets Jenkins uses for its icons: ☀️
+    /// from 80, 🌤️ from 60, ☁️ from 40, 🌧️ from 20 and ⛈️ below
+    pub fn weather_emoji(&self) -> &'static str {
+        match self.score {
+            0..=19 => "⛈️",
+            20..=39 => "🌧️",
+            40..=59 => "☁️",
+            60..=79 => "🌤️",
+            _ => "☀️",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::build::BuildStatus;
+    use crate::job::{BallColor, HealthReport};
+
+    static COLORS: [BallColor; 15] = [
+        BallColor::Blue,
+        BallColor::BlueAnime,
+        BallColor::Yellow,
+        BallColor::YellowAnime,
+        BallColor::Red,
+        BallColor::RedAnime,
+        BallColor::Grey,
+        BallColor::GreyAnime,
+        BallColor::Disabled,
+        BallColor::DisabledAnime,
+        BallColor::Aborted,
+        BallColor::AbortedAnime,
+        BallColor::NotBuilt,
+        BallColor::NotBuiltAnime,
+        BallColor::Unknown,
+    ];
+
+    static STATUSES: [BuildStatus; 5] = [
+        BuildStatus::Success,
+        BuildStatus::Unstable,
+        BuildStatus::Failure,
+        BuildStatus::NotBuilt,
+        BuildStatus::Aborted,
+    ];
+
+    #[test]
+    fn ball_colors_snapshot() {
+        let snapshot: Vec<String> = COLORS
+            .iter()
+            .map(|color| {
+                format!(
+                    "{:?}|{}|{}|{:?}",
+                    color,
+                    color,
+                    color.emoji(),
+                    color.ansi_color()
+                )
+            })
+            .collect();
+        assert_eq!(
+            snapshot,
+            vec![
+                r#"Blue|Success|✅|"\u{1b}[34m""#,
+                r#"BlueAnime|Success (in progress)|🔄|"\u{1b}[34m""#,
+                r#"Yellow|Unstable|⚠️|"\u{1b}[33m""#,
+                r#"YellowAnime|Unstable (in progress)|🔄|"\u{1b}[33m""#,
+                r#"Red|Failed|❌|"\u{1b}[31m""#,
+                r#"RedAnime|Failed (in progress)|🔄|"\u{1b}[31m""#,
+                r#"Grey|Pending|⚪|"\u{1b}[90m""#,
+                r#"GreyAnime|Pending (in progress)|🔄|"\u{1b}[90m""#,
+                r#"Disabled|Disabled|🚫|"\u{1b}[90m""#,
+                r#"DisabledAnime|Disabled (in progress)|🔄|"\u{1b}[90m""#,
+                r#"Aborted|Aborted|⛔|"\u{1b}[90m""#,
+                r#"AbortedAnime|Aborted (in progress)|🔄|"\u{1b}[90m""#,
+                r#"NotBuilt|Not built|⚪|"\u{1b}[90m""#,
+                r#"NotBuiltAnime|Not built (in progress)|🔄|"\u{1b}[90m""#,
+                r#"Unknown|Unknown|❔|"\u{1b}[90m""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn build_statuses_snapshot() {
+        let snapshot: Vec<String> = STATUSES
+            .iter()
+            .map(|status| format!("{:?}|{}|{}", status, status, status.badge_color()))
+            .collect();
+        assert_eq!(
+            snapshot,
+            vec![
+                "Success|Success|#4c1",
+                "Unstable|Unstable|#dfb317",
+                "Failure|Failed|#e05d44",
+                "NotBuilt|Not built|#9f9f9f",
+                "Aborted|Aborted|#9f9f9f",
+            ]
+        );
+    }
+
+    #[test]
+    fn weather_follows_jenkins_buckets() {
+        let weather = |score| {
+            HealthReport {
+                description: String::new(),
+                icon_class_name: String::new(),
+                icon_url: String::new(),
+                score,
+            }
+            .weather_emoji()
+        };
+        let snapshot: Vec<(u16, &str)> = [0, 19, 20, 39, 40, 59, 60, 79, 80, 100]
+            .iter()
+            .map(|score| (*score, weather(*score)))
+            .collect();
+        assert_eq!(
+            snapshot,
+            vec![
+                (0, "⛈️"),
+                (19, "⛈️"),
+                (20, "🌧️"),
+                (39, "🌧️"),
+                (40, "☁️"),
+                (59, "☁️"),
+                (60, "🌤️"),
+                (79, "🌤️"),
+                (80, "☀️"),
+                (100, "☀️"),
+            ]
+        );
+    }
+}
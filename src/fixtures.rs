@@ -0,0 +1,597 @@
+//! Capture the JSON served by a Jenkins instance, and check how it deserializes into the types of
+//! this crate
+//!
+//! A `FixtureSet` can be saved after capture, and its `VerificationReport` compared between
+//! Jenkins versions to spot fields that stopped being parsed after an upgrade.
+
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use failure::Error;
+use serde::de::{self, value::BorrowedStrDeserializer, DeserializeSeed, MapAccess, SeqAccess};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize};
+
+use crate::build::{
+    BuildFlowRun, BuildNumber, CommonBuild, FreeStyleBuild, MatrixBuild, MatrixRun, MavenBuild,
+    MavenModuleSetBuild, MultiJobBuild, WorkflowRun,
+};
+use crate::client_internals::{Name, Path};
+use crate::helpers::Class;
+use crate::home::Home;
+use crate::job::{
+    BuildFlowJob, CommonJob, ExternalJob, Folder, FreeStyleProject, MatrixConfiguration,
+    MatrixProject, MavenModule, MavenModuleSet, MultiJobProject, OrganizationFolder, WorkflowJob,
+    WorkflowMultiBranchProject,
+};
+use crate::nodes::ComputerSet;
+use crate::queue::Queue;
+use crate::view::{CommonView, DashboardView, ListView, MyView, NestedView};
+use crate::Jenkins;
+
+/// An endpoint of Jenkins to capture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "endpoint", rename_all = "camelCase")]
+pub enum Endpoint {
+    /// Jenkins `Home`
+    Home,
+    /// A `Job`
+    Job {
+        /// Name of the job
+        name: String,
+    },
+    /// The last `Build` of a job
+    LastBuild {
+        /// Name of the job
+        job_name: String,
+    },
+    /// A `View`
+    View {
+        /// Name of the view
+        name: String,
+    },
+    /// The `Queue`
+    Queue,
+    /// The `ComputerSet`
+    Computers,
+}
+
+impl Endpoint {
+    fn path(&self) -> Path<'_> {
+        match *self {
+            Endpoint::Home => Path::Home,
+            Endpoint::Job { ref name } => Path::Job {
+                name: Name::Name(name),
+                configuration: None,
+            },
+            Endpoint::LastBuild { ref job_name } => Path::Build {
+                job_name: Name::Name(job_name),
+                number: BuildNumber::LastBuild,
+                configuration: None,
+            },
+            Endpoint::View { ref name } => Path::View {
+                name: Name::Name(name),
+            },
+            Endpoint::Queue => Path::Queue,
+            Endpoint::Computers => Path::Computers,
+        }
+    }
+
+    /// Parse `json` into the type of this endpoint, and serialize it back
+    fn round_trip(&self, json: &serde_json::Value) -> Result<serde_json::Value, serde_json::Error> {
+        fn round_trip<T>(json: &serde_json::Value) -> Result<serde_json::Value, serde_json::Error>
+        where
+            for<'de> T: Deserialize<'de> + Serialize,
+        {
+            serde_json::to_value(serde_json::from_value::<T>(json.clone())?)
+        }
+
+        match *self {
+            Endpoint::Home => round_trip::<Home>(json),
+            Endpoint::Job { .. } => round_trip::<CommonJob>(json),
+            Endpoint::LastBuild { .. } => round_trip::<CommonBuild>(json),
+            Endpoint::View { .. } => round_trip::<CommonView>(json),
+            Endpoint::Queue => round_trip::<Queue>(json),
+            Endpoint::Computers => round_trip::<ComputerSet>(json),
+        }
+    }
+}
+
+impl Endpoint {
+    /// Parse `json` into the type registered for `class` among the specializations of the type
+    /// of this endpoint, and return the paths of the fields it read. `None` if no type is
+    /// registered for `class`, or if this endpoint has no specializations
+    fn read_as_class(
+        &self,
+        class: &str,
+        json: &serde_json::Value,
+    ) -> Option<Result<BTreeSet<String>, serde_json::Error>> {
+        macro_rules! read_as_one_of {
+            ($($variant:ty),*) => {{
+                $(
+                    if class == <$variant>::with_class() {
+                        return Some(read_fields::<$variant>(json));
+                    }
+                )*
+                None
+            }};
+        }
+
+        match *self {
+            Endpoint::Job { .. } => read_as_one_of!(
+                FreeStyleProject,
+                WorkflowJob,
+                MatrixProject,
+                MatrixConfiguration,
+                MavenModuleSet,
+                MavenModule,
+                ExternalJob,
+                MultiJobProject,
+                BuildFlowJob,
+                Folder,
+                OrganizationFolder,
+                WorkflowMultiBranchProject
+            ),
+            Endpoint::LastBuild { .. } => read_as_one_of!(
+                FreeStyleBuild,
+                WorkflowRun,
+                MatrixBuild,
+                MatrixRun,
+                MavenModuleSetBuild,
+                MavenBuild,
+                MultiJobBuild,
+                BuildFlowRun
+            ),
+            Endpoint::View { .. } => read_as_one_of!(ListView, NestedView, MyView, DashboardView),
+            Endpoint::Home | Endpoint::Queue | Endpoint::Computers => None,
+        }
+    }
+
+    /// Can the JSON of this endpoint be read as a specialization chosen from its `_class`. The
+    /// common types of these endpoints keep every field they don't know, so fields they drop
+    /// are only found by reading the specialization
+    fn is_specialized(&self) -> bool {
+        match *self {
+            Endpoint::Job { .. } | Endpoint::LastBuild { .. } | Endpoint::View { .. } => true,
+            Endpoint::Home | Endpoint::Queue | Endpoint::Computers => false,
+        }
+    }
+}
+
+/// JSON captured for an `Endpoint`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Endpoint captured
+    pub endpoint: Endpoint,
+    /// JSON served by Jenkins
+    pub json: serde_json::Value,
+}
+
+/// A set of `Fixture` captured from a Jenkins instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureSet {
+    /// Version of Jenkins, from the `X-Jenkins` header
+    pub jenkins_version: Option<String>,
+    /// Fixtures captured
+    pub fixtures: Vec<Fixture>,
+}
+
+/// Capture the JSON of each endpoint of `spec`
+pub fn capture(jenkins_client: &Jenkins, spec: &[Endpoint]) -> Result<FixtureSet, Error> {
+    let fixtures = spec
+        .iter()
+        .map(|endpoint| {
+            Ok(Fixture {
+                endpoint: endpoint.clone(),
+                json: jenkins_client.get(&endpoint.path())?.json()?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(FixtureSet {
+        jenkins_version: jenkins_client.export_session().version,
+        fixtures,
+    })
+}
+
+/// How the JSON of an `Endpoint` deserialized
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndpointReport {
+    /// Endpoint checked
+    pub endpoint: Endpoint,
+    /// Error if the JSON failed to deserialize
+    pub error: Option<String>,
+    /// Fields served by Jenkins that were lost while deserializing, as paths like
+    /// `actions[].causes[].shortDescription`
+    pub dropped_fields: Vec<String>,
+    /// Fields missing from the JSON that were filled with a default value
+    pub defaulted_fields: Vec<String>,
+    /// `_class` of the JSON when no type of this crate is registered for it, so it could only
+    /// be read as the common type of the endpoint
+    #[serde(default)]
+    pub unknown_classes: Vec<String>,
+}
+
+/// Report of `FixtureSet::verify_deserializes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationReport {
+    /// Version of Jenkins the fixtures were captured from
+    pub jenkins_version: Option<String>,
+    /// Report for each fixture
+    pub endpoints: Vec<EndpointReport>,
+}
+
+impl VerificationReport {
+    /// Did every fixture deserialize into a registered type without losing or defaulting
+    /// fields
+    pub fn is_clean(&self) -> bool {
+        self.endpoints.iter().all(|endpoint| {
+            endpoint.error.is_none()
+                && endpoint.dropped_fields.is_empty()
+                && endpoint.defaulted_fields.is_empty()
+                && endpoint.unknown_classes.is_empty()
+        })
+    }
+}
+
+impl FixtureSet {
+    /// Deserialize each fixture into the type of this crate for its endpoint, and report what
+    /// was lost or defaulted on the way. Jobs, builds and views are read as the type registered
+    /// for their `_class`
+    pub fn verify_deserializes(&self) -> VerificationReport {
+        VerificationReport {
+            jenkins_version: self.jenkins_version.clone(),
+            endpoints: self.fixtures.iter().map(Fixture::verify).collect(),
+        }
+    }
+}
+
+impl Fixture {
+    fn verify(&self) -> EndpointReport {
+        let mut report = EndpointReport {
+            endpoint: self.endpoint.clone(),
+            error: None,
+            dropped_fields: vec![],
+            defaulted_fields: vec![],
+            unknown_classes: vec![],
+        };
+        let parsed = match self.endpoint.round_trip(&self.json) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                report.error = Some(error.to_string());
+                return report;
+            }
+        };
+        let served = field_paths(&self.json);
+        report.defaulted_fields = field_paths(&parsed).difference(&served).cloned().collect();
+
+        let kept = if self.endpoint.is_specialized() {
+            let class = self
+                .json
+                .get("_class")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("");
+            match self.endpoint.read_as_class(class, &self.json) {
+                Some(Ok(mut read)) => {
+                    let _ = read.insert("_class".to_string());
+                    read
+                }
+                Some(Err(error)) => {
+                    report.error = Some(error.to_string());
+                    return report;
+                }
+                None => {
+                    report.unknown_classes.push(class.to_string());
+                    served.clone()
+                }
+            }
+        } else {
+            field_paths(&parsed)
+        };
+        report.dropped_fields = served.difference(&kept).cloned().collect();
+        report
+    }
+}
+
+/// Deserialize `json` into `T`, and return the paths of the fields `T` read, formatted as by
+/// `field_paths`
+fn read_fields<T>(json: &serde_json::Value) -> Result<BTreeSet<String>, serde_json::Error>
+where
+    for<'de> T: Deserialize<'de>,
+{
+    let read = RefCell::new(BTreeSet::new());
+    let _ = T::deserialize(FieldReader {
+        json,
+        path: String::new(),
+        read: &read,
+    })?;
+    Ok(read.into_inner())
+}
+
+/// Deserializer of a JSON value recording the path of each field read. Fields the type being
+/// deserialized skips are not recorded
+struct FieldReader<'a, 'de> {
+    json: &'de serde_json::Value,
+    path: String,
+    read: &'a RefCell<BTreeSet<String>>,
+}
+
+impl<'a, 'de> FieldReader<'a, 'de> {
+    fn record(&self) {
+        if !self.path.is_empty() {
+            let _ = self.read.borrow_mut().insert(self.path.clone());
+        }
+    }
+}
+
+impl<'a, 'de> Deserializer<'de> for FieldReader<'a, 'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.record();
+        match *self.json {
+            serde_json::Value::Object(ref fields) => visitor.visit_map(FieldsReader {
+                fields: fields.iter(),
+                value: None,
+                path: self.path,
+                read: self.read,
+            }),
+            serde_json::Value::Array(ref items) => visitor.visit_seq(ItemsReader {
+                items: items.iter(),
+                path: format!("{}[]", self.path),
+                read: self.read,
+            }),
+            _ => self.json.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.json.is_null() {
+            self.record();
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.record();
+        self.json.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// Fields of a JSON object, read by a `FieldReader`
+struct FieldsReader<'a, 'de> {
+    fields: serde_json::map::Iter<'de>,
+    /// Value and path of the field whose name was just read
+    value: Option<(&'de serde_json::Value, String)>,
+    path: String,
+    read: &'a RefCell<BTreeSet<String>>,
+}
+
+impl<'a, 'de> MapAccess<'de> for FieldsReader<'a, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.fields.next() {
+            Some((name, value)) => {
+                let path = if self.path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", self.path, name)
+                };
+                self.value = Some((value, path));
+                seed.deserialize(BorrowedStrDeserializer::new(name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (json, path) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before its key"))?;
+        seed.deserialize(FieldReader {
+            json,
+            path,
+            read: self.read,
+        })
+    }
+}
+
+/// Items of a JSON array, read by a `FieldReader`
+struct ItemsReader<'a, 'de> {
+    items: std::slice::Iter<'de, serde_json::Value>,
+    path: String,
+    read: &'a RefCell<BTreeSet<String>>,
+}
+
+impl<'a, 'de> SeqAccess<'de> for ItemsReader<'a, 'de> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.items.next() {
+            Some(json) => seed
+                .deserialize(FieldReader {
+                    json,
+                    path: self.path.clone(),
+                    read: self.read,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Paths to every field of `json`, the items of an array being merged under `[]`
+fn field_paths(json: &serde_json::Value) -> BTreeSet<String> {
+    fn walk(json: &serde_json::Value, prefix: &str, paths: &mut BTreeSet<String>) {
+        match *json {
+            serde_json::Value::Object(ref fields) => {
+                for (name, value) in fields {
+                    let path = if prefix.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", prefix, name)
+                    };
+                    let _ = paths.insert(path.clone());
+                    walk(value, &path, paths);
+                }
+            }
+            serde_json::Value::Array(ref items) => {
+                for item in items {
+                    walk(item, &format!("{}[]", prefix), paths);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let mut paths = BTreeSet::new();
+    walk(json, "", &mut paths);
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static QUEUE: &str = r#"{"_class":"hudson.model.Queue","items":[{
+        "_class":"hudson.model.Queue$WaitingItem","actions":[],"blocked":false,"buildable":false,
+        "id":3,"inQueueSince":1551000000000,"params":"","stuck":false,
+        "task":{"_class":"hudson.model.FreeStyleProject","name":"job","url":"http://none/job/job/"},
+        "url":"queue/item/3/","why":"In the quiet period","timestampOfNewField":12}]}"#;
+
+    #[test]
+    fn can_capture_and_verify_fixtures() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/fixtures", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _queue = mockito::mock("GET", "/fixtures/queue/api/json?depth=1")
+            .with_header("X-Jenkins", "2.164")
+            .with_body(QUEUE)
+            .create();
+        let _view = mockito::mock("GET", "/fixtures/view/broken/api/json?depth=1")
+            .with_body(r#"{"_class":"hudson.model.ListView","name":"broken"}"#)
+            .create();
+
+        let fixtures = capture(
+            &jenkins_client,
+            &[
+                Endpoint::Queue,
+                Endpoint::View {
+                    name: "broken".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(fixtures.jenkins_version, Some("2.164".to_string()));
+
+        let saved = serde_json::to_string(&fixtures).unwrap();
+        let report = serde_json::from_str::<FixtureSet>(&saved)
+            .unwrap()
+            .verify_deserializes();
+        assert!(!report.is_clean());
+
+        let queue = &report.endpoints[0];
+        assert_eq!(queue.error, None);
+        assert_eq!(
+            queue.dropped_fields,
+            vec!["_class", "items[].timestampOfNewField"]
+        );
+        assert!(queue
+            .defaulted_fields
+            .contains(&"items[].executable".to_string()));
+
+        let view = &report.endpoints[1];
+        assert!(view.error.as_ref().unwrap().contains("missing field"));
+    }
+
+    static JOB: &str = r#"{"_class":"hudson.model.FreeStyleProject","name":"job",
+        "displayName":"job","fullDisplayName":"job","fullName":"job","displayNameOrNull":null,
+        "url":"http://none/job/job/","color":"blue","buildable":true,"keepDependencies":false,
+        "nextBuildNumber":2,"inQueue":false,"actions":[{"_class":"hudson.model.CauseAction"}],
+        "description":"","concurrentBuild":false,"scm":{"_class":"hudson.scm.NullSCM"},
+        "upstreamProjects":[],"downstreamProjects":[],"labelExpression":null,"builds":[],
+        "healthReport":[],"property":[],"queueItem":null,"lastBuild":null,"firstBuild":null,
+        "lastStableBuild":null,"lastSuccessfulBuild":null,"lastUnstableBuild":null,
+        "lastUnsuccessfulBuild":null,"lastFailedBuild":null,"lastCompletedBuild":null,
+        "resumeBlocked":{"since":12}}"#;
+
+    #[test]
+    fn reports_fields_dropped_by_job_class_and_unknown_classes() {
+        let job: serde_json::Value = serde_json::from_str(JOB).unwrap();
+        let mut unknown = job.clone();
+        unknown["_class"] = "com.example.FancyProject".into();
+        let fixtures = FixtureSet {
+            jenkins_version: None,
+            fixtures: vec![
+                Fixture {
+                    endpoint: Endpoint::Job {
+                        name: "job".to_string(),
+                    },
+                    json: job,
+                },
+                Fixture {
+                    endpoint: Endpoint::Job {
+                        name: "job".to_string(),
+                    },
+                    json: unknown,
+                },
+            ],
+        };
+
+        let report = fixtures.verify_deserializes();
+        assert!(!report.is_clean());
+
+        let known = &report.endpoints[0];
+        assert_eq!(known.error, None);
+        assert_eq!(
+            known.dropped_fields,
+            vec!["resumeBlocked", "resumeBlocked.since"]
+        );
+        assert!(known.unknown_classes.is_empty());
+
+        let unknown = &report.endpoints[1];
+        assert_eq!(unknown.error, None);
+        assert_eq!(unknown.unknown_classes, vec!["com.example.FancyProject"]);
+    }
+}
@@ -1,11 +1,474 @@
 //! helper traits and macros
 
+use std::iter::FromIterator;
+use std::ops::{Add, Sub};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use failure::Error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
 /// Trait to implement to match the _class provided by Jenkins
 pub trait Class {
     /// Should reply the _class provided by Jenkins for a type
     fn with_class() -> &'static str;
 }
 
+/// Match a `name` against a `pattern`, where `*` matches any sequence of characters and `?`
+/// matches any single character
+pub(crate) fn wildcard_matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => {
+            (0..=name.len()).any(|skipped| wildcard_matches(rest, &name[skipped..]))
+        }
+        Some(('?', rest)) => !name.is_empty() && wildcard_matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && wildcard_matches(rest, &name[1..]),
+    }
+}
+
+/// Filter on the names of jobs or views
+///
+/// Matching is case sensitive. A glob must match the whole name, while a regex matches if it
+/// matches any part of the name, unless anchored with `^` and `$`.
+#[derive(Debug, Clone)]
+pub enum NameFilter {
+    /// Glob, where `*` matches any sequence of characters and `?` matches any single character
+    Glob(String),
+    /// Regular expression
+    Regex(Regex),
+}
+
+impl NameFilter {
+    /// Create a filter from a glob
+    pub fn glob(glob: &str) -> Self {
+        NameFilter::Glob(glob.to_string())
+    }
+
+    /// Create a filter from a regex
+    ///
+    /// # Errors
+    /// This methods will return an error if `regex` is invalid
+    pub fn regex(regex: &str) -> Result<Self, Error> {
+        Ok(NameFilter::Regex(Regex::new(regex)?))
+    }
+
+    /// Check if a name matches the filter
+    pub fn matches(&self, name: &str) -> bool {
+        match *self {
+            NameFilter::Glob(ref glob) => wildcard_matches(
+                &glob.chars().collect::<Vec<_>>(),
+                &name.chars().collect::<Vec<_>>(),
+            ),
+            NameFilter::Regex(ref regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// A duration provided by Jenkins, in milliseconds. Jenkins uses negative values, usually `-1`,
+/// when the duration is unknown, like the estimated duration of a job that never succeeded.
+/// Some APIs, like Blue Ocean, send `null` instead
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "Option<i64>", into = "i64")]
+pub enum MaybeDuration {
+    /// A known duration
+    Known(Duration),
+    /// An unknown duration, sent as a negative value by Jenkins
+    #[default]
+    Unknown,
+}
+
+impl MaybeDuration {
+    /// Build a duration from a raw number of milliseconds, negative if unknown
+    pub fn from_millis(millis: i64) -> Self {
+        if millis < 0 {
+            MaybeDuration::Unknown
+        } else {
+            MaybeDuration::Known(Duration::from_millis(millis as u64))
+        }
+    }
+
+    /// Raw number of milliseconds, as sent by Jenkins: `-1` if unknown
+    pub fn millis(self) -> i64 {
+        match self {
+            MaybeDuration::Known(duration) => duration.as_millis() as i64,
+            MaybeDuration::Unknown => -1,
+        }
+    }
+
+    /// Is this duration known
+    pub fn is_known(self) -> bool {
+        self != MaybeDuration::Unknown
+    }
+
+    /// Get this duration as a `Duration`, `None` if it is unknown
+    pub fn as_duration(self) -> Option<Duration> {
+        match self {
+            MaybeDuration::Known(duration) => Some(duration),
+            MaybeDuration::Unknown => None,
+        }
+    }
+}
+
+impl From<i64> for MaybeDuration {
+    fn from(millis: i64) -> Self {
+        MaybeDuration::from_millis(millis)
+    }
+}
+
+impl From<Option<i64>> for MaybeDuration {
+    fn from(millis: Option<i64>) -> Self {
+        millis.map_or(MaybeDuration::Unknown, MaybeDuration::from_millis)
+    }
+}
+
+impl From<MaybeDuration> for i64 {
+    fn from(duration: MaybeDuration) -> Self {
+        duration.millis()
+    }
+}
+
+impl From<Duration> for MaybeDuration {
+    fn from(duration: Duration) -> Self {
+        MaybeDuration::Known(duration)
+    }
+}
+
+/// Durations are only ordered when both are known, an unknown duration being only equal to
+/// another unknown one
+impl PartialOrd for MaybeDuration {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (MaybeDuration::Known(duration), MaybeDuration::Known(other)) => {
+                Some(duration.cmp(other))
+            }
+            (MaybeDuration::Unknown, MaybeDuration::Unknown) => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl Add for MaybeDuration {
+    type Output = MaybeDuration;
+
+    /// Add two durations, the result being unknown if any of them is
+    fn add(self, other: MaybeDuration) -> MaybeDuration {
+        match (self, other) {
+            (MaybeDuration::Known(duration), MaybeDuration::Known(other)) => {
+                MaybeDuration::Known(duration + other)
+            }
+            _ => MaybeDuration::Unknown,
+        }
+    }
+}
+
+impl Sub for MaybeDuration {
+    type Output = MaybeDuration;
+
+    /// Subtract two durations, the result being unknown if any of them is, and saturating at
+    /// zero
+    fn sub(self, other: MaybeDuration) -> MaybeDuration {
+        match (self, other) {
+            (MaybeDuration::Known(duration), MaybeDuration::Known(other)) => {
+                MaybeDuration::Known(duration.checked_sub(other).unwrap_or_default())
+            }
+            _ => MaybeDuration::Unknown,
+        }
+    }
+}
+
+/// A timestamp provided by Jenkins, in milliseconds since epoch. Jenkins uses `0` when the time
+/// is unknown, like the time a build started before it left the queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum MaybeTimestamp {
+    /// A known timestamp, in milliseconds since epoch
+    Known(u64),
+    /// An unknown timestamp, sent as `0` by Jenkins
+    #[default]
+    Unknown,
+}
+
+impl MaybeTimestamp {
+    /// Build a timestamp from a raw number of milliseconds since epoch, `0` if unknown
+    pub fn from_millis(millis: u64) -> Self {
+        if millis == 0 {
+            MaybeTimestamp::Unknown
+        } else {
+            MaybeTimestamp::Known(millis)
+        }
+    }
+
+    /// Raw number of milliseconds since epoch, as sent by Jenkins: `0` if unknown
+    pub fn millis(self) -> u64 {
+        self.known_millis().unwrap_or(0)
+    }
+
+    /// Number of milliseconds since epoch, `None` if the timestamp is unknown
+    pub fn known_millis(self) -> Option<u64> {
+        match self {
+            MaybeTimestamp::Known(millis) => Some(millis),
+            MaybeTimestamp::Unknown => None,
+        }
+    }
+
+    /// Is this timestamp known
+    pub fn is_known(self) -> bool {
+        self != MaybeTimestamp::Unknown
+    }
+
+    /// Get this timestamp as a `SystemTime`, `None` if it is unknown
+    pub fn as_system_time(self) -> Option<SystemTime> {
+        self.known_millis()
+            .map(|millis| UNIX_EPOCH + Duration::from_millis(millis))
+    }
+
+    /// Time elapsed since this timestamp at `now`, zero if it is in the future, unknown if the
+    /// timestamp is unknown
+    pub fn elapsed_at(self, now: SystemTime) -> MaybeDuration {
+        match self.as_system_time() {
+            Some(time) => MaybeDuration::Known(now.duration_since(time).unwrap_or_default()),
+            None => MaybeDuration::Unknown,
+        }
+    }
+}
+
+impl From<u64> for MaybeTimestamp {
+    fn from(millis: u64) -> Self {
+        MaybeTimestamp::from_millis(millis)
+    }
+}
+
+impl From<MaybeTimestamp> for u64 {
+    fn from(timestamp: MaybeTimestamp) -> Self {
+        timestamp.millis()
+    }
+}
+
+/// Timestamps are only ordered when both are known, an unknown timestamp being only equal to
+/// another unknown one
+impl PartialOrd for MaybeTimestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (MaybeTimestamp::Known(timestamp), MaybeTimestamp::Known(other)) => {
+                Some(timestamp.cmp(other))
+            }
+            (MaybeTimestamp::Unknown, MaybeTimestamp::Unknown) => Some(std::cmp::Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl Add<MaybeDuration> for MaybeTimestamp {
+    type Output = MaybeTimestamp;
+
+    /// Time after a duration, unknown if the timestamp or the duration is
+    fn add(self, duration: MaybeDuration) -> MaybeTimestamp {
+        match (self, duration) {
+            (MaybeTimestamp::Known(millis), MaybeDuration::Known(duration)) => {
+                MaybeTimestamp::Known(millis + duration.as_millis() as u64)
+            }
+            _ => MaybeTimestamp::Unknown,
+        }
+    }
+}
+
+impl Sub<MaybeDuration> for MaybeTimestamp {
+    type Output = MaybeTimestamp;
+
+    /// Time before a duration, unknown if the timestamp or the duration is
+    fn sub(self, duration: MaybeDuration) -> MaybeTimestamp {
+        match (self, duration) {
+            (MaybeTimestamp::Known(millis), MaybeDuration::Known(duration)) => {
+                MaybeTimestamp::from_millis(millis.saturating_sub(duration.as_millis() as u64))
+            }
+            _ => MaybeTimestamp::Unknown,
+        }
+    }
+}
+
+impl Sub for MaybeTimestamp {
+    type Output = MaybeDuration;
+
+    /// Duration between two timestamps, unknown if any of them is, and saturating at zero
+    fn sub(self, other: MaybeTimestamp) -> MaybeDuration {
+        match (self, other) {
+            (MaybeTimestamp::Known(millis), MaybeTimestamp::Known(other)) => {
+                MaybeDuration::Known(Duration::from_millis(millis.saturating_sub(other)))
+            }
+            _ => MaybeDuration::Unknown,
+        }
+    }
+}
+
+/// Statistics over durations sent by Jenkins. Unknown durations are skipped, and counted in
+/// `skipped`, so they never weigh on the total or the average
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DurationStats {
+    /// Number of known durations
+    pub count: usize,
+    /// Number of unknown durations skipped
+    pub skipped: usize,
+    /// Sum of the known durations
+    pub total: Duration,
+    /// Shortest known duration
+    pub min: Option<Duration>,
+    /// Longest known duration
+    pub max: Option<Duration>,
+}
+
+impl DurationStats {
+    /// Average of the known durations, `None` if there are none
+    pub fn average(&self) -> Option<Duration> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.total / self.count as u32)
+        }
+    }
+}
+
+impl FromIterator<MaybeDuration> for DurationStats {
+    fn from_iter<I: IntoIterator<Item = MaybeDuration>>(durations: I) -> Self {
+        let mut stats = DurationStats::default();
+        for duration in durations {
+            match duration {
+                MaybeDuration::Known(duration) => {
+                    stats.count += 1;
+                    stats.total += duration;
+                    stats.min = Some(stats.min.map_or(duration, |min| min.min(duration)));
+                    stats.max = Some(stats.max.map_or(duration, |max| max.max(duration)));
+                }
+                MaybeDuration::Unknown => stats.skipped += 1,
+            }
+        }
+        stats
+    }
+}
+
+/// Is an error a response from Jenkins with a 404 status
+pub(crate) fn is_not_found(error: &Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        == Some(reqwest::StatusCode::NOT_FOUND)
+}
+
+/// Apply `f` to each item, running at most `concurrency` calls at the same time, and return
+/// the results in the order of the items
+pub(crate) fn map_concurrently<T, R, F>(items: &[T], concurrency: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..concurrency.max(1).min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        match items.get(index) {
+                            Some(item) => results.push((index, f(item))),
+                            None => break,
+                        }
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("a worker panicked"))
+            .collect()
+    });
+    results.sort_by_key(|result| result.0);
+    results.into_iter().map(|result| result.1).collect()
+}
+
+/// Convert HTML to plain text: tags are removed, line breaks and paragraphs are replaced with
+/// new lines, contents of scripts and styles are dropped and entities are decoded
+pub(crate) fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    let mut skip_until: Option<&str> = None;
+    while let Some(start) = rest.find(['<', '&']) {
+        if skip_until.is_none() {
+            text.push_str(&rest[..start]);
+        }
+        rest = &rest[start..];
+        if rest.starts_with('&') {
+            let entity_end = rest
+                .find(';')
+                .filter(|end| *end <= 10)
+                .and_then(|end| decode_entity(&rest[1..end]).map(|c| (end, c)));
+            match entity_end {
+                Some((end, c)) => {
+                    if skip_until.is_none() {
+                        text.push(c);
+                    }
+                    rest = &rest[(end + 1)..];
+                }
+                None => {
+                    if skip_until.is_none() {
+                        text.push('&');
+                    }
+                    rest = &rest[1..];
+                }
+            }
+            continue;
+        }
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = rest[1..end].trim().to_lowercase();
+        let name: String = tag
+            .trim_end_matches('/')
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '/')
+            .collect();
+        rest = &rest[(end + 1)..];
+        match skip_until {
+            Some(closing) if name == closing => skip_until = None,
+            Some(_) => (),
+            None => match name.as_str() {
+                "script" => skip_until = Some("/script"),
+                "style" => skip_until = Some("/style"),
+                "br" | "/p" | "/div" | "/li" | "/tr" | "/h1" | "/h2" | "/h3" | "/h4" | "/h5"
+                | "/h6" => text.push('\n'),
+                _ => (),
+            },
+        }
+    }
+    if skip_until.is_none() {
+        text.push_str(rest);
+    }
+    text.trim_end().to_string()
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+            u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(std::char::from_u32)
+        }
+        _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(std::char::from_u32),
+        _ => None,
+    }
+}
+
 macro_rules! register_class {
     ($class:expr => $variant:ty) => {
         impl Class for $variant {
@@ -41,3 +504,173 @@ macro_rules! specialize {
         }
     };
 }
+
+/// Stub HTTP server, for tests needing different responses to the same request
+#[cfg(test)]
+pub(crate) mod test_server {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve each response to a new connection, in order, and return the server url
+    pub(crate) fn serve_in_order(responses: Vec<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let _ = thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    pub(crate) fn response(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n{}\r\n{}",
+            status,
+            body.len(),
+            headers,
+            body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{DurationStats, MaybeDuration, MaybeTimestamp};
+
+    #[test]
+    fn negative_durations_are_unknown() {
+        let unknown: MaybeDuration = serde_json::from_str("-1").unwrap();
+        assert_eq!(unknown, MaybeDuration::Unknown);
+        assert_eq!(unknown.millis(), -1);
+        assert!(!unknown.is_known());
+        assert_eq!(unknown.as_duration(), None);
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), "-1");
+    }
+
+    #[test]
+    fn null_durations_are_unknown() {
+        let unknown: MaybeDuration = serde_json::from_str("null").unwrap();
+        assert_eq!(unknown, MaybeDuration::Unknown);
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), "-1");
+    }
+
+    #[test]
+    fn zero_duration_is_known() {
+        let zero: MaybeDuration = serde_json::from_str("0").unwrap();
+        assert_eq!(zero.as_duration(), Some(Duration::from_millis(0)));
+    }
+
+    #[test]
+    fn durations_serialize_as_millis() {
+        let duration = MaybeDuration::from(Duration::from_secs(2));
+        assert_eq!(duration, MaybeDuration::from_millis(2000));
+        assert_eq!(serde_json::to_string(&duration).unwrap(), "2000");
+        assert_eq!(duration.as_duration(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn arithmetic_keeps_unknown_durations_unknown() {
+        let millis = MaybeDuration::from_millis;
+        assert_eq!(millis(1500) + millis(500), millis(2000));
+        assert_eq!(millis(500) - millis(1500), millis(0));
+        assert!(!(millis(-1) + millis(500)).is_known());
+        assert!(!(millis(500) - millis(-1)).is_known());
+        assert!(millis(500) < millis(1500));
+        assert_eq!(millis(-1).partial_cmp(&millis(500)), None);
+    }
+
+    #[test]
+    fn unknowns_compare_equal() {
+        let duration = MaybeDuration::Unknown;
+        assert_eq!(
+            duration.partial_cmp(&MaybeDuration::Unknown),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert!(duration <= MaybeDuration::Unknown);
+        assert!(!(duration < MaybeDuration::Unknown));
+
+        let timestamp = MaybeTimestamp::Unknown;
+        assert_eq!(
+            timestamp.partial_cmp(&MaybeTimestamp::Unknown),
+            Some(std::cmp::Ordering::Equal)
+        );
+        assert!(timestamp >= MaybeTimestamp::Unknown);
+        assert_eq!(
+            timestamp.partial_cmp(&MaybeTimestamp::from_millis(1000)),
+            None
+        );
+    }
+
+    #[test]
+    fn zero_timestamps_are_unknown() {
+        let unknown: MaybeTimestamp = serde_json::from_str("0").unwrap();
+        assert_eq!(unknown, MaybeTimestamp::Unknown);
+        assert_eq!(unknown.millis(), 0);
+        assert_eq!(unknown.as_system_time(), None);
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), "0");
+
+        let known: MaybeTimestamp = serde_json::from_str("1500000000000").unwrap();
+        assert_eq!(known.known_millis(), Some(1_500_000_000_000));
+        assert_eq!(
+            known.as_system_time(),
+            Some(UNIX_EPOCH + Duration::from_millis(1_500_000_000_000))
+        );
+    }
+
+    #[test]
+    fn timestamp_arithmetic_keeps_unknowns_unknown() {
+        let start = MaybeTimestamp::from_millis(1000);
+        assert_eq!(
+            start + MaybeDuration::from_millis(500),
+            MaybeTimestamp::from_millis(1500)
+        );
+        assert_eq!(
+            MaybeTimestamp::from_millis(1500) - start,
+            MaybeDuration::from_millis(500)
+        );
+        assert_eq!(
+            start - MaybeDuration::from_millis(400),
+            MaybeTimestamp::from_millis(600)
+        );
+        assert_eq!(start + MaybeDuration::Unknown, MaybeTimestamp::Unknown);
+        assert_eq!(start - MaybeDuration::Unknown, MaybeTimestamp::Unknown);
+        assert_eq!(start - MaybeTimestamp::Unknown, MaybeDuration::Unknown);
+        assert_eq!(
+            MaybeTimestamp::Unknown.elapsed_at(UNIX_EPOCH + Duration::from_secs(10)),
+            MaybeDuration::Unknown
+        );
+        assert_eq!(
+            start.elapsed_at(UNIX_EPOCH + Duration::from_secs(10)),
+            MaybeDuration::from_millis(9000)
+        );
+    }
+
+    #[test]
+    fn unknown_durations_never_weigh_on_stats() {
+        let stats: DurationStats = [1000, -1, 3000, -1, -5]
+            .iter()
+            .map(|millis| MaybeDuration::from_millis(*millis))
+            .collect();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.skipped, 3);
+        assert_eq!(stats.total, Duration::from_millis(4000));
+        assert_eq!(stats.average(), Some(Duration::from_millis(2000)));
+        assert_eq!(stats.min, Some(Duration::from_millis(1000)));
+        assert_eq!(stats.max, Some(Duration::from_millis(3000)));
+
+        let only_unknown: DurationStats = vec![MaybeDuration::Unknown; 3].into_iter().collect();
+        assert_eq!(only_unknown.skipped, 3);
+        assert_eq!(only_unknown.average(), None);
+        assert_eq!(only_unknown.min, None);
+    }
+}
@@ -1,7 +1,15 @@
 //! Jenkins Home, describing state of the master
 
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
 use failure::Error;
-use serde::Deserialize;
+use log::debug;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::client;
 
 use crate::client_internals::Path;
 use crate::job::ShortJob;
@@ -9,7 +17,7 @@ use crate::view::ShortView;
 use crate::Jenkins;
 
 /// Describe how Jenkins allocates jobs to agents
-#[derive(Debug, Deserialize, Clone, Copy)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum Mode {
     /// Any job can be started on this node
@@ -19,7 +27,7 @@ pub enum Mode {
 }
 
 /// Index of Jenkins, with details about the master, a list of `Job` and a list of `View`
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Home {
     /// Mode of the node for job selections
@@ -46,9 +54,545 @@ pub struct Home {
     pub views: Vec<ShortView>,
 }
 
+/// Information about a Jenkins instance that became ready
+#[derive(Debug, Clone)]
+pub struct ReadyInfo {
+    /// Time spent waiting for Jenkins to be ready
+    pub waited: Duration,
+    /// Version of Jenkins, from the `X-Jenkins` header
+    pub version: Option<String>,
+}
+
+/// Only the system message of Jenkins
+#[derive(Debug, Deserialize)]
+struct SystemMessage {
+    description: Option<String>,
+}
+
+/// Quote `text` as a single-quoted Groovy string, in which `$` is not interpolated
+fn groovy_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('\'');
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Start of the banner served with a `503` by Jenkins while it is starting or restarting
+const STARTING_BANNER: &str = "Please wait while Jenkins is";
+
 impl Jenkins {
     /// Get Jenkins `Home`
     pub fn get_home(&self) -> Result<Home, Error> {
         Ok(self.get(&Path::Home)?.json()?)
     }
+
+    /// Get the system message of Jenkins, shown on its dashboard. It is empty if none is set
+    pub fn get_system_message(&self) -> Result<String, Error> {
+        let message: SystemMessage = self
+            .get_with_params(&Path::Home, [("tree", "description")])?
+            .json()?;
+        Ok(message.description.unwrap_or_default())
+    }
+
+    /// Set the system message of Jenkins, shown on its dashboard, by running a Groovy script in
+    /// the script console. Jenkins has no endpoint to set only the system message, so this needs
+    /// the permission to run scripts
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::ScriptFailed`](../client/enum.Error.html#variant.ScriptFailed) if the script
+    /// printed anything, as when it failed with an exception
+    pub fn set_system_message_with_script(&self, message: &str) -> Result<(), Error> {
+        let script = format!(
+            "jenkins.model.Jenkins.instance.setSystemMessage({})",
+            groovy_string(message)
+        );
+        let body = serde_urlencoded::to_string([("script", script)])?;
+        let output = self.post_with_body(&Path::ScriptText, body, &[])?.text()?;
+        if output.trim().is_empty() {
+            Ok(())
+        } else {
+            Err(client::Error::ScriptFailed { output }.into())
+        }
+    }
+
+    /// Put Jenkins in quiet down mode, to prepare it for a restart: builds already running
+    /// continue, but no new build is started. The `reason` is shown in the banner of Jenkins
+    pub fn quiet_down(&self, reason: Option<&str>) -> Result<(), Error> {
+        let _ = match reason {
+            Some(reason) => self.post_with_body(&Path::QuietDown, "", &[("message", reason)])?,
+            None => self.post(&Path::QuietDown)?,
+        };
+        Ok(())
+    }
+
+    /// Cancel the quiet down mode of Jenkins, so that builds start again
+    pub fn cancel_quiet_down(&self) -> Result<(), Error> {
+        let _ = self.post(&Path::CancelQuietDown)?;
+        Ok(())
+    }
+
+    /// Restart Jenkins immediately, aborting running builds
+    ///
+    /// Jenkins may close the connection before answering, or answer with a `503` and its "Please
+    /// wait while Jenkins is restarting" page, possibly after a redirect: both mean the restart
+    /// was accepted and are not reported as errors. Use `wait_until_ready` to wait for Jenkins to
+    /// be back.
+    pub fn restart(&self) -> Result<(), Error> {
+        self.post_restart(&Path::Restart)
+    }
+
+    /// Restart Jenkins once running builds are done, putting it in quiet down mode until then
+    ///
+    /// As with `restart`, a dropped connection or the restarting page are not reported as
+    /// errors.
+    pub fn safe_restart(&self) -> Result<(), Error> {
+        self.post_restart(&Path::SafeRestart)
+    }
+
+    fn post_restart(&self, path: &Path) -> Result<(), Error> {
+        match self.post_unchecked(path) {
+            Ok(mut response) => {
+                if response.status() == StatusCode::SERVICE_UNAVAILABLE
+                    && response.text()?.contains(STARTING_BANNER)
+                {
+                    debug!("Jenkins is restarting");
+                } else {
+                    let _ = response.error_for_status()?;
+                }
+                Ok(())
+            }
+            Err(error) if is_dropped_connection(&error) => {
+                debug!("connection dropped by Jenkins while restarting: {}", error);
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Wait until Jenkins is ready to serve api calls, checking every `poll`
+    ///
+    /// Jenkins is not ready while connections are refused, while requests time out or while it
+    /// serves its "Please wait while Jenkins is getting ready to work" page. It is ready once an
+    /// api call with the credentials of this client succeeds.
+    pub fn wait_until_ready(&self, timeout: Duration, poll: Duration) -> Result<ReadyInfo, Error> {
+        let start = Instant::now();
+        loop {
+            match self.get_unchecked_with_params(&Path::Home, [("tree", "mode")]) {
+                Ok(mut response) => match response.status() {
+                    StatusCode::OK => {
+                        return Ok(ReadyInfo {
+                            waited: start.elapsed(),
+                            version: response
+                                .headers()
+                                .get("X-Jenkins")
+                                .and_then(|version| version.to_str().ok())
+                                .map(|version| version.to_string()),
+                        });
+                    }
+                    StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                        return Err(client::Error::CredentialsRejected {
+                            status: response.status().as_u16(),
+                        }
+                        .into());
+                    }
+                    StatusCode::SERVICE_UNAVAILABLE
+                        if response.text()?.contains(STARTING_BANNER) =>
+                    {
+                        debug!("Jenkins is still starting");
+                    }
+                    _ => {
+                        let _ = response.error_for_status()?;
+                    }
+                },
+                Err(error) => match error.downcast::<reqwest::Error>() {
+                    Ok(error) => debug!("Jenkins is not reachable yet: {}", error),
+                    Err(error) => match error.downcast_ref::<client::Error>() {
+                        Some(client::Error::Timeout) => debug!("Jenkins did not answer in time"),
+                        _ => return Err(error),
+                    },
+                },
+            }
+
+            let waited = start.elapsed();
+            if waited >= timeout {
+                return Err(client::Error::NotReady { waited }.into());
+            }
+            thread::sleep(std::cmp::min(poll, timeout - waited));
+        }
+    }
+}
+
+/// Is an error caused by Jenkins closing an established connection, as opposed to Jenkins not
+/// being reachable at all or not answering in time
+fn is_dropped_connection(error: &Error) -> bool {
+    let error = match error.downcast_ref::<reqwest::Error>() {
+        Some(error) if error.is_http() && !error.is_timeout() => error,
+        _ => return false,
+    };
+    let mut source: Option<&(dyn std::error::Error + 'static)> = match error.get_ref() {
+        Some(source) => Some(source),
+        None => None,
+    };
+    while let Some(cause) = source {
+        if let Some(io_error) = cause.downcast_ref::<io::Error>() {
+            if io_error.kind() == io::ErrorKind::ConnectionRefused {
+                return false;
+            }
+        }
+        source = cause.source();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mockito;
+
+    use crate::helpers::test_server::{response, serve_in_order};
+
+    use super::groovy_string;
+
+    static STARTING_PAGE: &str = "<html><body>Please wait while Jenkins is getting ready to work \
+                                  ...</body></html>";
+
+    #[test]
+    fn can_wait_for_jenkins_to_start() {
+        let url = serve_in_order(vec![
+            response("503 Service Unavailable", "", STARTING_PAGE),
+            response("503 Service Unavailable", "", STARTING_PAGE),
+            response(
+                "200 OK",
+                "X-Jenkins: 2.150.1\r\n",
+                r#"{"_class":"hudson.model.Hudson","mode":"NORMAL"}"#,
+            ),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let ready = jenkins_client
+            .wait_until_ready(Duration::from_secs(5), Duration::from_millis(100))
+            .unwrap();
+
+        assert!(ready.waited >= Duration::from_millis(200));
+        assert_eq!(ready.version, Some("2.150.1".to_string()));
+    }
+
+    #[test]
+    fn should_fail_on_unexpected_unavailability() {
+        let url = serve_in_order(vec![response("503 Service Unavailable", "", "maintenance")]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let error = jenkins_client
+            .wait_until_ready(Duration::from_secs(5), Duration::from_millis(100))
+            .unwrap_err();
+
+        assert!(error.downcast_ref::<crate::client::Error>().is_none());
+    }
+
+    #[test]
+    fn should_time_out_while_jenkins_is_starting() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/never-started", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _starting = mockito::mock("GET", "/never-started/api/json?tree=mode")
+            .with_status(503)
+            .with_body(STARTING_PAGE)
+            .create();
+
+        let error = jenkins_client
+            .wait_until_ready(Duration::from_millis(200), Duration::from_millis(50))
+            .unwrap_err();
+
+        match error.downcast::<crate::client::Error>() {
+            Ok(crate::client::Error::NotReady { waited }) => {
+                assert!(waited >= Duration::from_millis(200))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_time_out_while_jenkins_is_not_reachable() {
+        let jenkins_client = crate::JenkinsBuilder::new("http://127.0.0.1:1")
+            .build()
+            .unwrap();
+
+        let error = jenkins_client
+            .wait_until_ready(Duration::from_millis(200), Duration::from_millis(50))
+            .unwrap_err();
+
+        assert!(error.downcast_ref::<crate::client::Error>().is_some());
+    }
+
+    #[test]
+    fn should_keep_waiting_when_requests_time_out() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let _ = std::thread::spawn(move || {
+            let (_hanging, _) = listener.accept().unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            stream
+                .write_all(
+                    response(
+                        "200 OK",
+                        "",
+                        r#"{"_class":"hudson.model.Hudson","mode":"NORMAL"}"#,
+                    )
+                    .as_bytes(),
+                )
+                .unwrap();
+        });
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .with_timeout(Duration::from_millis(200))
+            .build()
+            .unwrap();
+
+        let ready = jenkins_client
+            .wait_until_ready(Duration::from_secs(5), Duration::from_millis(50))
+            .unwrap();
+
+        assert!(ready.waited >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn should_fail_when_credentials_are_rejected() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/rejecting", mockito::server_url()))
+                .with_user("user", Some("wrong password"))
+                .build()
+                .unwrap();
+
+        let _rejected = mockito::mock("GET", "/rejecting/api/json?tree=mode")
+            .with_status(401)
+            .create();
+
+        let error = jenkins_client
+            .wait_until_ready(Duration::from_secs(5), Duration::from_millis(50))
+            .unwrap_err();
+
+        assert_eq!(
+            format!("{}", error),
+            "Jenkins is ready but rejected the credentials: 401"
+        );
+    }
+
+    #[test]
+    fn can_quiet_down_and_cancel() {
+        let home = |quieting_down: bool| {
+            format!(
+                r#"{{"_class":"hudson.model.Hudson","mode":"NORMAL","nodeDescription":"the master Jenkins node",
+                "nodeName":"","numExecutors":2,"description":null,"jobs":[],"quietingDown":{},
+                "slaveAgentPort":50000,"useCrumbs":false,"useSecurity":true,"views":[]}}"#,
+                quieting_down
+            )
+        };
+        let url = serve_in_order(vec![
+            response("200 OK", "", ""),
+            response("200 OK", "", &home(true)),
+            response("200 OK", "", ""),
+            response("200 OK", "", &home(false)),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        jenkins_client.quiet_down(None).unwrap();
+        assert!(jenkins_client.get_home().unwrap().quieting_down);
+        jenkins_client.cancel_quiet_down().unwrap();
+        assert!(!jenkins_client.get_home().unwrap().quieting_down);
+    }
+
+    #[test]
+    fn quiet_down_posts_to_jenkins() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/quiet", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let quiet_down = mockito::mock("POST", "/quiet/quietDown").create();
+        let with_reason =
+            mockito::mock("POST", "/quiet/quietDown?message=Upgrading+plugins").create();
+        let cancel = mockito::mock("POST", "/quiet/cancelQuietDown").create();
+
+        jenkins_client.quiet_down(None).unwrap();
+        jenkins_client
+            .quiet_down(Some("Upgrading plugins"))
+            .unwrap();
+        jenkins_client.cancel_quiet_down().unwrap();
+        quiet_down.assert();
+        with_reason.assert();
+        cancel.assert();
+    }
+
+    #[test]
+    fn restart_posts_to_jenkins() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/restarting", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let restart = mockito::mock("POST", "/restarting/restart").create();
+        let safe_restart = mockito::mock("POST", "/restarting/safeRestart").create();
+
+        jenkins_client.restart().unwrap();
+        jenkins_client.safe_restart().unwrap();
+        restart.assert();
+        safe_restart.assert();
+    }
+
+    #[test]
+    fn restart_accepts_the_restarting_page() {
+        let url = serve_in_order(vec![response(
+            "503 Service Unavailable",
+            "",
+            "<html><body>Please wait while Jenkins is restarting ...</body></html>",
+        )]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        jenkins_client.restart().unwrap();
+    }
+
+    #[test]
+    fn restart_accepts_a_dropped_connection() {
+        let url = serve_in_order(vec!["".to_string()]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        jenkins_client.safe_restart().unwrap();
+    }
+
+    #[test]
+    fn restart_fails_when_jenkins_is_not_reachable() {
+        let jenkins_client = crate::JenkinsBuilder::new("http://127.0.0.1:1")
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        assert!(jenkins_client.restart().is_err());
+    }
+
+    #[test]
+    fn restart_fails_when_refused() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/restart-forbidden", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let _forbidden = mockito::mock("POST", "/restart-forbidden/restart")
+            .with_status(403)
+            .create();
+
+        assert!(jenkins_client.restart().is_err());
+    }
+
+    #[test]
+    fn can_quote_groovy_strings() {
+        assert_eq!(groovy_string("plain"), "'plain'");
+        assert_eq!(
+            groovy_string("it's ${not} a \\ \"template\"\nbye"),
+            r#"'it\'s ${not} a \\ "template"\nbye'"#
+        );
+        assert_eq!(groovy_string("déploiement 🚀"), "'déploiement 🚀'");
+    }
+
+    #[test]
+    fn can_get_system_message() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/system-message", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _message = mockito::mock("GET", "/system-message/api/json?tree=description")
+            .with_body(r#"{"_class":"hudson.model.Hudson","description":"<b>Upgrade tonight</b>"}"#)
+            .create();
+
+        assert_eq!(
+            jenkins_client.get_system_message().unwrap(),
+            "<b>Upgrade tonight</b>"
+        );
+    }
+
+    #[test]
+    fn system_message_is_empty_when_not_set() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/no-system-message", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _message = mockito::mock("GET", "/no-system-message/api/json?tree=description")
+            .with_body(r#"{"_class":"hudson.model.Hudson","description":null}"#)
+            .create();
+
+        assert_eq!(jenkins_client.get_system_message().unwrap(), "");
+    }
+
+    #[test]
+    fn can_set_system_message_with_script() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/set-message", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let script = mockito::mock("POST", "/set-message/scriptText")
+            .match_body(mockito::Matcher::Exact(
+                serde_urlencoded::to_string([(
+                    "script",
+                    "jenkins.model.Jenkins.instance.setSystemMessage('Jenkins is \\'down\\'')",
+                )])
+                .unwrap(),
+            ))
+            .create();
+
+        jenkins_client
+            .set_system_message_with_script("Jenkins is 'down'")
+            .unwrap();
+        script.assert();
+    }
+
+    #[test]
+    fn should_fail_when_script_fails() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/failing-script", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let _script = mockito::mock("POST", "/failing-script/scriptText")
+            .with_body("groovy.lang.MissingMethodException: No signature of method")
+            .create();
+
+        match jenkins_client
+            .set_system_message_with_script("message")
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::ScriptFailed { output }) => {
+                assert!(output.starts_with("groovy.lang.MissingMethodException"))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,456 @@
+//! Abort running builds in bulk
+
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::client;
+use crate::client_internals::{Name, Path};
+use crate::helpers::{map_concurrently, MaybeTimestamp};
+use crate::Jenkins;
+
+use super::JobFilter;
+
+/// Options of a bulk abort
+#[derive(Debug, Clone, Copy)]
+pub struct AbortOptions {
+    concurrency: usize,
+    scan_limit: usize,
+    dry_run: bool,
+}
+
+impl Default for AbortOptions {
+    fn default() -> Self {
+        AbortOptions {
+            concurrency: 4,
+            scan_limit: 50,
+            dry_run: false,
+        }
+    }
+}
+
+impl AbortOptions {
+    /// Set the maximum number of requests sent to Jenkins at the same time, 4 by default
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the number of most recent builds of a job scanned for running builds, 50 by default
+    pub fn with_scan_limit(mut self, scan_limit: usize) -> Self {
+        self.scan_limit = scan_limit.max(1);
+        self
+    }
+
+    /// Only report the builds that would be aborted, without stopping them
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+}
+
+/// Outcome of aborting a build
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AbortOutcome {
+    /// The build was stopped
+    Stopped,
+    /// The build finished before it could be stopped
+    AlreadyFinished,
+    /// The build would have been stopped, but this was a dry run
+    DryRun,
+    /// Stopping the build failed
+    Failed(String),
+}
+
+/// Result of aborting a build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildAbortResult {
+    /// URL of the build
+    pub url: String,
+    /// Number of the build
+    pub number: u32,
+    /// Outcome of the abort
+    pub outcome: AbortOutcome,
+}
+
+/// Report of a bulk abort, with the outcome for each running build found
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbortReport {
+    /// Was this a dry run
+    pub dry_run: bool,
+    /// Results for each running build found
+    pub builds: Vec<BuildAbortResult>,
+}
+
+impl AbortReport {
+    /// Number of builds no longer running, stopped or finished on their own
+    pub fn stopped(&self) -> usize {
+        self.builds
+            .iter()
+            .filter(|build| {
+                matches!(
+                    build.outcome,
+                    AbortOutcome::Stopped | AbortOutcome::AlreadyFinished
+                )
+            })
+            .count()
+    }
+
+    /// Number of builds that failed to be stopped
+    pub fn failed(&self) -> usize {
+        self.builds
+            .iter()
+            .filter(|build| matches!(build.outcome, AbortOutcome::Failed(_)))
+            .count()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RunningBuild {
+    url: String,
+    number: u32,
+    #[serde(default)]
+    building: bool,
+    #[serde(default)]
+    timestamp: MaybeTimestamp,
+}
+
+#[derive(Debug, Deserialize)]
+struct JobBuilds {
+    builds: Vec<RunningBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ComputerExecutors {
+    computer: Vec<Executors>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Executors {
+    #[serde(default)]
+    executors: Vec<Executable>,
+    #[serde(default)]
+    one_off_executors: Vec<Executable>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Executable {
+    current_executable: Option<RunningBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuildingState {
+    building: bool,
+}
+
+static EXECUTABLES_TREE: &str = "computer[executors[currentExecutable[url,number,timestamp]],\
+                                 oneOffExecutors[currentExecutable[url,number,timestamp]]]";
+
+/// Name of the job of a build from its URL, with parent folders separated by `/`
+fn job_name_of_build(jenkins_client: &Jenkins, url: &str) -> Option<String> {
+    match jenkins_client.url_to_path(url) {
+        Path::Build {
+            job_name: Name::UrlEncodedName(name),
+            configuration: None,
+            ..
+        } => name
+            .split("/job/")
+            .map(|part| urlencoding::decode(part).ok())
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| parts.join("/")),
+        _ => None,
+    }
+}
+
+impl Jenkins {
+    /// Stop the running builds of the job at `job_url`
+    pub(crate) fn abort_all_running_of(
+        &self,
+        job_url: &str,
+        options: AbortOptions,
+    ) -> Result<AbortReport, Error> {
+        let path = self.url_to_path(job_url);
+        if let Path::Job { .. } = path {
+            let tree = format!("builds[number,url,building]{{0,{}}}", options.scan_limit);
+            let job: JobBuilds = self
+                .get_with_params(&path, [("tree", tree.as_str())])?
+                .json()?;
+            let running = job
+                .builds
+                .into_iter()
+                .filter(|build| build.building)
+                .collect::<Vec<_>>();
+            Ok(self.abort_builds(&running, options))
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: job_url.to_string(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into())
+        }
+    }
+
+    /// Stop every build that has been running for longer than `threshold`, found from the
+    /// executors of the computers. If a `filter` is given, only builds of top level jobs
+    /// matching it are stopped
+    pub fn abort_builds_running_longer_than(
+        &self,
+        threshold: Duration,
+        filter: Option<JobFilter>,
+        options: AbortOptions,
+    ) -> Result<AbortReport, Error> {
+        let jobs = match filter {
+            Some(ref filter) => Some(
+                self.list_jobs(filter.view.as_deref())?
+                    .into_iter()
+                    .filter(|job| filter.matches(job))
+                    .map(|job| job.name)
+                    .collect::<HashSet<_>>(),
+            ),
+            None => None,
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let threshold = threshold.as_millis() as u64;
+
+        let computers: ComputerExecutors = self
+            .get_with_params(&Path::Computers, [("tree", EXECUTABLES_TREE)])?
+            .json()?;
+        let running = computers
+            .computer
+            .into_iter()
+            .flat_map(|computer| {
+                computer
+                    .executors
+                    .into_iter()
+                    .chain(computer.one_off_executors)
+            })
+            .filter_map(|executor| executor.current_executable)
+            .filter(|build| match build.timestamp.known_millis() {
+                Some(timestamp) => now.saturating_sub(timestamp) > threshold,
+                None => false,
+            })
+            .filter(|build| match jobs {
+                Some(ref jobs) => match job_name_of_build(self, &build.url) {
+                    Some(name) => jobs.contains(&name),
+                    None => false,
+                },
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        Ok(self.abort_builds(&running, options))
+    }
+
+    fn abort_builds(&self, builds: &[RunningBuild], options: AbortOptions) -> AbortReport {
+        let results = map_concurrently(builds, options.concurrency, |build| {
+            let outcome = if options.dry_run {
+                AbortOutcome::DryRun
+            } else {
+                self.abort_build(&build.url)
+            };
+            BuildAbortResult {
+                url: build.url.clone(),
+                number: build.number,
+                outcome,
+            }
+        });
+        AbortReport {
+            dry_run: options.dry_run,
+            builds: results,
+        }
+    }
+
+    fn abort_build(&self, url: &str) -> AbortOutcome {
+        let (job_name, number, configuration) = match self.url_to_path(url) {
+            Path::Build {
+                job_name,
+                number,
+                configuration,
+            } => (job_name, number, configuration),
+            _ => return AbortOutcome::Failed(format!("invalid build url {}", url)),
+        };
+        let stop = self.post(&Path::StopBuild {
+            job_name: job_name.clone(),
+            number: number.clone(),
+            configuration: configuration.clone(),
+        });
+        match stop {
+            Ok(_) => AbortOutcome::Stopped,
+            Err(error) => {
+                let state = self
+                    .get_with_params(
+                        &Path::Build {
+                            job_name,
+                            number,
+                            configuration,
+                        },
+                        [("tree", "building")],
+                    )
+                    .and_then(|mut response| Ok(response.json::<BuildingState>()?));
+                match state {
+                    Ok(BuildingState { building: false }) => AbortOutcome::AlreadyFinished,
+                    _ => AbortOutcome::Failed(error.to_string()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jenkins_client(base: &str) -> Jenkins {
+        crate::JenkinsBuilder::new(&format!("{}/{}", mockito::server_url(), base))
+            .disable_csrf()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn can_abort_all_running_builds() {
+        let jenkins_client = jenkins_client("abort-all");
+        let base = format!("{}/abort-all/job/deploy", mockito::server_url());
+        let _builds = mockito::mock(
+            "GET",
+            "/abort-all/job/deploy/api/json?tree=builds%5Bnumber%2Curl%2Cbuilding%5D%7B0%2C50%7D",
+        )
+        .with_body(format!(
+            r#"{{"builds":[
+                {{"number":4,"url":"{0}/4/","building":true}},
+                {{"number":3,"url":"{0}/3/","building":true}},
+                {{"number":2,"url":"{0}/2/","building":true}},
+                {{"number":1,"url":"{0}/1/","building":false}}
+            ]}}"#,
+            base
+        ))
+        .create();
+        let stop_4 = mockito::mock("POST", "/abort-all/job/deploy/4/stop")
+            .expect(1)
+            .create();
+        let _stop_3 = mockito::mock("POST", "/abort-all/job/deploy/3/stop")
+            .with_status(500)
+            .create();
+        let _state_3 = mockito::mock("GET", "/abort-all/job/deploy/3/api/json?tree=building")
+            .with_body(r#"{"building":false}"#)
+            .create();
+        let _stop_2 = mockito::mock("POST", "/abort-all/job/deploy/2/stop")
+            .with_status(500)
+            .create();
+        let _state_2 = mockito::mock("GET", "/abort-all/job/deploy/2/api/json?tree=building")
+            .with_body(r#"{"building":true}"#)
+            .create();
+
+        let report = jenkins_client
+            .abort_all_running_of(&format!("{}/", base), AbortOptions::default())
+            .unwrap();
+
+        stop_4.assert();
+        let outcomes = report
+            .builds
+            .iter()
+            .map(|build| (build.number, build.outcome.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(outcomes[0], (4, AbortOutcome::Stopped));
+        assert_eq!(outcomes[1], (3, AbortOutcome::AlreadyFinished));
+        assert_eq!(outcomes[2].0, 2);
+        assert!(matches!(outcomes[2].1, AbortOutcome::Failed(_)));
+        assert_eq!(report.stopped(), 2);
+        assert_eq!(report.failed(), 1);
+    }
+
+    #[test]
+    fn dry_run_aborts_nothing() {
+        let jenkins_client = jenkins_client("abort-dry-run");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let _computers = mockito::mock(
+            "GET",
+            mockito::Matcher::Regex(r"^/abort-dry-run/computer/api/json\?tree=".to_string()),
+        )
+        .with_body(format!(
+            r#"{{"computer":[{{"executors":[
+                {{"currentExecutable":{{"number":7,"url":"{0}/abort-dry-run/job/old/7/","timestamp":{1}}}}},
+                {{"currentExecutable":null}}
+            ]}}]}}"#,
+            mockito::server_url(),
+            now - 3_600_000
+        ))
+        .create();
+        let stop = mockito::mock("POST", "/abort-dry-run/job/old/7/stop")
+            .expect(0)
+            .create();
+
+        let report = jenkins_client
+            .abort_builds_running_longer_than(
+                Duration::from_secs(60),
+                None,
+                AbortOptions::default().dry_run(),
+            )
+            .unwrap();
+
+        stop.assert();
+        assert!(report.dry_run);
+        assert_eq!(report.builds.len(), 1);
+        assert_eq!(report.builds[0].outcome, AbortOutcome::DryRun);
+    }
+
+    #[test]
+    fn can_abort_builds_running_longer_than() {
+        let jenkins_client = jenkins_client("abort-old");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let _computers = mockito::mock(
+            "GET",
+            mockito::Matcher::Regex(r"^/abort-old/computer/api/json\?tree=".to_string()),
+        )
+        .with_body(format!(
+            r#"{{"computer":[
+                {{"executors":[
+                    {{"currentExecutable":{{"number":7,"url":"{0}/abort-old/job/old%20job/7/","timestamp":{1}}}}},
+                    {{"currentExecutable":{{"number":2,"url":"{0}/abort-old/job/recent/2/","timestamp":{2}}}}}
+                ]}},
+                {{"executors":[],"oneOffExecutors":[
+                    {{"currentExecutable":{{"number":5,"url":"{0}/abort-old/job/other/5/","timestamp":{1}}}}}
+                ]}}
+            ]}}"#,
+            mockito::server_url(),
+            now - 3_600_000,
+            now - 1_000
+        ))
+        .create();
+        let _jobs = mockito::mock("GET", "/abort-old/api/json?tree=jobs%5Bname%2Ccolor%5D")
+            .with_body(
+                r#"{"jobs":[
+                    {"_class":"hudson.model.FreeStyleProject","name":"old job","color":"blue_anime"},
+                    {"_class":"hudson.model.FreeStyleProject","name":"recent","color":"blue_anime"},
+                    {"_class":"hudson.model.FreeStyleProject","name":"other","color":"blue_anime"}
+                ]}"#,
+            )
+            .create();
+        let stop = mockito::mock("POST", "/abort-old/job/old%20job/7/stop")
+            .expect(1)
+            .create();
+
+        let report = jenkins_client
+            .abort_builds_running_longer_than(
+                Duration::from_secs(60),
+                Some(JobFilter::new().with_name_glob("old*")),
+                AbortOptions::default(),
+            )
+            .unwrap();
+
+        stop.assert();
+        assert_eq!(report.builds.len(), 1);
+        assert_eq!(report.builds[0].number, 7);
+        assert_eq!(report.builds[0].outcome, AbortOutcome::Stopped);
+    }
+}
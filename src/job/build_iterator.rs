@@ -0,0 +1,231 @@
+//! Iterating over every build of a job, fetching them page by page
+
+use std::collections::VecDeque;
+
+use failure::Error;
+use serde::Deserialize;
+
+use crate::build::{CommonBuild, ShortBuild};
+use crate::client;
+use crate::client_internals::Path;
+use crate::Jenkins;
+
+/// Number of builds listed by each request of a `BuildIterator`, unless changed with
+/// `BuildIterator::with_page_size`
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Iterator over every build of a job, from the most recent one, returned by
+/// [`Job::builds_iter`](trait.Job.html#method.builds_iter)
+///
+/// Builds are listed a page at a time with a range on `allBuilds`, and each build is only fetched
+/// when it is reached. Builds started while iterating shift the pages, builds already yielded
+/// are not yielded again
+#[derive(Debug)]
+pub struct BuildIterator<'a> {
+    jenkins_client: &'a Jenkins,
+    job_url: String,
+    page_size: u32,
+    /// Index of the first build of the next page
+    next_index: u32,
+    /// Builds listed and not yet yielded
+    pending: VecDeque<ShortBuild>,
+    /// Number of the last build yielded, all other builds yielded have a higher number
+    last_number: Option<u32>,
+    exhausted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobAllBuilds {
+    #[serde(default)]
+    all_builds: Vec<ShortBuild>,
+}
+
+impl<'a> BuildIterator<'a> {
+    pub(crate) fn new(jenkins_client: &'a Jenkins, job_url: &str) -> Self {
+        BuildIterator {
+            jenkins_client,
+            job_url: job_url.to_string(),
+            page_size: DEFAULT_PAGE_SIZE,
+            next_index: 0,
+            pending: VecDeque::new(),
+            last_number: None,
+            exhausted: false,
+        }
+    }
+
+    /// Change the number of builds listed by each request, at least 1
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// List the next page of builds, keeping only those older than the last build yielded. The
+    /// iterator is exhausted once a page is not full
+    fn fetch_page(&mut self) -> Result<(), Error> {
+        let path = self.jenkins_client.url_to_path(&self.job_url);
+        if let Path::Job { .. } = path {
+            let page: JobAllBuilds = self
+                .jenkins_client
+                .get_with_params(
+                    &path,
+                    [(
+                        "tree",
+                        format!(
+                            "allBuilds[number,url]{{{},{}}}",
+                            self.next_index,
+                            self.next_index + self.page_size
+                        ),
+                    )],
+                )?
+                .json()?;
+            self.next_index += self.page_size;
+            if (page.all_builds.len() as u32) < self.page_size {
+                self.exhausted = true;
+            }
+            let last_number = self.last_number;
+            self.pending.extend(
+                page.all_builds
+                    .into_iter()
+                    .filter(|build| last_number.is_none_or(|last| build.number < last)),
+            );
+            Ok(())
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.job_url.clone(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into())
+        }
+    }
+}
+
+impl<'a> Iterator for BuildIterator<'a> {
+    type Item = Result<CommonBuild, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if self.exhausted {
+                return None;
+            }
+            if let Err(error) = self.fetch_page() {
+                self.exhausted = true;
+                return Some(Err(error));
+            }
+        }
+        let build = self.pending.pop_front()?;
+        self.last_number = Some(build.number);
+        Some(build.get_full_build(self.jenkins_client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use crate::job::Job;
+
+    fn page(url: &str, numbers: &[u32]) -> String {
+        format!(
+            r#"{{"allBuilds":[{}]}}"#,
+            numbers
+                .iter()
+                .map(|number| format!(r#"{{"number":{0},"url":"{1}/job/job/{0}/"}}"#, number, url))
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+
+    fn build(url: &str, number: u32) -> String {
+        format!(
+            r#"{{"_class":"hudson.model.FreeStyleBuild","url":"{1}/job/job/{0}/","number":{0},
+            "duration":10,"estimatedDuration":10,"timestamp":1500000000000,"keepLog":false,
+            "result":"SUCCESS","displayName":"{0}","building":false,"id":"{0}","queueId":1}}"#,
+            number, url
+        )
+    }
+
+    #[test]
+    fn can_iterate_over_all_builds_by_pages() {
+        let url = format!("{}/builds-iter", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let job: crate::job::CommonJob = serde_json::from_str(&format!(
+            r#"{{"_class":"hudson.model.FreeStyleProject","name":"job","displayName":"job",
+            "url":"{}/job/job/"}}"#,
+            url
+        ))
+        .unwrap();
+
+        // build 6 starts after the first page is listed, shifting the next pages by one
+        let _first = mockito::mock(
+            "GET",
+            "/builds-iter/job/job/api/json?tree=allBuilds%5Bnumber%2Curl%5D%7B0%2C2%7D",
+        )
+        .with_body(page(&url, &[5, 4]))
+        .create();
+        let _second = mockito::mock(
+            "GET",
+            "/builds-iter/job/job/api/json?tree=allBuilds%5Bnumber%2Curl%5D%7B2%2C4%7D",
+        )
+        .with_body(page(&url, &[4, 3]))
+        .create();
+        let _third = mockito::mock(
+            "GET",
+            "/builds-iter/job/job/api/json?tree=allBuilds%5Bnumber%2Curl%5D%7B4%2C6%7D",
+        )
+        .with_body(page(&url, &[2]))
+        .create();
+        let mocks: Vec<_> = [5, 4, 3, 2]
+            .iter()
+            .map(|number| {
+                mockito::mock(
+                    "GET",
+                    &*format!("/builds-iter/job/job/{}/api/json?depth=1", number),
+                )
+                .with_body(build(&url, *number))
+                .expect(1)
+                .create()
+            })
+            .collect();
+
+        let mut builds = job.builds_iter(&jenkins_client).with_page_size(2);
+        let numbers: Vec<u32> = builds.by_ref().map(|build| build.unwrap().number).collect();
+        assert_eq!(numbers, vec![5, 4, 3, 2]);
+        assert!(builds.next().is_none());
+        for mock in mocks {
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn stops_on_empty_page() {
+        let url = format!("{}/builds-iter-empty", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let first = mockito::mock(
+            "GET",
+            "/builds-iter-empty/job/job/api/json?tree=allBuilds%5Bnumber%2Curl%5D%7B0%2C1%7D",
+        )
+        .with_body(page(&url, &[1]))
+        .create();
+        let second = mockito::mock(
+            "GET",
+            "/builds-iter-empty/job/job/api/json?tree=allBuilds%5Bnumber%2Curl%5D%7B1%2C2%7D",
+        )
+        .with_body(r#"{"allBuilds":[]}"#)
+        .expect(1)
+        .create();
+        let _build = mockito::mock("GET", "/builds-iter-empty/job/job/1/api/json?depth=1")
+            .with_body(build(&url, 1))
+            .create();
+
+        let numbers: Vec<u32> =
+            super::BuildIterator::new(&jenkins_client, &format!("{}/job/job/", url))
+                .with_page_size(1)
+                .map(|build| build.unwrap().number)
+                .collect();
+        assert_eq!(numbers, vec![1]);
+        first.assert();
+        second.assert();
+    }
+}
@@ -1,7 +1,11 @@
 //! Helper to build a job
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+
 use failure::Error;
 
-use reqwest::header::LOCATION;
+use reqwest::header::{HeaderMap, LOCATION};
 
 use serde::{self, Serialize};
 use serde_urlencoded;
@@ -13,7 +17,10 @@ use crate::queue::ShortQueueItem;
 use crate::Jenkins;
 
 /// Helper to build a job
-#[derive(Debug)]
+///
+/// The builder can be cloned, or used with `send_ref` and `send_n`, to trigger the same
+/// configuration many times, parameters being serialized only once
+#[derive(Debug, Clone)]
 pub struct JobBuilder<'a, 'b, 'c, 'd> {
     job_name: Name<'a>,
     jenkins_client: &'b Jenkins,
@@ -66,70 +73,110 @@ impl<'a, 'b, 'c, 'd> JobBuilder<'a, 'b, 'c, 'd> {
         })
     }
 
-    /// Trigger the build
-    pub fn send(self) -> Result<ShortQueueItem, Error> {
-        let response = match (self.token, self.parameters) {
-            (Some(token), None) => {
-                let bound_cause = self.cause.unwrap_or("");
-                let bound_delay = format!("{}", self.delay.unwrap_or(0));
-                let mut qps: Vec<(&str, &str)> = Vec::new();
-                qps.push(("token", &token));
-                if self.cause.is_some() {
-                    qps.push(("cause", &bound_cause));
+    pub(crate) fn new_from_name(name: Name<'a>, jenkins_client: &'b Jenkins) -> Self {
+        JobBuilder {
+            job_name: name,
+            jenkins_client,
+            delay: None,
+            cause: None,
+            token: None,
+            parameters: None,
+        }
+    }
+
+    /// Get the request triggering the build
+    fn trigger(&self) -> Trigger<'a> {
+        let mut qps = vec![];
+        match (self.token, self.parameters.clone()) {
+            (Some(token), _) => {
+                qps.push(("token", token.to_string()));
+                if let Some(cause) = self.cause {
+                    qps.push(("cause", cause.to_string()));
                 }
-                if self.delay.is_some() {
-                    qps.push(("delay", &bound_delay));
+                if let Some(delay) = self.delay {
+                    qps.push(("delay", delay.to_string()));
                 }
-
-                self.jenkins_client.get_with_params(
-                    &Path::BuildJob {
-                        name: self.job_name,
+                Trigger::Remote {
+                    path: Path::BuildJob {
+                        name: self.job_name.clone(),
                     },
-                    &qps,
-                )?
-            }
-            (Some(_token), Some(_parameters)) => unreachable!(),
-            (None, None) => {
-                let bound_delay = format!("{}", self.delay.unwrap_or(0));
-                let mut qps: Vec<(&str, &str)> = Vec::new();
-                if self.delay.is_some() {
-                    qps.push(("delay", &bound_delay));
+                    qps,
                 }
-                self.jenkins_client.post_with_body(
-                    &Path::BuildJob {
-                        name: self.job_name,
-                    },
-                    "",
-                    &qps,
-                )?
             }
-            (None, Some(parameters)) => {
-                let bound_delay = format!("{}", self.delay.unwrap_or(0));
-                let mut qps: Vec<(&str, &str)> = Vec::new();
-                if self.delay.is_some() {
-                    qps.push(("delay", &bound_delay));
+            (None, parameters) => {
+                if let Some(delay) = self.delay {
+                    qps.push(("delay", delay.to_string()));
                 }
-                self.jenkins_client.post_with_body(
-                    &Path::BuildJobWithParameters {
-                        name: self.job_name,
+                match parameters {
+                    None => Trigger::Post {
+                        path: Path::BuildJob {
+                            name: self.job_name.clone(),
+                        },
+                        body: String::new(),
+                        qps,
                     },
-                    parameters,
-                    &qps,
-                )?
+                    Some(parameters) => Trigger::Post {
+                        path: Path::BuildJobWithParameters {
+                            name: self.job_name.clone(),
+                        },
+                        body: parameters,
+                        qps,
+                    },
+                }
+            }
+        }
+    }
+
+    /// Trigger the build
+    pub fn send(self) -> Result<ShortQueueItem, Error> {
+        self.send_ref()
+    }
+
+    /// Trigger the build, keeping the builder to trigger it again
+    pub fn send_ref(&self) -> Result<ShortQueueItem, Error> {
+        let jenkins_client = self.jenkins_client;
+        let response = match self.trigger() {
+            Trigger::Remote { path, qps } => jenkins_client.get_with_params(&path, &qps)?,
+            Trigger::Post { path, body, qps } => {
+                let qps: Vec<(&str, &str)> = qps
+                    .iter()
+                    .map(|(key, value)| (*key, value.as_str()))
+                    .collect();
+                jenkins_client.post_with_body(&path, body, &qps)?
             }
         };
-        if let Some(location) = response.headers().get(LOCATION) {
-            Ok(ShortQueueItem {
-                url: location.to_str().unwrap().to_string(),
-                other_fields: None,
+        queue_item_from_location(response.headers())
+    }
+
+    /// Trigger the build `count` times, waiting `interval` between two triggers, and return the
+    /// result of each trigger
+    pub fn send_n(&self, count: usize, interval: Duration) -> Vec<Result<ShortQueueItem, Error>> {
+        (0..count)
+            .map(|index| {
+                if index > 0 {
+                    thread::sleep(interval);
+                }
+                self.send_ref()
             })
-        } else {
-            Err(client::Error::InvalidUrl {
-                url: "".to_string(),
-                expected: client::error::ExpectedType::QueueItem,
+            .collect()
+    }
+
+    /// Trigger the build with the asynchronous client
+    #[cfg(feature = "async")]
+    pub fn send_async(
+        self,
+        jenkins_client: &crate::r#async::Jenkins,
+    ) -> crate::r#async::JenkinsFuture<ShortQueueItem> {
+        use futures::Future;
+
+        let jenkins_client = jenkins_client.shared();
+        let response = match self.trigger() {
+            Trigger::Remote { path, qps } => jenkins_client.async_get_with_params(&path, &qps),
+            Trigger::Post { path, body, qps } => {
+                jenkins_client.async_post_with_body(&path, body, &qps)
             }
-            .into())
-        }
+        };
+        Box::new(response.and_then(|response| queue_item_from_location(response.headers())))
     }
 
     /// Add a delay before the job will be built
@@ -175,4 +222,171 @@ impl<'a, 'b, 'c, 'd> JobBuilder<'a, 'b, 'c, 'd> {
         self.parameters = Some(serde_urlencoded::to_string(parameters)?);
         Ok(self)
     }
+
+    /// Build with string parameters read from a Java `.properties` file
+    ///
+    /// Parameters are added to the ones already set.
+    /// # Errors
+    /// This methods will return an error if the file can't be parsed, if a parameter was already
+    /// set, or if passing parameters to a remote build.
+    pub fn with_parameters_from_properties<R: Read>(self, mut reader: R) -> Result<Self, Error> {
+        let mut content = String::new();
+        let _ = reader.read_to_string(&mut content)?;
+        let parameters = super::parameters_file::parse_properties(&content)?;
+        self.with_additional_parameters(parameters)
+    }
+
+    /// Build with string parameters read from a YAML file containing a flat map
+    ///
+    /// Parameters are added to the ones already set.
+    /// # Errors
+    /// This methods will return an error if the file isn't a map of scalar values, if a
+    /// parameter was already set, or if passing parameters to a remote build.
+    #[cfg(feature = "yaml")]
+    pub fn with_parameters_from_yaml<R: Read>(self, reader: R) -> Result<Self, Error> {
+        let parameters = super::parameters_file::parse_yaml(reader)?;
+        self.with_additional_parameters(parameters)
+    }
+
+    pub(crate) fn with_additional_parameters(
+        mut self,
+        parameters: Vec<(String, String)>,
+    ) -> Result<Self, Error> {
+        if self.token.is_some() {
+            return Err(client::Error::UnsupportedBuildConfiguration.into());
+        }
+        let mut all_parameters: Vec<(String, String)> = match self.parameters {
+            Some(ref existing) => serde_urlencoded::from_str(existing)?,
+            None => vec![],
+        };
+        for (name, value) in parameters {
+            if all_parameters.iter().any(|existing| existing.0 == name) {
+                return Err(client::Error::DuplicateParameter { name }.into());
+            }
+            all_parameters.push((name, value));
+        }
+        self.parameters = Some(serde_urlencoded::to_string(&all_parameters)?);
+        Ok(self)
+    }
+}
+
+/// Request triggering a build
+enum Trigger<'a> {
+    /// Build triggered remotely with a token
+    Remote {
+        path: Path<'a>,
+        qps: Vec<(&'static str, String)>,
+    },
+    /// Build triggered by a user
+    Post {
+        path: Path<'a>,
+        body: String,
+        qps: Vec<(&'static str, String)>,
+    },
+}
+
+/// Get the `ShortQueueItem` of a triggered build from the `Location` header of the response
+fn queue_item_from_location(headers: &HeaderMap) -> Result<ShortQueueItem, Error> {
+    if let Some(location) = headers.get(LOCATION) {
+        Ok(ShortQueueItem {
+            url: location.to_str()?.to_string(),
+            other_fields: None,
+        })
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: "".to_string(),
+            expected: client::error::ExpectedType::QueueItem,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    static JENKINS_URL: &str = "http://none:8080";
+
+    #[derive(Serialize)]
+    struct Parameters {
+        first: String,
+    }
+
+    #[test]
+    fn can_add_parameters_from_properties() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let builder = jenkins_client
+            .job_builder("job")
+            .unwrap()
+            .with_parameters(&Parameters {
+                first: "1".to_string(),
+            })
+            .unwrap()
+            .with_parameters_from_properties("second = two words \n".as_bytes())
+            .unwrap();
+        assert_eq!(
+            builder.parameters,
+            Some("first=1&second=two+words+".to_string())
+        );
+    }
+
+    #[test]
+    fn should_fail_on_duplicate_parameter() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let builder = jenkins_client
+            .job_builder("job")
+            .unwrap()
+            .with_parameters(&Parameters {
+                first: "1".to_string(),
+            })
+            .unwrap()
+            .with_parameters_from_properties("second=2\nfirst=3".as_bytes());
+        assert_eq!(
+            format!("{}", builder.unwrap_err()),
+            "parameter 'first' is already set"
+        );
+    }
+
+    #[test]
+    fn should_fail_on_parameters_for_remote_build() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        let builder = jenkins_client
+            .job_builder("job")
+            .unwrap()
+            .remotely_with_token_and_cause("token", None)
+            .unwrap()
+            .with_parameters_from_properties("a=1".as_bytes());
+        assert!(builder.is_err());
+    }
+
+    #[test]
+    fn can_send_same_build_many_times() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/send-n", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let mock = mockito::mock("POST", "/send-n/job/job/buildWithParameters")
+            .match_body("first=1")
+            .with_header("Location", "http://none:8080/queue/item/1/")
+            .expect(4)
+            .create();
+
+        let builder = jenkins_client
+            .job_builder("job")
+            .unwrap()
+            .with_parameters(&Parameters {
+                first: "1".to_string(),
+            })
+            .unwrap();
+        let results = builder.send_n(3, std::time::Duration::from_millis(1));
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(Result::is_ok));
+        assert!(builder.clone().send().is_ok());
+        mock.assert();
+    }
 }
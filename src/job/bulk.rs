@@ -0,0 +1,424 @@
+//! Enable or disable many jobs at once
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::client_internals::{Name, Path};
+use crate::helpers::{map_concurrently, Class, NameFilter};
+use crate::Jenkins;
+
+use super::BallColor;
+
+/// Filter selecting the jobs to act on
+///
+/// An empty filter matches every job. Jobs without a build status, like folders, never match
+/// as they can't be enabled or disabled.
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    name: Option<NameFilter>,
+    pub(crate) view: Option<String>,
+    class: Option<String>,
+}
+
+impl JobFilter {
+    /// Create a filter matching every job
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match jobs whose name matches a glob, where `*` matches any sequence of characters
+    /// and `?` matches any single character
+    pub fn with_name_glob(self, glob: &str) -> Self {
+        self.with_name(NameFilter::glob(glob))
+    }
+
+    /// Only match jobs whose name matches a regex. The regex is not anchored
+    ///
+    /// # Errors
+    /// This methods will return an error if `regex` is invalid
+    pub fn with_name_regex(self, regex: &str) -> Result<Self, Error> {
+        Ok(self.with_name(NameFilter::regex(regex)?))
+    }
+
+    /// Only match jobs whose name matches a `NameFilter`
+    pub fn with_name(mut self, name: NameFilter) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Only match jobs in a view
+    pub fn in_view(mut self, view_name: &str) -> Self {
+        self.view = Some(view_name.to_string());
+        self
+    }
+
+    /// Only match jobs of a kind, like `FreeStyleProject` or `WorkflowJob`
+    pub fn of_kind<T: Class>(mut self) -> Self {
+        self.class = Some(T::with_class().to_string());
+        self
+    }
+
+    pub(crate) fn matches(&self, job: &ListedJob) -> bool {
+        if job.color.is_none() {
+            return false;
+        }
+        if let Some(ref class) = self.class {
+            if job.class.as_ref() != Some(class) {
+                return false;
+            }
+        }
+        match self.name {
+            Some(ref name) => name.matches(&job.name),
+            None => true,
+        }
+    }
+}
+
+/// Options of a bulk change
+#[derive(Debug, Clone, Copy)]
+pub struct BulkOptions {
+    concurrency: usize,
+}
+
+impl Default for BulkOptions {
+    fn default() -> Self {
+        BulkOptions { concurrency: 4 }
+    }
+}
+
+impl BulkOptions {
+    /// Set the maximum number of requests sent to Jenkins at the same time, 4 by default
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+}
+
+/// Outcome of a bulk change on a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobOutcome {
+    /// The job was changed
+    Changed,
+    /// The job was already in the requested state, and was skipped
+    AlreadyInState,
+    /// Changing the job failed
+    Failed(String),
+}
+
+/// Result of a bulk change on a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobResult {
+    /// Name of the job
+    pub name: String,
+    /// Outcome of the change
+    pub outcome: JobOutcome,
+}
+
+/// Report of a bulk change, with the outcome for each job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkReport {
+    /// Are the jobs being enabled or disabled
+    pub enabled: bool,
+    /// Results for each job matched
+    pub jobs: Vec<JobResult>,
+}
+
+impl BulkReport {
+    /// Number of jobs changed
+    pub fn changed(&self) -> usize {
+        self.count(|outcome| *outcome == JobOutcome::Changed)
+    }
+
+    /// Number of jobs that failed to be changed
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(*outcome, JobOutcome::Failed(_)))
+    }
+
+    fn count<F: Fn(&JobOutcome) -> bool>(&self, predicate: F) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| predicate(&job.outcome))
+            .count()
+    }
+
+    /// List of the jobs actually changed, to revert them with `Jenkins::undo_bulk_set_enabled`
+    pub fn undo_list(&self) -> UndoList {
+        UndoList {
+            enabled: !self.enabled,
+            jobs: self
+                .jobs
+                .iter()
+                .filter(|job| job.outcome == JobOutcome::Changed)
+                .map(|job| job.name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Jobs to revert after a bulk change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoList {
+    /// State to restore
+    pub enabled: bool,
+    /// Names of the jobs to restore
+    pub jobs: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListedJob {
+    #[serde(rename = "_class")]
+    class: Option<String>,
+    pub(crate) name: String,
+    color: Option<BallColor>,
+}
+
+impl ListedJob {
+    fn is_enabled(&self) -> bool {
+        !matches!(
+            self.color,
+            Some(BallColor::Disabled) | Some(BallColor::DisabledAnime)
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JobList {
+    jobs: Vec<ListedJob>,
+}
+
+impl Jenkins {
+    pub(crate) fn list_jobs(&self, view: Option<&str>) -> Result<Vec<ListedJob>, Error> {
+        let path = match view {
+            Some(view) => Path::View {
+                name: Name::Name(view),
+            },
+            None => Path::Home,
+        };
+        let list: JobList = self
+            .get_with_params(&path, [("tree", "jobs[name,color]")])?
+            .json()?;
+        Ok(list.jobs)
+    }
+
+    fn set_enabled(&self, jobs: Vec<ListedJob>, enabled: bool, options: BulkOptions) -> BulkReport {
+        let results = map_concurrently(&jobs, options.concurrency, |job| {
+            let outcome = if job.is_enabled() == enabled {
+                JobOutcome::AlreadyInState
+            } else {
+                let name = Name::Name(&job.name);
+                let path = if enabled {
+                    Path::JobEnable { name }
+                } else {
+                    Path::JobDisable { name }
+                };
+                match self.post(&path) {
+                    Ok(_) => JobOutcome::Changed,
+                    Err(error) => JobOutcome::Failed(error.to_string()),
+                }
+            };
+            JobResult {
+                name: job.name.clone(),
+                outcome,
+            }
+        });
+
+        BulkReport {
+            enabled,
+            jobs: results,
+        }
+    }
+
+    /// Enable or disable every job matching `filter`, skipping jobs already in the requested
+    /// state
+    ///
+    /// Jobs are listed from the view of the filter, or from the top level of Jenkins. A job
+    /// failing to be changed doesn't stop the others from being changed.
+    pub fn bulk_set_enabled(
+        &self,
+        filter: JobFilter,
+        enabled: bool,
+        options: BulkOptions,
+    ) -> Result<BulkReport, Error> {
+        let jobs = self
+            .list_jobs(filter.view.as_deref())?
+            .into_iter()
+            .filter(|job| filter.matches(job))
+            .collect();
+        Ok(self.set_enabled(jobs, enabled, options))
+    }
+
+    /// Revert the jobs changed by a bulk change, from its `UndoList`
+    pub fn undo_bulk_set_enabled(
+        &self,
+        undo_list: &UndoList,
+        options: BulkOptions,
+    ) -> Result<BulkReport, Error> {
+        let mut jobs = self.list_jobs(None)?;
+        jobs.retain(|job| undo_list.jobs.contains(&job.name));
+        let mut report = self.set_enabled(jobs, undo_list.enabled, options);
+        for name in &undo_list.jobs {
+            if !report.jobs.iter().any(|job| &job.name == name) {
+                report.jobs.push(JobResult {
+                    name: name.clone(),
+                    outcome: JobOutcome::Failed("job not found".to_string()),
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use mockito;
+
+    use crate::job::{FreeStyleProject, WorkflowJob};
+
+    fn job(class: &str, name: &str, color: Option<BallColor>) -> ListedJob {
+        ListedJob {
+            class: Some(class.to_string()),
+            name: name.to_string(),
+            color,
+        }
+    }
+
+    fn freestyle(name: &str) -> ListedJob {
+        job("hudson.model.FreeStyleProject", name, Some(BallColor::Blue))
+    }
+
+    #[test]
+    fn empty_filter_matches_jobs_with_a_status() {
+        let filter = JobFilter::new();
+        assert!(filter.matches(&freestyle("deploy")));
+        assert!(!filter.matches(&job(
+            "com.cloudbees.hudson.plugins.folder.Folder",
+            "folder",
+            None
+        )));
+    }
+
+    #[test]
+    fn can_filter_on_name_glob() {
+        let filter = JobFilter::new().with_name_glob("deploy-*-prod");
+        assert!(filter.matches(&freestyle("deploy-api-prod")));
+        assert!(filter.matches(&freestyle("deploy--prod")));
+        assert!(!filter.matches(&freestyle("deploy-api-staging")));
+        assert!(!filter.matches(&freestyle("release-deploy-api-prod")));
+
+        let filter = JobFilter::new().with_name_glob("build-?");
+        assert!(filter.matches(&freestyle("build-1")));
+        assert!(!filter.matches(&freestyle("build-10")));
+    }
+
+    #[test]
+    fn can_filter_on_name_regex() {
+        let filter = JobFilter::new()
+            .with_name_regex("^deploy-(api|web)$")
+            .unwrap();
+        assert!(filter.matches(&freestyle("deploy-api")));
+        assert!(filter.matches(&freestyle("deploy-web")));
+        assert!(!filter.matches(&freestyle("deploy-db")));
+
+        let filter = JobFilter::new().with_name_regex("prod").unwrap();
+        assert!(filter.matches(&freestyle("deploy-prod-eu")));
+
+        assert!(JobFilter::new().with_name_regex("(unclosed").is_err());
+    }
+
+    #[test]
+    fn can_filter_on_kind() {
+        let filter = JobFilter::new()
+            .of_kind::<WorkflowJob>()
+            .with_name_glob("deploy*");
+        assert!(filter.matches(&job(
+            "org.jenkinsci.plugins.workflow.job.WorkflowJob",
+            "deploy",
+            Some(BallColor::Red)
+        )));
+        assert!(!filter.matches(&freestyle("deploy")));
+
+        let filter = JobFilter::new().of_kind::<FreeStyleProject>();
+        assert!(filter.matches(&freestyle("deploy")));
+        assert!(!filter.matches(&ListedJob {
+            class: None,
+            name: "deploy".to_string(),
+            color: Some(BallColor::Blue),
+        }));
+    }
+
+    #[test]
+    fn can_disable_jobs_and_undo() {
+        let url = format!("{}/bulk", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        let _list = mockito::mock(
+            "GET",
+            "/bulk/view/freeze/api/json?tree=jobs%5Bname%2Ccolor%5D",
+        )
+        .with_body(
+            r#"{"_class":"hudson.model.ListView","jobs":[
+                {"_class":"hudson.model.FreeStyleProject","name":"deploy-api","color":"blue"},
+                {"_class":"hudson.model.FreeStyleProject","name":"deploy-web","color":"disabled"},
+                {"_class":"hudson.model.FreeStyleProject","name":"deploy-db","color":"red"},
+                {"_class":"hudson.model.FreeStyleProject","name":"test-api","color":"blue"}
+            ]}"#,
+        )
+        .create();
+        let disable_api = mockito::mock("POST", "/bulk/job/deploy-api/disable").create();
+        let disable_db = mockito::mock("POST", "/bulk/job/deploy-db/disable")
+            .with_status(500)
+            .create();
+
+        let report = jenkins_client
+            .bulk_set_enabled(
+                JobFilter::new()
+                    .in_view("freeze")
+                    .with_name_glob("deploy-*"),
+                false,
+                BulkOptions::default().with_concurrency(2),
+            )
+            .unwrap();
+        disable_api.assert();
+        disable_db.assert();
+
+        let outcomes: Vec<(&str, &JobOutcome)> = report
+            .jobs
+            .iter()
+            .map(|job| (job.name.as_str(), &job.outcome))
+            .collect();
+        assert_eq!(outcomes[0], ("deploy-api", &JobOutcome::Changed));
+        assert_eq!(outcomes[1], ("deploy-web", &JobOutcome::AlreadyInState));
+        assert_eq!(outcomes[2].0, "deploy-db");
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(report.changed(), 1);
+        assert_eq!(report.failed(), 1);
+
+        let undo_list = report.undo_list();
+        assert!(undo_list.enabled);
+        assert_eq!(undo_list.jobs, vec!["deploy-api".to_string()]);
+
+        let undo_list: UndoList =
+            serde_json::from_str(&serde_json::to_string(&undo_list).unwrap()).unwrap();
+        let _home = mockito::mock("GET", "/bulk/api/json?tree=jobs%5Bname%2Ccolor%5D")
+            .with_body(
+                r#"{"jobs":[
+                {"_class":"hudson.model.FreeStyleProject","name":"deploy-api","color":"disabled"},
+                {"_class":"hudson.model.FreeStyleProject","name":"deploy-db","color":"red"}
+            ]}"#,
+            )
+            .create();
+        let enable_api = mockito::mock("POST", "/bulk/job/deploy-api/enable").create();
+
+        let report = jenkins_client
+            .undo_bulk_set_enabled(&undo_list, BulkOptions::default())
+            .unwrap();
+        enable_api.assert();
+        assert_eq!(report.changed(), 1);
+        assert_eq!(report.jobs.len(), 1);
+    }
+}
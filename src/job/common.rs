@@ -4,20 +4,23 @@ use failure::Error;
 use serde::{self, Deserialize, Serialize};
 use serde_json;
 
-use crate::helpers::Class;
+use crate::helpers::{is_not_found, Class};
 
-use super::JobBuilder;
+use super::polling::{parse_polling_log, PollResult};
+use super::{
+    AbortOptions, AbortReport, BuildIterator, JobBuilder, RecentBuilds, RunnabilityReport,
+};
 use crate::action::CommonAction;
-use crate::build::{CommonBuild, ShortBuild};
+use crate::build::{BuildStatus, CommonBuild, ShortBuild};
 use crate::client;
 use crate::client_internals::{Name, Path};
-use crate::property::CommonProperty;
+use crate::property::{CommonProperty, ParameterDefinition};
 use crate::queue::ShortQueueItem;
 use crate::view::ViewName;
 use crate::Jenkins;
 
 /// Ball Color corresponding to a `BuildStatus`
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum BallColor {
     /// Success
@@ -46,10 +49,74 @@ pub enum BallColor {
     AbortedAnime,
     /// Not Build
     #[serde(rename = "notbuilt")]
+    #[default]
     NotBuilt,
     /// Not Build, and build is on-going
     #[serde(rename = "notbuilt_anime")]
     NotBuiltAnime,
+    /// Color not known by this crate
+    #[serde(other)]
+    Unknown,
+}
+
+impl BallColor {
+    /// Is a build on-going
+    pub fn is_animated(self) -> bool {
+        self != self.without_animation()
+    }
+
+    /// Is a build on-going, same as `is_animated`
+    pub fn is_building(self) -> bool {
+        self.is_animated()
+    }
+
+    /// Get the status of the last completed build, ignoring the animation of an on-going build.
+    /// Grey and disabled colors don't correspond to a status
+    pub fn status(self) -> Option<BuildStatus> {
+        match self.without_animation() {
+            BallColor::Blue => Some(BuildStatus::Success),
+            BallColor::Yellow => Some(BuildStatus::Unstable),
+            BallColor::Red => Some(BuildStatus::Failure),
+            BallColor::Aborted => Some(BuildStatus::Aborted),
+            BallColor::NotBuilt => Some(BuildStatus::NotBuilt),
+            _ => None,
+        }
+    }
+
+    /// Get the color without the animation of an on-going build
+    pub fn without_animation(self) -> BallColor {
+        match self {
+            BallColor::BlueAnime => BallColor::Blue,
+            BallColor::YellowAnime => BallColor::Yellow,
+            BallColor::RedAnime => BallColor::Red,
+            BallColor::GreyAnime => BallColor::Grey,
+            BallColor::DisabledAnime => BallColor::Disabled,
+            BallColor::AbortedAnime => BallColor::Aborted,
+            BallColor::NotBuiltAnime => BallColor::NotBuilt,
+            color => color,
+        }
+    }
+
+    /// Severity of a color when aggregating colors, ignoring animation. From the least to the
+    /// most severe: disabled, not built, grey and unknown, blue, aborted, yellow, red
+    pub fn severity(self) -> u8 {
+        match self.without_animation() {
+            BallColor::Disabled => 0,
+            BallColor::NotBuilt | BallColor::Grey | BallColor::Unknown => 1,
+            BallColor::Blue => 2,
+            BallColor::Aborted => 3,
+            BallColor::Yellow => 4,
+            _ => 5,
+        }
+    }
+
+    /// Get the most severe color, without animation, or `None` if there are no colors
+    pub fn worst<I: IntoIterator<Item = BallColor>>(colors: I) -> Option<BallColor> {
+        colors
+            .into_iter()
+            .map(BallColor::without_animation)
+            .max_by_key(|color| color.severity())
+    }
 }
 
 /// Health Report of a `Job`
@@ -65,6 +132,23 @@ pub struct HealthReport {
     /// Score of the `Job`
     pub score: u16,
 }
+impl HealthReport {
+    /// Get the absolute URL of the icon of this `HealthReport`
+    ///
+    /// Jenkins returns either a bare image name, resolved from its `images/48x48/` folder,
+    /// or a path relative to its root, like `static/<hash>/images/...`
+    pub fn absolute_icon_url(&self, jenkins_client: &Jenkins) -> String {
+        if self.icon_url.contains("://") {
+            self.icon_url.clone()
+        } else if self.icon_url.starts_with('/') {
+            jenkins_client.url(&self.icon_url)
+        } else if self.icon_url.contains('/') {
+            jenkins_client.url(&format!("/{}", self.icon_url))
+        } else {
+            jenkins_client.url(&format!("/images/48x48/{}", self.icon_url))
+        }
+    }
+}
 
 /// Short Job that is used in lists and links from other structs
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -74,7 +158,8 @@ pub struct ShortJob<T: Job = CommonJob> {
     pub name: String,
     /// URL for the job
     pub url: String,
-    /// Ball Color for the status of the job
+    /// Ball Color for the status of the job, `NotBuilt` for items without one like folders
+    #[serde(default, deserialize_with = "color_or_default")]
     pub color: BallColor,
     #[serde(flatten)]
     pub(crate) other_fields: Option<serde_json::Value>,
@@ -82,6 +167,15 @@ pub struct ShortJob<T: Job = CommonJob> {
     #[serde(skip)]
     job_type: PhantomData<T>,
 }
+/// Folders send a `null` color, or no color at all
+fn color_or_default<'de, D>(deserializer: D) -> Result<BallColor, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let color: Option<BallColor> = Deserialize::deserialize(deserializer)?;
+    Ok(color.unwrap_or_default())
+}
+
 impl<T> ShortJob<T>
 where
     T: Job,
@@ -133,6 +227,18 @@ pub trait Job {
     /// Get the name of the project
     fn name(&self) -> &str;
 
+    /// Get the URL of a `Job` for users, on the public URL of Jenkins, see
+    /// [`Jenkins::public_url_for`](../struct.Jenkins.html#method.public_url_for)
+    fn public_url(&self, jenkins_client: &Jenkins) -> Result<String, Error> {
+        jenkins_client.public_url_for(self.url())
+    }
+
+    /// Get the definitions of the parameters accepted by this job, from its
+    /// `ParametersDefinitionProperty`. Jobs without properties have no parameters
+    fn parameters(&self) -> Result<Vec<ParameterDefinition>, Error> {
+        Ok(vec![])
+    }
+
     /// Enable a `Job`. It may need to be refreshed as it may have been updated
     fn enable(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
         let path = jenkins_client.url_to_path(&self.url());
@@ -141,7 +247,7 @@ pub trait Job {
             configuration: None,
         } = path
         {
-            let _ = jenkins_client.post(&Path::JobEnable { name })?;
+            let _ = jenkins_client.post_idempotent(&Path::JobEnable { name })?;
             Ok(())
         } else {
             Err(client::Error::InvalidUrl {
@@ -160,7 +266,7 @@ pub trait Job {
             configuration: None,
         } = path
         {
-            let _ = jenkins_client.post(&Path::JobDisable { name })?;
+            let _ = jenkins_client.post_idempotent(&Path::JobDisable { name })?;
             Ok(())
         } else {
             Err(client::Error::InvalidUrl {
@@ -171,6 +277,59 @@ pub trait Job {
         }
     }
 
+    /// Set the description of a `Job`, that can contain HTML. It may need to be refreshed as it
+    /// may have been updated
+    fn set_description(&self, jenkins_client: &Jenkins, description: &str) -> Result<(), Error> {
+        let path = jenkins_client.url_to_path(&self.url());
+        if let Path::Job {
+            name,
+            configuration: None,
+        } = path
+        {
+            jenkins_client.submit_job_description(name, description)
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into())
+        }
+    }
+
+    /// Stop every running build of this job, among its most recent builds. A build that
+    /// finishes before it could be stopped is reported as `AlreadyFinished`
+    fn abort_all_running(
+        &self,
+        jenkins_client: &Jenkins,
+        options: AbortOptions,
+    ) -> Result<AbortReport, Error> {
+        jenkins_client.abort_all_running_of(self.url(), options)
+    }
+
+    /// Get up to `limit` of the most recent builds of this job, by strictly descending number.
+    /// Jenkins may send them out of order or repeat some after a restart: they are sorted and
+    /// deduplicated, and the anomalies found are reported
+    fn recent_builds_ordered(
+        &self,
+        jenkins_client: &Jenkins,
+        limit: u32,
+    ) -> Result<RecentBuilds, Error> {
+        jenkins_client.recent_builds_of(self.url(), limit)
+    }
+
+    /// Iterate over every build of this job, from the most recent one. Builds are listed by
+    /// pages and fetched only when reached, the `builds` of the job only holding the most
+    /// recent ones
+    fn builds_iter<'a>(&self, jenkins_client: &'a Jenkins) -> BuildIterator<'a> {
+        BuildIterator::new(jenkins_client, self.url())
+    }
+
+    /// Check whether an online node matching the label expression of this job has an idle
+    /// executor to run it now
+    fn can_run_now(&self, jenkins_client: &Jenkins) -> Result<RunnabilityReport, Error> {
+        jenkins_client.runnability_of(self.url())
+    }
+
     /// Add this job to the view `view_name`
     fn add_to_view<'a, V>(&self, jenkins_client: &Jenkins, view_name: V) -> Result<(), Error>
     where
@@ -284,17 +443,23 @@ macro_rules! job_build_with_common_fields_and_impl {
             pub display_name_or_null: Option<String>,
             /// URL for the job
             pub url: String,
-            /// Ball Color for the status of the job
+            /// Ball Color for the status of the job, `NotBuilt` for items without one like folders
+            #[serde(default)]
             pub color: BallColor,
             /// Is the job buildable?
+            #[serde(default)]
             pub buildable: bool,
             /// Are dependencies kept for this job?
+            #[serde(default)]
             pub keep_dependencies: bool,
             /// Next build number
+            #[serde(default)]
             pub next_build_number: u32,
             /// Is this job currently in build queue
+            #[serde(default)]
             pub in_queue: bool,
             /// Actions of a job
+            #[serde(default)]
             pub actions: Vec<Option<CommonAction>>,
             /// Link to the last build
             pub last_build: Option<ShortBuild<$build_type>>,
@@ -313,12 +478,15 @@ macro_rules! job_build_with_common_fields_and_impl {
             /// Link to the last failed build
             pub last_failed_build: Option<ShortBuild<$build_type>>,
             /// List of builds of the job
+            #[serde(default)]
             pub builds: Vec<ShortBuild>,
             /// HealthReport of the job
+            #[serde(default)]
             pub health_report: Vec<HealthReport>,
             /// Queue item of this job if it's waiting
             pub queue_item: Option<ShortQueueItem>,
             /// Properties of the job
+            #[serde(default)]
             property: Vec<CommonProperty>,
             $(
                 $(#[$field_attr])*
@@ -337,6 +505,25 @@ macro_rules! job_build_with_common_fields_and_impl {
             fn name(&self) -> &str {
                 &self.name
             }
+
+            fn parameters(
+                &self,
+            ) -> Result<Vec<$crate::property::ParameterDefinition>, failure::Error> {
+                use $crate::helpers::Class;
+                use $crate::property::ParametersDefinitionProperty;
+
+                let mut parameters = vec![];
+                for property in &self.property {
+                    if property.class.as_deref() == Some(ParametersDefinitionProperty::with_class()) {
+                        parameters.extend(
+                            property
+                                .as_variant::<ParametersDefinitionProperty>()?
+                                .parameter_definitions,
+                        );
+                    }
+                }
+                Ok(parameters)
+            }
         }
     };
 }
@@ -349,6 +536,9 @@ job_build_with_common_fields_and_impl!(
         /// _class provided by Jenkins
         #[serde(rename = "_class")]
         pub class: Option<String>,
+        /// Description of the job, that can contain HTML
+        #[serde(default)]
+        pub description: Option<String>,
 
         private_fields {
             #[serde(flatten)]
@@ -378,7 +568,8 @@ pub trait BuildableJob: Job + Sized {
 
 /// Common trait for jobs that can poll a SCM
 pub trait SCMPollable: Job + Sized {
-    /// Poll configured SCM for changes
+    /// Poll configured SCM for changes. The polling runs asynchronously, its outcome can be
+    /// checked later with `get_scm_poll_log` or `last_poll_result`
     fn poll_scm(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
         let path = jenkins_client.url_to_path(&self.url());
         if let Path::Job {
@@ -386,7 +577,7 @@ pub trait SCMPollable: Job + Sized {
             configuration: None,
         } = path
         {
-            let _ = jenkins_client.post(&Path::PollSCMJob { name })?;
+            let _ = jenkins_client.post_idempotent(&Path::PollSCMJob { name })?;
             Ok(())
         } else {
             Err(client::Error::InvalidUrl {
@@ -396,4 +587,156 @@ pub trait SCMPollable: Job + Sized {
             .into())
         }
     }
+
+    /// Get the plain text log of the last SCM polling, from `<job_url>/scmPollLog/pollingLog`.
+    /// Jenkins answers with a `404` if the job was never polled
+    fn get_scm_poll_log(&self, jenkins_client: &Jenkins) -> Result<String, Error> {
+        let path = jenkins_client.url_to_path(self.url());
+        if let Path::Job {
+            name,
+            configuration: None,
+        } = path
+        {
+            Ok(jenkins_client
+                .get_file(&Path::SCMPollLog { name })?
+                .text()?)
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: self.url().to_string(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into())
+        }
+    }
+
+    /// Get the log of the last SCM polling, `None` if the job was never polled
+    fn polling_log(&self, jenkins_client: &Jenkins) -> Result<Option<String>, Error> {
+        match self.get_scm_poll_log(jenkins_client) {
+            Ok(log) => Ok(Some(log)),
+            Err(ref error) if is_not_found(error) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Get the result of the last SCM polling, read from its log
+    fn last_poll_result(&self, jenkins_client: &Jenkins) -> Result<PollResult, Error> {
+        Ok(match self.polling_log(jenkins_client)? {
+            Some(log) => parse_polling_log(&log),
+            None => PollResult::NeverPolled,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BallColor, HealthReport, ShortJob};
+
+    static JENKINS_URL: &str = "http://none:8080";
+
+    fn health_report(icon_url: &str) -> HealthReport {
+        HealthReport {
+            description: "Build stability: No recent builds failed.".to_string(),
+            icon_class_name: "icon-health-80plus".to_string(),
+            icon_url: icon_url.to_string(),
+            score: 100,
+        }
+    }
+
+    #[test]
+    fn can_get_absolute_icon_url() {
+        let jenkins_client = crate::JenkinsBuilder::new(JENKINS_URL).build().unwrap();
+
+        assert_eq!(
+            health_report("health-80plus.png").absolute_icon_url(&jenkins_client),
+            "http://none:8080/images/48x48/health-80plus.png"
+        );
+        assert_eq!(
+            health_report("static/a1b2c3/images/48x48/health-80plus.png")
+                .absolute_icon_url(&jenkins_client),
+            "http://none:8080/static/a1b2c3/images/48x48/health-80plus.png"
+        );
+        assert_eq!(
+            health_report("/static/a1b2c3/images/48x48/health-80plus.png")
+                .absolute_icon_url(&jenkins_client),
+            "http://none:8080/static/a1b2c3/images/48x48/health-80plus.png"
+        );
+        assert_eq!(
+            health_report("http://cdn/health-80plus.png").absolute_icon_url(&jenkins_client),
+            "http://cdn/health-80plus.png"
+        );
+    }
+
+    #[test]
+    fn can_get_worst_color() {
+        assert_eq!(
+            BallColor::worst(vec![
+                BallColor::Blue,
+                BallColor::RedAnime,
+                BallColor::Yellow
+            ]),
+            Some(BallColor::Red)
+        );
+        assert_eq!(
+            BallColor::worst(vec![BallColor::Blue, BallColor::YellowAnime]),
+            Some(BallColor::Yellow)
+        );
+        assert_eq!(
+            BallColor::worst(vec![
+                BallColor::Disabled,
+                BallColor::NotBuilt,
+                BallColor::Blue
+            ]),
+            Some(BallColor::Blue)
+        );
+        assert_eq!(BallColor::worst(vec![]), None);
+        assert!(BallColor::AbortedAnime.is_animated());
+        assert!(!BallColor::Aborted.is_animated());
+    }
+
+    #[test]
+    fn can_get_status_of_color() {
+        use crate::build::BuildStatus;
+
+        assert_eq!(BallColor::Blue.status(), Some(BuildStatus::Success));
+        assert_eq!(BallColor::YellowAnime.status(), Some(BuildStatus::Unstable));
+        assert_eq!(BallColor::RedAnime.status(), Some(BuildStatus::Failure));
+        assert_eq!(BallColor::AbortedAnime.status(), Some(BuildStatus::Aborted));
+        assert_eq!(BallColor::NotBuilt.status(), Some(BuildStatus::NotBuilt));
+        assert_eq!(BallColor::Grey.status(), None);
+        assert_eq!(BallColor::DisabledAnime.status(), None);
+        assert!(BallColor::BlueAnime.is_building());
+        assert!(!BallColor::Blue.is_building());
+    }
+
+    #[test]
+    fn can_deserialize_items_without_color() {
+        let jobs: Vec<ShortJob> = serde_json::from_str(
+            r#"[
+                {"_class": "com.cloudbees.hudson.plugins.folder.Folder", "name": "folder",
+                 "url": "http://none:8080/job/folder/"},
+                {"_class": "com.cloudbees.hudson.plugins.folder.Folder", "name": "null",
+                 "url": "http://none:8080/job/null/", "color": null},
+                {"_class": "hudson.model.FreeStyleProject", "name": "job",
+                 "url": "http://none:8080/job/job/", "color": "red_anime"},
+                {"_class": "hudson.model.FreeStyleProject", "name": "new",
+                 "url": "http://none:8080/job/new/", "color": "purple"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            jobs.iter().map(|job| job.color).collect::<Vec<_>>(),
+            vec![
+                BallColor::NotBuilt,
+                BallColor::NotBuilt,
+                BallColor::RedAnime,
+                BallColor::Unknown
+            ]
+        );
+        assert_eq!(BallColor::Unknown.status(), None);
+        assert_eq!(
+            BallColor::worst(vec![BallColor::Unknown, BallColor::Disabled]),
+            Some(BallColor::Unknown)
+        );
+    }
 }
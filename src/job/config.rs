@@ -0,0 +1,379 @@
+//! Reading and writing the `config.xml` of jobs, and provisioning jobs idempotently
+
+use failure::Error;
+use regex::{Captures, Regex};
+use reqwest::{Response, StatusCode};
+
+use super::JobName;
+use crate::client;
+use crate::client_internals::{Name, Path};
+use crate::helpers::is_not_found;
+use crate::Jenkins;
+
+/// What to do in `Jenkins::get_or_create_job` when the job already exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnExists {
+    /// Keep the job as it is
+    LeaveAlone,
+    /// Replace the configuration of the job if it differs from the one given
+    UpdateIfDifferent,
+    /// Return an [`Error::JobAlreadyExists`](../client/enum.Error.html#variant.JobAlreadyExists)
+    Fail,
+}
+
+/// What `Jenkins::get_or_create_job` did
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GetOrCreateOutcome {
+    /// The job didn't exist and was created
+    Created,
+    /// The job existed and was left alone
+    LeftAlone,
+    /// The job existed with the same configuration
+    Unchanged,
+    /// The job existed with a different configuration, that was replaced
+    Updated,
+}
+
+/// Normalize a `config.xml` to compare it with another, ignoring the differences Jenkins
+/// introduces when saving a configuration: the XML declaration, line endings, whitespace
+/// between elements, empty elements written as `<a></a>` or `<a/>`, and the `plugin`
+/// attributes recording plugin versions
+fn normalize_config(config_xml: &str) -> String {
+    let declaration = Regex::new(r"^\s*<\?xml[^>]*\?>").unwrap();
+    let between_elements = Regex::new(r">\s+<").unwrap();
+    let plugin_attribute = Regex::new(r#"\s+plugin="[^"]*""#).unwrap();
+    let empty_element =
+        Regex::new(r"<([A-Za-z_][\w.\-]*)((?:\s[^<>]*?)?)></([A-Za-z_][\w.\-]*)>").unwrap();
+
+    let config_xml = config_xml.replace("\r\n", "\n");
+    let config_xml = declaration.replace(&config_xml, "");
+    let config_xml = between_elements.replace_all(&config_xml, "><");
+    let config_xml = plugin_attribute.replace_all(&config_xml, "");
+    empty_element
+        .replace_all(&config_xml, |captures: &Captures| {
+            if captures[1] == captures[3] {
+                format!("<{}{}/>", &captures[1], &captures[2])
+            } else {
+                captures[0].to_string()
+            }
+        })
+        .trim()
+        .to_string()
+}
+
+/// Is a response to `createItem` Jenkins refusing to create an item that already exists
+fn is_already_exists(response: &mut Response) -> Result<bool, Error> {
+    if response.status() != StatusCode::BAD_REQUEST {
+        return Ok(false);
+    }
+    let in_header = response
+        .headers()
+        .get("X-Error")
+        .and_then(|error| error.to_str().ok())
+        .map(|error| error.contains("already exists"))
+        .unwrap_or(false);
+    Ok(in_header || response.text()?.contains("already exists"))
+}
+
+impl Jenkins {
+    /// Get the `config.xml` of the job `job_name`
+    pub fn get_job_config<'a, J>(&self, job_name: J) -> Result<String, Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        Ok(self
+            .get_file(&Path::JobConfig {
+                name: Name::Name(job_name.into().0),
+            })?
+            .text()?)
+    }
+
+    /// Replace the `config.xml` of the job `job_name`
+    pub fn update_job_config<'a, J>(&self, job_name: J, config_xml: &str) -> Result<(), Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        let response = self.post_xml_unchecked(
+            &Path::JobConfig {
+                name: Name::Name(job_name.into().0),
+            },
+            config_xml,
+            &[],
+        )?;
+        let _ = response.error_for_status()?;
+        Ok(())
+    }
+
+    /// Create the job `job_name` from a `config.xml`. A job in a folder is created with a name
+    /// like `folder/job`, the folder must already exist
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::JobAlreadyExists`](../client/enum.Error.html#variant.JobAlreadyExists)
+    /// if there is already a job with that name
+    pub fn create_job<'a, J>(&self, job_name: J, config_xml: &str) -> Result<(), Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        let job_name = job_name.into().0;
+        let (parent, name) = match job_name.rfind('/') {
+            Some(index) => (Some(Name::Name(&job_name[..index])), &job_name[index + 1..]),
+            None => (None, job_name),
+        };
+        let mut response =
+            self.post_xml_unchecked(&Path::CreateItem { parent }, config_xml, &[("name", name)])?;
+        if is_already_exists(&mut response)? {
+            return Err(client::Error::JobAlreadyExists {
+                job_name: job_name.to_string(),
+            }
+            .into());
+        }
+        let _ = response.error_for_status()?;
+        Ok(())
+    }
+
+    /// Get the `config.xml` of the job `job_name`, or `None` if there is no such job
+    fn job_config_if_exists(&self, job_name: &str) -> Result<Option<String>, Error> {
+        match self.get_job_config(job_name) {
+            Ok(config_xml) => Ok(Some(config_xml)),
+            Err(ref error) if is_not_found(error) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Apply `on_exists` to the existing job `job_name`, whose configuration is `current`
+    fn apply_on_exists(
+        &self,
+        job_name: &str,
+        config_xml: &str,
+        current: &str,
+        on_exists: OnExists,
+    ) -> Result<GetOrCreateOutcome, Error> {
+        match on_exists {
+            OnExists::LeaveAlone => Ok(GetOrCreateOutcome::LeftAlone),
+            OnExists::Fail => Err(client::Error::JobAlreadyExists {
+                job_name: job_name.to_string(),
+            }
+            .into()),
+            OnExists::UpdateIfDifferent => {
+                if normalize_config(current) == normalize_config(config_xml) {
+                    Ok(GetOrCreateOutcome::Unchanged)
+                } else {
+                    self.update_job_config(job_name, config_xml)?;
+                    Ok(GetOrCreateOutcome::Updated)
+                }
+            }
+        }
+    }
+
+    /// Create the job `job_name` from a `config.xml` if it doesn't exist, or apply `on_exists`
+    /// to it. When comparing configurations, differences Jenkins introduces when saving one, like
+    /// whitespace or plugin versions, are ignored
+    ///
+    /// If another client creates the job between the check that it exists and its creation,
+    /// `on_exists` is applied to the job it created
+    pub fn get_or_create_job<'a, J>(
+        &self,
+        job_name: J,
+        config_xml: &str,
+        on_exists: OnExists,
+    ) -> Result<GetOrCreateOutcome, Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        let job_name = job_name.into().0;
+        if let Some(current) = self.job_config_if_exists(job_name)? {
+            return self.apply_on_exists(job_name, config_xml, &current, on_exists);
+        }
+        match self.create_job(job_name, config_xml) {
+            Ok(()) => Ok(GetOrCreateOutcome::Created),
+            Err(error) => match error.downcast::<client::Error>() {
+                Ok(client::Error::JobAlreadyExists { .. }) => {
+                    let current = self.get_job_config(job_name)?;
+                    self.apply_on_exists(job_name, config_xml, &current, on_exists)
+                }
+                Ok(error) => Err(error.into()),
+                Err(error) => Err(error),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CONFIG: &str = r#"<?xml version='1.0' encoding='UTF-8'?>
+<project>
+  <description>Built by provisioning</description>
+  <keepDependencies>false</keepDependencies>
+  <properties></properties>
+  <scm class="hudson.scm.NullSCM"/>
+  <builders>
+    <hudson.tasks.Shell>
+      <command>make
+make test</command>
+    </hudson.tasks.Shell>
+  </builders>
+</project>
+"#;
+
+    static SAVED_CONFIG: &str = "<?xml version='1.1' encoding='UTF-8'?>\r\n<project>\r\n  \
+        <description>Built by provisioning</description>\r\n  <keepDependencies>false\
+        </keepDependencies>\r\n  <properties/>\r\n  <scm class=\"hudson.scm.NullSCM\"/>\r\n  \
+        <builders>\r\n    <hudson.tasks.Shell plugin=\"shell@1.0\">\r\n      <command>make\r\n\
+        make test</command>\r\n    </hudson.tasks.Shell>\r\n  </builders>\r\n</project>";
+
+    static OTHER_CONFIG: &str = r#"<?xml version='1.1' encoding='UTF-8'?>
+<project>
+  <description>Changed by hand</description>
+  <keepDependencies>false</keepDependencies>
+  <properties/>
+  <scm class="hudson.scm.NullSCM"/>
+  <builders/>
+</project>"#;
+
+    #[test]
+    fn configs_saved_by_jenkins_are_the_same() {
+        assert_eq!(normalize_config(CONFIG), normalize_config(SAVED_CONFIG));
+        assert_ne!(normalize_config(CONFIG), normalize_config(OTHER_CONFIG));
+    }
+
+    #[test]
+    fn normalization_keeps_text() {
+        assert_eq!(
+            normalize_config("<a>\n  <b>x  y</b>\n  <c></c>\n  <d></e>\n</a>"),
+            "<a><b>x  y</b><c/><d></e></a>"
+        );
+        assert_ne!(
+            normalize_config("<a><b>make</b></a>"),
+            normalize_config("<a><b>make test</b></a>")
+        );
+    }
+
+    fn client(base: &str) -> Jenkins {
+        crate::JenkinsBuilder::new(&format!("{}/{}", mockito::server_url(), base))
+            .disable_csrf()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn creates_missing_job() {
+        let jenkins_client = client("provision-missing");
+        let _missing = mockito::mock("GET", "/provision-missing/job/folder/job/job/config.xml")
+            .with_status(404)
+            .create();
+        let create = mockito::mock("POST", "/provision-missing/job/folder/createItem?name=job")
+            .match_header("content-type", "application/xml")
+            .match_body(CONFIG)
+            .create();
+
+        assert_eq!(
+            jenkins_client
+                .get_or_create_job("folder/job", CONFIG, OnExists::Fail)
+                .unwrap(),
+            GetOrCreateOutcome::Created
+        );
+        create.assert();
+    }
+
+    #[test]
+    fn applies_policy_to_existing_job() {
+        let jenkins_client = client("provision-existing");
+        let _same = mockito::mock("GET", "/provision-existing/job/same/config.xml")
+            .with_body(SAVED_CONFIG)
+            .create();
+        let _other = mockito::mock("GET", "/provision-existing/job/other/config.xml")
+            .with_body(OTHER_CONFIG)
+            .create();
+        let update_same = mockito::mock("POST", "/provision-existing/job/same/config.xml")
+            .expect(0)
+            .create();
+        let update_other = mockito::mock("POST", "/provision-existing/job/other/config.xml")
+            .match_body(CONFIG)
+            .expect(1)
+            .create();
+        let create = mockito::mock("POST", mockito::Matcher::Regex("createItem".to_string()))
+            .expect(0)
+            .create();
+
+        for name in &["same", "other"] {
+            assert_eq!(
+                jenkins_client
+                    .get_or_create_job(*name, CONFIG, OnExists::LeaveAlone)
+                    .unwrap(),
+                GetOrCreateOutcome::LeftAlone
+            );
+        }
+        assert_eq!(
+            jenkins_client
+                .get_or_create_job("same", CONFIG, OnExists::UpdateIfDifferent)
+                .unwrap(),
+            GetOrCreateOutcome::Unchanged
+        );
+        assert_eq!(
+            jenkins_client
+                .get_or_create_job("other", CONFIG, OnExists::UpdateIfDifferent)
+                .unwrap(),
+            GetOrCreateOutcome::Updated
+        );
+        match jenkins_client
+            .get_or_create_job("same", CONFIG, OnExists::Fail)
+            .unwrap_err()
+            .downcast::<client::Error>()
+        {
+            Ok(client::Error::JobAlreadyExists { job_name }) => assert_eq!(job_name, "same"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        update_same.assert();
+        update_other.assert();
+        create.assert();
+    }
+
+    #[test]
+    fn applies_policy_to_job_created_concurrently() {
+        use crate::helpers::test_server::{response, serve_in_order};
+
+        let already_exists = response(
+            "400 Bad Request",
+            "X-Error: A job already exists with the name 'job'\r\n",
+            "",
+        );
+        let url = serve_in_order(vec![
+            response("404 Not Found", "", ""),
+            already_exists.clone(),
+            response("200 OK", "", OTHER_CONFIG),
+            response("200 OK", "", ""),
+            response("404 Not Found", "", ""),
+            already_exists,
+            response("200 OK", "", OTHER_CONFIG),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url)
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            jenkins_client
+                .get_or_create_job("job", CONFIG, OnExists::UpdateIfDifferent)
+                .unwrap(),
+            GetOrCreateOutcome::Updated
+        );
+        assert!(jenkins_client
+            .get_or_create_job("job", CONFIG, OnExists::Fail)
+            .is_err());
+    }
+
+    #[test]
+    fn create_fails_on_other_errors() {
+        let jenkins_client = client("provision-invalid");
+        let _create = mockito::mock("POST", "/provision-invalid/createItem?name=job")
+            .with_status(400)
+            .with_header("X-Error", "No such job type")
+            .create();
+
+        let error = jenkins_client.create_job("job", CONFIG).unwrap_err();
+        assert!(error.downcast_ref::<client::Error>().is_none());
+    }
+}
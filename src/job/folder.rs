@@ -0,0 +1,173 @@
+use failure::Error;
+use serde::Deserialize;
+
+use crate::helpers::{map_concurrently, Class};
+
+use super::{BallColor, CommonJob, HealthReport, Job, ShortJob};
+use crate::action::CommonAction;
+use crate::client;
+use crate::client_internals::{Name, Path};
+use crate::view::ShortView;
+use crate::Jenkins;
+
+macro_rules! folder_with_common_fields_and_impl {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                pub $field:ident: $field_type:ty,
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        pub struct $name {
+            /// Name of the folder
+            pub name: String,
+            /// Display Name of the folder
+            pub display_name: String,
+            /// Full Display Name of the folder
+            pub full_display_name: Option<String>,
+            /// Full Name of the folder, including its parent folders
+            pub full_name: Option<String>,
+            /// Display Name of the folder
+            pub display_name_or_null: Option<String>,
+            /// URL for the folder
+            pub url: String,
+            /// Description of the folder
+            pub description: Option<String>,
+            /// Actions of the folder
+            #[serde(default)]
+            pub actions: Vec<Option<CommonAction>>,
+            /// HealthReport of the folder
+            #[serde(default)]
+            pub health_report: Vec<HealthReport>,
+            /// Jobs in the folder
+            #[serde(default)]
+            pub jobs: Vec<ShortJob>,
+            /// Primary view of the folder
+            pub primary_view: Option<ShortView>,
+            /// Views of the folder
+            #[serde(default)]
+            pub views: Vec<ShortView>,
+            $(
+                $(#[$field_attr])*
+                pub $field: $field_type,
+            )*
+        }
+        impl Job for $name {
+            fn url(&self) -> &str {
+                &self.url
+            }
+
+            fn name(&self) -> &str {
+                &self.name
+            }
+        }
+        impl $name {
+            /// Get the worst color of the jobs in this folder and its sub folders, as ordered
+            /// by `BallColor::severity`: red, then yellow, aborted, blue, not built and grey,
+            /// and disabled. A folder without jobs is `NotBuilt`
+            pub fn aggregate_color(&self, jenkins_client: &Jenkins) -> Result<BallColor, Error> {
+                crate::job::folder::aggregate_color(jenkins_client, &self.url)
+            }
+        }
+    };
+}
+
+/// An item of a folder, only with its url and color. Items without a color are folders
+#[derive(Deserialize, Debug)]
+struct FolderItem {
+    url: String,
+    color: Option<BallColor>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FolderItems {
+    #[serde(default)]
+    jobs: Vec<FolderItem>,
+}
+
+pub(crate) fn aggregate_color(jenkins_client: &Jenkins, url: &str) -> Result<BallColor, Error> {
+    let path = jenkins_client.url_to_path(url);
+    if let Path::Job { .. } = path {
+        let items: FolderItems = jenkins_client
+            .get_with_params(&path, [("tree", "jobs[url,color]")])?
+            .json()?;
+        let mut colors = vec![];
+        for item in items.jobs {
+            colors.push(match item.color {
+                Some(color) => color,
+                None => aggregate_color(jenkins_client, &item.url)?,
+            });
+        }
+        Ok(BallColor::worst(colors).unwrap_or(BallColor::NotBuilt))
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: url.to_string(),
+            expected: client::error::ExpectedType::Job,
+        }
+        .into())
+    }
+}
+
+impl Jenkins {
+    fn list_folder_items(&self, path: &Path) -> Result<Vec<FolderItem>, Error> {
+        let items: FolderItems = self
+            .get_with_params(path, [("tree", "jobs[url,color]")])?
+            .json()?;
+        Ok(items.jobs)
+    }
+
+    /// Get all the jobs in the folder `root`, or in Jenkins, and in their sub folders. Folders
+    /// of the same level are listed concurrently, then the jobs are fetched concurrently,
+    /// sending at most `max_concurrency` requests at the same time
+    pub fn crawl_jobs(
+        &self,
+        root: Option<&str>,
+        max_concurrency: usize,
+    ) -> Result<Vec<CommonJob>, Error> {
+        let root = match root {
+            Some(name) => Path::Job {
+                name: Name::Name(name),
+                configuration: None,
+            },
+            None => Path::Home,
+        };
+        let mut items = self.list_folder_items(&root)?;
+        let mut jobs = vec![];
+        while !items.is_empty() {
+            let (leaves, folders): (Vec<FolderItem>, Vec<FolderItem>) =
+                items.into_iter().partition(|item| item.color.is_some());
+            jobs.extend(leaves.into_iter().map(|item| item.url));
+            items = vec![];
+            for folder_items in map_concurrently(&folders, max_concurrency, |folder| {
+                self.list_folder_items(&self.url_to_path(&folder.url))
+            }) {
+                items.extend(folder_items?);
+            }
+        }
+        map_concurrently(&jobs, max_concurrency, |url| {
+            Ok(self.get(&self.url_to_path(url))?.json()?)
+        })
+        .into_iter()
+        .collect()
+    }
+}
+
+folder_with_common_fields_and_impl!(
+    /// A folder, from the CloudBees Folders plugin, grouping other `Job`
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Folder {}
+);
+register_class!("com.cloudbees.hudson.plugins.folder.Folder" => Folder);
+
+folder_with_common_fields_and_impl!(
+    /// An organization folder, from the Branch API plugin, with a `Job` for each repository
+    /// found when scanning an organization
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OrganizationFolder {}
+);
+register_class!("jenkins.branch.OrganizationFolder" => OrganizationFolder);
@@ -1,13 +1,29 @@
 //! Jenkins Jobs
 
 use failure::Error;
+use serde::Deserialize;
 
-use crate::client_internals::{Name, Path};
+use crate::client_internals::{InternalAdvancedQueryParams, Name, Path, TreeQueryParam};
 use crate::queue::ShortQueueItem;
 use crate::Jenkins;
 
+mod abort;
+pub use self::abort::{AbortOptions, AbortOutcome, AbortReport, BuildAbortResult};
 pub mod builder;
 use self::builder::JobBuilder;
+mod build_iterator;
+pub use self::build_iterator::{BuildIterator, DEFAULT_PAGE_SIZE};
+mod bulk;
+pub use self::bulk::{BulkOptions, BulkReport, JobFilter, JobOutcome, JobResult, UndoList};
+mod config;
+pub use self::config::{GetOrCreateOutcome, OnExists};
+mod parameters_file;
+mod polling;
+pub use self::polling::PollResult;
+mod recent_builds;
+pub use self::recent_builds::{OrderingAnomaly, RecentBuilds};
+mod runnability;
+pub use self::runnability::{MatchingNode, Runnability, RunnabilityReport};
 
 #[macro_use]
 mod common;
@@ -28,6 +44,11 @@ mod multijob;
 pub use self::multijob::MultiJobProject;
 mod external;
 pub use self::external::ExternalJob;
+#[macro_use]
+mod folder;
+pub use self::folder::{Folder, OrganizationFolder};
+mod multibranch;
+pub use self::multibranch::{BranchSource, BranchSummary, WorkflowMultiBranchProject};
 
 impl Jenkins {
     /// Get a `Job` from it's `job_name`
@@ -44,6 +65,58 @@ impl Jenkins {
         // self.get_job_as(job_name, None)
     }
 
+    /// Get a `Job` from it's `job_name`, overriding the depth set when building the client
+    pub fn get_job_with_depth<'a, J>(&self, job_name: J, depth: u8) -> Result<CommonJob, Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        Ok(self
+            .get_with_depth(
+                &Path::Job {
+                    name: Name::Name(job_name.into().0),
+                    configuration: None,
+                },
+                depth,
+            )?
+            .json()?)
+    }
+
+    /// Get a `Job` from it's `job_name`, with only the fields selected by `tree`
+    ///
+    /// As the response only contains the selected fields, it should be read as a type whose
+    /// other fields are optional or have a default value, or as a `CommonJob` if `tree`
+    /// includes at least `name`, `displayName`, `url`, `buildable`, `builds`, `inQueue`,
+    /// `keepDependencies`, `nextBuildNumber`, `concurrentBuild` and `scm`
+    ///
+    /// ```rust
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct JobName {
+    ///     name: String,
+    /// }
+    ///
+    /// # fn example(jenkins: &jenkins_api::Jenkins) -> Result<(), failure::Error> {
+    /// let job: JobName = jenkins.get_job_with_tree("job name", "name")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_job_with_tree<'a, J, Q, T>(&self, job_name: J, tree: Q) -> Result<T, Error>
+    where
+        J: Into<JobName<'a>>,
+        Q: Into<TreeQueryParam>,
+        for<'de> T: Deserialize<'de>,
+    {
+        Ok(self
+            .get_with_params(
+                &Path::Job {
+                    name: Name::Name(job_name.into().0),
+                    configuration: None,
+                },
+                InternalAdvancedQueryParams::from(crate::client::AdvancedQuery::Tree(tree.into())),
+            )?
+            .json()?)
+    }
+
     /// Build a `Job` from it's `job_name`
     pub fn build_job<'a, J>(&self, job_name: J) -> Result<ShortQueueItem, Error>
     where
@@ -60,14 +133,575 @@ impl Jenkins {
         JobBuilder::new_from_job_name(job_name, self)
     }
 
+    /// Set the description of a `Job` from it's `job_name`. The description can contain HTML
+    pub fn set_job_description<'a, J>(&self, job_name: J, description: &str) -> Result<(), Error>
+    where
+        J: Into<JobName<'a>>,
+    {
+        self.submit_job_description(Name::Name(job_name.into().0), description)
+    }
+
+    pub(crate) fn submit_job_description(
+        &self,
+        name: Name<'_>,
+        description: &str,
+    ) -> Result<(), Error> {
+        let body = serde_urlencoded::to_string([("description", description)])?;
+        let _ = self.post_with_body(&Path::SubmitJobDescription { name }, body, &[])?;
+        Ok(())
+    }
+
     /// Poll SCM of a `Job` from it's `job_name`
     pub fn poll_scm_job<'a, J>(&self, job_name: J) -> Result<(), Error>
     where
         J: Into<JobName<'a>>,
     {
-        let _ = self.post(&Path::PollSCMJob {
+        let _ = self.post_idempotent(&Path::PollSCMJob {
             name: Name::Name(job_name.into().0),
         })?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use super::{BallColor, Folder, Job, OrganizationFolder};
+
+    #[test]
+    fn can_act_on_job_in_folder() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .disable_csrf()
+            .build()
+            .unwrap();
+
+        let _folder = mockito::mock("GET", "/job/my%20folder/api/json?depth=1")
+            .with_body(format!(
+                r#"{{
+                    "_class": "com.cloudbees.hudson.plugins.folder.Folder",
+                    "name": "my folder",
+                    "displayName": "my folder",
+                    "fullName": "my folder",
+                    "url": "{url}/job/my%20folder/",
+                    "jobs": [{{
+                        "_class": "hudson.model.FreeStyleProject",
+                        "name": "inner",
+                        "url": "{url}/job/my%20folder/job/inner/",
+                        "color": "blue"
+                    }}]
+                }}"#,
+                url = mockito::server_url()
+            ))
+            .create();
+        let _inner = mockito::mock("GET", "/job/my%20folder/job/inner/api/json?depth=1")
+            .with_body(format!(
+                r#"{{
+                    "_class": "hudson.model.FreeStyleProject",
+                    "name": "inner",
+                    "displayName": "inner",
+                    "url": "{url}/job/my%20folder/job/inner/",
+                    "color": "blue",
+                    "buildable": true,
+                    "keepDependencies": false,
+                    "nextBuildNumber": 1,
+                    "inQueue": false
+                }}"#,
+                url = mockito::server_url()
+            ))
+            .create();
+        let enable = mockito::mock("POST", "/job/my%20folder/job/inner/enable").create();
+        let build = mockito::mock("POST", "/job/my%20folder/job/inner/build")
+            .with_status(201)
+            .with_header(
+                "Location",
+                &format!("{}/queue/item/1/", mockito::server_url()),
+            )
+            .create();
+
+        let folder = jenkins_client
+            .get_job("my folder")
+            .unwrap()
+            .as_variant::<Folder>()
+            .unwrap();
+        assert_eq!(folder.jobs.len(), 1);
+
+        let inner = folder.jobs[0].get_full_job(&jenkins_client).unwrap();
+        assert_eq!(inner.name, "inner");
+        inner.enable(&jenkins_client).unwrap();
+        let _ = jenkins_client.get_job("my folder/inner").unwrap();
+        let _ = jenkins_client.build_job("my folder/inner").unwrap();
+
+        enable.assert();
+        build.assert();
+    }
+
+    #[test]
+    fn can_walk_organization_folder_to_branch_job() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .build()
+            .unwrap();
+        let url = mockito::server_url();
+
+        let _organization = mockito::mock("GET", "/job/my-org/api/json?depth=1")
+            .with_body(format!(
+                r#"{{
+                    "_class": "jenkins.branch.OrganizationFolder",
+                    "actions": [
+                        {{}},
+                        {{"_class": "jenkins.branch.OrganizationFolder$OrganizationScan"}},
+                        {{"_class": "com.cloudbees.plugins.credentials.ViewCredentialsAction"}}
+                    ],
+                    "description": null,
+                    "displayName": "my-org",
+                    "displayNameOrNull": null,
+                    "fullDisplayName": "my-org",
+                    "fullName": "my-org",
+                    "name": "my-org",
+                    "url": "{url}/job/my-org/",
+                    "healthReport": [],
+                    "jobs": [
+                        {{
+                            "_class": "org.jenkinsci.plugins.workflow.multibranch.WorkflowMultiBranchProject",
+                            "name": "my-repo",
+                            "url": "{url}/job/my-org/job/my-repo/"
+                        }}
+                    ],
+                    "primaryView": {{
+                        "_class": "jenkins.branch.OrganizationFolderViewHolder$ViewImpl",
+                        "name": "Repositories",
+                        "url": "{url}/job/my-org/"
+                    }},
+                    "views": [
+                        {{
+                            "_class": "jenkins.branch.OrganizationFolderViewHolder$ViewImpl",
+                            "name": "Repositories",
+                            "url": "{url}/job/my-org/"
+                        }}
+                    ]
+                }}"#,
+                url = url
+            ))
+            .create();
+        let _repository = mockito::mock("GET", "/job/my-org/job/my-repo/api/json?depth=1")
+            .with_body(format!(
+                r#"{{
+                    "_class": "org.jenkinsci.plugins.workflow.multibranch.WorkflowMultiBranchProject",
+                    "displayName": "my-repo",
+                    "fullName": "my-org/my-repo",
+                    "name": "my-repo",
+                    "url": "{url}/job/my-org/job/my-repo/",
+                    "jobs": [
+                        {{
+                            "_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob",
+                            "name": "master",
+                            "url": "{url}/job/my-org/job/my-repo/job/master/",
+                            "color": "blue"
+                        }}
+                    ]
+                }}"#,
+                url = url
+            ))
+            .create();
+        let _branch = mockito::mock("GET", "/job/my-org/job/my-repo/job/master/api/json?depth=1")
+            .with_body(format!(
+                r#"{{
+                    "_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob",
+                    "displayName": "master",
+                    "fullName": "my-org/my-repo/master",
+                    "name": "master",
+                    "url": "{url}/job/my-org/job/my-repo/job/master/",
+                    "color": "blue",
+                    "buildable": true,
+                    "keepDependencies": false,
+                    "nextBuildNumber": 2,
+                    "inQueue": false
+                }}"#,
+                url = url
+            ))
+            .create();
+
+        let organization = jenkins_client
+            .get_job("my-org")
+            .unwrap()
+            .as_variant::<OrganizationFolder>()
+            .unwrap();
+        assert_eq!(organization.jobs.len(), 1);
+        assert!(organization.health_report.is_empty());
+
+        let repository = organization.jobs[0].get_full_job(&jenkins_client).unwrap();
+        assert_eq!(repository.full_name, Some("my-org/my-repo".to_string()));
+
+        let branch = jenkins_client
+            .get_job(&format!("{}/master", repository.full_name.unwrap()))
+            .unwrap();
+        assert_eq!(branch.name, "master");
+    }
+
+    #[test]
+    fn unknown_organization_folder_subclass_is_kept_as_common_job() {
+        let job: super::CommonJob = serde_json::from_str(
+            r#"{
+                "_class": "com.example.branch.CustomOrganizationFolder",
+                "name": "my-org",
+                "displayName": "my-org",
+                "url": "http://none/job/my-org/",
+                "jobs": [{"name": "my-repo", "url": "http://none/job/my-org/job/my-repo/"}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(job.name, "my-org");
+        assert!(job.as_variant::<OrganizationFolder>().is_err());
+    }
+
+    #[test]
+    fn can_aggregate_color_of_nested_folders() {
+        let url = format!("{}/aggregate", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let folder: Folder = serde_json::from_str(&format!(
+            r#"{{
+                "_class": "com.cloudbees.hudson.plugins.folder.Folder",
+                "name": "team",
+                "displayName": "team",
+                "url": "{url}/job/team/"
+            }}"#,
+            url = url
+        ))
+        .unwrap();
+
+        let _team = mockito::mock("GET", "/aggregate/job/team/api/json?tree=jobs%5Burl%2Ccolor%5D")
+            .with_body(format!(
+                r#"{{"jobs": [
+                    {{"_class": "hudson.model.FreeStyleProject", "url": "{url}/job/team/job/a/", "color": "blue_anime"}},
+                    {{"_class": "hudson.model.FreeStyleProject", "url": "{url}/job/team/job/b/", "color": "disabled"}},
+                    {{"_class": "com.cloudbees.hudson.plugins.folder.Folder", "url": "{url}/job/team/job/sub/"}}
+                ]}}"#,
+                url = url
+            ))
+            .create();
+        let _sub = mockito::mock(
+            "GET",
+            "/aggregate/job/team/job/sub/api/json?tree=jobs%5Burl%2Ccolor%5D",
+        )
+        .with_body(format!(
+            r#"{{"jobs": [
+                {{"_class": "hudson.model.FreeStyleProject", "url": "{url}/job/team/job/sub/job/c/", "color": "yellow"}},
+                {{"_class": "com.cloudbees.hudson.plugins.folder.Folder", "url": "{url}/job/team/job/sub/job/empty/"}}
+            ]}}"#,
+            url = url
+        ))
+        .create();
+        let _empty = mockito::mock(
+            "GET",
+            "/aggregate/job/team/job/sub/job/empty/api/json?tree=jobs%5Burl%2Ccolor%5D",
+        )
+        .with_body(r#"{"jobs": []}"#)
+        .create();
+
+        assert_eq!(
+            folder.aggregate_color(&jenkins_client).unwrap(),
+            BallColor::Yellow
+        );
+    }
+
+    #[test]
+    fn can_crawl_jobs_of_folder_tree() {
+        let url = format!("{}/crawl", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+
+        let _home = mockito::mock("GET", "/crawl/api/json?tree=jobs%5Burl%2Ccolor%5D")
+            .with_body(format!(
+                r#"{{"jobs": [
+                    {{"_class": "hudson.model.FreeStyleProject", "url": "{url}/job/a/", "color": "blue"}},
+                    {{"_class": "com.cloudbees.hudson.plugins.folder.Folder", "url": "{url}/job/team/"}}
+                ]}}"#,
+                url = url
+            ))
+            .create();
+        let _team = mockito::mock("GET", "/crawl/job/team/api/json?tree=jobs%5Burl%2Ccolor%5D")
+            .with_body(format!(
+                r#"{{"jobs": [
+                    {{"_class": "hudson.model.FreeStyleProject", "url": "{url}/job/team/job/b/", "color": "red"}},
+                    {{"_class": "com.cloudbees.hudson.plugins.folder.Folder", "url": "{url}/job/team/job/empty/"}}
+                ]}}"#,
+                url = url
+            ))
+            .create();
+        let _empty = mockito::mock(
+            "GET",
+            "/crawl/job/team/job/empty/api/json?tree=jobs%5Burl%2Ccolor%5D",
+        )
+        .with_body(r#"{"jobs": []}"#)
+        .create();
+        let mut job_mocks = vec![];
+        for (path, name) in &[("/crawl/job/a", "a"), ("/crawl/job/team/job/b", "b")] {
+            job_mocks.push(
+                mockito::mock("GET", &format!("{}/api/json?depth=1", path)[..])
+                    .with_body(format!(
+                        r#"{{
+                            "_class": "hudson.model.FreeStyleProject",
+                            "name": "{name}",
+                            "displayName": "{name}",
+                            "url": "{url}{path}/",
+                            "buildable": true,
+                            "builds": [],
+                            "color": "blue",
+                            "inQueue": false,
+                            "keepDependencies": false,
+                            "nextBuildNumber": 1,
+                            "concurrentBuild": false,
+                            "scm": {{}},
+                            "description": null,
+                            "property": [],
+                            "actions": [],
+                            "healthReport": [],
+                            "queueItem": null,
+                            "firstBuild": null,
+                            "lastBuild": null,
+                            "lastCompletedBuild": null,
+                            "lastFailedBuild": null,
+                            "lastStableBuild": null,
+                            "lastSuccessfulBuild": null,
+                            "lastUnstableBuild": null,
+                            "lastUnsuccessfulBuild": null,
+                            "upstreamProjects": [],
+                            "downstreamProjects": []
+                        }}"#,
+                        name = name,
+                        url = mockito::server_url(),
+                        path = path
+                    ))
+                    .create(),
+            );
+        }
+
+        let jobs = jenkins_client.crawl_jobs(None, 2).unwrap();
+        assert_eq!(
+            jobs.iter().map(|job| job.name()).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        for mock in job_mocks {
+            mock.assert();
+        }
+    }
+
+    #[test]
+    fn can_get_job_with_tree() {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PartialJob {
+            name: String,
+            #[serde(default)]
+            next_build_number: u32,
+        }
+
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/job-tree", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let mock = mockito::mock("GET", "/job-tree/job/big%20job/api/json?tree=name")
+            .with_body(r#"{"_class": "hudson.model.FreeStyleProject", "name": "big job"}"#)
+            .create();
+        let job: PartialJob = jenkins_client.get_job_with_tree("big job", "name").unwrap();
+        mock.assert();
+        assert_eq!(job.name, "big job");
+        assert_eq!(job.next_build_number, 0);
+    }
+
+    #[test]
+    fn can_get_last_poll_result() {
+        use super::{FreeStyleProject, PollResult, SCMPollable};
+
+        let url = format!("{}/poll-result", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let job = |name: &str| -> FreeStyleProject {
+            serde_json::from_value(serde_json::json!({
+                "_class": "hudson.model.FreeStyleProject",
+                "name": name,
+                "displayName": name,
+                "url": format!("{}/job/{}/", url, name),
+                "buildable": true,
+                "builds": [],
+                "color": "blue",
+                "inQueue": false,
+                "keepDependencies": false,
+                "nextBuildNumber": 1,
+                "concurrentBuild": false,
+                "scm": {},
+                "description": "",
+                "property": [],
+                "actions": [],
+                "healthReport": [],
+                "queueItem": null,
+                "upstreamProjects": [],
+                "downstreamProjects": []
+            }))
+            .unwrap()
+        };
+
+        let _polled = mockito::mock("GET", "/poll-result/job/polled/scmPollLog/pollingLog")
+            .with_body("Started on Mar 1, 2019 10:00:00 AM\nDone. Took 0.4 sec\nNo changes\n")
+            .create();
+        let _never = mockito::mock("GET", "/poll-result/job/never/scmPollLog/pollingLog")
+            .with_status(404)
+            .create();
+
+        assert_eq!(
+            job("polled").last_poll_result(&jenkins_client).unwrap(),
+            PollResult::NoChanges {
+                started: Some("Mar 1, 2019 10:00:00 AM".to_string())
+            }
+        );
+        assert_eq!(
+            job("never").last_poll_result(&jenkins_client).unwrap(),
+            PollResult::NeverPolled
+        );
+
+        assert_eq!(
+            job("polled").get_scm_poll_log(&jenkins_client).unwrap(),
+            "Started on Mar 1, 2019 10:00:00 AM\nDone. Took 0.4 sec\nNo changes\n"
+        );
+        assert!(crate::helpers::is_not_found(
+            &job("never").get_scm_poll_log(&jenkins_client).unwrap_err()
+        ));
+    }
+
+    #[test]
+    fn can_set_job_description() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/describe-job", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let description = "<h2>Nightly</h2>\nOwned by <a href=\"mailto:team@example.com\">team</a>";
+        let job = |description: Option<&str>| {
+            serde_json::json!({
+                "_class": "hudson.model.FreeStyleProject",
+                "name": "normal job",
+                "displayName": "normal job",
+                "url": format!("{}/describe-job/job/normal%20job/", mockito::server_url()),
+                "description": description,
+                "color": "blue"
+            })
+            .to_string()
+        };
+        let submit = mockito::mock("POST", "/describe-job/job/normal%20job/submitDescription")
+            .match_header("content-type", "application/x-www-form-urlencoded")
+            .match_body(
+                serde_urlencoded::to_string([("description", description)])
+                    .unwrap()
+                    .as_str(),
+            )
+            .expect(2)
+            .create();
+        let _job = mockito::mock("GET", "/describe-job/job/normal%20job/api/json?depth=1")
+            .with_body(job(Some(description)))
+            .create();
+
+        jenkins_client
+            .set_job_description("normal job", description)
+            .unwrap();
+        let described: super::CommonJob = serde_json::from_str(&job(None)).unwrap();
+        described
+            .set_description(&jenkins_client, description)
+            .unwrap();
+        submit.assert();
+
+        let refreshed = jenkins_client.get_job("normal job").unwrap();
+        assert_eq!(refreshed.description.as_deref(), Some(description));
+    }
+
+    #[test]
+    fn falls_back_to_get_when_post_not_allowed() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/poll-fallback", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let post = mockito::mock("POST", "/poll-fallback/job/legacy/polling")
+            .with_status(405)
+            .with_header("Allow", "GET, HEAD")
+            .expect(1)
+            .create();
+        let get = mockito::mock("GET", "/poll-fallback/job/legacy/polling")
+            .expect(2)
+            .create();
+
+        assert!(jenkins_client.poll_scm_job("legacy").is_ok());
+        assert!(jenkins_client.poll_scm_job("legacy").is_ok());
+
+        post.assert();
+        get.assert();
+    }
+
+    #[test]
+    fn reports_fallback_not_allowed_either() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/poll-no-fallback", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let _post = mockito::mock("POST", "/poll-no-fallback/job/legacy/polling")
+            .with_status(405)
+            .with_header("Allow", "GET")
+            .create();
+        let _get = mockito::mock("GET", "/poll-no-fallback/job/legacy/polling")
+            .with_status(405)
+            .with_header("Allow", "PUT")
+            .create();
+
+        match jenkins_client
+            .poll_scm_job("legacy")
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::MethodNotAllowed { method, allow, .. }) => {
+                assert_eq!(method, "GET");
+                assert_eq!(allow, vec!["PUT".to_string()]);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn does_not_fall_back_on_unsafe_command() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/delete-no-fallback", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let _post = mockito::mock("POST", "/delete-no-fallback/view/old/doDelete")
+            .with_status(405)
+            .with_header("Allow", "GET, HEAD")
+            .create();
+        let get = mockito::mock("GET", "/delete-no-fallback/view/old/doDelete")
+            .expect(0)
+            .create();
+
+        match jenkins_client
+            .delete_view("old")
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::MethodNotAllowed {
+                url, method, allow, ..
+            }) => {
+                assert_eq!(
+                    url,
+                    format!(
+                        "{}/delete-no-fallback/view/old/doDelete",
+                        mockito::server_url()
+                    )
+                );
+                assert_eq!(method, "POST");
+                assert_eq!(allow, vec!["GET".to_string(), "HEAD".to_string()]);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        get.assert();
+    }
+}
@@ -0,0 +1,288 @@
+//! Pipeline multibranch projects
+
+use failure::Error;
+use serde::Deserialize;
+
+use crate::helpers::{map_concurrently, Class, MaybeTimestamp};
+
+use super::{BallColor, HealthReport, Job, ShortJob};
+use crate::action::CommonAction;
+use crate::build::BuildStatus;
+use crate::client;
+use crate::client_internals::Path;
+use crate::view::ShortView;
+use crate::Jenkins;
+
+/// Maximum number of builds read at the same time when summarizing branches
+const BRANCH_SUMMARY_CONCURRENCY: usize = 4;
+
+folder_with_common_fields_and_impl!(
+    /// A pipeline multibranch project, with a `WorkflowJob` for each branch found in a
+    /// repository
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct WorkflowMultiBranchProject {
+        /// Sources the branches are found in
+        #[serde(default)]
+        pub sources: Vec<BranchSource>,
+    }
+);
+register_class!("org.jenkinsci.plugins.workflow.multibranch.WorkflowMultiBranchProject" => WorkflowMultiBranchProject);
+
+/// A source of branches of a `WorkflowMultiBranchProject`, like a git repository
+#[derive(Deserialize, Debug, Clone)]
+pub struct BranchSource {
+    /// _class provided by Jenkins
+    #[serde(rename = "_class")]
+    pub class: Option<String>,
+}
+
+/// Summary of the last build of a branch of a `WorkflowMultiBranchProject`
+#[derive(Debug, Clone)]
+pub struct BranchSummary {
+    /// Name of the branch job
+    pub name: String,
+    /// URL of the branch job
+    pub url: String,
+    /// Is the branch job disabled, as done for branches removed from the repository
+    pub disabled: bool,
+    /// Number of the last build
+    pub last_build_number: Option<u32>,
+    /// Result of the last build
+    pub last_build_result: Option<BuildStatus>,
+    /// Timestamp of the last build, unknown if the branch was never built
+    pub last_build_timestamp: MaybeTimestamp,
+    /// SHA1 of the commit built by the last build, if it has git data
+    pub last_built_sha1: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BranchLastBuild {
+    number: u32,
+    url: String,
+    result: Option<BuildStatus>,
+    timestamp: MaybeTimestamp,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Branch {
+    name: String,
+    url: String,
+    color: Option<BallColor>,
+    last_build: Option<BranchLastBuild>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Branches {
+    #[serde(default)]
+    jobs: Vec<Branch>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BuiltRevision {
+    #[serde(rename = "SHA1")]
+    sha1: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RevisionAction {
+    last_built_revision: Option<BuiltRevision>,
+}
+
+#[derive(Deserialize, Debug)]
+struct BuildRevisions {
+    #[serde(default)]
+    actions: Vec<Option<RevisionAction>>,
+}
+
+impl WorkflowMultiBranchProject {
+    /// Get a summary of the last build of each branch, with the SHA1 of the commit built when
+    /// it is known
+    pub fn branch_summaries(&self, jenkins_client: &Jenkins) -> Result<Vec<BranchSummary>, Error> {
+        let path = jenkins_client.url_to_path(&self.url);
+        let branches: Branches = if let Path::Job { .. } = path {
+            jenkins_client
+                .get_with_params(
+                    &path,
+                    [(
+                        "tree",
+                        "jobs[name,url,color,lastBuild[number,url,result,timestamp]]",
+                    )],
+                )?
+                .json()?
+        } else {
+            return Err(client::Error::InvalidUrl {
+                url: self.url.clone(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into());
+        };
+
+        map_concurrently(&branches.jobs, BRANCH_SUMMARY_CONCURRENCY, |branch| {
+            let last_built_sha1 = match branch.last_build {
+                Some(ref last_build) => last_built_sha1(jenkins_client, &last_build.url)?,
+                None => None,
+            };
+            Ok(BranchSummary {
+                name: branch.name.clone(),
+                url: branch.url.clone(),
+                disabled: match branch.color {
+                    Some(color) => color.without_animation() == BallColor::Disabled,
+                    None => false,
+                },
+                last_build_number: branch.last_build.as_ref().map(|build| build.number),
+                last_build_result: branch.last_build.as_ref().and_then(|build| build.result),
+                last_build_timestamp: branch
+                    .last_build
+                    .as_ref()
+                    .map_or(MaybeTimestamp::Unknown, |build| build.timestamp),
+                last_built_sha1,
+            })
+        })
+        .into_iter()
+        .collect()
+    }
+}
+
+/// Get the SHA1 of the commit built by a build, from its first git data
+fn last_built_sha1(jenkins_client: &Jenkins, build_url: &str) -> Result<Option<String>, Error> {
+    let path = jenkins_client.url_to_path(build_url);
+    if let Path::Build { .. } = path {
+        let revisions: BuildRevisions = jenkins_client
+            .get_with_params(&path, [("tree", "actions[lastBuiltRevision[SHA1]]")])?
+            .json()?;
+        Ok(revisions
+            .actions
+            .into_iter()
+            .flatten()
+            .find_map(|action| action.last_built_revision)
+            .map(|revision| revision.sha1))
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: build_url.to_string(),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use super::*;
+    use crate::job::CommonJob;
+
+    #[test]
+    fn can_read_branches_and_sources() {
+        let job: CommonJob = serde_json::from_str(
+            r#"{
+                "_class": "org.jenkinsci.plugins.workflow.multibranch.WorkflowMultiBranchProject",
+                "name": "repo",
+                "displayName": "repo",
+                "url": "http://none/job/repo/",
+                "jobs": [
+                    {"_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob", "name": "master",
+                     "url": "http://none/job/repo/job/master/", "color": "blue"},
+                    {"_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob", "name": "develop",
+                     "url": "http://none/job/repo/job/develop/", "color": "red"}
+                ],
+                "sources": [{"_class": "jenkins.branch.BranchSource"}]
+            }"#,
+        )
+        .unwrap();
+        let project = job.as_variant::<WorkflowMultiBranchProject>().unwrap();
+        assert_eq!(
+            project
+                .jobs
+                .iter()
+                .map(|branch| branch.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["master", "develop"]
+        );
+        assert_eq!(
+            project.sources[0].class,
+            Some("jenkins.branch.BranchSource".to_string())
+        );
+    }
+
+    #[test]
+    fn can_summarize_branches() {
+        let url = format!("{}/multibranch", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let project: WorkflowMultiBranchProject = serde_json::from_str(&format!(
+            r#"{{
+                "_class": "org.jenkinsci.plugins.workflow.multibranch.WorkflowMultiBranchProject",
+                "name": "repo",
+                "displayName": "repo",
+                "url": "{url}/job/repo/"
+            }}"#,
+            url = url
+        ))
+        .unwrap();
+
+        let _branches = mockito::mock(
+            "GET",
+            "/multibranch/job/repo/api/json?tree=jobs%5Bname%2Curl%2Ccolor%2ClastBuild%5Bnumber%2Curl%2Cresult%2Ctimestamp%5D%5D",
+        )
+        .with_body(format!(
+            r#"{{"jobs": [
+                {{"_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob", "name": "master",
+                  "url": "{url}/job/repo/job/master/", "color": "blue_anime",
+                  "lastBuild": {{"_class": "org.jenkinsci.plugins.workflow.job.WorkflowRun",
+                  "number": 12, "url": "{url}/job/repo/job/master/12/", "result": null,
+                  "timestamp": 1550000000000}}}},
+                {{"_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob", "name": "feature%2Fold",
+                  "url": "{url}/job/repo/job/feature%252Fold/", "color": "disabled",
+                  "lastBuild": {{"_class": "org.jenkinsci.plugins.workflow.job.WorkflowRun",
+                  "number": 3, "url": "{url}/job/repo/job/feature%252Fold/3/", "result": "FAILURE",
+                  "timestamp": 1540000000000}}}},
+                {{"_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob", "name": "new",
+                  "url": "{url}/job/repo/job/new/", "color": "notbuilt", "lastBuild": null}}
+            ]}}"#,
+            url = url
+        ))
+        .create();
+        let _master = mockito::mock(
+            "GET",
+            "/multibranch/job/repo/job/master/12/api/json?tree=actions%5BlastBuiltRevision%5BSHA1%5D%5D",
+        )
+        .with_body(
+            r#"{"actions": [{}, null, {"_class": "hudson.plugins.git.util.BuildData",
+                "lastBuiltRevision": {"SHA1": "0123456789abcdef0123456789abcdef01234567"}}]}"#,
+        )
+        .create();
+        let _old = mockito::mock(
+            "GET",
+            "/multibranch/job/repo/job/feature%252Fold/3/api/json?tree=actions%5BlastBuiltRevision%5BSHA1%5D%5D",
+        )
+        .with_body(r#"{"actions": [{}]}"#)
+        .create();
+
+        let summaries = project.branch_summaries(&jenkins_client).unwrap();
+        assert_eq!(summaries.len(), 3);
+
+        assert_eq!(summaries[0].name, "master");
+        assert!(!summaries[0].disabled);
+        assert_eq!(summaries[0].last_build_number, Some(12));
+        assert_eq!(summaries[0].last_build_result, None);
+        assert_eq!(
+            summaries[0].last_build_timestamp,
+            MaybeTimestamp::Known(1550000000000)
+        );
+        assert_eq!(
+            summaries[0].last_built_sha1,
+            Some("0123456789abcdef0123456789abcdef01234567".to_string())
+        );
+
+        assert!(summaries[1].disabled);
+        assert_eq!(summaries[1].last_build_result, Some(BuildStatus::Failure));
+        assert_eq!(summaries[1].last_built_sha1, None);
+
+        assert_eq!(summaries[2].last_build_number, None);
+        assert!(!summaries[2].last_build_timestamp.is_known());
+        assert_eq!(summaries[2].last_built_sha1, None);
+    }
+}
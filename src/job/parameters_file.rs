@@ -0,0 +1,337 @@
+//! Parsers for files holding parameters of a build
+
+use crate::client;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// Before the first non whitespace character of a line
+    LineStart,
+    /// In a comment, until the end of line
+    Comment,
+    /// Reading the key
+    Key,
+    /// Whitespace after the key, before a separator or the value
+    BeforeSeparator,
+    /// Whitespace after the separator, before the value
+    AfterSeparator,
+    /// Reading the value, until the end of line
+    Value,
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '\x0c'
+}
+
+fn invalid(line: usize, message: &str) -> client::Error {
+    client::Error::InvalidParametersFile {
+        line,
+        message: message.to_string(),
+    }
+}
+
+fn read_utf16_unit<I: Iterator<Item = char>>(
+    chars: &mut I,
+    line: usize,
+) -> Result<u16, client::Error> {
+    let hex: String = chars.take(4).collect();
+    if hex.len() != 4 {
+        return Err(invalid(line, "malformed \\uxxxx encoding"));
+    }
+    u16::from_str_radix(&hex, 16).map_err(|_| invalid(line, "malformed \\uxxxx encoding"))
+}
+
+/// Read the character escaped by a `\`, `escaped` being the character following it
+fn unescape<I: Iterator<Item = char>>(
+    escaped: char,
+    chars: &mut I,
+    line: usize,
+) -> Result<char, client::Error> {
+    Ok(match escaped {
+        't' => '\t',
+        'n' => '\n',
+        'r' => '\r',
+        'f' => '\x0c',
+        'u' => {
+            let mut units = vec![read_utf16_unit(chars, line)?];
+            if (0xD800..0xDC00).contains(&units[0]) {
+                if chars.next() != Some('\\') || chars.next() != Some('u') {
+                    return Err(invalid(line, "unpaired surrogate in \\uxxxx encoding"));
+                }
+                units.push(read_utf16_unit(chars, line)?);
+            }
+            std::char::decode_utf16(units)
+                .next()
+                .and_then(Result::ok)
+                .ok_or_else(|| invalid(line, "unpaired surrogate in \\uxxxx encoding"))?
+        }
+        other => other,
+    })
+}
+
+fn push_property(properties: &mut Vec<(String, String)>, key: String, value: String) {
+    if let Some(existing) = properties.iter_mut().find(|property| property.0 == key) {
+        existing.1 = value;
+    } else {
+        properties.push((key, value));
+    }
+}
+
+/// Parse the content of a Java `.properties` file, as described in
+/// [`Properties.load`](https://docs.oracle.com/javase/8/docs/api/java/util/Properties.html#load-java.io.Reader-)
+///
+/// A key set more than once keeps its last value. Whitespace after a value is kept.
+pub(crate) fn parse_properties(input: &str) -> Result<Vec<(String, String)>, client::Error> {
+    let mut properties = vec![];
+    let mut state = State::LineStart;
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut line = 1;
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\r' || c == '\n' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                let _ = chars.next();
+            }
+            if state != State::LineStart && state != State::Comment {
+                push_property(
+                    &mut properties,
+                    std::mem::take(&mut key),
+                    std::mem::take(&mut value),
+                );
+            }
+            state = State::LineStart;
+            line += 1;
+            continue;
+        }
+        match state {
+            State::Comment => continue,
+            State::LineStart if is_whitespace(c) => continue,
+            State::LineStart if c == '#' || c == '!' => {
+                state = State::Comment;
+                continue;
+            }
+            State::LineStart => state = State::Key,
+            _ => (),
+        }
+
+        let (c, escaped) = if c == '\\' {
+            match chars.next() {
+                None => break,
+                Some(end_of_line) if end_of_line == '\r' || end_of_line == '\n' => {
+                    if end_of_line == '\r' && chars.peek() == Some(&'\n') {
+                        let _ = chars.next();
+                    }
+                    line += 1;
+                    while let Some(&next) = chars.peek() {
+                        if !is_whitespace(next) {
+                            break;
+                        }
+                        let _ = chars.next();
+                    }
+                    continue;
+                }
+                Some(escaped) => (unescape(escaped, &mut chars, line)?, true),
+            }
+        } else {
+            (c, false)
+        };
+
+        match state {
+            State::Key if !escaped && (c == '=' || c == ':') => state = State::AfterSeparator,
+            State::Key if !escaped && is_whitespace(c) => state = State::BeforeSeparator,
+            State::Key => key.push(c),
+            State::BeforeSeparator if !escaped && (c == '=' || c == ':') => {
+                state = State::AfterSeparator
+            }
+            State::BeforeSeparator | State::AfterSeparator if !escaped && is_whitespace(c) => (),
+            State::BeforeSeparator | State::AfterSeparator | State::Value => {
+                state = State::Value;
+                value.push(c);
+            }
+            State::LineStart | State::Comment => unreachable!(),
+        }
+    }
+    if state != State::LineStart && state != State::Comment {
+        push_property(&mut properties, key, value);
+    }
+
+    Ok(properties)
+}
+
+/// Parse the content of a YAML file containing a flat map of scalar values
+#[cfg(feature = "yaml")]
+pub(crate) fn parse_yaml<R: std::io::Read>(
+    reader: R,
+) -> Result<Vec<(String, String)>, failure::Error> {
+    use serde_yaml::Value;
+
+    let mapping: serde_yaml::Mapping = serde_yaml::from_reader(reader)?;
+    mapping
+        .into_iter()
+        .map(|(key, value)| {
+            let key = match key {
+                Value::String(key) => key,
+                Value::Number(key) => key.to_string(),
+                Value::Bool(key) => key.to_string(),
+                _ => return Err(invalid(0, "keys must be scalar values").into()),
+            };
+            let value = match value {
+                Value::String(value) => value,
+                Value::Number(value) => value.to_string(),
+                Value::Bool(value) => value.to_string(),
+                Value::Null => String::new(),
+                _ => {
+                    return Err(invalid(
+                        0,
+                        &format!("value of parameter '{}' must be a scalar value", key),
+                    )
+                    .into())
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(input: &str) -> Vec<(String, String)> {
+        parse_properties(input).unwrap()
+    }
+
+    fn property(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn can_parse_separators() {
+        assert_eq!(parsed("a=1"), vec![property("a", "1")]);
+        assert_eq!(parsed("a:1"), vec![property("a", "1")]);
+        assert_eq!(parsed("a 1"), vec![property("a", "1")]);
+        assert_eq!(parsed("a \t = \t 1"), vec![property("a", "1")]);
+        assert_eq!(parsed("a  :1"), vec![property("a", "1")]);
+        assert_eq!(parsed("a==1"), vec![property("a", "=1")]);
+        assert_eq!(parsed("a = :1"), vec![property("a", ":1")]);
+        assert_eq!(parsed("a=1=2:3"), vec![property("a", "1=2:3")]);
+    }
+
+    #[test]
+    fn can_parse_keys_without_values() {
+        assert_eq!(parsed("a"), vec![property("a", "")]);
+        assert_eq!(parsed("a="), vec![property("a", "")]);
+        assert_eq!(parsed("a   "), vec![property("a", "")]);
+        assert_eq!(parsed("=1"), vec![property("", "1")]);
+    }
+
+    #[test]
+    fn keeps_whitespace_in_and_after_values() {
+        assert_eq!(parsed("a=one two  "), vec![property("a", "one two  ")]);
+        assert_eq!(parsed("a=\\  padded"), vec![property("a", "  padded")]);
+    }
+
+    #[test]
+    fn can_parse_escaped_separators_in_keys() {
+        assert_eq!(parsed("a\\=b=1"), vec![property("a=b", "1")]);
+        assert_eq!(parsed("a\\:b:1"), vec![property("a:b", "1")]);
+        assert_eq!(parsed("a\\ b 1"), vec![property("a b", "1")]);
+        assert_eq!(parsed("\\#a=1"), vec![property("#a", "1")]);
+        assert_eq!(parsed("a\\\\=1"), vec![property("a\\", "1")]);
+    }
+
+    #[test]
+    fn can_parse_escapes_in_values() {
+        assert_eq!(
+            parsed("a=\\t\\n\\r\\f\\=\\:\\q"),
+            vec![property("a", "\t\n\r\x0c=:q")]
+        );
+    }
+
+    #[test]
+    fn can_parse_unicode_escapes() {
+        assert_eq!(
+            parsed("caf\\u00e9=\\u00E9t\\u00e9"),
+            vec![property("café", "été")]
+        );
+        assert_eq!(parsed("a=\\ud83d\\ude00"), vec![property("a", "😀")]);
+        assert_eq!(parsed("a=é"), vec![property("a", "é")]);
+    }
+
+    #[test]
+    fn fails_on_malformed_unicode_escapes() {
+        assert!(parse_properties("a=\\u00").is_err());
+        assert!(parse_properties("a=\\u00zz").is_err());
+        assert!(parse_properties("a=\\ud83d").is_err());
+        assert!(parse_properties("a=\\ud83dxx").is_err());
+        assert!(parse_properties("a=\\ude00").is_err());
+
+        let error = parse_properties("a=1\nb=\\uzzzz").unwrap_err();
+        assert_eq!(
+            format!("{}", error),
+            "invalid parameters file at line 2: malformed \\uxxxx encoding"
+        );
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        assert_eq!(
+            parsed("# comment\n! other comment\n\n   \n  # indented comment\na=1 # not a comment"),
+            vec![property("a", "1 # not a comment")]
+        );
+        assert_eq!(parsed("# comment \\\na=1"), vec![property("a", "1")]);
+    }
+
+    #[test]
+    fn can_parse_continuation_lines() {
+        assert_eq!(
+            parsed("a=one, \\\n    two, \\\n\tthree"),
+            vec![property("a", "one, two, three")]
+        );
+        assert_eq!(parsed("long\\\n  key=1"), vec![property("longkey", "1")]);
+        assert_eq!(
+            parsed("a=\\\\\nb=2"),
+            vec![property("a", "\\"), property("b", "2")]
+        );
+        assert_eq!(parsed("a=1\\"), vec![property("a", "1")]);
+    }
+
+    #[test]
+    fn can_parse_any_line_terminator() {
+        assert_eq!(
+            parsed("a=1\r\nb=2\rc=3\nd=4\\\r\n  5"),
+            vec![
+                property("a", "1"),
+                property("b", "2"),
+                property("c", "3"),
+                property("d", "45")
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_last_value_of_repeated_keys() {
+        assert_eq!(
+            parsed("a=1\nb=2\na=3"),
+            vec![property("a", "3"), property("b", "2")]
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn can_parse_flat_yaml() {
+        let parameters = parse_yaml("a: 1\nb: \"  spaced  \"\nc: true\nd: ~\n".as_bytes()).unwrap();
+        assert_eq!(
+            parameters,
+            vec![
+                property("a", "1"),
+                property("b", "  spaced  "),
+                property("c", "true"),
+                property("d", "")
+            ]
+        );
+        assert!(parse_yaml("a:\n  b: 1\n".as_bytes()).is_err());
+        assert!(parse_yaml("- a\n- b\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Result of the last SCM polling of a job, read from its polling log
+
+/// Number of lines of the log kept when it can't be parsed
+const UNPARSED_TAIL_LINES: usize = 10;
+
+/// Result of the last SCM polling of a job
+///
+/// `started` is the time the polling started, as written by Jenkins in its own locale and
+/// timezone, like `Mar 1, 2019 10:00:00 AM`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PollResult {
+    /// The job was never polled
+    NeverPolled,
+    /// Changes were found, and a build was triggered
+    ChangesFound {
+        /// Time the polling started
+        started: Option<String>,
+    },
+    /// No changes were found
+    NoChanges {
+        /// Time the polling started
+        started: Option<String>,
+    },
+    /// Polling failed, for example when the credentials are rejected by the repository
+    Error {
+        /// Time the polling started
+        started: Option<String>,
+        /// First error reported in the log
+        message: String,
+    },
+    /// The log couldn't be parsed, for example while polling is on-going
+    Unparsed {
+        /// Last lines of the log
+        tail: String,
+    },
+}
+
+/// Parse a polling log, from the git or subversion plugins. An error reported in the log
+/// takes precedence over the final status line, as plugins can report "No changes" after
+/// failing to reach the repository
+pub(crate) fn parse_polling_log(log: &str) -> PollResult {
+    let lines: Vec<&str> = log.lines().map(str::trim_end).collect();
+    if lines.iter().all(|line| line.trim().is_empty()) {
+        return PollResult::NeverPolled;
+    }
+
+    let started = lines
+        .iter()
+        .find_map(|line| line.strip_prefix("Started on "))
+        .map(|started| started.trim().to_string());
+    let error = lines.iter().find_map(|line| {
+        line.strip_prefix("ERROR: ")
+            .or_else(|| line.strip_prefix("FATAL: "))
+    });
+    let status = lines
+        .iter()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty());
+
+    match (error, status) {
+        (Some(message), _) => PollResult::Error {
+            started,
+            message: message.trim().to_string(),
+        },
+        (None, Some("Changes found")) => PollResult::ChangesFound { started },
+        (None, Some("No changes")) => PollResult::NoChanges { started },
+        _ => PollResult::Unparsed {
+            tail: lines[lines.len().saturating_sub(UNPARSED_TAIL_LINES)..].join("\n"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static GIT_CHANGES_FOUND: &str = "\
+Started on Mar 1, 2019 10:00:00 AM
+Using strategy: Default
+[poll] Last Built Revision: Revision 4d3c2b1a (refs/remotes/origin/master)
+ > git --version # timeout=10
+ > git ls-remote -h https://git.example.com/app.git # timeout=10
+Found 3 remote heads on https://git.example.com/app.git
+[poll] Latest remote head revision on refs/heads/master is: 9f8e7d6c
+Done. Took 0.61 sec
+Changes found
+";
+
+    static GIT_NO_CHANGES: &str = "\
+Started on Mar 1, 2019 10:05:00 AM
+Using strategy: Default
+[poll] Last Built Revision: Revision 9f8e7d6c (refs/remotes/origin/master)
+ > git ls-remote -h https://git.example.com/app.git # timeout=10
+Found 3 remote heads on https://git.example.com/app.git
+[poll] Latest remote head revision on refs/heads/master is: 9f8e7d6c - already built by 12
+Done. Took 0.4 sec
+No changes
+";
+
+    static GIT_AUTH_ERROR: &str = "\
+Started on Mar 1, 2019 10:10:00 AM
+Using strategy: Default
+[poll] Last Built Revision: Revision 9f8e7d6c (refs/remotes/origin/master)
+ > git ls-remote -h https://git.example.com/app.git # timeout=10
+FATAL: hudson.plugins.git.GitException: Command \"git ls-remote -h https://git.example.com/app.git\" returned status code 128:
+stderr: remote: HTTP Basic: Access denied
+fatal: Authentication failed for 'https://git.example.com/app.git/'
+Done. Took 1.2 sec
+No changes
+";
+
+    static SVN_CHANGES_FOUND: &str = "\
+Started on Mar 1, 2019 11:00:00 AM
+Received SCM poll call on master for app on Mar 1, 2019 11:00:00 AM
+https://svn.example.com/repo/trunk is at revision 1,235
+  (changed from 1,234)
+Done. Took 0.3 sec
+Changes found
+";
+
+    static SVN_ERROR: &str = "\
+Started on Mar 1, 2019 11:05:00 AM
+Received SCM poll call on master for app on Mar 1, 2019 11:05:00 AM
+ERROR: Failed to check repository revision for https://svn.example.com/repo/trunk
+org.tmatesoft.svn.core.SVNAuthenticationException: svn: E170001: Authentication required
+\tat org.tmatesoft.svn.core.internal.wc.DefaultSVNAuthenticationManager.acknowledgeAuthentication
+Done. Took 0.9 sec
+No changes
+";
+
+    #[test]
+    fn can_parse_git_logs() {
+        assert_eq!(
+            parse_polling_log(GIT_CHANGES_FOUND),
+            PollResult::ChangesFound {
+                started: Some("Mar 1, 2019 10:00:00 AM".to_string())
+            }
+        );
+        assert_eq!(
+            parse_polling_log(GIT_NO_CHANGES),
+            PollResult::NoChanges {
+                started: Some("Mar 1, 2019 10:05:00 AM".to_string())
+            }
+        );
+        match parse_polling_log(GIT_AUTH_ERROR) {
+            PollResult::Error { message, .. } => assert!(message.contains("status code 128")),
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn can_parse_subversion_logs() {
+        assert_eq!(
+            parse_polling_log(SVN_CHANGES_FOUND),
+            PollResult::ChangesFound {
+                started: Some("Mar 1, 2019 11:00:00 AM".to_string())
+            }
+        );
+        assert_eq!(
+            parse_polling_log(SVN_ERROR),
+            PollResult::Error {
+                started: Some("Mar 1, 2019 11:05:00 AM".to_string()),
+                message:
+                    "Failed to check repository revision for https://svn.example.com/repo/trunk"
+                        .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_never_polled_and_unknown_logs() {
+        assert_eq!(parse_polling_log(""), PollResult::NeverPolled);
+        assert_eq!(parse_polling_log("\n  \n"), PollResult::NeverPolled);
+        assert_eq!(
+            parse_polling_log("Started on Mar 1, 2019 10:00:00 AM\nUsing strategy: Default\n"),
+            PollResult::Unparsed {
+                tail: "Started on Mar 1, 2019 10:00:00 AM\nUsing strategy: Default".to_string()
+            }
+        );
+    }
+}
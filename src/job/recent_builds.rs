@@ -0,0 +1,194 @@
+//! Recent builds of a job, in a strict order whatever the order sent by Jenkins
+
+use std::cmp::Reverse;
+
+use failure::Error;
+use serde::Deserialize;
+
+use crate::build::ShortBuild;
+use crate::client;
+use crate::client_internals::Path;
+use crate::Jenkins;
+
+/// Anomaly in the order of builds sent by Jenkins
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderingAnomaly {
+    /// A build was listed more than once
+    Duplicate {
+        /// Number of the build
+        number: u32,
+    },
+    /// A build was listed after a build with a lower number
+    OutOfOrder {
+        /// Number of the build
+        number: u32,
+        /// Number of the build listed before it
+        after: u32,
+    },
+}
+
+/// Report of `Job::recent_builds_ordered`
+#[derive(Debug)]
+pub struct RecentBuilds {
+    /// Builds, by strictly descending number
+    pub builds: Vec<ShortBuild>,
+    /// Anomalies found in the order sent by Jenkins, empty if it was already strictly
+    /// descending
+    pub anomalies: Vec<OrderingAnomaly>,
+}
+
+impl RecentBuilds {
+    /// Sort `builds` by descending number and remove duplicates, recording the anomalies in the
+    /// order they were received in
+    fn from_server_order(mut builds: Vec<ShortBuild>) -> Self {
+        let mut anomalies = vec![];
+        for (index, build) in builds.iter().enumerate() {
+            if builds[..index]
+                .iter()
+                .any(|previous| previous.number == build.number)
+            {
+                anomalies.push(OrderingAnomaly::Duplicate {
+                    number: build.number,
+                });
+            } else if index > 0 && builds[index - 1].number < build.number {
+                anomalies.push(OrderingAnomaly::OutOfOrder {
+                    number: build.number,
+                    after: builds[index - 1].number,
+                });
+            }
+        }
+
+        builds.sort_by_key(|build| Reverse(build.number));
+        builds.dedup_by_key(|build| build.number);
+        debug_assert!(builds
+            .windows(2)
+            .all(|pair| pair[0].number > pair[1].number));
+        RecentBuilds { builds, anomalies }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JobBuilds {
+    #[serde(default)]
+    builds: Vec<ShortBuild>,
+}
+
+impl Jenkins {
+    /// Get up to `limit` of the most recent builds of the job at `job_url`
+    pub(crate) fn recent_builds_of(
+        &self,
+        job_url: &str,
+        limit: u32,
+    ) -> Result<RecentBuilds, Error> {
+        let path = self.url_to_path(job_url);
+        if let Path::Job { .. } = path {
+            let job: JobBuilds = self
+                .get_with_params(
+                    &path,
+                    [("tree", format!("builds[number,url]{{0,{}}}", limit))],
+                )?
+                .json()?;
+            let mut recent = RecentBuilds::from_server_order(job.builds);
+            recent.builds.truncate(limit as usize);
+            Ok(recent)
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: job_url.to_string(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builds(numbers: &[u32]) -> Vec<ShortBuild> {
+        serde_json::from_str(&format!(
+            "[{}]",
+            numbers
+                .iter()
+                .map(|number| format!(
+                    r#"{{"number":{},"url":"http://none/job/job/{}/"}}"#,
+                    number, number
+                ))
+                .collect::<Vec<_>>()
+                .join(",")
+        ))
+        .unwrap()
+    }
+
+    fn numbers(recent: &RecentBuilds) -> Vec<u32> {
+        recent.builds.iter().map(|build| build.number).collect()
+    }
+
+    #[test]
+    fn ordered_builds_have_no_anomalies() {
+        let recent = RecentBuilds::from_server_order(builds(&[12, 11, 9, 3]));
+        assert_eq!(numbers(&recent), vec![12, 11, 9, 3]);
+        assert!(recent.anomalies.is_empty());
+    }
+
+    #[test]
+    fn can_reorder_interleaved_builds() {
+        let recent = RecentBuilds::from_server_order(builds(&[12, 8, 11, 7, 10]));
+        assert_eq!(numbers(&recent), vec![12, 11, 10, 8, 7]);
+        assert_eq!(
+            recent.anomalies,
+            vec![
+                OrderingAnomaly::OutOfOrder {
+                    number: 11,
+                    after: 8
+                },
+                OrderingAnomaly::OutOfOrder {
+                    number: 10,
+                    after: 7
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn can_remove_duplicated_builds() {
+        let recent = RecentBuilds::from_server_order(builds(&[12, 11, 11, 10, 12]));
+        assert_eq!(numbers(&recent), vec![12, 11, 10]);
+        assert_eq!(
+            recent.anomalies,
+            vec![
+                OrderingAnomaly::Duplicate { number: 11 },
+                OrderingAnomaly::Duplicate { number: 12 },
+            ]
+        );
+    }
+
+    #[test]
+    fn can_get_recent_builds_of_job() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/recent-builds", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _job = mockito::mock(
+            "GET",
+            "/recent-builds/job/job/api/json?tree=builds%5Bnumber%2Curl%5D%7B0%2C3%7D",
+        )
+        .with_body(
+            r#"{"_class":"hudson.model.FreeStyleProject","builds":[
+                {"number":5,"url":"http://none/job/job/5/"},
+                {"number":3,"url":"http://none/job/job/3/"},
+                {"number":4,"url":"http://none/job/job/4/"}
+            ]}"#,
+        )
+        .create();
+
+        let recent = jenkins_client
+            .recent_builds_of(
+                &format!("{}/recent-builds/job/job/", mockito::server_url()),
+                3,
+            )
+            .unwrap();
+        assert_eq!(numbers(&recent), vec![5, 4, 3]);
+        assert_eq!(recent.anomalies.len(), 1);
+    }
+}
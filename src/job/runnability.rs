@@ -0,0 +1,265 @@
+//! Check whether a job can run on the nodes currently available
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::client;
+use crate::client_internals::Path;
+use crate::nodes::label::LabelExpression;
+use crate::Jenkins;
+
+/// A node whose labels match the label expression of a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchingNode {
+    /// Name of the node
+    pub name: String,
+    /// Is the node online
+    pub online: bool,
+    /// Number of idle executors of the node, `0` if it is offline
+    pub idle_executors: u32,
+}
+
+/// Whether a job can run now
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Runnability {
+    /// An online node matching the job has an idle executor
+    RunnableNow,
+    /// Some nodes match the job, but none can start it now
+    WouldQueue {
+        /// Why the job can't start now
+        reason: String,
+    },
+    /// No node matches the job
+    Unsatisfiable,
+}
+
+/// Report of `Job::can_run_now`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunnabilityReport {
+    /// Label expression of the job, `None` if it can run anywhere
+    pub label_expression: Option<String>,
+    /// Labels of the expression that no node has
+    pub unknown_labels: Vec<String>,
+    /// Nodes matching the label expression
+    pub matching_nodes: Vec<MatchingNode>,
+    /// Overall verdict
+    pub verdict: Runnability,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobLabel {
+    label_expression: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeLabels {
+    computer: Vec<NodeState>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeState {
+    display_name: String,
+    offline: bool,
+    #[serde(default)]
+    assigned_labels: Vec<LabelName>,
+    #[serde(default)]
+    executors: Vec<ExecutorState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelName {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExecutorState {
+    idle: bool,
+}
+
+static NODES_TREE: &str = "computer[displayName,offline,assignedLabels[name],executors[idle]]";
+
+fn runnability(
+    label_expression: Option<String>,
+    nodes: Vec<NodeState>,
+) -> Result<RunnabilityReport, Error> {
+    let expression = match label_expression {
+        Some(ref expression) if !expression.trim().is_empty() => {
+            Some(LabelExpression::parse(expression)?)
+        }
+        _ => None,
+    };
+
+    let mut unknown_labels = vec![];
+    if let Some(ref expression) = expression {
+        for label in expression.labels() {
+            if !nodes.iter().any(|node| {
+                node.assigned_labels
+                    .iter()
+                    .any(|assigned| assigned.name == label)
+            }) {
+                unknown_labels.push(label.to_string());
+            }
+        }
+    }
+
+    let matching_nodes = nodes
+        .into_iter()
+        .filter(|node| match expression {
+            Some(ref expression) => {
+                let labels = node
+                    .assigned_labels
+                    .iter()
+                    .map(|label| label.name.as_str())
+                    .collect::<Vec<_>>();
+                expression.matches(&labels)
+            }
+            None => true,
+        })
+        .map(|node| MatchingNode {
+            idle_executors: if node.offline {
+                0
+            } else {
+                node.executors
+                    .iter()
+                    .filter(|executor| executor.idle)
+                    .count() as u32
+            },
+            online: !node.offline,
+            name: node.display_name,
+        })
+        .collect::<Vec<_>>();
+
+    let verdict = if matching_nodes.is_empty() {
+        Runnability::Unsatisfiable
+    } else if !matching_nodes.iter().any(|node| node.online) {
+        Runnability::WouldQueue {
+            reason: "all matching nodes are offline".to_string(),
+        }
+    } else if !matching_nodes.iter().any(|node| node.idle_executors > 0) {
+        Runnability::WouldQueue {
+            reason: "all executors of the matching online nodes are busy".to_string(),
+        }
+    } else {
+        Runnability::RunnableNow
+    };
+
+    Ok(RunnabilityReport {
+        label_expression,
+        unknown_labels,
+        matching_nodes,
+        verdict,
+    })
+}
+
+impl Jenkins {
+    /// Check whether the job at `job_url` can run now
+    pub(crate) fn runnability_of(&self, job_url: &str) -> Result<RunnabilityReport, Error> {
+        let path = self.url_to_path(job_url);
+        if let Path::Job { .. } = path {
+            let job: JobLabel = self
+                .get_with_params(&path, [("tree", "labelExpression")])?
+                .json()?;
+            let nodes: NodeLabels = self
+                .get_with_params(&Path::Computers, [("tree", NODES_TREE)])?
+                .json()?;
+            runnability(job.label_expression, nodes.computer)
+        } else {
+            Err(client::Error::InvalidUrl {
+                url: job_url.to_string(),
+                expected: client::error::ExpectedType::Job,
+            }
+            .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static NODES: &str = r#"{"computer":[
+        {"displayName":"master","offline":false,
+        "assignedLabels":[{"name":"master"}],"executors":[{"idle":false},{"idle":false}]},
+        {"displayName":"linux-1","offline":false,
+        "assignedLabels":[{"name":"linux"},{"name":"docker"},{"name":"linux-1"}],
+        "executors":[{"idle":true},{"idle":false}]},
+        {"displayName":"windows-1","offline":true,
+        "assignedLabels":[{"name":"windows"},{"name":"windows-1"}],"executors":[{"idle":true}]}
+    ]}"#;
+
+    fn report(label_expression: Option<&str>) -> RunnabilityReport {
+        let nodes: NodeLabels = serde_json::from_str(NODES).unwrap();
+        runnability(label_expression.map(str::to_string), nodes.computer).unwrap()
+    }
+
+    #[test]
+    fn job_matching_idle_node_is_runnable() {
+        let report = report(Some("linux && docker"));
+        assert_eq!(report.verdict, Runnability::RunnableNow);
+        assert_eq!(
+            report.matching_nodes,
+            vec![MatchingNode {
+                name: "linux-1".to_string(),
+                online: true,
+                idle_executors: 1,
+            }]
+        );
+        assert!(report.unknown_labels.is_empty());
+    }
+
+    #[test]
+    fn job_matching_offline_or_busy_nodes_would_queue() {
+        match report(Some("windows")).verdict {
+            Runnability::WouldQueue { reason } => assert!(reason.contains("offline")),
+            verdict => panic!("unexpected verdict {:?}", verdict),
+        }
+        match report(Some("master")).verdict {
+            Runnability::WouldQueue { reason } => assert!(reason.contains("busy")),
+            verdict => panic!("unexpected verdict {:?}", verdict),
+        }
+    }
+
+    #[test]
+    fn job_with_unknown_label_is_unsatisfiable() {
+        let report = report(Some("linux && gpu"));
+        assert_eq!(report.verdict, Runnability::Unsatisfiable);
+        assert_eq!(report.unknown_labels, vec!["gpu"]);
+    }
+
+    #[test]
+    fn job_without_label_can_run_anywhere() {
+        let report = report(None);
+        assert_eq!(report.matching_nodes.len(), 3);
+        assert_eq!(report.verdict, Runnability::RunnableNow);
+    }
+
+    #[test]
+    fn can_check_runnability_of_job() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/runnability", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _job = mockito::mock(
+            "GET",
+            "/runnability/job/build/api/json?tree=labelExpression",
+        )
+        .with_body(r#"{"_class":"hudson.model.FreeStyleProject","labelExpression":"!windows"}"#)
+        .create();
+        let _nodes = mockito::mock(
+            "GET",
+            "/runnability/computer/api/json?tree=computer%5BdisplayName%2Coffline%2CassignedLabels%5Bname%5D%2Cexecutors%5Bidle%5D%5D",
+        )
+        .with_body(NODES)
+        .create();
+
+        let report = jenkins_client
+            .runnability_of(&format!("{}/runnability/job/build/", mockito::server_url()))
+            .unwrap();
+        assert_eq!(report.label_expression, Some("!windows".to_string()));
+        assert_eq!(report.matching_nodes.len(), 2);
+        assert_eq!(report.verdict, Runnability::RunnableNow);
+    }
+}
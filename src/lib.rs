@@ -34,7 +34,7 @@
 //!     let build = job.last_build.as_ref().unwrap().get_full_build(&jenkins)?;
 //!
 //!     println!(
-//!         "last build for job {} at {} was {:?}",
+//!         "last build for job {} at {:?} was {:?}",
 //!         job.name, build.timestamp, build.result
 //!     );
 //!     Ok(())
@@ -66,13 +66,25 @@ pub mod client;
 pub mod helpers;
 
 pub mod action;
+#[cfg(feature = "async")]
+pub mod r#async;
+pub mod blue_ocean;
 pub mod build;
 pub mod changeset;
+pub mod diagnostics;
+#[cfg(feature = "display-helpers")]
+pub mod display;
+pub mod fixtures;
 pub mod home;
 pub mod job;
 pub mod nodes;
+pub mod pipeline;
+pub mod plugin;
 pub mod property;
 pub mod queue;
+pub mod recipes;
+pub mod schema;
 pub mod scm;
 pub mod user;
+pub mod version;
 pub mod view;
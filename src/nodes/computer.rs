@@ -2,10 +2,13 @@
 
 use std::collections::HashMap;
 
+use failure::Error;
 use serde::{self, Deserialize, Serialize};
 use serde_json;
 
+use crate::client_internals::{Name, Path};
 use crate::helpers::Class;
+use crate::Jenkins;
 
 use super::monitor;
 
@@ -24,7 +27,58 @@ impl<'a> From<&'a String> for ComputerName<'a> {
 }
 
 /// Trait implemented by specialization of computers
-pub trait Computer {}
+pub trait Computer {
+    /// Get the name of a `Computer`
+    fn display_name(&self) -> &str;
+    /// Is the `Computer` marked temporarily offline
+    fn temporarily_offline(&self) -> bool;
+
+    /// Toggle the temporarily offline state of a `Computer`, with an optional `reason` when
+    /// taking it offline. Jenkins toggles the state whatever it is, use `mark_offline` or
+    /// `mark_online` to get a given state
+    fn toggle_offline(&self, jenkins_client: &Jenkins, reason: Option<&str>) -> Result<(), Error> {
+        let _ = jenkins_client.post_with_body(
+            &Path::ComputerToggleOffline {
+                name: Name::Name(url_name(self.display_name())),
+            },
+            "",
+            &[("offlineMessage", reason.unwrap_or(""))],
+        )?;
+        Ok(())
+    }
+
+    /// Mark a `Computer` temporarily offline, with an optional `reason`. It reads the current
+    /// state of the `Computer` first and does nothing if it already is offline
+    fn mark_offline(&self, jenkins_client: &Jenkins, reason: Option<&str>) -> Result<(), Error> {
+        let current = jenkins_client.get_node(url_name(self.display_name()))?;
+        if current.temporarily_offline {
+            Ok(())
+        } else {
+            self.toggle_offline(jenkins_client, reason)
+        }
+    }
+
+    /// Bring a `Computer` back online. It reads the current state of the `Computer` first and
+    /// does nothing if it already is online
+    fn mark_online(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        let current = jenkins_client.get_node(url_name(self.display_name()))?;
+        if current.temporarily_offline {
+            self.toggle_offline(jenkins_client, None)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Name of a `Computer` in its URL, the built-in node being named `master` or `Built-In Node`
+/// but being at `(master)` or `(built-in)`
+fn url_name(display_name: &str) -> &str {
+    match display_name {
+        "master" => "(master)",
+        "Built-In Node" => "(built-in)",
+        name => name,
+    }
+}
 
 macro_rules! computer_with_common_fields_and_impl {
     (
@@ -71,6 +125,7 @@ macro_rules! computer_with_common_fields_and_impl {
             /// Is the computer temporarily offline
             pub temporarily_offline: bool,
             /// Monitor data provided by the computer
+            #[serde(default)]
             pub monitor_data: HashMap<String, monitor::Data>,
             /// Executors of the computer
             pub executors: Vec<Executor>,
@@ -90,7 +145,15 @@ macro_rules! computer_with_common_fields_and_impl {
                 $private_field: $private_field_type,
             )*)*
         }
-        impl Computer for $name {}
+        impl Computer for $name {
+            fn display_name(&self) -> &str {
+                &self.display_name
+            }
+
+            fn temporarily_offline(&self) -> bool {
+                self.temporarily_offline
+            }
+        }
     };
 }
 
@@ -122,7 +185,7 @@ computer_with_common_fields_and_impl!(
     #[serde(rename_all = "camelCase")]
     pub struct SlaveComputer {}
 );
-register_class!("hudson.slave.SlaveComputer" => SlaveComputer);
+register_class!("hudson.slaves.SlaveComputer" => SlaveComputer);
 
 /// An `Executor` of a `Computer`
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -136,6 +199,8 @@ pub enum Executor {
         /// is idle, or if it is building a job the current user doesn't have
         /// permissions to see
         current_executable: Option<crate::build::ShortBuild>,
+        /// Is the executor idle
+        idle: bool,
         /// Is it likely stuck
         likely_stuck: bool,
         /// Executor number
@@ -0,0 +1,404 @@
+//! Label expressions, selecting the nodes a job can run on
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+use failure::Error;
+
+use crate::client;
+
+/// A label expression, as set on a job to restrict where it can run
+///
+/// Operators are, from the highest to the lowest precedence: `!`, `&&`, `||`, `->` and `<->`.
+/// Parentheses can group sub-expressions, and labels can be quoted with `"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelExpression {
+    /// A single label
+    Label(String),
+    /// `!a`
+    Not(Box<LabelExpression>),
+    /// `a && b`
+    And(Box<LabelExpression>, Box<LabelExpression>),
+    /// `a || b`
+    Or(Box<LabelExpression>, Box<LabelExpression>),
+    /// `a -> b`
+    Implies(Box<LabelExpression>, Box<LabelExpression>),
+    /// `a <-> b`
+    Iff(Box<LabelExpression>, Box<LabelExpression>),
+}
+
+impl LabelExpression {
+    /// Parse a label expression
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::InvalidLabelExpression`](../../client/enum.Error.html#variant.InvalidLabelExpression)
+    /// if the expression is invalid
+    pub fn parse(expression: &str) -> Result<Self, Error> {
+        let invalid = |reason: String| client::Error::InvalidLabelExpression {
+            expression: expression.to_string(),
+            reason,
+        };
+        let tokens = tokenize(expression).map_err(invalid)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+        };
+        let parsed = parser.iff().map_err(invalid)?;
+        match parser.peek() {
+            None => Ok(parsed),
+            Some(token) => Err(invalid(format!("unexpected {}", token)).into()),
+        }
+    }
+
+    /// Does a node with these `labels` match the expression
+    pub fn matches<S: AsRef<str>>(&self, labels: &[S]) -> bool {
+        match *self {
+            LabelExpression::Label(ref label) => labels.iter().any(|l| l.as_ref() == label),
+            LabelExpression::Not(ref a) => !a.matches(labels),
+            LabelExpression::And(ref a, ref b) => a.matches(labels) && b.matches(labels),
+            LabelExpression::Or(ref a, ref b) => a.matches(labels) || b.matches(labels),
+            LabelExpression::Implies(ref a, ref b) => !a.matches(labels) || b.matches(labels),
+            LabelExpression::Iff(ref a, ref b) => a.matches(labels) == b.matches(labels),
+        }
+    }
+
+    /// Labels used in the expression
+    pub fn labels(&self) -> Vec<&str> {
+        let mut labels = vec![];
+        self.collect_labels(&mut labels);
+        labels
+    }
+
+    fn collect_labels<'a>(&'a self, labels: &mut Vec<&'a str>) {
+        match *self {
+            LabelExpression::Label(ref label) => {
+                if !labels.contains(&label.as_str()) {
+                    labels.push(label);
+                }
+            }
+            LabelExpression::Not(ref a) => a.collect_labels(labels),
+            LabelExpression::And(ref a, ref b)
+            | LabelExpression::Or(ref a, ref b)
+            | LabelExpression::Implies(ref a, ref b)
+            | LabelExpression::Iff(ref a, ref b) => {
+                a.collect_labels(labels);
+                b.collect_labels(labels);
+            }
+        }
+    }
+}
+
+impl FromStr for LabelExpression {
+    type Err = Error;
+
+    fn from_str(expression: &str) -> Result<Self, Error> {
+        Self::parse(expression)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Label(String),
+    Not,
+    And,
+    Or,
+    Implies,
+    Iff,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Label(ref label) => write!(f, "label '{}'", label),
+            Token::Not => write!(f, "'!'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Implies => write!(f, "'->'"),
+            Token::Iff => write!(f, "'<->'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn expect(chars: &mut Peekable<CharIndices<'_>>, expected: &str) -> Result<(), String> {
+    for c in expected.chars() {
+        match chars.next() {
+            Some((_, found)) if found == c => (),
+            _ => return Err(format!("expected '{}'", expected)),
+        }
+    }
+    Ok(())
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = expression.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                let _ = chars.next();
+            }
+            '(' | ')' | '!' => {
+                let _ = chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Not,
+                });
+            }
+            '&' => {
+                expect(&mut chars, "&&")?;
+                tokens.push(Token::And);
+            }
+            '|' => {
+                expect(&mut chars, "||")?;
+                tokens.push(Token::Or);
+            }
+            '<' => {
+                expect(&mut chars, "<->")?;
+                tokens.push(Token::Iff);
+            }
+            '-' if expression[start..].starts_with("->") => {
+                expect(&mut chars, "->")?;
+                tokens.push(Token::Implies);
+            }
+            '"' => {
+                let _ = chars.next();
+                let mut label = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => label.push(escaped),
+                            None => return Err("unterminated quoted label".to_string()),
+                        },
+                        Some((_, c)) => label.push(c),
+                        None => return Err("unterminated quoted label".to_string()),
+                    }
+                }
+                tokens.push(Token::Label(label));
+            }
+            _ => {
+                let mut end = expression.len();
+                while let Some(&(index, c)) = chars.peek() {
+                    if c.is_whitespace()
+                        || "()!&|<\"".contains(c)
+                        || expression[index..].starts_with("->")
+                    {
+                        end = index;
+                        break;
+                    }
+                    let _ = chars.next();
+                }
+                tokens.push(Token::Label(expression[start..end].to_string()));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_if(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn iff(&mut self) -> Result<LabelExpression, String> {
+        let mut left = self.implies()?;
+        while self.next_if(&Token::Iff) {
+            left = LabelExpression::Iff(Box::new(left), Box::new(self.implies()?));
+        }
+        Ok(left)
+    }
+
+    fn implies(&mut self) -> Result<LabelExpression, String> {
+        let left = self.or()?;
+        if self.next_if(&Token::Implies) {
+            Ok(LabelExpression::Implies(
+                Box::new(left),
+                Box::new(self.implies()?),
+            ))
+        } else {
+            Ok(left)
+        }
+    }
+
+    fn or(&mut self) -> Result<LabelExpression, String> {
+        let mut left = self.and()?;
+        while self.next_if(&Token::Or) {
+            left = LabelExpression::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<LabelExpression, String> {
+        let mut left = self.not()?;
+        while self.next_if(&Token::And) {
+            left = LabelExpression::And(Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<LabelExpression, String> {
+        if self.next_if(&Token::Not) {
+            Ok(LabelExpression::Not(Box::new(self.not()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<LabelExpression, String> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        match token {
+            Some(Token::Label(label)) => Ok(LabelExpression::Label(label)),
+            Some(Token::Open) => {
+                let inner = self.iff()?;
+                if self.next_if(&Token::Close) {
+                    Ok(inner)
+                } else {
+                    Err("missing ')'".to_string())
+                }
+            }
+            Some(token) => Err(format!("unexpected {}", token)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LabelExpression::*;
+    use super::*;
+
+    fn label(name: &str) -> Box<LabelExpression> {
+        Box::new(Label(name.to_string()))
+    }
+
+    fn parse(expression: &str) -> LabelExpression {
+        LabelExpression::parse(expression).unwrap()
+    }
+
+    #[test]
+    fn can_parse_single_label() {
+        assert_eq!(parse("linux"), Label("linux".to_string()));
+        assert_eq!(parse("  linux-x64 "), Label("linux-x64".to_string()));
+        assert_eq!(parse(r#""my label""#), Label("my label".to_string()));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(
+            parse("a || b && c"),
+            Or(label("a"), Box::new(And(label("b"), label("c"))))
+        );
+        assert_eq!(
+            parse("a && b || c"),
+            Or(Box::new(And(label("a"), label("b"))), label("c"))
+        );
+    }
+
+    #[test]
+    fn not_binds_tightest() {
+        assert_eq!(parse("!a && b"), And(Box::new(Not(label("a"))), label("b")));
+        assert_eq!(parse("!!a"), Not(Box::new(Not(label("a")))));
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        assert_eq!(
+            parse("(a || b) && c"),
+            And(Box::new(Or(label("a"), label("b"))), label("c"))
+        );
+        assert_eq!(
+            parse("!(a || b)"),
+            Not(Box::new(Or(label("a"), label("b"))))
+        );
+    }
+
+    #[test]
+    fn implies_is_right_associative_and_below_or() {
+        assert_eq!(
+            parse("a || b -> c -> d"),
+            Implies(
+                Box::new(Or(label("a"), label("b"))),
+                Box::new(Implies(label("c"), label("d")))
+            )
+        );
+        assert_eq!(
+            parse("a<->b->c"),
+            Iff(label("a"), Box::new(Implies(label("b"), label("c"))))
+        );
+    }
+
+    #[test]
+    fn operators_need_no_spaces() {
+        assert_eq!(parse("a&&!b"), And(label("a"), Box::new(Not(label("b")))));
+        assert_eq!(parse("(a)||(b)"), Or(label("a"), label("b")));
+    }
+
+    #[test]
+    fn can_evaluate_expressions() {
+        let node = ["linux", "docker", "node-1"];
+        assert!(parse("linux").matches(&node));
+        assert!(!parse("windows").matches(&node));
+        assert!(parse("linux && docker").matches(&node));
+        assert!(parse("windows || docker").matches(&node));
+        assert!(!parse("linux && !docker").matches(&node));
+        assert!(parse("!(windows || mac) && node-1").matches(&node));
+        assert!(parse("windows -> gpu").matches(&node));
+        assert!(!parse("linux -> gpu").matches(&node));
+        assert!(parse("windows <-> gpu").matches(&node));
+        assert!(!parse("linux <-> gpu").matches(&node));
+    }
+
+    #[test]
+    fn unknown_labels_never_match() {
+        let expression = parse("linux && unknown");
+        assert!(!expression.matches(&["linux"]));
+        assert!(!expression.matches::<&str>(&[]));
+        assert!(parse("!unknown").matches(&["linux"]));
+        assert_eq!(expression.labels(), vec!["linux", "unknown"]);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in &[
+            "",
+            "a &&",
+            "&& a",
+            "(a || b",
+            "a || b)",
+            "a & b",
+            "a | b",
+            "a <- b",
+            "a b",
+            r#""unterminated"#,
+            "!",
+        ] {
+            let error = LabelExpression::parse(expression).unwrap_err();
+            match error.downcast::<client::Error>() {
+                Ok(client::Error::InvalidLabelExpression { .. }) => (),
+                other => panic!("unexpected result for '{}': {:?}", expression, other),
+            }
+        }
+    }
+}
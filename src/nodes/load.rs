@@ -0,0 +1,117 @@
+//! Load statistics of Jenkins, over time
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::client_internals::Path;
+use crate::Jenkins;
+
+/// Values of a statistic over time, sampled at a fixed interval
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeSeries {
+    /// Values, the most recent first
+    #[serde(default)]
+    pub history: Vec<f32>,
+    /// Most recent value
+    #[serde(default)]
+    pub latest: f32,
+}
+
+/// Values of a statistic over time, sampled at three intervals
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiStageTimeSeries {
+    /// Values sampled every 10 seconds
+    #[serde(default)]
+    pub sec10: TimeSeries,
+    /// Values sampled every minute
+    #[serde(default)]
+    pub min: TimeSeries,
+    /// Values sampled every hour
+    #[serde(default)]
+    pub hour: TimeSeries,
+}
+
+/// Load statistics of all the nodes of Jenkins. Statistics unknown to an older Jenkins are
+/// empty
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadStatistics {
+    /// Number of executors available to start a build
+    #[serde(default)]
+    pub available_executors: MultiStageTimeSeries,
+    /// Number of busy executors
+    #[serde(default)]
+    pub busy_executors: MultiStageTimeSeries,
+    /// Number of executors being connected
+    #[serde(default)]
+    pub connecting_executors: MultiStageTimeSeries,
+    /// Number of executors defined
+    #[serde(default)]
+    pub defined_executors: MultiStageTimeSeries,
+    /// Number of idle executors
+    #[serde(default)]
+    pub idle_executors: MultiStageTimeSeries,
+    /// Number of online executors
+    #[serde(default)]
+    pub online_executors: MultiStageTimeSeries,
+    /// Number of items in the queue that could be built by the executors
+    #[serde(default)]
+    pub queue_length: MultiStageTimeSeries,
+    /// Number of executors
+    #[serde(default)]
+    pub total_executors: MultiStageTimeSeries,
+    /// Number of items in the queue
+    #[serde(default)]
+    pub total_queue_length: MultiStageTimeSeries,
+}
+
+impl Jenkins {
+    /// Get the overall `LoadStatistics` of Jenkins
+    pub fn get_load_statistics(&self) -> Result<LoadStatistics, Error> {
+        Ok(self.get(&Path::OverallLoad)?.json()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    #[test]
+    fn can_get_load_statistics() {
+        let jenkins_client = crate::JenkinsBuilder::new(&format!("{}/load", mockito::server_url()))
+            .build()
+            .unwrap();
+
+        let _load = mockito::mock("GET", "/load/overallLoad/api/json?depth=1")
+            .with_body(
+                r#"{
+                    "_class": "hudson.model.OverallLoadStatistics",
+                    "busyExecutors": {
+                        "hour": {"history": [0.4, 0.1], "latest": 0.4},
+                        "min": {"history": [1.0], "latest": 1.0},
+                        "sec10": {"history": [1.0, 1.0, 0.0], "latest": 1.0}
+                    },
+                    "queueLength": {
+                        "hour": {"history": [], "latest": 0.0},
+                        "min": {"history": [], "latest": 0.0},
+                        "sec10": {"history": [], "latest": 0.0}
+                    },
+                    "totalExecutors": {
+                        "hour": {"history": [2.0], "latest": 2.0},
+                        "min": {"history": [2.0], "latest": 2.0},
+                        "sec10": {"history": [2.0], "latest": 2.0}
+                    },
+                    "totalQueueLength": {}
+                }"#,
+            )
+            .create();
+
+        let load = jenkins_client.get_load_statistics().unwrap();
+        assert_eq!(load.busy_executors.sec10.history, vec![1.0, 1.0, 0.0]);
+        assert_eq!(load.busy_executors.hour.latest, 0.4);
+        assert_eq!(load.total_executors.min.latest, 2.0);
+        assert!(load.total_queue_length.sec10.history.is_empty());
+    }
+}
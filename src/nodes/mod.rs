@@ -1,12 +1,17 @@
 //! Jenkins Slaves Informations
 
+use std::collections::HashMap;
+
 use failure::Error;
+use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
 
 use crate::client_internals::{Name, Path};
 use crate::Jenkins;
 
 pub mod computer;
+pub mod label;
+pub mod load;
 pub mod monitor;
 
 /// List of `Computer` associated to the `Jenkins` instance
@@ -24,30 +29,304 @@ pub struct ComputerSet {
     pub computers: Vec<computer::CommonComputer>,
 }
 
+/// Names of the built-in node, also known as master. Its URL was changed from `(master)` to
+/// `(built-in)` in Jenkins 2.307
+const BUILT_IN_NODE_NAMES: [&str; 2] = ["(built-in)", "(master)"];
+
 impl Jenkins {
+    /// Get the built-in node, trying its current name before falling back to the name used by
+    /// older Jenkins
+    fn get_built_in_node(&self) -> Result<Response, Error> {
+        match self.get(&Path::Computer {
+            name: Name::Name(BUILT_IN_NODE_NAMES[0]),
+        }) {
+            Err(ref error)
+                if error
+                    .downcast_ref::<reqwest::Error>()
+                    .and_then(reqwest::Error::status)
+                    == Some(StatusCode::NOT_FOUND) =>
+            {
+                self.get(&Path::Computer {
+                    name: Name::Name(BUILT_IN_NODE_NAMES[1]),
+                })
+            }
+            response => response,
+        }
+    }
+
     /// Get a `ComputerSet`
     pub fn get_nodes(&self) -> Result<ComputerSet, Error> {
         Ok(self.get(&Path::Computers)?.json()?)
     }
 
     /// Get a `Computer`
+    ///
+    /// The built-in node can be retrieved with either `(built-in)` or `(master)`, whatever the
+    /// version of Jenkins
     pub fn get_node<'a, C>(&self, computer_name: C) -> Result<computer::CommonComputer, Error>
     where
         C: Into<computer::ComputerName<'a>>,
     {
+        let computer_name = computer_name.into().0;
+        if BUILT_IN_NODE_NAMES.contains(&computer_name) {
+            return Ok(self.get_built_in_node()?.json()?);
+        }
         Ok(self
             .get(&Path::Computer {
-                name: Name::Name(&computer_name.into().0),
+                name: Name::Name(computer_name),
             })?
             .json()?)
     }
 
     /// Get the master `Computer`
     pub fn get_master_node(&self) -> Result<computer::MasterComputer, Error> {
+        Ok(self.get_built_in_node()?.json()?)
+    }
+
+    /// Get the data of the monitors of a `Computer`, by monitor name
+    pub fn get_node_monitor_data<'a, C>(
+        &self,
+        computer_name: C,
+    ) -> Result<HashMap<String, monitor::Monitor>, Error>
+    where
+        C: Into<computer::ComputerName<'a>>,
+    {
         Ok(self
-            .get(&Path::Computer {
-                name: Name::Name("(master)"),
-            })?
-            .json()?)
+            .get_node(computer_name)?
+            .monitor_data
+            .iter()
+            .map(|(name, data)| (name.clone(), monitor::Monitor::from_data(name, data)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use super::computer::{self, CommonComputer, Computer, MasterComputer, SlaveComputer};
+    use super::monitor;
+
+    static COMPUTER: &str = r#"{
+        "_class": "hudson.model.Hudson$MasterComputer",
+        "displayName": "master",
+        "description": "the master Jenkins node",
+        "icon": "computer.png",
+        "iconClassName": "icon-computer",
+        "idle": true,
+        "jnlpAgent": false,
+        "launchSupported": true,
+        "manualLaunchAllowed": true,
+        "numExecutors": 2,
+        "offline": false,
+        "offlineCause": null,
+        "offlineCauseReason": "",
+        "temporarilyOffline": false,
+        "monitorData": {
+            "hudson.node_monitors.ArchitectureMonitor": "Linux (amd64)",
+            "hudson.node_monitors.ClockMonitor": {
+                "_class": "hudson.util.ClockDifference",
+                "diff": 0
+            },
+            "hudson.node_monitors.DiskSpaceMonitor": {
+                "_class": "hudson.node_monitors.DiskSpaceMonitorDescriptor$DiskSpace",
+                "timestamp": 1500000000000,
+                "path": "/var/jenkins_home",
+                "size": 1073741824
+            },
+            "hudson.node_monitors.TemporarySpaceMonitor": {
+                "_class": "hudson.node_monitors.DiskSpaceMonitorDescriptor$DiskSpace",
+                "timestamp": 1500000000000
+            },
+            "hudson.node_monitors.ResponseTimeMonitor": null,
+            "org.example.CountMonitor": 12,
+            "org.example.ListMonitor": ["a", "b"]
+        },
+        "executors": [{
+            "currentExecutable": {
+                "_class": "hudson.model.FreeStyleBuild",
+                "number": 3,
+                "url": "http://none:8080/job/long%20job/3/"
+            },
+            "idle": false,
+            "likelyStuck": false,
+            "number": 0,
+            "progress": 42
+        }, {}],
+        "oneOffExecutors": [],
+        "assignedLabels": [{"name": "master"}]
+    }"#;
+
+    #[test]
+    fn can_get_master_node_on_older_jenkins() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/older-jenkins", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let built_in = mockito::mock(
+            "GET",
+            "/older-jenkins/computer/%28built-in%29/api/json?depth=1",
+        )
+        .with_status(404)
+        .expect(2)
+        .create();
+        let master = mockito::mock(
+            "GET",
+            "/older-jenkins/computer/%28master%29/api/json?depth=1",
+        )
+        .with_body(COMPUTER)
+        .expect(2)
+        .create();
+
+        let computer = jenkins_client.get_master_node().unwrap();
+        assert_eq!(computer.num_executors, 2);
+        let computer = jenkins_client.get_node("(built-in)").unwrap();
+        assert_eq!(computer.display_name, "master");
+
+        built_in.assert();
+        master.assert();
+    }
+
+    #[test]
+    fn can_get_built_in_node() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/newer-jenkins", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let built_in = mockito::mock(
+            "GET",
+            "/newer-jenkins/computer/%28built-in%29/api/json?depth=1",
+        )
+        .with_body(COMPUTER)
+        .expect(2)
+        .create();
+
+        let computer = jenkins_client.get_master_node().unwrap();
+        assert!(computer.idle);
+        match computer.executors[0] {
+            computer::Executor::Executor {
+                current_executable: Some(ref build),
+                idle: false,
+                progress: computer::ExecutorProgress::Percent(42),
+                ..
+            } => assert_eq!(build.number, 3),
+            ref other => panic!("unexpected executor {:?}", other),
+        }
+        let computer = jenkins_client.get_node("(master)").unwrap();
+        match computer.monitor_data["org.example.CountMonitor"] {
+            monitor::Data::Other(ref value) => assert_eq!(value, 12),
+            ref other => panic!("unexpected monitor data {:?}", other),
+        }
+
+        built_in.assert();
+    }
+
+    #[test]
+    fn can_mark_node_offline_and_online() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .disable_csrf()
+            .build()
+            .unwrap();
+        let computer: CommonComputer = serde_json::from_str(
+            &COMPUTER.replace(r#""displayName": "master""#, r#""displayName": "agent 1""#),
+        )
+        .unwrap();
+
+        let _node = mockito::mock("GET", "/computer/agent%201/api/json?depth=1")
+            .with_body(COMPUTER)
+            .expect(2)
+            .create();
+        let toggle = mockito::mock(
+            "POST",
+            "/computer/agent%201/toggleOffline?offlineMessage=maintenance",
+        )
+        .expect(1)
+        .create();
+
+        computer
+            .mark_offline(&jenkins_client, Some("maintenance"))
+            .unwrap();
+        computer.mark_online(&jenkins_client).unwrap();
+
+        toggle.assert();
+    }
+
+    #[test]
+    fn can_get_typed_node_monitor_data() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/monitored", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _node = mockito::mock("GET", "/monitored/computer/agent/api/json?depth=1")
+            .with_body(COMPUTER)
+            .create();
+
+        let monitors = jenkins_client.get_node_monitor_data("agent").unwrap();
+
+        match monitors["hudson.node_monitors.DiskSpaceMonitor"] {
+            monitor::Monitor::DiskSpace(ref disk_space) => {
+                assert_eq!(disk_space.size, 1_073_741_824);
+                assert_eq!(disk_space.path, "/var/jenkins_home");
+            }
+            ref other => panic!("unexpected monitor {:?}", other),
+        }
+        match monitors["hudson.node_monitors.ClockMonitor"] {
+            monitor::Monitor::ClockDifference(ref clock) => assert_eq!(clock.diff, 0),
+            ref other => panic!("unexpected monitor {:?}", other),
+        }
+        match monitors["hudson.node_monitors.ArchitectureMonitor"] {
+            monitor::Monitor::Architecture(ref architecture) => {
+                assert_eq!(architecture, "Linux (amd64)")
+            }
+            ref other => panic!("unexpected monitor {:?}", other),
+        }
+        match monitors["hudson.node_monitors.TemporarySpaceMonitor"] {
+            monitor::Monitor::Unknown(_) => (),
+            ref other => panic!("unexpected monitor {:?}", other),
+        }
+        match monitors["org.example.CountMonitor"] {
+            monitor::Monitor::Unknown(monitor::Data::Other(_)) => (),
+            ref other => panic!("unexpected monitor {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_get_nodes() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/listing", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let agent = COMPUTER
+            .replace(
+                "hudson.model.Hudson$MasterComputer",
+                "hudson.slaves.SlaveComputer",
+            )
+            .replace(r#""displayName": "master""#, r#""displayName": "agent 1""#)
+            .replace(r#""offline": false"#, r#""offline": true"#);
+        let _nodes = mockito::mock("GET", "/listing/computer/api/json?depth=1")
+            .with_body(format!(
+                r#"{{
+                    "_class": "hudson.model.ComputerSet",
+                    "busyExecutors": 0,
+                    "displayName": "Nodes",
+                    "totalExecutors": 2,
+                    "computer": [{}, {}]
+                }}"#,
+                COMPUTER, agent
+            ))
+            .create();
+
+        let nodes = jenkins_client.get_nodes().unwrap();
+
+        assert_eq!(nodes.total_executors, 2);
+        assert_eq!(nodes.computers.len(), 2);
+        assert!(nodes.computers[0].as_variant::<MasterComputer>().is_ok());
+        let agent = nodes.computers[1].as_variant::<SlaveComputer>().unwrap();
+        assert_eq!(agent.display_name, "agent 1");
+        assert!(agent.offline);
     }
 }
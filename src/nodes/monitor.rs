@@ -16,6 +16,8 @@ pub enum Data {
     MonitorData(CommonMonitorData),
     /// An empty monitor, meaning it was not able to retrieve data
     Empty,
+    /// Any other value, from a monitor providing data in an unknown format
+    Other(serde_json::Value),
 }
 
 /// Trait implemented by specialization of monitor data
@@ -51,8 +53,8 @@ pub struct SwapSpaceMonitor {
 register_class!("hudson.node_monitors.SwapSpaceMonitor$MemoryUsage2" => SwapSpaceMonitor);
 impl MonitorData for SwapSpaceMonitor {}
 
-/// Swap Space Monitor
-#[derive(Deserialize, Debug)]
+/// Disk Space Monitor
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DiskSpaceMonitorDescriptor {
     /// Timestamp
@@ -86,3 +88,50 @@ pub struct ClockDifference {
 }
 register_class!("hudson.util.ClockDifference" => ClockDifference);
 impl MonitorData for ClockDifference {}
+
+/// Data of the common monitors of a `Computer`, identified by the name of the monitor
+#[derive(Debug, Clone)]
+pub enum Monitor {
+    /// Free space in the workspace, from `hudson.node_monitors.DiskSpaceMonitor`
+    DiskSpace(DiskSpaceMonitorDescriptor),
+    /// Free space in the temporary folder, from `hudson.node_monitors.TemporarySpaceMonitor`
+    TemporarySpace(DiskSpaceMonitorDescriptor),
+    /// Memory and swap usage, from `hudson.node_monitors.SwapSpaceMonitor`
+    SwapSpace(SwapSpaceMonitor),
+    /// Response time, from `hudson.node_monitors.ResponseTimeMonitor`
+    ResponseTime(ResponseTimeMonitor),
+    /// Clock difference with the master, from `hudson.node_monitors.ClockMonitor`
+    ClockDifference(ClockDifference),
+    /// Architecture, from `hudson.node_monitors.ArchitectureMonitor`
+    Architecture(String),
+    /// Data from another monitor, or data that could not be read as expected
+    Unknown(Data),
+}
+
+impl Monitor {
+    /// Read the `data` provided by the monitor `monitor_name`
+    pub fn from_data(monitor_name: &str, data: &Data) -> Monitor {
+        let typed = match (monitor_name, data) {
+            ("hudson.node_monitors.DiskSpaceMonitor", Data::MonitorData(data)) => {
+                data.as_variant().ok().map(Monitor::DiskSpace)
+            }
+            ("hudson.node_monitors.TemporarySpaceMonitor", Data::MonitorData(data)) => {
+                data.as_variant().ok().map(Monitor::TemporarySpace)
+            }
+            ("hudson.node_monitors.SwapSpaceMonitor", Data::MonitorData(data)) => {
+                data.as_variant().ok().map(Monitor::SwapSpace)
+            }
+            ("hudson.node_monitors.ResponseTimeMonitor", Data::MonitorData(data)) => {
+                data.as_variant().ok().map(Monitor::ResponseTime)
+            }
+            ("hudson.node_monitors.ClockMonitor", Data::MonitorData(data)) => {
+                data.as_variant().ok().map(Monitor::ClockDifference)
+            }
+            ("hudson.node_monitors.ArchitectureMonitor", Data::String(architecture)) => {
+                Some(Monitor::Architecture(architecture.clone()))
+            }
+            _ => None,
+        };
+        typed.unwrap_or_else(|| Monitor::Unknown(data.clone()))
+    }
+}
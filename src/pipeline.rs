@@ -0,0 +1,349 @@
+//! Stages and steps of pipeline builds, from the pipeline stage view plugin (`wfapi`)
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::build::{BuildNumber, CommonBuild, WorkflowRun};
+use crate::client;
+use crate::client_internals::{Name, Path};
+use crate::helpers::{Class, MaybeDuration, MaybeTimestamp};
+use crate::Jenkins;
+
+/// Status of a pipeline run, stage or step
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WorkflowStatus {
+    /// Successful
+    Success,
+    /// Unstable
+    Unstable,
+    /// Failed
+    Failed,
+    /// Aborted
+    Aborted,
+    /// Skipped
+    NotExecuted,
+    /// Running
+    InProgress,
+    /// Waiting for an input
+    PausedPendingInput,
+    /// Status not known by this crate
+    #[serde(other)]
+    Unknown,
+}
+
+/// Description of a pipeline run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowDescription {
+    /// Id of the run, its build number
+    pub id: String,
+    /// Name of the run
+    pub name: String,
+    /// Status of the run
+    pub status: WorkflowStatus,
+    /// Start of the run
+    pub start_time_millis: MaybeTimestamp,
+    /// Duration of the run
+    pub duration_millis: MaybeDuration,
+    /// Stages of the run
+    #[serde(default)]
+    pub stages: Vec<WorkflowStage>,
+}
+
+/// A stage of a pipeline run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowStage {
+    /// Id of the flow node starting the stage
+    pub id: String,
+    /// Name of the stage
+    pub name: String,
+    /// Status of the stage
+    pub status: WorkflowStatus,
+    /// Duration of the stage
+    pub duration_millis: MaybeDuration,
+    /// Start of the stage, unknown if it was not executed
+    pub start_time_millis: MaybeTimestamp,
+    #[serde(skip)]
+    build_url: String,
+}
+
+/// A flow node of a stage, usually a step
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowFlowNode {
+    /// Id of the flow node
+    pub id: String,
+    /// Name of the flow node
+    pub name: String,
+    /// Status of the flow node
+    pub status: WorkflowStatus,
+    /// Description of the parameters of the step, like the script of a `sh` step
+    pub parameter_description: Option<String>,
+    /// Duration of the flow node
+    pub duration_millis: MaybeDuration,
+    /// Start of the flow node
+    pub start_time_millis: MaybeTimestamp,
+    /// Ids of the parent flow nodes
+    #[serde(default)]
+    pub parent_nodes: Vec<String>,
+    #[serde(skip)]
+    build_url: String,
+}
+
+/// Log of a flow node
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowNodeLog {
+    /// Id of the flow node
+    pub node_id: String,
+    /// Status of the flow node
+    pub node_status: WorkflowStatus,
+    /// Length of the full log
+    #[serde(default)]
+    pub length: u64,
+    /// Is the log truncated
+    #[serde(default)]
+    pub has_more: bool,
+    /// Text of the log, in HTML
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StageDescription {
+    #[serde(default)]
+    stage_flow_nodes: Vec<WorkflowFlowNode>,
+}
+
+/// Split the URL of a build in its job name and build number
+fn job_and_number<'a>(
+    jenkins_client: &Jenkins,
+    build_url: &'a str,
+) -> Result<(Name<'a>, BuildNumber), Error> {
+    if let Path::Build {
+        job_name, number, ..
+    } = jenkins_client.url_to_path(build_url)
+    {
+        Ok((job_name, number))
+    } else {
+        Err(client::Error::InvalidUrl {
+            url: build_url.to_string(),
+            expected: client::error::ExpectedType::Build,
+        }
+        .into())
+    }
+}
+
+pub(crate) fn get_workflow_description(
+    jenkins_client: &Jenkins,
+    build_url: &str,
+) -> Result<WorkflowDescription, Error> {
+    let (job_name, number) = job_and_number(jenkins_client, build_url)?;
+    let mut description: WorkflowDescription = jenkins_client
+        .get_file(&Path::PipelineDescription {
+            job_name,
+            number,
+            configuration: None,
+        })?
+        .json()?;
+    for stage in &mut description.stages {
+        stage.build_url = build_url.to_string();
+    }
+    Ok(description)
+}
+
+impl WorkflowRun {
+    /// Get the stages of this pipeline run
+    pub fn get_workflow_description(
+        &self,
+        jenkins_client: &Jenkins,
+    ) -> Result<WorkflowDescription, Error> {
+        get_workflow_description(jenkins_client, &self.url)
+    }
+}
+
+impl CommonBuild {
+    /// Get the stages of this build, if it is a pipeline run
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::InvalidObjectType`](../client/enum.Error.html#variant.InvalidObjectType)
+    /// if the build is not a `WorkflowRun`
+    pub fn get_workflow_description(
+        &self,
+        jenkins_client: &Jenkins,
+    ) -> Result<WorkflowDescription, Error> {
+        match self.class.as_deref() {
+            Some(class) if class == WorkflowRun::with_class() => {
+                get_workflow_description(jenkins_client, &self.url)
+            }
+            class => Err(client::Error::InvalidObjectType {
+                object_type: client::error::ExpectedType::Build,
+                variant_name: class.unwrap_or("unknown").to_string(),
+                action: client::error::Action::GetWorkflowDescription,
+            }
+            .into()),
+        }
+    }
+}
+
+impl WorkflowStage {
+    /// Get the flow nodes of this stage
+    pub fn get_flow_nodes(&self, jenkins_client: &Jenkins) -> Result<Vec<WorkflowFlowNode>, Error> {
+        let (job_name, number) = job_and_number(jenkins_client, &self.build_url)?;
+        let description: StageDescription = jenkins_client
+            .get_file(&Path::FlowNodeDescription {
+                job_name,
+                number,
+                node_id: Name::Name(&self.id),
+            })?
+            .json()?;
+        Ok(description
+            .stage_flow_nodes
+            .into_iter()
+            .map(|node| WorkflowFlowNode {
+                build_url: self.build_url.clone(),
+                ..node
+            })
+            .collect())
+    }
+}
+
+impl WorkflowFlowNode {
+    /// Get the log of this flow node
+    pub fn get_log(&self, jenkins_client: &Jenkins) -> Result<WorkflowNodeLog, Error> {
+        let (job_name, number) = job_and_number(jenkins_client, &self.build_url)?;
+        Ok(jenkins_client
+            .get_file(&Path::FlowNodeLog {
+                job_name,
+                number,
+                node_id: Name::Name(&self.id),
+            })?
+            .json()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(class: &str, url: &str) -> CommonBuild {
+        serde_json::from_str(&format!(
+            r#"{{"_class":"{}","url":"{}","number":2,"duration":10,"estimatedDuration":10,
+            "timestamp":1500000000000,"keepLog":false,"result":"SUCCESS","displayName":"2",
+            "building":false,"id":"2","queueId":1}}"#,
+            class, url
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_get_stages_steps_and_logs() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/wfapi", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _describe = mockito::mock("GET", "/wfapi/job/pipeline/2/wfapi/describe")
+            .with_body(
+                r##"{"_links":{},"id":"2","name":"#2","status":"FAILED",
+                "startTimeMillis":1500000000000,"endTimeMillis":1500000010000,
+                "durationMillis":10000,"queueDurationMillis":5,"pauseDurationMillis":0,
+                "stages":[
+                    {"id":"6","name":"Build","execNode":"","status":"SUCCESS",
+                    "startTimeMillis":1500000000100,"durationMillis":4000,"pauseDurationMillis":0},
+                    {"id":"12","name":"Test","execNode":"","status":"FAILED",
+                    "startTimeMillis":1500000004100,"durationMillis":5000,"pauseDurationMillis":0},
+                    {"id":"18","name":"Deploy","execNode":"","status":"NOT_EXECUTED",
+                    "startTimeMillis":0,"durationMillis":0,"pauseDurationMillis":0}
+                ]}"##,
+            )
+            .create();
+        let _stage = mockito::mock(
+            "GET",
+            "/wfapi/job/pipeline/2/execution/node/12/wfapi/describe",
+        )
+        .with_body(
+            r#"{"id":"12","name":"Test","status":"FAILED","stageFlowNodes":[
+                    {"id":"13","name":"Shell Script","execNode":"","status":"FAILED",
+                    "parameterDescription":"make test","startTimeMillis":1500000004200,
+                    "durationMillis":4800,"pauseDurationMillis":0,"parentNodes":["12"]}
+                ]}"#,
+        )
+        .create();
+        let _log = mockito::mock("GET", "/wfapi/job/pipeline/2/execution/node/13/wfapi/log")
+            .with_body(
+                r#"{"nodeId":"13","nodeStatus":"FAILED","length":22,"hasMore":false,
+                "text":"+ make test\nFAILED\n","consoleUrl":"/job/pipeline/2/execution/node/13/log"}"#,
+            )
+            .create();
+
+        let build = build(
+            "org.jenkinsci.plugins.workflow.job.WorkflowRun",
+            &format!("{}/wfapi/job/pipeline/2/", mockito::server_url()),
+        );
+        let description = build.get_workflow_description(&jenkins_client).unwrap();
+        assert_eq!(description.status, WorkflowStatus::Failed);
+        assert_eq!(
+            description
+                .stages
+                .iter()
+                .map(|stage| stage.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Build", "Test", "Deploy"]
+        );
+        assert_eq!(
+            description.stages[1].start_time_millis,
+            MaybeTimestamp::Known(1500000004100)
+        );
+        assert_eq!(
+            description.stages[1].duration_millis.as_duration(),
+            Some(std::time::Duration::from_secs(5))
+        );
+        assert_eq!(description.stages[2].status, WorkflowStatus::NotExecuted);
+        assert!(!description.stages[2].start_time_millis.is_known());
+
+        let nodes = description.stages[1]
+            .get_flow_nodes(&jenkins_client)
+            .unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(
+            nodes[0].parameter_description,
+            Some("make test".to_string())
+        );
+        assert_eq!(nodes[0].parent_nodes, vec!["12"]);
+
+        let log = nodes[0].get_log(&jenkins_client).unwrap();
+        assert_eq!(log.node_status, WorkflowStatus::Failed);
+        assert_eq!(log.text, "+ make test\nFAILED\n");
+    }
+
+    #[test]
+    fn cant_get_workflow_description_of_other_builds() {
+        let jenkins_client = crate::JenkinsBuilder::new(&mockito::server_url())
+            .build()
+            .unwrap();
+        let build = build(
+            "hudson.model.FreeStyleBuild",
+            &format!("{}/job/freestyle/2/", mockito::server_url()),
+        );
+        match build
+            .get_workflow_description(&jenkins_client)
+            .unwrap_err()
+            .downcast::<client::Error>()
+        {
+            Ok(client::Error::InvalidObjectType { variant_name, .. }) => {
+                assert_eq!(variant_name, "hudson.model.FreeStyleBuild")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_statuses_are_kept() {
+        let status: WorkflowStatus = serde_json::from_str(r#""SOMETHING_NEW""#).unwrap();
+        assert_eq!(status, WorkflowStatus::Unknown);
+    }
+}
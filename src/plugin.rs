@@ -0,0 +1,471 @@
+//! Plugins installed on Jenkins
+
+use std::cmp::Ordering;
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::client_internals::Path;
+use crate::Jenkins;
+
+/// A plugin installed on Jenkins
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Plugin {
+    /// Short name of the plugin, used as its id
+    pub short_name: String,
+    /// Display name of the plugin
+    pub long_name: String,
+    /// Installed version
+    pub version: String,
+    /// Is the plugin enabled
+    pub enabled: bool,
+    /// Is the plugin loaded
+    pub active: bool,
+    /// Is an update available for this plugin. Older Jenkins don't provide it
+    #[serde(default)]
+    pub has_update: bool,
+    /// URL of the plugin homepage
+    pub url: Option<String>,
+    /// Plugins this plugin depends on
+    #[serde(default)]
+    pub dependencies: Vec<PluginDependency>,
+}
+
+/// A dependency of a `Plugin` on another plugin
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginDependency {
+    /// Short name of the plugin depended on
+    pub short_name: String,
+    /// Minimum version required
+    pub version: String,
+    /// Is the dependency optional
+    pub optional: bool,
+}
+
+impl Plugin {
+    /// Enable this plugin. Jenkins needs to be restarted for the change to take effect
+    pub fn enable(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        let _ = jenkins_client.post(&Path::PluginMakeEnabled {
+            short_name: &self.short_name,
+        })?;
+        Ok(())
+    }
+
+    /// Disable this plugin. Jenkins needs to be restarted for the change to take effect
+    pub fn disable(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        let _ = jenkins_client.post(&Path::PluginMakeDisabled {
+            short_name: &self.short_name,
+        })?;
+        Ok(())
+    }
+
+    /// Is the installed version at least `version`. Plugin versions are not strict semver, this
+    /// compares them the way Maven does: numbers numerically, and qualifiers like `-beta-2` or
+    /// `-SNAPSHOT` before the release they qualify
+    pub fn is_at_least(&self, version: &str) -> bool {
+        compare_versions(&self.version, version) != Ordering::Less
+    }
+}
+
+/// Status of an installation by the update center
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallationStatus {
+    /// Type of the status, like `Pending`, `Installing`, `Success`,
+    /// `SuccessButRequiresRestart` or `Failure`
+    #[serde(rename = "type")]
+    pub status_type: String,
+    /// Has the installation succeeded
+    #[serde(default)]
+    pub success: bool,
+}
+
+/// A job of the update center, like the installation of a plugin
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCenterJob {
+    /// _class provided by Jenkins, like `hudson.model.UpdateCenter$InstallationJob`
+    #[serde(rename = "_class")]
+    pub class: Option<String>,
+    /// ID of the job, increasing with each job
+    pub id: u32,
+    /// Type of the job, like `InstallationJob` or `ConnectionCheckJob`
+    #[serde(rename = "type")]
+    pub job_type: String,
+    /// Error of the job, if it failed
+    pub error_message: Option<String>,
+    /// Short name of the plugin, for installation jobs
+    pub name: Option<String>,
+    /// Status of the installation, for installation jobs
+    pub status: Option<InstallationStatus>,
+}
+
+impl UpdateCenterJob {
+    /// Is the job done, successfully or not
+    pub fn is_done(&self) -> bool {
+        match self.status {
+            Some(ref status) => !matches!(status.status_type.as_str(), "Pending" | "Installing"),
+            None => self.error_message.is_some(),
+        }
+    }
+
+    /// Has the job succeeded
+    pub fn is_success(&self) -> bool {
+        self.status.as_ref().is_some_and(|status| status.success)
+    }
+}
+
+/// The update center, with the jobs installing plugins
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCenter {
+    /// Jobs of the update center, the most recent last
+    pub jobs: Vec<UpdateCenterJob>,
+}
+
+impl UpdateCenter {
+    /// Latest job installing the plugin `short_name`
+    pub fn installation(&self, short_name: &str) -> Option<&UpdateCenterJob> {
+        self.jobs
+            .iter()
+            .filter(|job| job.name.as_deref() == Some(short_name))
+            .max_by_key(|job| job.id)
+    }
+}
+
+/// Escape `text` to be used as an XML attribute value
+fn xml_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Part of a version, between separators or at a change between digits and letters
+#[derive(Debug, PartialEq)]
+enum VersionItem {
+    Number(u64),
+    Qualifier(String),
+}
+
+impl VersionItem {
+    /// Rank of a qualifier, a missing one being a release
+    fn qualifier_rank(qualifier: &str) -> u8 {
+        match qualifier {
+            "alpha" | "a" => 0,
+            "beta" | "b" => 1,
+            "milestone" | "m" => 2,
+            "rc" | "cr" => 3,
+            "snapshot" => 4,
+            "" | "ga" | "final" | "release" => 5,
+            "sp" => 6,
+            _ => 7,
+        }
+    }
+
+    fn compare(left: Option<&Self>, right: Option<&Self>) -> Ordering {
+        match (left, right) {
+            (Some(VersionItem::Number(left)), Some(VersionItem::Number(right))) => left.cmp(right),
+            (Some(VersionItem::Number(_)), Some(VersionItem::Qualifier(_))) => Ordering::Greater,
+            (Some(VersionItem::Qualifier(_)), Some(VersionItem::Number(_))) => Ordering::Less,
+            (Some(VersionItem::Qualifier(left)), Some(VersionItem::Qualifier(right))) => {
+                Self::qualifier_rank(left)
+                    .cmp(&Self::qualifier_rank(right))
+                    .then_with(|| left.cmp(right))
+            }
+            (None, Some(VersionItem::Number(right))) => 0.cmp(right),
+            (Some(VersionItem::Number(left)), None) => left.cmp(&0),
+            (None, Some(VersionItem::Qualifier(right))) => {
+                Self::qualifier_rank("").cmp(&Self::qualifier_rank(right))
+            }
+            (Some(VersionItem::Qualifier(left)), None) => {
+                Self::qualifier_rank(left).cmp(&Self::qualifier_rank(""))
+            }
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+fn version_items(version: &str) -> Vec<VersionItem> {
+    let mut items = vec![];
+    let mut current = String::new();
+    let push = |current: &mut String, items: &mut Vec<VersionItem>| {
+        if !current.is_empty() {
+            items.push(match current.parse() {
+                Ok(number) => VersionItem::Number(number),
+                Err(_) => VersionItem::Qualifier(current.to_lowercase()),
+            });
+            current.clear();
+        }
+    };
+    for c in version.trim().chars() {
+        if c == '.' || c == '-' || c == '_' {
+            push(&mut current, &mut items);
+        } else {
+            if current
+                .chars()
+                .last()
+                .is_some_and(|last| last.is_ascii_digit() != c.is_ascii_digit())
+            {
+                push(&mut current, &mut items);
+            }
+            current.push(c);
+        }
+    }
+    push(&mut current, &mut items);
+    items
+}
+
+/// Compare two plugin versions
+fn compare_versions(left: &str, right: &str) -> Ordering {
+    let left = version_items(left);
+    let right = version_items(right);
+    (0..std::cmp::max(left.len(), right.len()))
+        .map(|i| VersionItem::compare(left.get(i), right.get(i)))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// The plugin manager, listing installed `Plugin`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManager {
+    /// Installed plugins
+    pub plugins: Vec<Plugin>,
+}
+
+impl Jenkins {
+    /// Get the list of installed `Plugin`
+    pub fn get_plugins(&self) -> Result<Vec<Plugin>, Error> {
+        let plugin_manager: PluginManager = self.get_with_depth(&Path::PluginManager, 1)?.json()?;
+        Ok(plugin_manager.plugins)
+    }
+
+    /// Install the plugin `short_name`, at `version` or at the latest version. The installation
+    /// is asynchronous, its progress can be followed with `get_update_center`
+    pub fn install_plugin(&self, short_name: &str, version: Option<&str>) -> Result<(), Error> {
+        let body = format!(
+            r#"<jenkins><install plugin="{}@{}"/></jenkins>"#,
+            xml_attribute(short_name),
+            xml_attribute(version.unwrap_or("latest"))
+        );
+        let _ = self
+            .post_xml_unchecked(&Path::InstallNecessaryPlugins, &body, &[])?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Get the update center, to follow the installation of plugins
+    pub fn get_update_center(&self) -> Result<UpdateCenter, Error> {
+        Ok(self.get_with_depth(&Path::UpdateCenter, 1)?.json()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use mockito;
+
+    use super::compare_versions;
+
+    #[test]
+    fn can_compare_plugin_versions() {
+        let cases = [
+            ("2.30.1", "2.30.1", Ordering::Equal),
+            ("2.30.1", "2.30", Ordering::Greater),
+            ("2.30", "2.30.0", Ordering::Equal),
+            ("2.30.1", "2.4", Ordering::Greater),
+            ("2.9", "2.10", Ordering::Less),
+            ("1.0-beta-2", "1.0", Ordering::Less),
+            ("1.0-beta-2", "1.0-beta-10", Ordering::Less),
+            ("1.0-alpha-3", "1.0-beta-1", Ordering::Less),
+            ("1.0-rc1", "1.0-beta-2", Ordering::Greater),
+            ("1.0-SNAPSHOT", "1.0", Ordering::Less),
+            ("1.0-SNAPSHOT", "0.9", Ordering::Greater),
+            ("1.0.1", "1.0-beta-2", Ordering::Greater),
+            ("3.9.1-rc1234.abcdef", "3.9.1", Ordering::Less),
+            ("2.6_2", "2.6.1", Ordering::Greater),
+        ];
+        for (left, right, expected) in cases.iter() {
+            assert_eq!(
+                compare_versions(left, right),
+                *expected,
+                "comparing {} to {}",
+                left,
+                right
+            );
+            assert_eq!(
+                compare_versions(right, left),
+                expected.reverse(),
+                "comparing {} to {}",
+                right,
+                left
+            );
+        }
+    }
+
+    #[test]
+    fn can_install_plugin() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/install-plugin", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let latest = mockito::mock(
+            "POST",
+            "/install-plugin/pluginManager/installNecessaryPlugins",
+        )
+        .match_header("content-type", "application/xml")
+        .match_body(r#"<jenkins><install plugin="timestamper@latest"/></jenkins>"#)
+        .create();
+        let pinned = mockito::mock(
+            "POST",
+            "/install-plugin/pluginManager/installNecessaryPlugins",
+        )
+        .match_body(r#"<jenkins><install plugin="git@3.9.1"/></jenkins>"#)
+        .create();
+
+        assert!(jenkins_client.install_plugin("timestamper", None).is_ok());
+        assert!(jenkins_client.install_plugin("git", Some("3.9.1")).is_ok());
+
+        latest.assert();
+        pinned.assert();
+    }
+
+    #[test]
+    fn can_follow_plugin_installation() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/update-center", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _update_center = mockito::mock("GET", "/update-center/updateCenter/api/json?depth=1")
+            .with_body(
+                r#"{
+                    "_class": "hudson.model.UpdateCenter",
+                    "availables": [],
+                    "jobs": [
+                        {"_class": "hudson.model.UpdateCenter$ConnectionCheckJob",
+                        "errorMessage": null, "id": 0, "type": "ConnectionCheckJob"},
+                        {"_class": "hudson.model.UpdateCenter$InstallationJob",
+                        "errorMessage": null, "id": 1, "type": "InstallationJob",
+                        "name": "timestamper",
+                        "status": {"_class": "hudson.model.UpdateCenter$DownloadJob$Failure",
+                        "type": "Failure"}},
+                        {"_class": "hudson.model.UpdateCenter$InstallationJob",
+                        "errorMessage": null, "id": 2, "type": "InstallationJob",
+                        "name": "timestamper",
+                        "status": {"_class": "hudson.model.UpdateCenter$DownloadJob$Success",
+                        "success": true, "type": "Success"}},
+                        {"_class": "hudson.model.UpdateCenter$InstallationJob",
+                        "errorMessage": null, "id": 3, "type": "InstallationJob",
+                        "name": "git",
+                        "status": {"_class": "hudson.model.UpdateCenter$DownloadJob$Installing",
+                        "type": "Installing"}}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let update_center = jenkins_client.get_update_center().unwrap();
+
+        let timestamper = update_center.installation("timestamper").unwrap();
+        assert_eq!(timestamper.id, 2);
+        assert!(timestamper.is_done());
+        assert!(timestamper.is_success());
+        let git = update_center.installation("git").unwrap();
+        assert!(!git.is_done());
+        assert!(!git.is_success());
+        assert!(update_center.installation("unknown").is_none());
+    }
+
+    #[test]
+    fn can_enable_and_disable_plugin() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/toggle-plugin", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+        let plugin: super::Plugin = serde_json::from_str(
+            r#"{"active": true, "enabled": true, "longName": "Timestamper",
+            "shortName": "timestamper", "version": "1.8.10"}"#,
+        )
+        .unwrap();
+
+        let disable = mockito::mock(
+            "POST",
+            "/toggle-plugin/pluginManager/plugin/timestamper/makeDisabled",
+        )
+        .create();
+        let enable = mockito::mock(
+            "POST",
+            "/toggle-plugin/pluginManager/plugin/timestamper/makeEnabled",
+        )
+        .create();
+
+        assert!(plugin.disable(&jenkins_client).is_ok());
+        assert!(plugin.enable(&jenkins_client).is_ok());
+
+        disable.assert();
+        enable.assert();
+    }
+
+    #[test]
+    fn can_get_plugins() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/plugins", mockito::server_url()))
+                .with_depth(0)
+                .build()
+                .unwrap();
+
+        let _plugins = mockito::mock("GET", "/plugins/pluginManager/api/json?depth=1")
+            .with_body(
+                r#"{
+                    "_class": "hudson.LocalPluginManager",
+                    "plugins": [
+                        {
+                            "active": true,
+                            "enabled": true,
+                            "hasUpdate": true,
+                            "longName": "Git plugin",
+                            "shortName": "git",
+                            "url": "https://wiki.jenkins.io/display/JENKINS/Git+Plugin",
+                            "version": "3.9.1",
+                            "dependencies": [
+                                {"optional": false, "shortName": "scm-api", "version": "2.2.7"},
+                                {"optional": true, "shortName": "credentials", "version": "2.1.17"}
+                            ]
+                        },
+                        {
+                            "active": false,
+                            "enabled": false,
+                            "longName": "Old plugin",
+                            "shortName": "old",
+                            "version": "1.0"
+                        }
+                    ]
+                }"#,
+            )
+            .create();
+
+        let plugins = jenkins_client.get_plugins().unwrap();
+
+        assert_eq!(plugins.len(), 2);
+        assert_eq!(plugins[0].short_name, "git");
+        assert!(plugins[0].has_update);
+        assert_eq!(plugins[1].version, "1.0");
+        assert!(!plugins[1].has_update);
+        assert!(plugins[0].is_at_least("3.9"));
+        assert!(plugins[0].is_at_least("3.9.1"));
+        assert!(!plugins[0].is_at_least("3.10"));
+        assert_eq!(plugins[0].dependencies.len(), 2);
+        assert_eq!(plugins[0].dependencies[0].short_name, "scm-api");
+        assert!(plugins[0].dependencies[1].optional);
+        assert!(plugins[1].dependencies.is_empty());
+    }
+}
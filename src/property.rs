@@ -35,9 +35,249 @@ pub struct RateLimitBranchProperty {}
 register_class!("jenkins.branch.RateLimitBranchProperty$JobPropertyImpl" => RateLimitBranchProperty);
 impl Property for RateLimitBranchProperty {}
 
+/// Email address of a user
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailAddressProperty {
+    /// Email address, if the user set one
+    pub address: Option<String>,
+}
+register_class!("hudson.tasks.Mailer$UserProperty" => EmailAddressProperty);
+impl Property for EmailAddressProperty {}
+
 /// Old builds of job are discarded
-#[derive(Deserialize, Debug, Copy, Clone)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct BuildDiscarderProperty {}
+pub struct BuildDiscarderProperty {
+    /// How builds are discarded
+    pub strategy: Option<BuildDiscarderStrategy>,
+}
 register_class!("jenkins.model.BuildDiscarderProperty" => BuildDiscarderProperty);
 impl Property for BuildDiscarderProperty {}
+
+/// How old builds are discarded, usually a `hudson.tasks.LogRotator`. A negative number means
+/// there is no limit
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildDiscarderStrategy {
+    /// _class provided by Jenkins
+    #[serde(rename = "_class")]
+    pub class: Option<String>,
+    /// Number of days to keep builds
+    #[serde(default = "unlimited")]
+    pub days_to_keep: i32,
+    /// Number of builds to keep
+    #[serde(default = "unlimited")]
+    pub num_to_keep: i32,
+    /// Number of days to keep artifacts of builds
+    #[serde(default = "unlimited")]
+    pub artifact_days_to_keep: i32,
+    /// Number of builds to keep artifacts of
+    #[serde(default = "unlimited")]
+    pub artifact_num_to_keep: i32,
+}
+
+fn unlimited() -> i32 {
+    -1
+}
+
+impl BuildDiscarderStrategy {
+    /// Is this strategy the `LogRotator` provided by Jenkins, the only one whose numbers are
+    /// known
+    pub fn is_log_rotator(&self) -> bool {
+        self.class.as_deref() == Some("hudson.tasks.LogRotator")
+    }
+}
+
+/// Job has parameters
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParametersDefinitionProperty {
+    /// Parameters accepted by the job
+    pub parameter_definitions: Vec<ParameterDefinition>,
+}
+register_class!("hudson.model.ParametersDefinitionProperty" => ParametersDefinitionProperty);
+impl Property for ParametersDefinitionProperty {}
+
+/// Definition of a parameter accepted by a job
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "RawParameterDefinition")]
+pub enum ParameterDefinition {
+    /// A string parameter, from `hudson.model.StringParameterDefinition`
+    String {
+        /// Name of the parameter
+        name: String,
+        /// Description of the parameter
+        description: Option<String>,
+        /// Default value of the parameter
+        default: Option<String>,
+    },
+    /// A boolean parameter, from `hudson.model.BooleanParameterDefinition`
+    Boolean {
+        /// Name of the parameter
+        name: String,
+        /// Description of the parameter
+        description: Option<String>,
+        /// Default value of the parameter
+        default: Option<bool>,
+    },
+    /// A parameter with a list of choices, from `hudson.model.ChoiceParameterDefinition`. The
+    /// first choice is the default
+    Choice {
+        /// Name of the parameter
+        name: String,
+        /// Description of the parameter
+        description: Option<String>,
+        /// Possible values of the parameter
+        choices: Vec<String>,
+    },
+    /// A password parameter, from `hudson.model.PasswordParameterDefinition`. Its default value
+    /// is never provided
+    Password {
+        /// Name of the parameter
+        name: String,
+        /// Description of the parameter
+        description: Option<String>,
+    },
+    /// Another kind of parameter
+    Other {
+        /// _class provided by Jenkins
+        class: Option<String>,
+        /// Name of the parameter
+        name: String,
+        /// Description of the parameter
+        description: Option<String>,
+    },
+}
+
+impl ParameterDefinition {
+    /// Name of the parameter
+    pub fn name(&self) -> &str {
+        match *self {
+            ParameterDefinition::String { ref name, .. }
+            | ParameterDefinition::Boolean { ref name, .. }
+            | ParameterDefinition::Choice { ref name, .. }
+            | ParameterDefinition::Password { ref name, .. }
+            | ParameterDefinition::Other { ref name, .. } => name,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawParameterDefinition {
+    #[serde(rename = "_class")]
+    class: Option<String>,
+    name: String,
+    description: Option<String>,
+    default_parameter_value: Option<RawParameterValue>,
+    choices: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct RawParameterValue {
+    value: Option<serde_json::Value>,
+}
+
+impl From<RawParameterDefinition> for ParameterDefinition {
+    fn from(raw: RawParameterDefinition) -> Self {
+        let default = raw
+            .default_parameter_value
+            .and_then(|default| default.value);
+        let RawParameterDefinition {
+            class,
+            name,
+            description,
+            choices,
+            ..
+        } = raw;
+        match class.as_deref() {
+            Some("hudson.model.StringParameterDefinition") => ParameterDefinition::String {
+                name,
+                description,
+                default: default.and_then(|value| value.as_str().map(str::to_string)),
+            },
+            Some("hudson.model.BooleanParameterDefinition") => ParameterDefinition::Boolean {
+                name,
+                description,
+                default: default.and_then(|value| value.as_bool()),
+            },
+            Some("hudson.model.ChoiceParameterDefinition") => ParameterDefinition::Choice {
+                name,
+                description,
+                choices: choices.unwrap_or_default(),
+            },
+            Some("hudson.model.PasswordParameterDefinition") => {
+                ParameterDefinition::Password { name, description }
+            }
+            _ => ParameterDefinition::Other {
+                class,
+                name,
+                description,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_read_parameter_definitions() {
+        let property: CommonProperty = serde_json::from_str(
+            r#"{"_class":"hudson.model.ParametersDefinitionProperty","parameterDefinitions":[
+            {"_class":"hudson.model.StringParameterDefinition","defaultParameterValue":{
+            "_class":"hudson.model.StringParameterValue","name":"branch","value":"master"},
+            "description":"Branch to build","name":"branch","type":"StringParameterDefinition"},
+            {"_class":"hudson.model.BooleanParameterDefinition","defaultParameterValue":{
+            "_class":"hudson.model.BooleanParameterValue","name":"deploy","value":false},
+            "description":"","name":"deploy","type":"BooleanParameterDefinition"},
+            {"_class":"hudson.model.ChoiceParameterDefinition","defaultParameterValue":{
+            "_class":"hudson.model.StringParameterValue","name":"env","value":"dev"},
+            "description":null,"name":"env","type":"ChoiceParameterDefinition",
+            "choices":["dev","prod"]},
+            {"_class":"hudson.model.PasswordParameterDefinition","defaultParameterValue":{
+            "_class":"hudson.model.PasswordParameterValue","name":"token"},"description":"",
+            "name":"token","type":"PasswordParameterDefinition"},
+            {"_class":"hudson.model.FileParameterDefinition","defaultParameterValue":null,
+            "description":"","name":"upload","type":"FileParameterDefinition"}]}"#,
+        )
+        .unwrap();
+
+        let parameters = property
+            .as_variant::<ParametersDefinitionProperty>()
+            .unwrap()
+            .parameter_definitions;
+        assert_eq!(
+            parameters,
+            vec![
+                ParameterDefinition::String {
+                    name: "branch".to_string(),
+                    description: Some("Branch to build".to_string()),
+                    default: Some("master".to_string()),
+                },
+                ParameterDefinition::Boolean {
+                    name: "deploy".to_string(),
+                    description: Some("".to_string()),
+                    default: Some(false),
+                },
+                ParameterDefinition::Choice {
+                    name: "env".to_string(),
+                    description: None,
+                    choices: vec!["dev".to_string(), "prod".to_string()],
+                },
+                ParameterDefinition::Password {
+                    name: "token".to_string(),
+                    description: Some("".to_string()),
+                },
+                ParameterDefinition::Other {
+                    class: Some("hudson.model.FileParameterDefinition".to_string()),
+                    name: "upload".to_string(),
+                    description: Some("".to_string()),
+                },
+            ]
+        );
+        assert_eq!(parameters[3].name(), "token");
+    }
+}
@@ -1,13 +1,19 @@
 //! Jenkins build queue
 
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
 use failure::Error;
+use log::{debug, warn};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
 use crate::action::CommonAction;
-use crate::build::ShortBuild;
+use crate::build::{CommonBuild, ShortBuild};
 use crate::client;
 use crate::client_internals::Path;
+use crate::helpers::{MaybeDuration, MaybeTimestamp};
 use crate::job::ShortJob;
 use crate::Jenkins;
 
@@ -33,12 +39,66 @@ impl ShortQueueItem {
             .into())
         }
     }
+
+    /// Wait for the item to leave the queue, checking it every `poll_interval`, and return its
+    /// build once it started
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::QueueItemCancelled`](../client/enum.Error.html#variant.QueueItemCancelled) if the
+    /// item is cancelled, and an
+    /// [`Error::WaitTimeout`](../client/enum.Error.html#variant.WaitTimeout) if the build didn't
+    /// start after `timeout`
+    pub fn wait_for_build(
+        &self,
+        jenkins_client: &Jenkins,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<ShortBuild, Error> {
+        let start = Instant::now();
+        loop {
+            let item = self.get_full_queue_item(jenkins_client)?;
+            if item.cancelled == Some(true) {
+                return Err(client::Error::QueueItemCancelled { url: item.url }.into());
+            }
+            if let Some(build) = item.executable {
+                return Ok(build);
+            }
+            debug!("queue item {} is still waiting: {:?}", item.id, item.why);
+
+            let waited = start.elapsed();
+            if waited >= timeout {
+                return Err(client::Error::WaitTimeout {
+                    waiting_for: client::error::ExpectedType::QueueItem,
+                    waited,
+                }
+                .into());
+            }
+            thread::sleep(std::cmp::min(poll_interval, timeout - waited));
+        }
+    }
+
+    /// Wait for the item to leave the queue like `wait_for_build`, then get the full details of
+    /// its build
+    pub fn wait_for_full_build(
+        &self,
+        jenkins_client: &Jenkins,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<CommonBuild, Error> {
+        self.wait_for_build(jenkins_client, poll_interval, timeout)?
+            .get_full_build(jenkins_client)
+    }
 }
 
 /// A queued item in Jenkins, with information about the `Job` and why / since when it's waiting
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueueItem {
+    /// _class provided by Jenkins, like `hudson.model.Queue$WaitingItem` or
+    /// `hudson.model.Queue$LeftItem`
+    #[serde(rename = "_class")]
+    pub class: Option<String>,
     /// Is this item blocked
     pub blocked: bool,
     /// Is this item buildable
@@ -48,7 +108,7 @@ pub struct QueueItem {
     /// ID in the queue
     pub id: u32,
     /// When was it added to the queue
-    pub in_queue_since: u64,
+    pub in_queue_since: MaybeTimestamp,
     /// Task parameters
     pub params: String,
     /// Is the job stuck? Node needed is offline, or waitied for very long in queue
@@ -80,24 +140,505 @@ impl QueueItem {
             .into())
         }
     }
+
+    /// Get the full details of the build started from this `QueueItem`, or `None` if it is
+    /// still waiting
+    pub fn get_build(&self, jenkins_client: &Jenkins) -> Result<Option<CommonBuild>, Error> {
+        self.executable
+            .as_ref()
+            .map(|build| build.get_full_build(jenkins_client))
+            .transpose()
+    }
+
+    /// Cancel this `QueueItem`
+    pub fn cancel(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        jenkins_client.cancel_queue_item(self.id as i32)
+    }
 }
 
 /// The Jenkins `Queue`, the list of `QueueItem` that are waiting to be built
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Queue {
     /// List of items currently in the queue
     pub items: Vec<QueueItem>,
 }
 
+/// Fields of the queue needed by `QueueLite`
+const QUEUE_LITE_TREE: &str = "items[id,inQueueSince,stuck,blocked,buildable,task[name,url]]";
+
+/// Task of a `QueueLiteItem`, with only its name and URL
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QueueLiteTask {
+    /// Name of the task
+    #[serde(default)]
+    pub name: String,
+    /// URL of the task
+    #[serde(default)]
+    pub url: String,
+}
+
+/// A queued item with only its state and task, without its actions
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueLiteItem {
+    /// ID in the queue
+    pub id: u32,
+    /// When was it added to the queue
+    pub in_queue_since: MaybeTimestamp,
+    /// Is the job stuck? Node needed is offline, or waitied for very long in queue
+    pub stuck: bool,
+    /// Is this item blocked
+    pub blocked: bool,
+    /// Is this item buildable
+    pub buildable: bool,
+    /// Task waiting in the queue
+    pub task: QueueLiteTask,
+}
+
+/// State of a `QueueLiteItem`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueueItemState {
+    /// The item is stuck, it has been buildable for a long time or its node is offline
+    Stuck,
+    /// The item is blocked, by another build of its job or by an upstream job
+    Blocked,
+    /// The item is buildable, waiting for an executor
+    Buildable,
+    /// The item is waiting, in its quiet period
+    Waiting,
+}
+
+impl QueueLiteItem {
+    /// State of the item, a stuck item being also buildable
+    pub fn state(&self) -> QueueItemState {
+        if self.stuck {
+            QueueItemState::Stuck
+        } else if self.blocked {
+            QueueItemState::Blocked
+        } else if self.buildable {
+            QueueItemState::Buildable
+        } else {
+            QueueItemState::Waiting
+        }
+    }
+
+    /// Time spent in the queue at `now`, unknown if the time it was added to the queue is
+    pub fn age(&self, now: SystemTime) -> MaybeDuration {
+        self.in_queue_since.elapsed_at(now)
+    }
+}
+
+/// Number of items in the queue in each state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStateCounts {
+    /// Stuck items
+    pub stuck: usize,
+    /// Blocked items
+    pub blocked: usize,
+    /// Buildable items
+    pub buildable: usize,
+    /// Waiting items
+    pub waiting: usize,
+}
+
+/// The Jenkins `Queue` with only the state and task of its items, for large queues
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct QueueLite {
+    /// List of items currently in the queue
+    pub items: Vec<QueueLiteItem>,
+}
+
+impl QueueLite {
+    /// Number of items in the queue
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Is the queue empty
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Count the items in each state
+    pub fn counts_by_state(&self) -> QueueStateCounts {
+        let mut counts = QueueStateCounts::default();
+        for item in &self.items {
+            match item.state() {
+                QueueItemState::Stuck => counts.stuck += 1,
+                QueueItemState::Blocked => counts.blocked += 1,
+                QueueItemState::Buildable => counts.buildable += 1,
+                QueueItemState::Waiting => counts.waiting += 1,
+            }
+        }
+        counts
+    }
+
+    /// Items in the state `state`
+    pub fn items_in_state(&self, state: QueueItemState) -> impl Iterator<Item = &QueueLiteItem> {
+        self.items.iter().filter(move |item| item.state() == state)
+    }
+
+    /// Item that has been in the queue for the longest time, among the items with a known time
+    /// they were added to the queue
+    pub fn oldest(&self) -> Option<&QueueLiteItem> {
+        self.items
+            .iter()
+            .filter_map(|item| {
+                item.in_queue_since
+                    .known_millis()
+                    .map(|since| (since, item))
+            })
+            .min_by_key(|(since, _)| *since)
+            .map(|(_, item)| item)
+    }
+
+    /// Time spent in the queue at `now` by the oldest item
+    pub fn oldest_age(&self, now: SystemTime) -> Option<Duration> {
+        self.oldest().and_then(|item| item.age(now).as_duration())
+    }
+
+    /// Number of items without a known time they were added to the queue, skipped by `oldest`
+    /// and `oldest_age`
+    pub fn unknown_since_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| !item.in_queue_since.is_known())
+            .count()
+    }
+
+    /// Number of items queued for each job, by URL of the job
+    pub fn items_per_job(&self) -> HashMap<&str, usize> {
+        let mut per_job = HashMap::new();
+        for item in &self.items {
+            *per_job.entry(item.task.url.as_str()).or_insert(0) += 1;
+        }
+        per_job
+    }
+}
+
 impl Jenkins {
     /// Get the Jenkins items queue
     pub fn get_queue(&self) -> Result<Queue, Error> {
         Ok(self.get(&Path::Queue)?.json()?)
     }
 
+    /// Get the Jenkins items queue with only the state and task of each item, which is much
+    /// lighter than `get_queue` for queues of thousands of items
+    pub fn get_queue_lite(&self) -> Result<QueueLite, Error> {
+        Ok(self
+            .get_with_params(&Path::Queue, [("tree", QUEUE_LITE_TREE)])?
+            .json()?)
+    }
+
     /// Get a queue item from it's ID
     pub fn get_queue_item(&self, id: i32) -> Result<QueueItem, Error> {
         Ok(self.get(&Path::QueueItem { id })?.json()?)
     }
+
+    /// Cancel a queue item from it's ID
+    pub fn cancel_queue_item(&self, id: i32) -> Result<(), Error> {
+        let _ = self.post(&Path::CancelQueueItem { id })?;
+        Ok(())
+    }
+
+    /// Cancel every item of the queue, returning the number of items cancelled
+    ///
+    /// Items that fail to be cancelled are skipped, and not counted
+    pub fn cancel_all_queue_items(&self) -> Result<usize, Error> {
+        let queue = self.get_queue()?;
+        let mut cancelled = 0;
+        for item in &queue.items {
+            match item.cancel(self) {
+                Ok(()) => cancelled += 1,
+                Err(error) => warn!("could not cancel queue item {}: {}", item.id, error),
+            }
+        }
+        Ok(cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use mockito;
+
+    fn queue_item(id: u32) -> String {
+        format!(
+            r#"{{"_class":"hudson.model.Queue$WaitingItem","actions":[],"blocked":false,
+            "buildable":false,"id":{id},"inQueueSince":1551000000000,"params":"","stuck":false,
+            "task":{{"_class":"hudson.model.FreeStyleProject","name":"job","url":"http://none/job/job/"}},
+            "url":"queue/item/{id}/","why":"In the quiet period"}}"#,
+            id = id
+        )
+    }
+
+    #[test]
+    fn can_wait_for_build() {
+        use crate::helpers::test_server::{response, serve_in_order};
+
+        let waiting = queue_item(4);
+        let started = waiting.replace(
+            r#""why":"In the quiet period""#,
+            r#""why":null,"executable":{"number":12,"url":"http://none/job/job/12/"}"#,
+        );
+        let url = serve_in_order(vec![
+            response("200 OK", "", &waiting),
+            response("200 OK", "", &started),
+        ]);
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let item = super::ShortQueueItem {
+            url: format!("{}/queue/item/4/", url),
+            other_fields: None,
+        };
+
+        let build = item
+            .wait_for_build(
+                &jenkins_client,
+                Duration::from_millis(1),
+                Duration::from_secs(5),
+            )
+            .unwrap();
+        assert_eq!(build.number, 12);
+    }
+
+    #[test]
+    fn should_fail_to_wait_for_cancelled_item() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/wait-cancelled", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _item = mockito::mock("GET", "/wait-cancelled/queue/item/5/api/json?depth=1")
+            .with_body(queue_item(5).replace(r#""why""#, r#""cancelled":true,"why""#))
+            .create();
+        let item = super::ShortQueueItem {
+            url: format!("{}/wait-cancelled/queue/item/5/", mockito::server_url()),
+            other_fields: None,
+        };
+
+        let error = item
+            .wait_for_build(
+                &jenkins_client,
+                Duration::from_millis(1),
+                Duration::from_secs(5),
+            )
+            .unwrap_err();
+        assert!(format!("{}", error).ends_with("was cancelled"));
+    }
+
+    #[test]
+    fn should_time_out_waiting_for_build() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/wait-timeout", mockito::server_url()))
+                .build()
+                .unwrap();
+        let _item = mockito::mock("GET", "/wait-timeout/queue/item/6/api/json?depth=1")
+            .with_body(queue_item(6))
+            .create();
+        let item = super::ShortQueueItem {
+            url: format!("{}/wait-timeout/queue/item/6/", mockito::server_url()),
+            other_fields: None,
+        };
+
+        let error = item
+            .wait_for_build(
+                &jenkins_client,
+                Duration::from_millis(5),
+                Duration::from_millis(20),
+            )
+            .unwrap_err();
+        match error.downcast::<crate::client::Error>() {
+            Ok(crate::client::Error::WaitTimeout { .. }) => (),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_get_build_of_left_item() {
+        let server = mockito::server_url();
+        let jenkins_client = crate::JenkinsBuilder::new(&format!("{}/left-item", server))
+            .build()
+            .unwrap();
+        let _build = mockito::mock("GET", "/left-item/job/job/12/api/json?depth=1")
+            .with_body(format!(
+                r##"{{"_class":"hudson.model.FreeStyleBuild","url":"{}/left-item/job/job/12/",
+                "number":12,"duration":10,"estimatedDuration":10,"timestamp":0,"keepLog":false,
+                "result":"SUCCESS","displayName":"#12","building":false,"id":"12","queueId":7,
+                "actions":[],"artifacts":[]}}"##,
+                server
+            ))
+            .create();
+
+        let waiting: super::QueueItem = serde_json::from_str(&queue_item(7)).unwrap();
+        assert!(waiting.get_build(&jenkins_client).unwrap().is_none());
+
+        let left: super::QueueItem = serde_json::from_str(
+            &queue_item(7)
+                .replace("Queue$WaitingItem", "Queue$LeftItem")
+                .replace(
+                    r#""why":"In the quiet period""#,
+                    &format!(
+                        r#""why":null,"executable":{{"number":12,"url":"{}/left-item/job/job/12/"}}"#,
+                        server
+                    ),
+                ),
+        )
+        .unwrap();
+        assert_eq!(left.class.as_deref(), Some("hudson.model.Queue$LeftItem"));
+        assert_eq!(left.get_build(&jenkins_client).unwrap().unwrap().number, 12);
+    }
+
+    #[test]
+    fn can_cancel_all_queue_items_past_errors() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/cancel-all", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let _queue = mockito::mock("GET", "/cancel-all/queue/api/json?depth=1")
+            .with_body(format!(
+                r#"{{"items":[{},{},{}]}}"#,
+                queue_item(1),
+                queue_item(2),
+                queue_item(3)
+            ))
+            .create();
+        let first = mockito::mock("POST", "/cancel-all/queue/cancelItem?id=1")
+            .with_status(302)
+            .with_header("Location", "/cancel-all/queue/api/json?depth=1")
+            .create();
+        let second = mockito::mock("POST", "/cancel-all/queue/cancelItem?id=2")
+            .with_status(500)
+            .create();
+        let third = mockito::mock("POST", "/cancel-all/queue/cancelItem?id=3")
+            .with_status(204)
+            .create();
+
+        let cancelled = jenkins_client.cancel_all_queue_items().unwrap();
+        first.assert();
+        second.assert();
+        third.assert();
+        assert_eq!(cancelled, 2);
+    }
+
+    /// A queue of `size` items, in every state, for 10 jobs
+    fn large_queue(size: u32) -> String {
+        let items: Vec<String> = (0..size)
+            .map(|id| {
+                format!(
+                    r#"{{"_class":"hudson.model.Queue$BuildableItem","actions":[{{"_class":"hudson.model.CauseAction","causes":[{{"shortDescription":"Started by timer"}}]}},{{}}],
+                    "blocked":{blocked},"buildable":{buildable},"id":{id},"inQueueSince":{since},
+                    "params":"","stuck":{stuck},
+                    "task":{{"_class":"hudson.model.FreeStyleProject","name":"job {job}","url":"http://none/job/job%20{job}/","color":"blue"}},
+                    "url":"queue/item/{id}/","why":"Waiting for next available executor"}}"#,
+                    id = id,
+                    blocked = id % 4 == 1,
+                    buildable = id % 4 == 0 || id % 4 == 3,
+                    stuck = id % 4 == 3,
+                    since = 1_551_000_000_000u64 + u64::from(id) * 1000,
+                    job = id % 10,
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"_class":"hudson.model.Queue","discoverableItems":[],"items":[{}]}}"#,
+            items.join(",")
+        )
+    }
+
+    #[test]
+    fn lite_queue_matches_full_queue() {
+        let json = large_queue(5000);
+
+        let start = std::time::Instant::now();
+        let lite: super::QueueLite = serde_json::from_str(&json).unwrap();
+        let lite_duration = start.elapsed();
+        let full: super::Queue = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(lite.len(), full.items.len());
+        for (lite_item, full_item) in lite.items.iter().zip(full.items.iter()) {
+            assert_eq!(lite_item.id, full_item.id);
+            assert_eq!(lite_item.in_queue_since, full_item.in_queue_since);
+            assert_eq!(lite_item.task.name, full_item.task.name);
+            assert_eq!(lite_item.stuck, full_item.stuck);
+        }
+        assert!(lite_duration < Duration::from_secs(5));
+
+        assert_eq!(
+            lite.counts_by_state(),
+            super::QueueStateCounts {
+                stuck: 1250,
+                blocked: 1250,
+                buildable: 1250,
+                waiting: 1250,
+            }
+        );
+        assert_eq!(
+            lite.items_in_state(super::QueueItemState::Stuck)
+                .map(|item| item.id)
+                .take(2)
+                .collect::<Vec<_>>(),
+            vec![3, 7]
+        );
+        let per_job = lite.items_per_job();
+        assert_eq!(per_job.len(), 10);
+        assert_eq!(per_job["http://none/job/job%203/"], 500);
+        assert_eq!(lite.oldest().map(|item| item.id), Some(0));
+        assert_eq!(
+            lite.oldest_age(std::time::UNIX_EPOCH + Duration::from_millis(1_551_000_060_000)),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn lite_queue_skips_unknown_times_in_queue() {
+        let lite: super::QueueLite = serde_json::from_str(
+            r#"{"items":[
+                {"id":1,"inQueueSince":0,"stuck":false,"blocked":false,"buildable":true,
+                 "task":{"name":"a","url":"http://none/job/a/"}},
+                {"id":2,"inQueueSince":1551000000000,"stuck":false,"blocked":false,
+                 "buildable":true,"task":{"name":"b","url":"http://none/job/b/"}}
+            ]}"#,
+        )
+        .unwrap();
+        let now = std::time::UNIX_EPOCH + Duration::from_millis(1_551_000_060_000);
+
+        assert_eq!(lite.oldest().map(|item| item.id), Some(2));
+        assert_eq!(lite.oldest_age(now), Some(Duration::from_secs(60)));
+        assert_eq!(lite.unknown_since_count(), 1);
+        assert!(!lite.items[0].age(now).is_known());
+    }
+
+    #[test]
+    fn can_get_lite_queue() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/queue-lite", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _queue = mockito::mock(
+            "GET",
+            "/queue-lite/queue/api/json?tree=items%5Bid%2CinQueueSince%2Cstuck%2Cblocked%2Cbuildable%2Ctask%5Bname%2Curl%5D%5D",
+        )
+        .with_body(
+            r#"{"_class":"hudson.model.Queue","items":[
+                {"_class":"hudson.model.Queue$BlockedItem","blocked":true,"buildable":false,
+                "id":8,"inQueueSince":1551000000000,"stuck":false,
+                "task":{"_class":"hudson.model.FreeStyleProject","name":"job","url":"http://none/job/job/"}},
+                {"_class":"hudson.model.Queue$BuildableItem","blocked":false,"buildable":true,
+                "id":9,"inQueueSince":1551000001000,"stuck":false,
+                "task":{"_class":"org.jenkinsci.plugins.workflow.support.steps.ExecutorStepExecution$PlaceholderTask"}}
+            ]}"#,
+        )
+        .create();
+
+        let queue = jenkins_client.get_queue_lite().unwrap();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.items[0].state(), super::QueueItemState::Blocked);
+        assert_eq!(queue.items[1].state(), super::QueueItemState::Buildable);
+        assert_eq!(queue.items[1].task.name, "");
+    }
 }
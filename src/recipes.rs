@@ -0,0 +1,629 @@
+//! Recipes chaining several calls to Jenkins for common needs
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! # extern crate failure;
+//! # extern crate jenkins_api;
+//! use std::time::Duration;
+//!
+//! use jenkins_api::recipes::{self, CancellationToken};
+//! use jenkins_api::JenkinsBuilder;
+//!
+//! fn main() -> Result<(), failure::Error> {
+//!     let jenkins = JenkinsBuilder::new("http://localhost:8080").build()?;
+//!     let cancel = CancellationToken::new();
+//!
+//!     let triggered = recipes::trigger_and_stream(
+//!         &jenkins,
+//!         "my job",
+//!         Duration::from_secs(2),
+//!         Duration::from_secs(600),
+//!         &mut std::io::stdout(),
+//!         &cancel,
+//!     )?;
+//!     println!("build finished with {:?}", triggered.build.result);
+//!
+//!     for job in recipes::red_jobs_with_failing_stage(&jenkins, "all", &cancel)? {
+//!         println!("{} is failing at {:?}", job.name, job.failing_stage);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use failure::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::build::{Artifact, CommonBuild, ShortBuild};
+use crate::client;
+use crate::client::error::ExpectedType;
+use crate::client_internals::{Name, Path};
+use crate::job::{BallColor, JobName};
+use crate::queue::ShortQueueItem;
+use crate::view::ViewName;
+use crate::Jenkins;
+
+/// Token to cancel a recipe from another thread. The recipe stops with an
+/// [`Error::Cancelled`](../client/enum.Error.html#variant.Cancelled) before its next call to
+/// Jenkins
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new token, not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every recipe using this token or one of its clones
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Was this token cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(client::Error::Cancelled.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Result of `trigger_and_stream`
+#[derive(Debug, Clone)]
+pub struct TriggeredBuild {
+    /// Queue item created when triggering the job
+    pub queue_item: ShortQueueItem,
+    /// The build, once completed
+    pub build: CommonBuild,
+    /// Number of bytes of console output written
+    pub console_bytes: u64,
+}
+
+/// Result of `fetch_latest_artifact`
+#[derive(Debug, Clone)]
+pub struct LatestArtifact {
+    /// Number of the build the artifact was taken from
+    pub build_number: u32,
+    /// The artifact
+    pub artifact: Artifact,
+    /// Number of bytes written
+    pub size: u64,
+}
+
+/// A failing job found by `red_jobs_with_failing_stage`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RedJob {
+    /// Name of the job
+    pub name: String,
+    /// URL of the job
+    pub url: String,
+    /// Number of its last build
+    pub build_number: Option<u32>,
+    /// First failed stage of its last build, for pipeline jobs
+    pub failing_stage: Option<String>,
+}
+
+/// Trigger a build of a job, wait for it to start, and write its console output to `output`
+/// while it runs. The completed build is returned
+///
+/// # Errors
+/// This will return an
+/// [`Error::WaitTimeout`](../client/enum.Error.html#variant.WaitTimeout) if the build didn't
+/// complete after `timeout`, or an
+/// [`Error::QueueItemCancelled`](../client/enum.Error.html#variant.QueueItemCancelled) if it was
+/// cancelled in the queue
+pub fn trigger_and_stream<'a, J, W>(
+    jenkins_client: &Jenkins,
+    job_name: J,
+    poll_interval: Duration,
+    timeout: Duration,
+    output: &mut W,
+    cancel: &CancellationToken,
+) -> Result<TriggeredBuild, Error>
+where
+    J: Into<JobName<'a>>,
+    W: Write,
+{
+    let started = Instant::now();
+    let wait = |waiting_for| -> Result<(), Error> {
+        let waited = started.elapsed();
+        if waited >= timeout {
+            return Err(client::Error::WaitTimeout {
+                waiting_for,
+                waited,
+            }
+            .into());
+        }
+        thread::sleep(std::cmp::min(poll_interval, timeout - waited));
+        Ok(())
+    };
+
+    cancel.check()?;
+    let queue_item = jenkins_client.build_job(job_name)?;
+    let short_build: ShortBuild = loop {
+        cancel.check()?;
+        let item = queue_item.get_full_queue_item(jenkins_client)?;
+        if item.cancelled == Some(true) {
+            return Err(client::Error::QueueItemCancelled { url: item.url }.into());
+        }
+        if let Some(build) = item.executable {
+            break build;
+        }
+        wait(ExpectedType::QueueItem)?;
+    };
+
+    let (job_name, number, configuration) = build_path_parts(jenkins_client, &short_build.url)?;
+    let mut console_bytes = 0;
+    loop {
+        cancel.check()?;
+        let mut response = jenkins_client.get_file(&Path::ProgressiveText {
+            job_name: job_name.clone(),
+            number: number.clone(),
+            configuration: configuration.clone(),
+            start: console_bytes,
+        })?;
+        let more_data = response
+            .headers()
+            .get("X-More-Data")
+            .and_then(|value| value.to_str().ok())
+            == Some("true");
+        let text_size = response
+            .headers()
+            .get("X-Text-Size")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let written = response.copy_to(output)?;
+        console_bytes = text_size.unwrap_or(console_bytes + written);
+        if !more_data {
+            break;
+        }
+        wait(ExpectedType::Build)?;
+    }
+
+    cancel.check()?;
+    let build = short_build.get_full_build(jenkins_client)?;
+    Ok(TriggeredBuild {
+        queue_item,
+        build,
+        console_bytes,
+    })
+}
+
+/// Download an artifact from the last successful build of a job, writing it to `output`. The
+/// artifact is matched on its file name or its relative path
+///
+/// # Errors
+/// This will return an
+/// [`Error::NoSuccessfulBuild`](../client/enum.Error.html#variant.NoSuccessfulBuild) if the job
+/// never succeeded, or an
+/// [`Error::ArtifactNotFound`](../client/enum.Error.html#variant.ArtifactNotFound) if the build
+/// has no matching artifact
+pub fn fetch_latest_artifact<'a, J, W>(
+    jenkins_client: &Jenkins,
+    job_name: J,
+    artifact_name: &str,
+    output: &mut W,
+    cancel: &CancellationToken,
+) -> Result<LatestArtifact, Error>
+where
+    J: Into<JobName<'a>>,
+    W: Write,
+{
+    cancel.check()?;
+    let job = jenkins_client.get_job(job_name)?;
+    let last_successful_build = match job.last_successful_build {
+        Some(build) => build,
+        None => return Err(client::Error::NoSuccessfulBuild { job_name: job.name }.into()),
+    };
+
+    cancel.check()?;
+    let build = last_successful_build.get_full_build(jenkins_client)?;
+    let artifact = build
+        .artifacts
+        .iter()
+        .find(|artifact| {
+            artifact.file_name == artifact_name || artifact.relative_path == artifact_name
+        })
+        .cloned()
+        .ok_or_else(|| client::Error::ArtifactNotFound {
+            name: artifact_name.to_string(),
+            build_url: build.url.clone(),
+        })?;
+
+    cancel.check()?;
+    let (job_name, number, configuration) = build_path_parts(jenkins_client, &build.url)?;
+    let size = jenkins_client
+        .get_file(&Path::Artifact {
+            job_name,
+            number,
+            configuration,
+            relative_path: &artifact.relative_path,
+        })?
+        .copy_to(output)?;
+
+    Ok(LatestArtifact {
+        build_number: build.number,
+        artifact,
+        size,
+    })
+}
+
+const WORKFLOW_JOB_CLASS: &str = "org.jenkinsci.plugins.workflow.job.WorkflowJob";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ViewJobs {
+    jobs: Vec<ViewJob>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ViewJob {
+    #[serde(rename = "_class")]
+    class: Option<String>,
+    name: String,
+    url: String,
+    color: Option<BallColor>,
+    last_build: Option<ShortBuild>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PipelineDescription {
+    #[serde(default)]
+    stages: Vec<PipelineStage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PipelineStage {
+    name: String,
+    status: String,
+}
+
+/// List the failing jobs of a view. For pipeline jobs, the first stage that failed in their
+/// last build is also returned
+pub fn red_jobs_with_failing_stage<'a, V>(
+    jenkins_client: &Jenkins,
+    view_name: V,
+    cancel: &CancellationToken,
+) -> Result<Vec<RedJob>, Error>
+where
+    V: Into<ViewName<'a>>,
+{
+    cancel.check()?;
+    let view: ViewJobs = jenkins_client
+        .get_with_params(
+            &Path::View {
+                name: Name::Name(view_name.into().0),
+            },
+            [("tree", "jobs[name,url,color,lastBuild[number,url]]")],
+        )?
+        .json()?;
+
+    view.jobs
+        .into_iter()
+        .filter(|job| job.color.map(BallColor::without_animation) == Some(BallColor::Red))
+        .map(|job| {
+            let failing_stage = match job.last_build {
+                Some(ref build) if job.class.as_deref() == Some(WORKFLOW_JOB_CLASS) => {
+                    cancel.check()?;
+                    let (job_name, number, configuration) =
+                        build_path_parts(jenkins_client, &build.url)?;
+                    let description: PipelineDescription = jenkins_client
+                        .get_file(&Path::PipelineDescription {
+                            job_name,
+                            number,
+                            configuration,
+                        })?
+                        .json()?;
+                    description
+                        .stages
+                        .into_iter()
+                        .find(|stage| stage.status == "FAILED")
+                        .map(|stage| stage.name)
+                }
+                _ => None,
+            };
+            Ok(RedJob {
+                build_number: job.last_build.as_ref().map(|build| build.number),
+                name: job.name,
+                url: job.url,
+                failing_stage,
+            })
+        })
+        .collect()
+}
+
+fn build_path_parts<'a>(
+    jenkins_client: &Jenkins,
+    url: &'a str,
+) -> Result<(Name<'a>, crate::build::BuildNumber, Option<Name<'a>>), Error> {
+    match jenkins_client.url_to_path(url) {
+        Path::Build {
+            job_name,
+            number,
+            configuration,
+        } => Ok((job_name, number, configuration)),
+        _ => Err(client::Error::InvalidUrl {
+            url: url.to_string(),
+            expected: ExpectedType::Build,
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::JenkinsBuilder;
+
+    fn jenkins(base: &str) -> Jenkins {
+        JenkinsBuilder::new(&format!("{}/{}", mockito::server_url(), base))
+            .disable_csrf()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn can_trigger_and_stream() {
+        let base = "recipe-stream";
+        let jenkins = jenkins(base);
+        let server = mockito::server_url();
+        let _build = mockito::mock("POST", "/recipe-stream/job/my-job/build")
+            .with_status(201)
+            .with_header("Location", &format!("{}/{}/queue/item/1/", server, base))
+            .create();
+        let _queue = mockito::mock("GET", "/recipe-stream/queue/item/1/api/json?depth=1")
+            .with_body(format!(
+                r#"{{"blocked":false,"buildable":false,"id":1,"inQueueSince":0,"params":"","stuck":false,"url":"queue/item/1/","why":null,"actions":[],"task":{{"name":"my-job","url":"{0}/{1}/job/my-job/"}},"executable":{{"number":3,"url":"{0}/{1}/job/my-job/3/"}}}}"#,
+                server, base
+            ))
+            .create();
+        let _first = mockito::mock(
+            "GET",
+            "/recipe-stream/job/my-job/3/logText/progressiveText?start=0",
+        )
+        .with_header("X-Text-Size", "6")
+        .with_header("X-More-Data", "true")
+        .with_body("hello\n")
+        .create();
+        let _second = mockito::mock(
+            "GET",
+            "/recipe-stream/job/my-job/3/logText/progressiveText?start=6",
+        )
+        .with_header("X-Text-Size", "11")
+        .with_body("done\n")
+        .create();
+        let _full = mockito::mock("GET", "/recipe-stream/job/my-job/3/api/json?depth=1")
+            .with_body(format!(
+                r##"{{"_class":"hudson.model.FreeStyleBuild","url":"{}/{}/job/my-job/3/","number":3,"duration":10,"estimatedDuration":10,"timestamp":0,"keepLog":false,"result":"SUCCESS","displayName":"#3","building":false,"id":"3","queueId":1,"actions":[],"artifacts":[]}}"##,
+                server, base
+            ))
+            .create();
+
+        let mut output = vec![];
+        let triggered = trigger_and_stream(
+            &jenkins,
+            "my-job",
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+            &mut output,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+
+        assert_eq!(output, b"hello\ndone\n");
+        assert_eq!(triggered.console_bytes, 11);
+        assert_eq!(triggered.build.number, 3);
+    }
+
+    #[test]
+    fn trigger_and_stream_stops_when_cancelled() {
+        let base = "recipe-cancel";
+        let jenkins = jenkins(base);
+        let _build = mockito::mock("POST", "/recipe-cancel/job/my-job/build")
+            .with_status(201)
+            .with_header(
+                "Location",
+                &format!("{}/{}/queue/item/2/", mockito::server_url(), base),
+            )
+            .create();
+        let _queue = mockito::mock("GET", "/recipe-cancel/queue/item/2/api/json?depth=1")
+            .with_body(r#"{"blocked":false,"buildable":true,"id":2,"inQueueSince":0,"params":"","stuck":false,"url":"queue/item/2/","why":"waiting","actions":[],"task":{"name":"my-job","url":"/job/my-job/"}}"#)
+            .create();
+
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+        let result = trigger_and_stream(
+            &jenkins,
+            "my-job",
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+            &mut vec![],
+            &cancel,
+        );
+        handle.join().unwrap();
+
+        match result.unwrap_err().downcast::<client::Error>() {
+            Ok(client::Error::Cancelled) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    fn job_body(server: &str, base: &str, last_successful_build: &str) -> String {
+        format!(
+            r#"{{"_class":"hudson.model.FreeStyleProject","name":"my-job","displayName":"my-job","fullDisplayName":"my-job","fullName":"my-job","url":"{}/{}/job/my-job/","buildable":true,"builds":[],"color":"blue","inQueue":false,"keepDependencies":false,"nextBuildNumber":3,"description":"","concurrentBuild":false,"actions":[],"healthReport":[],"property":[],"firstBuild":null,"lastBuild":null,"lastCompletedBuild":null,"lastFailedBuild":null,"lastStableBuild":null,"lastSuccessfulBuild":{},"lastUnstableBuild":null,"lastUnsuccessfulBuild":null,"queueItem":null,"upstreamProjects":[],"downstreamProjects":[],"scm":{{"_class":"hudson.scm.NullSCM"}}}}"#,
+            server, base, last_successful_build
+        )
+    }
+
+    #[test]
+    fn fetch_latest_artifact_fails_without_successful_build() {
+        let base = "recipe-never-succeeded";
+        let jenkins = jenkins(base);
+        let _job = mockito::mock("GET", "/recipe-never-succeeded/job/my-job/api/json?depth=1")
+            .with_body(job_body(&mockito::server_url(), base, "null"))
+            .create();
+
+        let result = fetch_latest_artifact(
+            &jenkins,
+            "my-job",
+            "out.zip",
+            &mut vec![],
+            &CancellationToken::new(),
+        );
+
+        match result.unwrap_err().downcast::<client::Error>() {
+            Ok(client::Error::NoSuccessfulBuild { job_name }) => assert_eq!(job_name, "my-job"),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fetch_latest_artifact_fails_without_matching_artifact() {
+        let base = "recipe-no-artifact";
+        let jenkins = jenkins(base);
+        let server = mockito::server_url();
+        let _job = mockito::mock("GET", "/recipe-no-artifact/job/my-job/api/json?depth=1")
+            .with_body(job_body(
+                &server,
+                base,
+                &format!(
+                    r#"{{"number":2,"url":"{}/{}/job/my-job/2/"}}"#,
+                    server, base
+                ),
+            ))
+            .create();
+        let _build = mockito::mock("GET", "/recipe-no-artifact/job/my-job/2/api/json?depth=1")
+            .with_body(format!(
+                r##"{{"_class":"hudson.model.FreeStyleBuild","url":"{}/{}/job/my-job/2/","number":2,"duration":10,"estimatedDuration":10,"timestamp":0,"keepLog":false,"result":"SUCCESS","displayName":"#2","building":false,"id":"2","queueId":1,"actions":[],"artifacts":[{{"displayPath":"other.zip","fileName":"other.zip","relativePath":"target/other.zip"}}]}}"##,
+                server, base
+            ))
+            .create();
+
+        let result = fetch_latest_artifact(
+            &jenkins,
+            "my-job",
+            "out.zip",
+            &mut vec![],
+            &CancellationToken::new(),
+        );
+
+        match result.unwrap_err().downcast::<client::Error>() {
+            Ok(client::Error::ArtifactNotFound { name, .. }) => assert_eq!(name, "out.zip"),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_fetch_latest_artifact() {
+        let base = "recipe-artifact";
+        let jenkins = jenkins(base);
+        let server = mockito::server_url();
+        let _job = mockito::mock("GET", "/recipe-artifact/job/my-job/api/json?depth=1")
+            .with_body(job_body(
+                &server,
+                base,
+                &format!(
+                    r#"{{"number":2,"url":"{}/{}/job/my-job/2/"}}"#,
+                    server, base
+                ),
+            ))
+            .create();
+        let _build = mockito::mock("GET", "/recipe-artifact/job/my-job/2/api/json?depth=1")
+            .with_body(format!(
+                r##"{{"_class":"hudson.model.FreeStyleBuild","url":"{}/{}/job/my-job/2/","number":2,"duration":10,"estimatedDuration":10,"timestamp":0,"keepLog":false,"result":"SUCCESS","displayName":"#2","building":false,"id":"2","queueId":1,"actions":[],"artifacts":[{{"displayPath":"out.zip","fileName":"out.zip","relativePath":"target/out.zip"}}]}}"##,
+                server, base
+            ))
+            .create();
+        let _artifact = mockito::mock(
+            "GET",
+            "/recipe-artifact/job/my-job/2/artifact/target/out.zip",
+        )
+        .with_body("content")
+        .create();
+
+        let mut output = vec![];
+        let latest = fetch_latest_artifact(
+            &jenkins,
+            "my-job",
+            "out.zip",
+            &mut output,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+
+        assert_eq!(output, b"content");
+        assert_eq!(latest.build_number, 2);
+        assert_eq!(latest.size, 7);
+        assert_eq!(latest.artifact.relative_path, "target/out.zip");
+    }
+
+    #[test]
+    fn red_jobs_only_describe_pipelines() {
+        let base = "recipe-red-jobs";
+        let jenkins = jenkins(base);
+        let server = mockito::server_url();
+        let _view = mockito::mock(
+            "GET",
+            "/recipe-red-jobs/view/all/api/json?tree=jobs%5Bname%2Curl%2Ccolor%2ClastBuild%5Bnumber%2Curl%5D%5D",
+        )
+        .with_body(format!(
+            r#"{{"jobs":[
+                {{"_class":"org.jenkinsci.plugins.workflow.job.WorkflowJob","name":"pipeline","url":"{0}/{1}/job/pipeline/","color":"red","lastBuild":{{"number":4,"url":"{0}/{1}/job/pipeline/4/"}}}},
+                {{"_class":"hudson.model.FreeStyleProject","name":"freestyle","url":"{0}/{1}/job/freestyle/","color":"red_anime","lastBuild":{{"number":7,"url":"{0}/{1}/job/freestyle/7/"}}}},
+                {{"_class":"hudson.model.FreeStyleProject","name":"green","url":"{0}/{1}/job/green/","color":"blue","lastBuild":{{"number":1,"url":"{0}/{1}/job/green/1/"}}}}
+            ]}}"#,
+            server, base
+        ))
+        .create();
+        let describe = mockito::mock("GET", "/recipe-red-jobs/job/pipeline/4/wfapi/describe")
+            .with_body(
+                r#"{"id":"4","status":"FAILED","stages":[
+                    {"id":"6","name":"Build","status":"SUCCESS"},
+                    {"id":"12","name":"Test","status":"FAILED"}
+                ]}"#,
+            )
+            .expect(1)
+            .create();
+
+        let red = red_jobs_with_failing_stage(&jenkins, "all", &CancellationToken::new()).unwrap();
+
+        describe.assert();
+        assert_eq!(
+            red,
+            vec![
+                RedJob {
+                    name: "pipeline".to_string(),
+                    url: format!("{}/{}/job/pipeline/", server, base),
+                    build_number: Some(4),
+                    failing_stage: Some("Test".to_string()),
+                },
+                RedJob {
+                    name: "freestyle".to_string(),
+                    url: format!("{}/{}/job/freestyle/", server, base),
+                    build_number: Some(7),
+                    failing_stage: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,411 @@
+//! Best-effort description of the fields Jenkins exposes for a `_class`, to build generic
+//! explorers of the API
+
+use std::sync::PoisonError;
+
+use failure::Error;
+use serde::Deserialize;
+
+use crate::client;
+use crate::client_internals::{Path, NO_PARAMS};
+use crate::helpers::is_not_found;
+use crate::Jenkins;
+
+/// Kind of a field, inferred from its value
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    /// The value was `null`, its kind is unknown
+    Null,
+    /// A boolean
+    Boolean,
+    /// A number
+    Number,
+    /// A string
+    String,
+    /// An array, with the kind of its first item if it was not empty
+    Array(Option<Box<FieldKind>>),
+    /// An object, with its `_class` if it had one
+    Object(Option<String>),
+}
+
+impl FieldKind {
+    fn of(value: &serde_json::Value) -> FieldKind {
+        match *value {
+            serde_json::Value::Null => FieldKind::Null,
+            serde_json::Value::Bool(_) => FieldKind::Boolean,
+            serde_json::Value::Number(_) => FieldKind::Number,
+            serde_json::Value::String(_) => FieldKind::String,
+            serde_json::Value::Array(ref items) => {
+                FieldKind::Array(items.first().map(|item| Box::new(FieldKind::of(item))))
+            }
+            serde_json::Value::Object(ref fields) => FieldKind::Object(
+                fields
+                    .get("_class")
+                    .and_then(|class| class.as_str())
+                    .map(|class| class.to_string()),
+            ),
+        }
+    }
+}
+
+/// A field of a class
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescriptor {
+    /// Name of the field, as in the JSON
+    pub name: String,
+    /// Kind of the field
+    pub kind: FieldKind,
+}
+
+/// Where the fields of a `ClassDescriptor` were read from
+#[derive(Debug, Clone, PartialEq)]
+pub enum DescriptorSource {
+    /// From the metadata of the descriptor of the class, at `/descriptorByName/<class>/`
+    Descriptor,
+    /// From the JSON of an instance of the class, at `url`
+    Sample {
+        /// URL of the instance
+        url: String,
+    },
+}
+
+/// Fields Jenkins exposes for a `_class`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDescriptor {
+    /// _class described
+    pub class: String,
+    /// Fields of the class, by name
+    pub fields: Vec<FieldDescriptor>,
+    /// Where the fields were read from
+    pub source: DescriptorSource,
+}
+
+impl ClassDescriptor {
+    /// Describe the fields of `json`, ignoring `_class`
+    fn from_json(class: &str, json: &serde_json::Value, source: DescriptorSource) -> Self {
+        let mut fields: Vec<FieldDescriptor> = json
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter(|(name, _)| name.as_str() != "_class")
+                    .map(|(name, value)| FieldDescriptor {
+                        name: name.clone(),
+                        kind: FieldKind::of(value),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        ClassDescriptor {
+            class: class.to_string(),
+            fields,
+            source,
+        }
+    }
+
+    /// Get a field by its name
+    pub fn field(&self, name: &str) -> Option<&FieldDescriptor> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(rename = "_class")]
+    class: Option<String>,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Items {
+    #[serde(rename = "_class")]
+    class: Option<String>,
+    #[serde(default)]
+    jobs: Vec<Item>,
+    #[serde(default)]
+    views: Vec<Item>,
+}
+
+impl Jenkins {
+    /// Describe the fields Jenkins exposes for the `_class` `class_name`, from the metadata of
+    /// its descriptor if Jenkins provides some, or else from the JSON of an instance of the
+    /// class among the home, its jobs and its views. Descriptors are cached by the client
+    ///
+    /// This is best-effort: fields that were `null` or empty in the instance sampled have an
+    /// unknown kind
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::UnknownClass`](../client/enum.Error.html#variant.UnknownClass)
+    /// if there is no metadata and no instance of the class
+    pub fn describe_class(&self, class_name: &str) -> Result<ClassDescriptor, Error> {
+        if let Some(descriptor) = self.cached_class_descriptor(class_name) {
+            return Ok(descriptor);
+        }
+        let descriptor = match self.describe_from_descriptor(class_name)? {
+            Some(descriptor) => descriptor,
+            None => self.describe_from_sample(class_name)?,
+        };
+        self.cache_class_descriptor(&descriptor);
+        Ok(descriptor)
+    }
+
+    fn cached_class_descriptor(&self, class_name: &str) -> Option<ClassDescriptor> {
+        self.class_descriptors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(class_name)
+            .cloned()
+    }
+
+    fn cache_class_descriptor(&self, descriptor: &ClassDescriptor) {
+        let _ = self
+            .class_descriptors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(descriptor.class.clone(), descriptor.clone());
+    }
+
+    /// Describe a class from `/descriptorByName/<class>/api/json`, `None` if Jenkins has no
+    /// metadata for it
+    fn describe_from_descriptor(&self, class_name: &str) -> Result<Option<ClassDescriptor>, Error> {
+        let json: serde_json::Value =
+            match self.get_with_params(&Path::DescriptorByName { class: class_name }, NO_PARAMS) {
+                Ok(mut response) => response.json()?,
+                Err(ref error) if is_not_found(error) => return Ok(None),
+                Err(error) => return Err(error),
+            };
+        let descriptor =
+            ClassDescriptor::from_json(class_name, &json, DescriptorSource::Descriptor);
+        if descriptor.fields.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(descriptor))
+        }
+    }
+
+    /// Describe a class from the JSON of the first instance found among the home, its jobs and
+    /// its views
+    fn describe_from_sample(&self, class_name: &str) -> Result<ClassDescriptor, Error> {
+        let items: Items = self
+            .get_with_params(
+                &Path::Home,
+                [("tree", "_class,jobs[_class,url],views[_class,url]")],
+            )?
+            .json()?;
+        let (path, url) = if items.class.as_deref() == Some(class_name) {
+            (Path::Home, self.url("/"))
+        } else {
+            match items
+                .jobs
+                .iter()
+                .chain(items.views.iter())
+                .find(|item| item.class.as_deref() == Some(class_name))
+            {
+                Some(item) => (self.url_to_path(&item.url), item.url.clone()),
+                None => {
+                    return Err(client::Error::UnknownClass {
+                        class: class_name.to_string(),
+                    }
+                    .into())
+                }
+            }
+        };
+        let json: serde_json::Value = self.get_with_depth(&path, 0)?.json()?;
+        Ok(ClassDescriptor::from_json(
+            class_name,
+            &json,
+            DescriptorSource::Sample { url },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DescriptorSource, FieldDescriptor, FieldKind};
+
+    static HOME: &str = r#"{
+        "_class": "hudson.model.Hudson",
+        "jobs": [
+            {"_class": "hudson.model.FreeStyleProject", "url": "{url}/job/normal%20job/"},
+            {"_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob", "url": "{url}/job/pipeline/"}
+        ],
+        "views": [
+            {"_class": "hudson.model.AllView", "url": "{url}/"}
+        ]
+    }"#;
+
+    static FREESTYLE_PROJECT: &str = r#"{
+        "_class": "hudson.model.FreeStyleProject",
+        "actions": [{}, {"_class": "hudson.plugins.jobConfigHistory.JobConfigHistoryProjectAction"}],
+        "buildable": true,
+        "color": "blue",
+        "description": null,
+        "healthReport": [],
+        "lastBuild": {"_class": "hudson.model.FreeStyleBuild", "number": 3, "url": "http://none/job/normal%20job/3/"},
+        "name": "normal job",
+        "nextBuildNumber": 4
+    }"#;
+
+    static WORKFLOW_JOB_DESCRIPTOR: &str = r#"{
+        "_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob$DescriptorImpl",
+        "displayName": "Pipeline",
+        "id": "org.jenkinsci.plugins.workflow.job.WorkflowJob",
+        "categoryId": "standalone-projects"
+    }"#;
+
+    #[test]
+    fn can_describe_core_class_from_sample() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/describe-core", mockito::server_url()))
+                .build()
+                .unwrap();
+        let url = format!("{}/describe-core", mockito::server_url());
+
+        let _descriptor = mockito::mock(
+            "GET",
+            "/describe-core/descriptorByName/hudson.model.FreeStyleProject/api/json",
+        )
+        .with_status(404)
+        .create();
+        let _home = mockito::mock(
+            "GET",
+            "/describe-core/api/json?tree=_class%2Cjobs%5B_class%2Curl%5D%2Cviews%5B_class%2Curl%5D",
+        )
+        .with_body(HOME.replace("{url}", &url))
+        .create();
+        let _job = mockito::mock("GET", "/describe-core/job/normal%20job/api/json?depth=0")
+            .with_body(FREESTYLE_PROJECT)
+            .create();
+
+        let descriptor = jenkins_client
+            .describe_class("hudson.model.FreeStyleProject")
+            .unwrap();
+
+        assert_eq!(
+            descriptor.source,
+            DescriptorSource::Sample {
+                url: format!("{}/job/normal%20job/", url)
+            }
+        );
+        assert_eq!(
+            descriptor
+                .fields
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "actions",
+                "buildable",
+                "color",
+                "description",
+                "healthReport",
+                "lastBuild",
+                "name",
+                "nextBuildNumber"
+            ]
+        );
+        assert_eq!(
+            descriptor.field("actions"),
+            Some(&FieldDescriptor {
+                name: "actions".to_string(),
+                kind: FieldKind::Array(Some(Box::new(FieldKind::Object(None))))
+            })
+        );
+        assert_eq!(
+            descriptor.field("lastBuild").map(|field| &field.kind),
+            Some(&FieldKind::Object(Some(
+                "hudson.model.FreeStyleBuild".to_string()
+            )))
+        );
+        assert_eq!(
+            descriptor.field("description").map(|field| &field.kind),
+            Some(&FieldKind::Null)
+        );
+        assert_eq!(
+            descriptor.field("healthReport").map(|field| &field.kind),
+            Some(&FieldKind::Array(None))
+        );
+        assert_eq!(
+            descriptor.field("nextBuildNumber").map(|field| &field.kind),
+            Some(&FieldKind::Number)
+        );
+    }
+
+    #[test]
+    fn can_describe_plugin_class_from_descriptor_and_cache_it() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/describe-plugin", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let descriptor_mock = mockito::mock(
+            "GET",
+            "/describe-plugin/descriptorByName/org.jenkinsci.plugins.workflow.job.WorkflowJob/api/json",
+        )
+        .with_body(WORKFLOW_JOB_DESCRIPTOR)
+        .expect(1)
+        .create();
+
+        let descriptor = jenkins_client
+            .describe_class("org.jenkinsci.plugins.workflow.job.WorkflowJob")
+            .unwrap();
+        let cached = jenkins_client
+            .describe_class("org.jenkinsci.plugins.workflow.job.WorkflowJob")
+            .unwrap();
+
+        descriptor_mock.assert();
+        assert_eq!(descriptor, cached);
+        assert_eq!(descriptor.source, DescriptorSource::Descriptor);
+        assert_eq!(
+            descriptor.fields,
+            vec![
+                FieldDescriptor {
+                    name: "categoryId".to_string(),
+                    kind: FieldKind::String
+                },
+                FieldDescriptor {
+                    name: "displayName".to_string(),
+                    kind: FieldKind::String
+                },
+                FieldDescriptor {
+                    name: "id".to_string(),
+                    kind: FieldKind::String
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fail_on_class_without_descriptor_or_instance() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/describe-unknown", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _descriptor = mockito::mock(
+            "GET",
+            "/describe-unknown/descriptorByName/org.example.Unknown/api/json",
+        )
+        .with_body(r#"{"_class": "org.example.Unknown$DescriptorImpl"}"#)
+        .create();
+        let _home = mockito::mock(
+            "GET",
+            "/describe-unknown/api/json?tree=_class%2Cjobs%5B_class%2Curl%5D%2Cviews%5B_class%2Curl%5D",
+        )
+        .with_body(r#"{"_class": "hudson.model.Hudson", "jobs": [], "views": []}"#)
+        .create();
+
+        match jenkins_client
+            .describe_class("org.example.Unknown")
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::UnknownClass { class }) => {
+                assert_eq!(class, "org.example.Unknown")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
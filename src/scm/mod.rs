@@ -8,18 +8,68 @@ use crate::helpers::Class;
 mod browser;
 pub use self::browser::*;
 
+/// Strategy used to merge, as `git merge --strategy`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MergeStrategy {
+    /// Default strategy of git
+    #[serde(alias = "default")]
+    #[default]
+    Default,
+    /// Resolve
+    #[serde(alias = "resolve")]
+    Resolve,
+    /// Recursive
+    #[serde(alias = "recursive")]
+    Recursive,
+    /// Octopus
+    #[serde(alias = "octopus")]
+    Octopus,
+    /// Ours
+    #[serde(alias = "ours")]
+    Ours,
+    /// Subtree
+    #[serde(alias = "subtree")]
+    Subtree,
+    /// Recursive, favoring their changes on conflicts
+    #[serde(alias = "recursive_theirs")]
+    RecursiveTheirs,
+    /// Strategy not known by this crate
+    #[serde(other)]
+    Unknown,
+}
+
+/// Fast forward mode used to merge
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FastForwardMode {
+    /// Fast forward when possible, as `git merge --ff`
+    #[serde(alias = "--ff")]
+    #[default]
+    Ff,
+    /// Only fast forward, as `git merge --ff-only`
+    #[serde(alias = "--ff-only")]
+    FfOnly,
+    /// Always create a merge commit, as `git merge --no-ff`
+    #[serde(alias = "--no-ff")]
+    NoFf,
+    /// Mode not known by this crate
+    #[serde(other)]
+    Unknown,
+}
+
 /// SCM merge options
 #[derive(Default, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct MergeOptions {
     /// Merge strategy
-    merge_strategy: String,
+    pub merge_strategy: MergeStrategy,
     /// Fast forward mode
-    fast_forward_mode: String,
-    /// Merge target
-    merge_target: Option<String>,
-    /// Remote branch
-    remote_branch_name: Option<String>,
+    pub fast_forward_mode: FastForwardMode,
+    /// Branch to merge to, if changes are merged before the build
+    pub merge_target: Option<String>,
+    /// Name of the remote of the branch to merge to
+    pub remote_branch_name: Option<String>,
 }
 
 /// Trait implemented by specialization of SCM
@@ -60,3 +110,38 @@ pub struct GitSCM {
 }
 register_class!("hudson.plugins.git.GitSCM" =>  GitSCM);
 impl SCM for GitSCM {}
+
+#[cfg(test)]
+mod tests {
+    use super::{FastForwardMode, MergeOptions, MergeStrategy};
+
+    #[test]
+    fn can_read_merge_options() {
+        let options: MergeOptions = serde_json::from_str(
+            r#"{"fastForwardMode": "FF_ONLY", "mergeRemote": "origin",
+            "mergeStrategy": "RECURSIVE_THEIRS", "mergeTarget": "master",
+            "remoteBranchName": "origin"}"#,
+        )
+        .unwrap();
+        assert_eq!(options.merge_strategy, MergeStrategy::RecursiveTheirs);
+        assert_eq!(options.fast_forward_mode, FastForwardMode::FfOnly);
+        assert_eq!(options.merge_target, Some("master".to_string()));
+        assert_eq!(options.remote_branch_name, Some("origin".to_string()));
+
+        let options: MergeOptions = serde_json::from_str(
+            r#"{"fastForwardMode": "--no-ff", "mergeStrategy": "default",
+            "mergeTarget": null, "remoteBranchName": null}"#,
+        )
+        .unwrap();
+        assert_eq!(options.merge_strategy, MergeStrategy::Default);
+        assert_eq!(options.fast_forward_mode, FastForwardMode::NoFf);
+
+        let options: MergeOptions = serde_json::from_str(
+            r#"{"fastForwardMode": "--ff-sometimes", "mergeStrategy": "patience",
+            "mergeTarget": null, "remoteBranchName": null}"#,
+        )
+        .unwrap();
+        assert_eq!(options.merge_strategy, MergeStrategy::Unknown);
+        assert_eq!(options.fast_forward_mode, FastForwardMode::Unknown);
+    }
+}
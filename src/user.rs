@@ -1,8 +1,15 @@
 //! A user, not always a Jenkins user
 
+use failure::Error;
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::client;
+use crate::client_internals::Path;
+use crate::property::{CommonProperty, EmailAddressProperty};
+use crate::Jenkins;
+
 /// Short User that is used in list and links from other structs
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -14,3 +21,407 @@ pub struct ShortUser {
     #[serde(flatten)]
     pub(crate) other_fields: Option<serde_json::Value>,
 }
+
+/// A Jenkins user
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    /// ID of the user, used to log in
+    pub id: String,
+    /// Full name of the user
+    pub full_name: String,
+    /// Description of the user
+    pub description: Option<String>,
+    /// Absolute URL to the user profile
+    pub absolute_url: String,
+    /// Properties of the user, like its email address
+    #[serde(default)]
+    pub property: Vec<CommonProperty>,
+}
+
+impl User {
+    /// Get the email address of the user, from its `EmailAddressProperty`
+    pub fn email_address(&self) -> Option<String> {
+        self.property
+            .iter()
+            .filter_map(|property| property.as_variant::<EmailAddressProperty>().ok())
+            .find_map(|property| property.address)
+    }
+}
+
+/// An API token of the user, to authenticate instead of its password
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiToken {
+    /// Name of the token
+    #[serde(rename = "tokenName")]
+    pub name: String,
+    /// UUID of the token, to revoke it
+    #[serde(rename = "tokenUuid")]
+    pub uuid: String,
+    /// Value of the token, to use as password. Jenkins only provides it when it is generated
+    #[serde(rename = "tokenValue")]
+    pub value: String,
+}
+
+/// Response of Jenkins to some commands, with a status and some data
+#[derive(Debug, Deserialize)]
+struct StatusResponse<T> {
+    status: String,
+    message: Option<String>,
+    data: Option<T>,
+}
+
+impl<T> StatusResponse<T> {
+    fn into_result(self) -> Result<Option<T>, client::Error> {
+        if self.status == "ok" {
+            Ok(self.data)
+        } else {
+            Err(client::Error::ApiTokenNotChanged {
+                message: self.message.unwrap_or(self.status),
+            })
+        }
+    }
+}
+
+/// How the client is authenticated, with the authorities granted
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Authentication {
+    /// Name of the user, `anonymous` when not authenticated
+    pub name: String,
+    /// Is the client anonymous
+    pub anonymous: bool,
+    /// Is the client authenticated
+    pub authenticated: bool,
+    /// Authorities granted, like `authenticated` or the groups of the user
+    #[serde(default)]
+    pub authorities: Vec<String>,
+}
+
+impl Jenkins {
+    /// Get the user authenticated by the credentials of this client, to check that they work
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::CredentialsRejected`](../client/enum.Error.html#variant.CredentialsRejected)
+    /// if Jenkins rejects the credentials, or if there are none and anonymous access is not
+    /// allowed
+    pub fn who_am_i(&self) -> Result<User, Error> {
+        let response = self.get_unchecked_with_params(
+            &Path::Me,
+            [("tree", "id,fullName,description,absoluteUrl")],
+        )?;
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err(client::Error::CredentialsRejected {
+                    status: response.status().as_u16(),
+                }
+                .into())
+            }
+            _ => Ok(response.error_for_status()?.json()?),
+        }
+    }
+
+    /// Get how the client is authenticated and the authorities granted, from `/whoAmI`
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::CredentialsRejected`](../client/enum.Error.html#variant.CredentialsRejected)
+    /// if Jenkins rejects the credentials
+    pub fn get_authentication(&self) -> Result<Authentication, Error> {
+        let response = self.get_unchecked_with_params(
+            &Path::WhoAmI,
+            [("tree", "name,anonymous,authenticated,authorities")],
+        )?;
+        match response.status() {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Err(client::Error::CredentialsRejected {
+                    status: response.status().as_u16(),
+                }
+                .into())
+            }
+            _ => Ok(response.error_for_status()?.json()?),
+        }
+    }
+
+    /// Generate a new API token for the user authenticated by the client, to be used as
+    /// password by later clients
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::ApiTokenNotChanged`](../client/enum.Error.html#variant.ApiTokenNotChanged)
+    /// if Jenkins didn't generate the token
+    pub fn generate_api_token(&self, name: &str) -> Result<ApiToken, Error> {
+        let body = serde_urlencoded::to_string([("newTokenName", name)])?;
+        let response: StatusResponse<ApiToken> = self
+            .post_with_body(&Path::GenerateApiToken, body, &[])?
+            .json()?;
+        Ok(response
+            .into_result()?
+            .ok_or_else(|| client::Error::ApiTokenNotChanged {
+                message: "no token in response".to_string(),
+            })?)
+    }
+
+    /// Revoke the API token `uuid` of the user authenticated by the client
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::ApiTokenNotChanged`](../client/enum.Error.html#variant.ApiTokenNotChanged)
+    /// if Jenkins didn't revoke the token
+    pub fn revoke_api_token(&self, uuid: &str) -> Result<(), Error> {
+        let body = serde_urlencoded::to_string([("tokenUuid", uuid)])?;
+        let response: StatusResponse<serde_json::Value> = self
+            .post_with_body(&Path::RevokeApiToken, body, &[])?
+            .json()?;
+        let _ = response.into_result()?;
+        Ok(())
+    }
+
+    /// Get the user `user_id`
+    pub fn get_user(&self, user_id: &str) -> Result<User, Error> {
+        Ok(self.get(&Path::User { id: user_id })?.json()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    #[test]
+    fn can_get_authenticated_user() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/who-am-i", mockito::server_url()))
+                .with_user("alice", Some("token"))
+                .build()
+                .unwrap();
+
+        let _me = mockito::mock(
+            "GET",
+            "/who-am-i/me/api/json?tree=id%2CfullName%2Cdescription%2CabsoluteUrl",
+        )
+        .match_header("authorization", "Basic YWxpY2U6dG9rZW4=")
+        .with_body(
+            r#"{
+                "_class": "hudson.model.User",
+                "absoluteUrl": "http://localhost:8080/user/alice",
+                "description": null,
+                "fullName": "Alice Liddell",
+                "id": "alice"
+            }"#,
+        )
+        .create();
+
+        let user = jenkins_client.who_am_i().unwrap();
+
+        assert_eq!(user.id, "alice");
+        assert_eq!(user.full_name, "Alice Liddell");
+        assert_eq!(user.description, None);
+        assert_eq!(user.absolute_url, "http://localhost:8080/user/alice");
+    }
+
+    #[test]
+    fn should_fail_when_credentials_are_rejected() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/who-am-i-rejected", mockito::server_url()))
+                .with_user("alice", Some("wrong"))
+                .build()
+                .unwrap();
+
+        let _me = mockito::mock(
+            "GET",
+            "/who-am-i-rejected/me/api/json?tree=id%2CfullName%2Cdescription%2CabsoluteUrl",
+        )
+        .with_status(401)
+        .create();
+
+        match jenkins_client
+            .who_am_i()
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::CredentialsRejected { status }) => assert_eq!(status, 401),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_get_authentication() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/authentication", mockito::server_url()))
+                .with_user("alice", Some("token"))
+                .build()
+                .unwrap();
+
+        let _who_am_i = mockito::mock(
+            "GET",
+            "/authentication/whoAmI/api/json?tree=name%2Canonymous%2Cauthenticated%2Cauthorities",
+        )
+        .match_header("authorization", "Basic YWxpY2U6dG9rZW4=")
+        .with_body(
+            r#"{
+                "_class": "hudson.security.WhoAmI",
+                "anonymous": false,
+                "authenticated": true,
+                "authorities": ["authenticated", "developers"],
+                "name": "alice"
+            }"#,
+        )
+        .create();
+
+        let authentication = jenkins_client.get_authentication().unwrap();
+
+        assert_eq!(authentication.name, "alice");
+        assert!(!authentication.anonymous);
+        assert!(authentication.authenticated);
+        assert_eq!(
+            authentication.authorities,
+            vec!["authenticated".to_string(), "developers".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_fail_to_get_authentication_with_invalid_credentials() {
+        let jenkins_client = crate::JenkinsBuilder::new(&format!(
+            "{}/authentication-rejected",
+            mockito::server_url()
+        ))
+        .with_user("alice", Some("wrong"))
+        .build()
+        .unwrap();
+
+        let _who_am_i = mockito::mock(
+            "GET",
+            "/authentication-rejected/whoAmI/api/json?tree=name%2Canonymous%2Cauthenticated%2Cauthorities",
+        )
+        .with_status(401)
+        .create();
+
+        match jenkins_client
+            .get_authentication()
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::CredentialsRejected { status }) => assert_eq!(status, 401),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_get_user() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/get-user", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _user = mockito::mock("GET", "/get-user/user/jean%20dupont/api/json?depth=1")
+            .with_body(
+                r#"{
+                    "_class": "hudson.model.User",
+                    "absoluteUrl": "http://localhost:8080/user/jean%20dupont",
+                    "description": "On call this week",
+                    "fullName": "Jean Dupont",
+                    "id": "jean dupont",
+                    "property": [
+                        {"_class": "jenkins.security.ApiTokenProperty"},
+                        {"_class": "hudson.tasks.Mailer$UserProperty", "address": "jean@example.com"}
+                    ]
+                }"#,
+            )
+            .create();
+
+        let user = jenkins_client.get_user("jean dupont").unwrap();
+
+        assert_eq!(user.full_name, "Jean Dupont");
+        assert_eq!(user.description, Some("On call this week".to_string()));
+        assert_eq!(user.property.len(), 2);
+        assert_eq!(user.email_address(), Some("jean@example.com".to_string()));
+    }
+
+    #[test]
+    fn can_generate_use_and_revoke_api_token() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/api-token", mockito::server_url()))
+                .with_user("alice", Some("password"))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let generate = mockito::mock(
+            "POST",
+            "/api-token/me/descriptorByName/jenkins.security.ApiTokenProperty/generateNewToken",
+        )
+        .match_header("content-type", "application/x-www-form-urlencoded")
+        .match_body("newTokenName=ci+tool")
+        .with_body(
+            r#"{"status": "ok", "data": {"tokenName": "ci tool",
+            "tokenUuid": "4f6d0e4c-9ab0-4b8e-8b7c-2a5f0f1d3e21",
+            "tokenValue": "11c4d5b5e5f1b3a8c2d9e7f6a5b4c3d2e1"}}"#,
+        )
+        .create();
+        let with_token = mockito::mock(
+            "GET",
+            "/api-token/me/api/json?tree=id%2CfullName%2Cdescription%2CabsoluteUrl",
+        )
+        .match_header(
+            "authorization",
+            "Basic YWxpY2U6MTFjNGQ1YjVlNWYxYjNhOGMyZDllN2Y2YTViNGMzZDJlMQ==",
+        )
+        .with_body(
+            r#"{"absoluteUrl": "http://localhost:8080/user/alice", "description": null,
+            "fullName": "alice", "id": "alice"}"#,
+        )
+        .create();
+        let revoke = mockito::mock(
+            "POST",
+            "/api-token/me/descriptorByName/jenkins.security.ApiTokenProperty/revoke",
+        )
+        .match_body("tokenUuid=4f6d0e4c-9ab0-4b8e-8b7c-2a5f0f1d3e21")
+        .with_body(r#"{"status": "ok"}"#)
+        .create();
+
+        let token = jenkins_client.generate_api_token("ci tool").unwrap();
+        assert_eq!(token.name, "ci tool");
+        assert_eq!(token.uuid, "4f6d0e4c-9ab0-4b8e-8b7c-2a5f0f1d3e21");
+
+        let token_client =
+            crate::JenkinsBuilder::new(&format!("{}/api-token", mockito::server_url()))
+                .with_user("alice", Some(&token.value))
+                .build()
+                .unwrap();
+        assert_eq!(token_client.who_am_i().unwrap().id, "alice");
+
+        assert!(jenkins_client.revoke_api_token(&token.uuid).is_ok());
+
+        generate.assert();
+        with_token.assert();
+        revoke.assert();
+    }
+
+    #[test]
+    fn should_fail_when_token_is_not_revoked() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/api-token-error", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let _revoke = mockito::mock(
+            "POST",
+            "/api-token-error/me/descriptorByName/jenkins.security.ApiTokenProperty/revoke",
+        )
+        .with_body(r#"{"status": "error", "message": "unknown token"}"#)
+        .create();
+
+        match jenkins_client
+            .revoke_api_token("unknown")
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::ApiTokenNotChanged { message }) => {
+                assert_eq!(message, "unknown token")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Version of Jenkins and metadata from the headers of its responses
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use failure::Error;
+use reqwest::header::HeaderMap;
+
+use crate::client;
+use crate::client_internals::Path;
+use crate::Jenkins;
+
+/// Version of Jenkins, as found in the `X-Jenkins` header. Versions are compared by their
+/// numbers, ignoring suffixes like `-SNAPSHOT`
+#[derive(Debug, Clone)]
+pub struct JenkinsVersion {
+    /// Major version
+    pub major: u32,
+    /// Minor version
+    pub minor: u32,
+    /// Patch version, 0 for weekly releases
+    pub patch: u32,
+    /// Version as sent by Jenkins
+    pub raw: String,
+}
+
+impl JenkinsVersion {
+    /// Build a version from its numbers
+    pub fn new(major: u32, minor: u32, patch: u32) -> Self {
+        JenkinsVersion {
+            major,
+            minor,
+            patch,
+            raw: format!("{}.{}.{}", major, minor, patch),
+        }
+    }
+
+    fn numbers(&self) -> (u32, u32, u32) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for JenkinsVersion {
+    type Err = client::Error;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let invalid = || client::Error::InvalidJenkinsVersion {
+            version: raw.to_string(),
+        };
+        let number = |part: Option<&str>| -> Result<u32, client::Error> {
+            match part {
+                None => Ok(0),
+                Some(part) => {
+                    let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                    digits.parse().map_err(|_| invalid())
+                }
+            }
+        };
+        let mut parts = raw.trim().splitn(3, '.');
+        let major = number(Some(parts.next().ok_or_else(invalid)?))?;
+        let minor = number(Some(parts.next().ok_or_else(invalid)?))?;
+        let patch = number(parts.next())?;
+        Ok(JenkinsVersion {
+            major,
+            minor,
+            patch,
+            raw: raw.to_string(),
+        })
+    }
+}
+
+impl PartialEq for JenkinsVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.numbers() == other.numbers()
+    }
+}
+
+impl Eq for JenkinsVersion {}
+
+impl PartialOrd for JenkinsVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JenkinsVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numbers().cmp(&other.numbers())
+    }
+}
+
+impl fmt::Display for JenkinsVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// Metadata Jenkins sends in the headers of its responses
+#[derive(Debug, Clone)]
+pub struct ServerMetadata {
+    /// Version of Jenkins, from the `X-Jenkins` header
+    pub version: JenkinsVersion,
+    /// Version of the Hudson API, from the `X-Hudson` header
+    pub hudson_version: Option<String>,
+    /// Public key identifying the instance, from the `X-Instance-Identity` header
+    pub instance_identity: Option<String>,
+}
+
+fn header(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string())
+}
+
+impl Jenkins {
+    /// Get the version of Jenkins, to check which features it supports
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::NotJenkins`](../client/enum.Error.html#variant.NotJenkins)
+    /// if the response has no `X-Jenkins` header, meaning the URL is probably not a Jenkins
+    /// server
+    pub fn get_version(&self) -> Result<JenkinsVersion, Error> {
+        Ok(self.get_server_metadata()?.version)
+    }
+
+    /// Get the metadata Jenkins sends in the headers of its responses
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::NotJenkins`](../client/enum.Error.html#variant.NotJenkins)
+    /// if the response has no `X-Jenkins` header, meaning the URL is probably not a Jenkins
+    /// server
+    pub fn get_server_metadata(&self) -> Result<ServerMetadata, Error> {
+        let response = self.get_with_params(&Path::Home, [("tree", "_class")])?;
+        let headers = response.headers();
+        let version = match header(headers, "X-Jenkins") {
+            Some(version) => version.parse()?,
+            None => {
+                return Err(client::Error::NotJenkins {
+                    url: response.url().to_string(),
+                }
+                .into())
+            }
+        };
+        Ok(ServerMetadata {
+            version,
+            hudson_version: header(headers, "X-Hudson"),
+            instance_identity: header(headers, "X-Instance-Identity"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito;
+
+    use super::JenkinsVersion;
+
+    #[test]
+    fn can_parse_versions() {
+        let lts: JenkinsVersion = "2.150.1".parse().unwrap();
+        assert_eq!(
+            (lts.major, lts.minor, lts.patch, lts.raw.as_str()),
+            (2, 150, 1, "2.150.1")
+        );
+        let weekly: JenkinsVersion = "2.164".parse().unwrap();
+        assert_eq!((weekly.major, weekly.minor, weekly.patch), (2, 164, 0));
+        let snapshot: JenkinsVersion = "2.176-SNAPSHOT".parse().unwrap();
+        assert_eq!(
+            (snapshot.major, snapshot.minor, snapshot.patch),
+            (2, 176, 0)
+        );
+        assert_eq!(snapshot.to_string(), "2.176-SNAPSHOT");
+
+        assert!("2".parse::<JenkinsVersion>().is_err());
+        assert!("unknown".parse::<JenkinsVersion>().is_err());
+    }
+
+    #[test]
+    fn versions_are_compared_by_numbers() {
+        let version = |raw: &str| raw.parse::<JenkinsVersion>().unwrap();
+        assert!(version("2.150.1") > version("2.150"));
+        assert!(version("2.164") > version("2.150.3"));
+        assert!(version("10.0") > version("2.999"));
+        assert_eq!(version("2.164"), JenkinsVersion::new(2, 164, 0));
+        assert_eq!(version("2.176-SNAPSHOT"), version("2.176"));
+    }
+
+    #[test]
+    fn can_get_version_and_metadata() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/version", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _home = mockito::mock("GET", "/version/api/json?tree=_class")
+            .with_header("X-Jenkins", "2.150.1")
+            .with_header("X-Hudson", "1.395")
+            .with_header("X-Instance-Identity", "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A")
+            .with_body(r#"{"_class":"hudson.model.Hudson"}"#)
+            .create();
+
+        let version = jenkins_client.get_version().unwrap();
+        assert_eq!(version, JenkinsVersion::new(2, 150, 1));
+        assert!(version >= JenkinsVersion::new(2, 150, 0));
+
+        let metadata = jenkins_client.get_server_metadata().unwrap();
+        assert_eq!(metadata.version.raw, "2.150.1");
+        assert_eq!(metadata.hudson_version, Some("1.395".to_string()));
+        assert_eq!(
+            metadata.instance_identity,
+            Some("MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8A".to_string())
+        );
+    }
+
+    #[test]
+    fn should_fail_without_jenkins_header() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/not-jenkins", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let _home = mockito::mock("GET", "/not-jenkins/api/json?tree=_class")
+            .with_body(r#"{"_class":"hudson.model.Hudson"}"#)
+            .create();
+
+        match jenkins_client
+            .get_version()
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::NotJenkins { url }) => assert_eq!(
+                url,
+                format!("{}/not-jenkins/api/json?tree=_class", mockito::server_url())
+            ),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -1,14 +1,16 @@
 //! Jenkins Views, use to group Jobs
 
 use failure::Error;
+use reqwest::StatusCode;
 use serde::{self, Deserialize, Serialize};
 use serde_json;
 
-use crate::helpers::Class;
+use crate::helpers::{html_to_text, Class, NameFilter};
 
+use crate::build::BuildStatus;
 use crate::client;
 use crate::client_internals::{Name, Path};
-use crate::job::{JobName, ShortJob};
+use crate::job::{BallColor, JobName, ShortJob};
 use crate::property::CommonProperty;
 use crate::Jenkins;
 
@@ -16,6 +18,9 @@ use crate::Jenkins;
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ShortView {
+    /// _class provided by Jenkins, to know the kind of view without getting it
+    #[serde(rename = "_class")]
+    pub class: Option<String>,
     /// Name of the view
     pub name: String,
     /// URL for the view
@@ -40,6 +45,15 @@ impl ShortView {
     }
 }
 
+/// Filter on the names of views, see [`NameFilter`](../helpers/enum.NameFilter.html) for
+/// how names are matched
+pub type ViewFilter = NameFilter;
+
+#[derive(Debug, Deserialize)]
+struct ViewList {
+    views: Vec<ShortView>,
+}
+
 /// Helper type to act on a view
 #[derive(Debug)]
 pub struct ViewName<'a>(pub &'a str);
@@ -68,6 +82,11 @@ impl<'a, T: View> From<&'a T> for ViewName<'a> {
 pub trait View {
     /// Get the name of the view
     fn name(&self) -> &str;
+
+    /// Delete this view
+    fn delete(&self, jenkins_client: &Jenkins) -> Result<(), Error> {
+        jenkins_client.delete_view(self.name())
+    }
 }
 
 /// A Jenkins `View` with a list of `ShortJob`
@@ -84,8 +103,10 @@ pub struct CommonView {
     /// URL for the view
     pub url: String,
     /// List of jobs in the view
+    #[serde(default)]
     pub jobs: Vec<ShortJob>,
     /// Properties of the view
+    #[serde(default)]
     pub property: Vec<CommonProperty>,
     #[serde(flatten)]
     other_fields: serde_json::Value,
@@ -108,8 +129,10 @@ pub struct ListView {
     /// URL for the view
     pub url: String,
     /// List of jobs in the view
+    #[serde(default)]
     pub jobs: Vec<ShortJob>,
     /// Properties of the view
+    #[serde(default)]
     pub property: Vec<CommonProperty>,
 }
 register_class!("hudson.model.ListView" => ListView);
@@ -163,6 +186,78 @@ impl ListView {
     }
 }
 
+/// A Jenkins `View` containing other views, from the Nested View plugin
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NestedView {
+    /// Description of the view
+    pub description: Option<String>,
+    /// Name of the view
+    pub name: String,
+    /// URL for the view
+    pub url: String,
+    /// List of views nested in this view
+    #[serde(default)]
+    pub views: Vec<ShortView>,
+    /// Properties of the view
+    #[serde(default)]
+    pub property: Vec<CommonProperty>,
+}
+register_class!("hudson.plugins.nested_view.NestedView" => NestedView);
+impl View for NestedView {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A Jenkins `View` with the jobs the current user has access to
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MyView {
+    /// Description of the view
+    pub description: Option<String>,
+    /// Name of the view
+    pub name: String,
+    /// URL for the view
+    pub url: String,
+    /// List of jobs in the view
+    #[serde(default)]
+    pub jobs: Vec<ShortJob>,
+    /// Properties of the view
+    #[serde(default)]
+    pub property: Vec<CommonProperty>,
+}
+register_class!("hudson.model.MyView" => MyView);
+impl View for MyView {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A Jenkins `View` displaying portlets about a list of jobs, from the Dashboard View plugin
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardView {
+    /// Description of the view
+    pub description: Option<String>,
+    /// Name of the view
+    pub name: String,
+    /// URL for the view
+    pub url: String,
+    /// List of jobs in the view
+    #[serde(default)]
+    pub jobs: Vec<ShortJob>,
+    /// Properties of the view
+    #[serde(default)]
+    pub property: Vec<CommonProperty>,
+}
+register_class!("hudson.plugins.view.dashboard.Dashboard" => DashboardView);
+impl View for DashboardView {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
 impl Jenkins {
     /// Get a `View`
     pub fn get_view<'a, V>(&self, view_name: V) -> Result<CommonView, Error>
@@ -176,6 +271,84 @@ impl Jenkins {
             .json()?)
     }
 
+    /// List the views, getting only their names, urls and classes
+    pub fn list_views(&self) -> Result<Vec<ShortView>, Error> {
+        let list: ViewList = self
+            .get_with_params(&Path::Home, [("tree", "views[name,url]")])?
+            .json()?;
+        Ok(list.views)
+    }
+
+    /// List the views whose name matches `filter`
+    pub fn find_views(&self, filter: &ViewFilter) -> Result<Vec<ShortView>, Error> {
+        Ok(self
+            .list_views()?
+            .into_iter()
+            .filter(|view| filter.matches(&view.name))
+            .collect())
+    }
+
+    /// Create a `ListView` named `view_name`
+    pub fn create_view<'a, V>(&self, view_name: V) -> Result<(), Error>
+    where
+        V: Into<ViewName<'a>>,
+    {
+        let name = view_name.into().0;
+        let mode = "hudson.model.ListView";
+        let json = serde_json::json!({ "name": name, "mode": mode }).to_string();
+        let body = serde_urlencoded::to_string([("name", name), ("mode", mode), ("json", &json)])?;
+        let _ = self.post_with_body(&Path::CreateView, body, &[])?;
+        Ok(())
+    }
+
+    /// Create the view `view_name` from a `config.xml`, as returned by `/view/<name>/config.xml`
+    ///
+    /// # Errors
+    /// This methods will return an
+    /// [`Error::ViewNotCreated`](../client/enum.Error.html#variant.ViewNotCreated)
+    /// with the message of Jenkins if it refused to create the view, as when there is already a
+    /// view with that name
+    pub fn create_view_from_config<'a, V>(
+        &self,
+        view_name: V,
+        config_xml: &str,
+    ) -> Result<(), Error>
+    where
+        V: Into<ViewName<'a>>,
+    {
+        let name = view_name.into().0;
+        let mut response =
+            self.post_xml_unchecked(&Path::CreateView, config_xml, &[("name", name)])?;
+        if response.status() == StatusCode::BAD_REQUEST {
+            let message = match response
+                .headers()
+                .get("X-Error")
+                .and_then(|error| error.to_str().ok())
+            {
+                Some(error) => error.to_string(),
+                None => html_to_text(&response.text()?).trim().to_string(),
+            };
+            return Err(client::Error::ViewNotCreated {
+                view_name: name.to_string(),
+                message,
+            }
+            .into());
+        }
+        let _ = response.error_for_status()?;
+        Ok(())
+    }
+
+    /// Delete the view `view_name`
+    pub fn delete_view<'a, V>(&self, view_name: V) -> Result<(), Error>
+    where
+        V: Into<ViewName<'a>>,
+    {
+        let _ = self.post(&Path::DeleteView {
+            name: Name::Name(view_name.into().0),
+        })?;
+        Ok(())
+    }
+
     /// Add the job `job_name` to the view `view_name`
     pub fn add_job_to_view<'a, 'b, V, J>(&self, view_name: V, job_name: J) -> Result<(), Error>
     where
@@ -201,4 +374,375 @@ impl Jenkins {
         })?;
         Ok(())
     }
+
+    /// Get a `Radiator` of the view `view_name`, with the state of each of its jobs, in a single
+    /// request
+    pub fn get_radiator<'a, V>(&self, view_name: V) -> Result<Radiator, Error>
+    where
+        V: Into<ViewName<'a>>,
+    {
+        Ok(self
+            .get_with_params(
+                &Path::View {
+                    name: Name::Name(view_name.into().0),
+                },
+                [(
+                    "tree",
+                    "name,url,jobs[name,url,color,lastBuild[number,result,culprits[fullName]]]",
+                )],
+            )?
+            .json()?)
+    }
+}
+
+/// State of the jobs of a view, ready to be displayed on a build radiator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Radiator {
+    /// Name of the view
+    pub name: String,
+    /// URL of the view
+    pub url: String,
+    /// Jobs of the view
+    pub jobs: Vec<RadiatorJob>,
+}
+
+/// State of a job on a `Radiator`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RadiatorJob {
+    /// Name of the job
+    pub name: String,
+    /// URL of the job
+    pub url: String,
+    /// Ball color of the job, absent for jobs without builds like folders
+    pub color: Option<BallColor>,
+    /// Last build of the job
+    pub last_build: Option<RadiatorBuild>,
+}
+
+/// Last build of a `RadiatorJob`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RadiatorBuild {
+    /// Build number
+    pub number: u32,
+    /// Result of the build, `None` while it is running
+    pub result: Option<BuildStatus>,
+    /// Users with changes in this build and in the failed builds before it. Empty for builds
+    /// that don't track culprits
+    #[serde(default, deserialize_with = "culprit_names")]
+    pub culprits: Vec<String>,
+}
+
+fn culprit_names<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Culprit {
+        full_name: String,
+    }
+    let culprits: Vec<Culprit> = Deserialize::deserialize(deserializer)?;
+    Ok(culprits
+        .into_iter()
+        .map(|culprit| culprit.full_name)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static VIEWS: &str = r#"{
+        "_class": "hudson.model.Hudson",
+        "views": [
+            {"_class": "hudson.model.AllView", "name": "all", "url": "http://none:8080/"},
+            {"_class": "hudson.model.ListView", "name": "Team API", "url": "http://none:8080/view/Team%20API/"},
+            {"_class": "hudson.model.ListView", "name": "team-web", "url": "http://none:8080/view/team-web/"},
+            {"_class": "hudson.plugins.nested_view.NestedView", "name": "releases", "url": "http://none:8080/view/releases/"}
+        ]
+    }"#;
+
+    static VIEW: &str = r#"{
+        "_class": "hudson.model.ListView",
+        "description": null,
+        "jobs": [],
+        "name": "new view",
+        "property": [],
+        "url": "http://none:8080/view/new%20view/"
+    }"#;
+
+    #[test]
+    fn can_create_and_delete_view() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/views", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let create = mockito::mock("POST", "/views/createView")
+            .match_header("content-type", "application/x-www-form-urlencoded")
+            .match_body(
+                "name=new+view&mode=hudson.model.ListView&json=%7B%22mode%22%3A%22hudson.model.ListView%22%2C%22name%22%3A%22new+view%22%7D",
+            )
+            .create();
+        jenkins_client.create_view("new view").unwrap();
+        create.assert();
+
+        let _view = mockito::mock("GET", "/views/view/new%20view/api/json?depth=1")
+            .with_body(VIEW)
+            .create();
+        let view = jenkins_client.get_view("new view").unwrap();
+
+        let delete = mockito::mock("POST", "/views/view/new%20view/doDelete").create();
+        view.delete(&jenkins_client).unwrap();
+        delete.assert();
+    }
+
+    static VIEW_CONFIG: &str = r#"<?xml version="1.1" encoding="UTF-8"?>
+<hudson.model.ListView>
+  <name>team view</name>
+  <filterExecutors>false</filterExecutors>
+  <filterQueue>false</filterQueue>
+  <properties class="hudson.model.View$PropertyList"/>
+  <jobNames>
+    <comparator class="hudson.util.CaseInsensitiveComparator"/>
+  </jobNames>
+  <jobFilters/>
+  <columns/>
+  <recurse>false</recurse>
+</hudson.model.ListView>"#;
+
+    #[test]
+    fn can_create_view_from_config() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/view-config", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let create = mockito::mock("POST", "/view-config/createView?name=team+view")
+            .match_header("content-type", "application/xml")
+            .match_body(VIEW_CONFIG)
+            .create();
+        jenkins_client
+            .create_view_from_config("team view", VIEW_CONFIG)
+            .unwrap();
+        create.assert();
+
+        let _view = mockito::mock("GET", "/view-config/view/team%20view/api/json?depth=1")
+            .with_body(
+                VIEW.replace("new view", "team view")
+                    .replace("new%20view", "team%20view"),
+            )
+            .create();
+        let view = jenkins_client.get_view("team view").unwrap();
+
+        let delete = mockito::mock("POST", "/view-config/view/team%20view/doDelete").create();
+        view.delete(&jenkins_client).unwrap();
+        delete.assert();
+    }
+
+    #[test]
+    fn should_surface_error_when_view_exists() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/view-exists", mockito::server_url()))
+                .disable_csrf()
+                .build()
+                .unwrap();
+
+        let _create = mockito::mock("POST", "/view-exists/createView?name=team+view")
+            .with_status(400)
+            .with_body(
+                "<html><body><h1>Error</h1><p>A view already exists with the name \
+                 &quot;team view&quot;</p></body></html>",
+            )
+            .create();
+
+        match jenkins_client
+            .create_view_from_config("team view", VIEW_CONFIG)
+            .unwrap_err()
+            .downcast::<crate::client::Error>()
+        {
+            Ok(crate::client::Error::ViewNotCreated { view_name, message }) => {
+                assert_eq!(view_name, "team view");
+                assert!(message.contains(r#"A view already exists with the name "team view""#));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn can_get_radiator() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/radiator", mockito::server_url()))
+                .build()
+                .unwrap();
+        let mock = mockito::mock(
+            "GET",
+            "/radiator/view/team/api/json?tree=name%2Curl%2Cjobs%5Bname%2Curl%2Ccolor%2ClastBuild%5Bnumber%2Cresult%2Cculprits%5BfullName%5D%5D%5D",
+        )
+        .with_body(
+            r#"{"_class":"hudson.model.ListView","name":"team","url":"http://none/view/team/",
+            "jobs":[
+                {"_class":"hudson.model.FreeStyleProject","name":"broken","url":"http://none/job/broken/",
+                "color":"red","lastBuild":{"_class":"hudson.model.FreeStyleBuild","number":8,
+                "result":"FAILURE","culprits":[{"fullName":"alice"},{"fullName":"bob"}]}},
+                {"_class":"org.jenkinsci.plugins.workflow.job.WorkflowJob","name":"running",
+                "url":"http://none/job/running/","color":"blue_anime","lastBuild":{
+                "_class":"org.jenkinsci.plugins.workflow.job.WorkflowRun","number":3,"result":null}},
+                {"_class":"com.cloudbees.hudson.plugins.folder.Folder","name":"folder",
+                "url":"http://none/job/folder/"}
+            ]}"#,
+        )
+        .create();
+
+        let radiator = jenkins_client.get_radiator("team").unwrap();
+        mock.assert();
+
+        assert_eq!(radiator.jobs.len(), 3);
+        let broken = radiator.jobs[0].last_build.as_ref().unwrap();
+        assert_eq!(broken.result, Some(BuildStatus::Failure));
+        assert_eq!(broken.culprits, vec!["alice", "bob"]);
+        let running = radiator.jobs[1].last_build.as_ref().unwrap();
+        assert_eq!(running.result, None);
+        assert!(running.culprits.is_empty());
+        assert_eq!(radiator.jobs[2].color, None);
+    }
+
+    #[test]
+    fn can_list_and_find_views() {
+        let jenkins_client =
+            crate::JenkinsBuilder::new(&format!("{}/list-views", mockito::server_url()))
+                .build()
+                .unwrap();
+
+        let mock = mockito::mock("GET", "/list-views/api/json?tree=views%5Bname%2Curl%5D")
+            .with_body(VIEWS)
+            .expect(2)
+            .create();
+
+        let views = jenkins_client.list_views().unwrap();
+        assert_eq!(views.len(), 4);
+        assert_eq!(
+            views[3].class,
+            Some("hudson.plugins.nested_view.NestedView".to_string())
+        );
+
+        let found = jenkins_client
+            .find_views(&ViewFilter::glob("team*"))
+            .unwrap();
+        assert_eq!(
+            found.iter().map(|view| &view.name[..]).collect::<Vec<_>>(),
+            vec!["team-web"]
+        );
+        mock.assert();
+    }
+
+    #[test]
+    fn glob_is_anchored_and_case_sensitive() {
+        let filter = ViewFilter::glob("team-?eb");
+        assert!(filter.matches("team-web"));
+        assert!(!filter.matches("Team-web"));
+        assert!(!filter.matches("my-team-web"));
+        assert!(!filter.matches("team-webs"));
+    }
+
+    #[test]
+    fn regex_is_not_anchored() {
+        let filter = ViewFilter::regex("(?i)team").unwrap();
+        assert!(filter.matches("Team API"));
+        assert!(filter.matches("my-team-web"));
+        let anchored = ViewFilter::regex("^team").unwrap();
+        assert!(!anchored.matches("Team API"));
+        assert!(!anchored.matches("my-team-web"));
+        assert!(ViewFilter::regex("[").is_err());
+    }
+
+    #[test]
+    fn can_deserialize_nested_view() {
+        let view: CommonView = serde_json::from_str(
+            r#"{
+                "_class": "hudson.plugins.nested_view.NestedView",
+                "description": "all releases",
+                "name": "releases",
+                "property": [],
+                "url": "http://none:8080/view/releases/",
+                "views": [{
+                    "_class": "hudson.model.ListView",
+                    "name": "api",
+                    "url": "http://none:8080/view/releases/view/api/"
+                }]
+            }"#,
+        )
+        .unwrap();
+        let nested = view.as_variant::<NestedView>().unwrap();
+        assert_eq!(nested.views.len(), 1);
+        assert_eq!(nested.views[0].name, "api");
+    }
+
+    #[test]
+    fn can_get_full_view_of_nested_view() {
+        let url = format!("{}/nested-views", mockito::server_url());
+        let jenkins_client = crate::JenkinsBuilder::new(&url).build().unwrap();
+        let short_view: ShortView = serde_json::from_str(&format!(
+            r#"{{"_class": "hudson.model.ListView", "name": "api", "url": "{}/view/releases/view/api/"}}"#,
+            url
+        ))
+        .unwrap();
+
+        let mock = mockito::mock(
+            "GET",
+            "/nested-views/view/releases/view/api/api/json?depth=1",
+        )
+        .with_body(VIEW)
+        .create();
+        let _ = short_view.get_full_view(&jenkins_client).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn can_deserialize_my_view() {
+        let view: CommonView = serde_json::from_str(
+            r#"{
+                "_class": "hudson.model.MyView",
+                "description": null,
+                "jobs": [{
+                    "_class": "hudson.model.FreeStyleProject",
+                    "name": "mine",
+                    "url": "http://none:8080/job/mine/",
+                    "color": "blue"
+                }],
+                "name": "My View",
+                "property": [],
+                "url": "http://none:8080/me/my-views/view/My%20View/"
+            }"#,
+        )
+        .unwrap();
+        let my_view = view.as_variant::<MyView>().unwrap();
+        assert_eq!(my_view.jobs[0].name, "mine");
+    }
+
+    #[test]
+    fn can_deserialize_dashboard_view() {
+        let view: CommonView = serde_json::from_str(
+            r#"{
+                "_class": "hudson.plugins.view.dashboard.Dashboard",
+                "description": "<b>status</b>",
+                "jobs": [{
+                    "_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob",
+                    "name": "pipeline",
+                    "url": "http://none:8080/job/pipeline/",
+                    "color": "red"
+                }],
+                "name": "dashboard",
+                "property": [],
+                "url": "http://none:8080/view/dashboard/"
+            }"#,
+        )
+        .unwrap();
+        let dashboard = view.as_variant::<DashboardView>().unwrap();
+        assert_eq!(dashboard.jobs[0].name, "pipeline");
+        assert!(view.as_variant::<ListView>().is_err());
+    }
 }
@@ -9,8 +9,11 @@ extern crate jenkins_api;
 
 use spectral::prelude::*;
 
-use jenkins_api::build::Build;
+use jenkins_api::action::causes::UserIdCause;
+use jenkins_api::build::{Build, BuildNumber, BuildStatus};
 use jenkins_api::job::{BuildableJob, Job, SCMPollable};
+use jenkins_api::nodes::computer::Computer;
+use jenkins_api::view::View;
 use jenkins_api::JenkinsBuilder;
 use std::{thread, time};
 
@@ -36,6 +39,63 @@ fn can_get_jenkins_home() {
     assert!(jenkins.get_home().is_ok());
 }
 
+#[test]
+fn can_quiet_down_and_cancel() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    assert!(jenkins.quiet_down(Some("testing quiet down")).is_ok());
+    let home = jenkins.get_home();
+    assert!(home.is_ok());
+    assert!(home.unwrap().quieting_down);
+
+    assert!(jenkins.cancel_quiet_down().is_ok());
+    let home = jenkins.get_home();
+    assert!(home.is_ok());
+    assert!(!home.unwrap().quieting_down);
+}
+
+#[test]
+#[ignore]
+fn can_safe_restart() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    assert!(jenkins.safe_restart().is_ok());
+    assert!(jenkins
+        .wait_until_ready(time::Duration::from_secs(300), time::Duration::from_secs(5))
+        .is_ok());
+}
+
+#[test]
+#[ignore]
+fn can_install_plugin() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    assert!(jenkins.install_plugin("timestamper", None).is_ok());
+    let start = time::Instant::now();
+    loop {
+        let update_center = jenkins.get_update_center().unwrap();
+        let installation = update_center.installation("timestamper").unwrap();
+        if installation.is_done() {
+            assert!(installation.is_success());
+            break;
+        }
+        assert!(start.elapsed() < time::Duration::from_secs(300));
+        thread::sleep(time::Duration::from_secs(1));
+    }
+}
+
 #[test]
 fn should_be_forbidden() {
     setup();
@@ -184,6 +244,74 @@ fn can_disable_job_and_reenable() {
     assert!(job_enabled_ok.buildable);
 }
 
+#[test]
+fn can_get_who_am_i() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+    let user = jenkins.who_am_i();
+    assert!(user.is_ok());
+    assert_that!(user.unwrap().id).is_equal_to("user".to_string());
+    let authentication = jenkins.get_authentication();
+    assert!(authentication.is_ok());
+    assert_that!(authentication.unwrap().authenticated).is_true();
+    assert!(jenkins.get_user("user").is_ok());
+
+    let rejected = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("wrong password"))
+        .build()
+        .unwrap();
+    assert!(rejected.who_am_i().is_err());
+    assert!(rejected.get_authentication().is_err());
+}
+
+#[test]
+fn can_generate_and_revoke_api_token() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+    let token = jenkins.generate_api_token("integration test");
+    assert!(token.is_ok());
+    let token = token.unwrap();
+
+    let token_client = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some(&token.value))
+        .build()
+        .unwrap();
+    assert!(token_client.get_home().is_ok());
+
+    assert!(jenkins.revoke_api_token(&token.uuid).is_ok());
+    assert!(token_client.get_home().is_err());
+}
+
+#[test]
+fn can_set_job_description() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+    let job = jenkins.get_job("normal job");
+    assert!(job.is_ok());
+    let job_ok = job.unwrap();
+
+    let description = "<b>described</b>\non two lines";
+    assert!(job_ok.set_description(&jenkins, description).is_ok());
+    let job_described = jenkins.get_job("normal job");
+    assert!(job_described.is_ok());
+    assert_that!(job_described.unwrap().description).is_equal_to(Some(description.to_string()));
+
+    let previous = job_ok.description.unwrap_or_default();
+    assert!(jenkins.set_job_description("normal job", &previous).is_ok());
+    let job_restored = jenkins.get_job("normal job");
+    assert!(job_restored.is_ok());
+    assert_that!(job_restored.unwrap().description.unwrap_or_default()).is_equal_to(previous);
+}
+
 #[test]
 fn can_add_and_remove_job_from_view_through_view() {
     setup();
@@ -239,6 +367,24 @@ fn can_add_and_remove_job_from_view_through_view() {
         .is_none());
 }
 
+#[test]
+fn can_create_and_delete_view() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    assert_that!(jenkins.create_view("created view")).is_ok();
+    assert_that!(jenkins.add_job_to_view("created view", "normal job")).is_ok();
+
+    let view = jenkins.get_view("created view").unwrap();
+    assert_that!(view.jobs.iter().any(|job| job.name == "normal job")).is_true();
+
+    assert_that!(view.delete(&jenkins)).is_ok();
+    assert_that!(format!("{:?}", jenkins.get_view("created view"))).contains("Status(404)");
+}
+
 #[test]
 fn can_add_and_remove_job_from_view_through_job() {
     setup();
@@ -339,6 +485,107 @@ fn can_get_queue_item() {
     }
 }
 
+#[test]
+fn can_wait_for_triggered_build() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let triggered = jenkins.build_job("job name").unwrap();
+    let build = triggered.wait_for_full_build(
+        &jenkins,
+        time::Duration::from_secs(1),
+        time::Duration::from_secs(30),
+    );
+    assert_that!(build).named("started build").is_ok();
+}
+
+#[test]
+fn can_get_build_from_queue_item() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let triggered = jenkins.build_job("job name").unwrap();
+    let mut queue_item = triggered.get_full_queue_item(&jenkins).unwrap();
+    for _ in 0..30 {
+        if queue_item.executable.is_some() {
+            break;
+        }
+        thread::sleep(time::Duration::from_secs(1));
+        queue_item = queue_item.refresh_item(&jenkins).unwrap();
+    }
+    let build = queue_item.get_build(&jenkins);
+    assert_that!(build).named("started build").is_ok();
+    assert!(build.unwrap().is_some());
+}
+
+#[test]
+fn can_delete_build() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let mut numbers = vec![];
+    for _ in 0..3 {
+        let build = jenkins
+            .build_job("job name")
+            .unwrap()
+            .wait_for_full_build(
+                &jenkins,
+                time::Duration::from_secs(1),
+                time::Duration::from_secs(30),
+            )
+            .unwrap()
+            .wait_for_completion(
+                &jenkins,
+                time::Duration::from_secs(1),
+                time::Duration::from_secs(60),
+            )
+            .unwrap();
+        numbers.push(build.number);
+    }
+
+    jenkins.delete_build("job name", numbers[0]).unwrap();
+    assert_that!(jenkins.get_build("job name", numbers[0]))
+        .named("deleted build")
+        .is_err();
+    for number in &numbers[1..] {
+        assert_that!(jenkins.get_build("job name", *number))
+            .named("remaining build")
+            .is_ok();
+    }
+}
+
+#[test]
+fn can_get_causes_of_user_triggered_build() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let build = jenkins
+        .build_job("job name")
+        .unwrap()
+        .wait_for_full_build(
+            &jenkins,
+            time::Duration::from_secs(1),
+            time::Duration::from_secs(30),
+        )
+        .unwrap();
+    let causes = build.get_causes().unwrap();
+    assert!(causes
+        .iter()
+        .any(|cause| cause.as_variant::<UserIdCause>().is_ok()));
+}
+
 #[test]
 fn can_get_console() {
     setup();
@@ -380,6 +627,55 @@ fn can_get_pipeline() {
     assert!(build.is_ok());
 }
 
+#[test]
+fn can_get_pipeline_stages() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let build = jenkins.get_build("pipeline job", 1).unwrap();
+    let description = build.get_workflow_description(&jenkins);
+    println!("{:#?}", description);
+    assert!(description.is_ok());
+
+    let description_ok = description.unwrap();
+    assert!(!description_ok.stages.is_empty());
+    assert!(description_ok
+        .stages
+        .iter()
+        .all(|stage| !stage.name.is_empty()));
+
+    let nodes = description_ok.stages[0].get_flow_nodes(&jenkins);
+    println!("{:#?}", nodes);
+    assert!(nodes.is_ok());
+}
+
+#[test]
+fn can_get_blue_ocean_nodes() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let blue_ocean = jenkins.blue_ocean();
+    let runs = blue_ocean.get_pipeline_runs("pipeline job");
+    println!("{:#?}", runs);
+    assert!(runs.is_ok());
+
+    let nodes = blue_ocean.get_run_nodes("pipeline job", 1);
+    println!("{:#?}", nodes);
+    assert!(nodes.is_ok());
+
+    let nodes_ok = nodes.unwrap();
+    assert!(!nodes_ok.is_empty());
+    let steps = blue_ocean.get_node_steps("pipeline job", 1, &nodes_ok[0].id);
+    println!("{:#?}", steps);
+    assert!(steps.is_ok());
+}
+
 #[test]
 fn can_build_job_with_delay() {
     setup();
@@ -442,6 +738,7 @@ fn can_get_build_with_git() {
     assert!(job.is_ok());
     let build = jenkins.get_build("git triggered", 2);
     assert!(build.is_ok());
+    assert!(build.unwrap().git_revision().is_some());
 }
 
 #[test]
@@ -647,6 +944,37 @@ fn can_get_nodes() {
     assert_that!(jenkins.get_nodes()).is_ok();
 }
 
+#[test]
+fn can_take_master_offline_and_online() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let master = jenkins.get_master_node().unwrap();
+    assert_that!(master.mark_offline(&jenkins, Some("maintenance"))).is_ok();
+    let master = jenkins.get_master_node().unwrap();
+    assert!(master.temporarily_offline);
+    assert_that!(master.mark_online(&jenkins)).is_ok();
+    assert!(!jenkins.get_master_node().unwrap().temporarily_offline);
+}
+
+#[test]
+fn can_get_plugins() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+    let plugins = jenkins.get_plugins();
+    assert_that!(plugins).is_ok();
+    assert!(plugins
+        .unwrap()
+        .iter()
+        .any(|plugin| plugin.short_name == "workflow-job"));
+}
+
 #[test]
 fn can_get_master() {
     setup();
@@ -675,6 +1003,43 @@ fn can_get_master_while_building() {
     assert_that!(jenkins.get_master_node()).is_ok();
 }
 
+#[test]
+fn can_get_build_of_busy_executor() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let job: jenkins_api::job::FreeStyleProject =
+        jenkins.get_job("long job").unwrap().as_variant().unwrap();
+    job.build(&jenkins).unwrap();
+
+    let mut running = None;
+    for _ in 0..30 {
+        let master = jenkins.get_master_node().unwrap();
+        running = master
+            .executors
+            .into_iter()
+            .find_map(|executor| match executor {
+                jenkins_api::nodes::computer::Executor::Executor {
+                    current_executable: Some(build),
+                    idle: false,
+                    ..
+                } => Some(build),
+                _ => None,
+            });
+        if running.is_some() {
+            break;
+        }
+        thread::sleep(time::Duration::from_secs(1));
+    }
+    let build = running.unwrap().get_full_build(&jenkins).unwrap();
+    assert_eq!(build.get_job(&jenkins).unwrap().name, "long job");
+
+    assert_that!(jenkins.get_load_statistics()).is_ok();
+}
+
 #[test]
 fn can_get_by_path_with_tree() {
     #[derive(Deserialize, Debug)]
@@ -682,7 +1047,7 @@ fn can_get_by_path_with_tree() {
     struct LastBuild {
         number: u8,
         duration: u8,
-        result: String,
+        result: Option<BuildStatus>,
     }
     #[derive(Deserialize, Debug)]
     #[serde(rename_all = "camelCase")]
@@ -732,3 +1097,69 @@ fn can_get_by_path_with_depth() {
 
     assert!(r.is_ok());
 }
+
+#[test]
+fn can_get_build_with_depth() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    for depth in 0..3 {
+        assert_that!(jenkins.get_build_with_depth("normal job", BuildNumber::LastBuild, depth))
+            .named(&format!("getting build with depth {}", depth))
+            .is_ok();
+    }
+}
+
+#[test]
+fn can_get_job_with_depth() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    for depth in 0..3 {
+        assert_that!(jenkins.get_job_with_depth("normal job", depth))
+            .named(&format!("getting job with depth {}", depth))
+            .is_ok();
+    }
+
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .with_depth(0)
+        .build()
+        .unwrap();
+    let job = jenkins.get_job("normal job");
+    assert_that!(job).is_ok();
+    assert_that!(job.unwrap().last_build.unwrap().get_full_build(&jenkins)).is_ok();
+}
+
+#[test]
+fn can_get_artifact_records() {
+    setup();
+    let jenkins = JenkinsBuilder::new(JENKINS_URL)
+        .with_user("user", Some("password"))
+        .build()
+        .unwrap();
+
+    let maven_build = jenkins
+        .get_job("maven job")
+        .unwrap()
+        .as_variant::<jenkins_api::job::MavenModuleSet>()
+        .unwrap()
+        .last_build
+        .unwrap()
+        .get_full_build(&jenkins)
+        .unwrap();
+    let maven_artifacts = maven_build.artifact_records(&jenkins);
+    assert_that!(maven_artifacts).is_ok();
+    assert!(!maven_artifacts.unwrap().is_empty());
+
+    let freestyle_build = jenkins.get_build("normal job", 1).unwrap();
+    let freestyle_artifacts = freestyle_build.artifact_records(&jenkins);
+    assert_that!(freestyle_artifacts).is_ok();
+    assert_that!(freestyle_artifacts.unwrap()).has_length(freestyle_build.artifacts.len());
+}